@@ -0,0 +1,77 @@
+//! Directory patching (BPM multi-file patches)
+
+use anyhow::{Context, Result};
+use log::info;
+use serde_json::json;
+use std::path::Path;
+use stitchr_core::PatchType;
+use stitchr_formats::bpm::BpmPatcher;
+
+use crate::utils::report::{self, FailureKind};
+
+/// Apply a multi-file patch to a source directory, writing a target directory
+///
+/// Every produced file is CRC-checked by the patch; a failure leaves the
+/// partially written output directory in place for inspection.
+pub fn execute(source_dir: &Path, patch_path: &Path, output_dir: &Path) -> Result<()> {
+    crate::say!("Loading source directory: {}", source_dir.display());
+    report::set("rom", json!({ "path": report::path(source_dir) }));
+    let patch_data = super::input::load_patch_with_checksum(patch_path)?.data;
+
+    let patch_type = crate::commands::dispatch::detect_patch_format(&patch_data)?;
+    if patch_type != PatchType::Bpm {
+        return Err(report::fail(
+            FailureKind::UnsupportedFormat,
+            format!(
+                "{} patches apply to a single file; directory input needs a BPM patch",
+                patch_type.name()
+            ),
+        ));
+    }
+    crate::say!(
+        "Detected format: {} ({})",
+        patch_type.name(),
+        patch_type.extension()
+    );
+
+    if output_dir.exists() && output_dir.read_dir()?.next().is_some() {
+        return Err(report::fail(
+            FailureKind::Usage,
+            format!("Output directory is not empty: {}", output_dir.display()),
+        ));
+    }
+
+    info!("Applying patch to directory...");
+    let report = BpmPatcher::apply_dir(source_dir, output_dir, &patch_data)
+        .context("Failed to apply patch")?;
+
+    crate::say!("Successfully patched: {}", output_dir.display());
+    report::set("output", json!({ "path": report::path(output_dir) }));
+    report::set(
+        "files",
+        json!({
+            "modified": report.modified,
+            "created": report.created,
+            "mirrored": report.mirrored,
+            "removed": report.removed,
+        }),
+    );
+    crate::say!(
+        "Files: {} modified, {} created, {} mirrored, {} removed",
+        report.modified.len(),
+        report.created.len(),
+        report.mirrored.len(),
+        report.removed.len()
+    );
+    for name in &report.modified {
+        info!("  modified: {}", name);
+    }
+    for name in &report.created {
+        info!("  created:  {}", name);
+    }
+    for name in &report.removed {
+        info!("  removed:  {}", name);
+    }
+
+    Ok(())
+}
@@ -2,43 +2,210 @@
 
 use anyhow::{Context, Result};
 use log::debug;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// A loaded ROM or patch
+pub struct Input {
+    pub data: Vec<u8>,
+    /// The file read from disk (the archive for archive members)
+    pub file: PathBuf,
+    /// Path outputs are named after: the member as if extracted next to
+    /// its archive, otherwise the file itself
+    pub name_path: PathBuf,
+    /// Whether the data came from inside an archive or a compressed disc
+    /// image, so it cannot be written back in place
+    pub in_archive: bool,
+    /// Whether the data was decompressed from a CSO, ZSO or CHD image
+    pub disc_image: bool,
+    /// The sheet and file layout when the data is the disc of a CUE sheet
+    #[cfg(feature = "disc")]
+    pub cue: Option<crate::utils::cue::CueInput>,
+}
 
 /// Load ROM file and display its checksum
-pub fn load_rom_with_checksum(rom_path: &Path) -> Result<Vec<u8>> {
-    println!("Loading ROM: {}", rom_path.display());
-    let rom_data = fs::read(rom_path).context("Failed to read ROM file")?;
+pub fn load_rom_with_checksum(rom_path: &Path) -> Result<Input> {
+    crate::say!("Loading ROM: {}", rom_path.display());
+    #[cfg(feature = "disc")]
+    let rom = if crate::utils::cue::is_cue(rom_path) {
+        read_cue(rom_path)?
+    } else {
+        expand_disc_image(read(rom_path, "rom").context("Failed to read ROM file")?)?
+    };
+    #[cfg(not(feature = "disc"))]
+    let rom = read(rom_path, "rom").context("Failed to read ROM file")?;
 
-    debug!("ROM size: {} bytes", rom_data.len());
+    debug!("ROM size: {} bytes", rom.data.len());
 
     #[cfg(feature = "validation")]
     {
-        let crc = crate::utils::validation::compute_crc32(&rom_data);
-        println!(
+        let crc = crate::utils::validation::compute_crc32(&rom.data);
+        crate::say!(
             "Input ROM CRC32: {}",
             crate::utils::validation::format_crc32(crc)
         );
     }
 
-    Ok(rom_data)
+    Ok(rom)
 }
 
 /// Load patch file and display its checksum
-pub fn load_patch_with_checksum(patch_path: &Path) -> Result<Vec<u8>> {
-    println!("Loading patch: {}", patch_path.display());
-    let patch_data = fs::read(patch_path).context("Failed to read patch file")?;
+pub fn load_patch_with_checksum(patch_path: &Path) -> Result<Input> {
+    crate::say!("Loading patch: {}", patch_path.display());
+    let patch = read(patch_path, "patch").context("Failed to read patch file")?;
 
-    debug!("Patch size: {} bytes", patch_data.len());
+    debug!("Patch size: {} bytes", patch.data.len());
 
     #[cfg(feature = "validation")]
     {
-        let crc = crate::utils::validation::compute_crc32(&patch_data);
-        println!(
+        let crc = crate::utils::validation::compute_crc32(&patch.data);
+        crate::say!(
             "Patch CRC32: {}",
             crate::utils::validation::format_crc32(crc)
         );
     }
 
-    Ok(patch_data)
+    Ok(patch)
+}
+
+/// Decompress a CSO, ZSO or CHD disc image so patches apply to the disc
+///
+/// Outputs are named with the uncompressed image's extension (`.iso`, or
+/// `.bin` for raw CD sectors).
+#[cfg(feature = "disc")]
+fn expand_disc_image(mut rom: Input) -> Result<Input> {
+    use crate::utils::report::{self, FailureKind};
+    use stitchr_features::disc::{DiscImage, ImageFormat, read_all};
+
+    if ImageFormat::detect(&rom.data) == ImageFormat::Plain {
+        return Ok(rom);
+    }
+    let image = DiscImage::open(Box::new(std::mem::take(&mut rom.data)))
+        .map_err(|e| report::fail(FailureKind::UnsupportedFormat, e))?;
+    rom.data = read_all(&image).map_err(|e| {
+        anyhow::anyhow!("Failed to decompress {} image: {}", image.format.name(), e)
+    })?;
+    rom.name_path = rom.name_path.with_extension(image.extension);
+    rom.in_archive = true;
+    rom.disc_image = true;
+
+    crate::say!(
+        "Decompressed {} image ({} bytes)",
+        image.format.name(),
+        rom.data.len()
+    );
+    report::set_in(
+        "rom",
+        "image",
+        serde_json::json!({ "format": image.format.name(), "size": rom.data.len() }),
+    );
+    Ok(rom)
+}
+
+/// Read the disc formed by the BIN files of a CUE sheet
+#[cfg(feature = "disc")]
+fn read_cue(path: &Path) -> Result<Input> {
+    use crate::utils::report::{self, FailureKind};
+    use serde_json::json;
+
+    let (cue, data) = crate::utils::cue::load(path)
+        .map_err(|e| report::fail(FailureKind::Io, format!("Failed to read CUE sheet: {}", e)))?;
+    crate::say!(
+        "Read CUE sheet: {} track{} in {} BIN file{} ({} bytes)",
+        cue.sheet.tracks.len(),
+        if cue.sheet.tracks.len() == 1 { "" } else { "s" },
+        cue.sheet.files.len(),
+        if cue.sheet.files.len() == 1 { "" } else { "s" },
+        data.len()
+    );
+    for (track, span) in cue.sheet.tracks.iter().zip(&cue.spans) {
+        debug!(
+            "Track {:02} {}: bytes {}..{}, LBA {}",
+            track.number, track.mode, span.start, span.end, span.lba
+        );
+    }
+
+    report::set("rom", report::file(path, &data));
+    report::set_in(
+        "rom",
+        "cue",
+        json!({
+            "files": cue.file_paths().iter().map(|p| report::path(p)).collect::<Vec<_>>(),
+            "tracks": cue.sheet.tracks.len(),
+        }),
+    );
+    Ok(Input {
+        data,
+        file: path.to_path_buf(),
+        name_path: path.to_path_buf(),
+        in_archive: false,
+        disc_image: false,
+        cue: Some(cue),
+    })
+}
+
+/// Read `path`, extracting the ROM or patch when it is an archive, and
+/// report it under `key` ("rom" or "patch")
+#[cfg(feature = "archive")]
+fn read(path: &Path, key: &str) -> Result<Input> {
+    use crate::utils::archive::{self, Role};
+    use crate::utils::report;
+
+    let role = if key == "patch" {
+        Role::Patch
+    } else {
+        Role::Rom
+    };
+    let (data, member) = archive::read(path, role)?;
+    report::set(key, report::file(path, &data));
+    let Some(member) = member else {
+        return Ok(Input {
+            data,
+            file: path.to_path_buf(),
+            name_path: path.to_path_buf(),
+            in_archive: false,
+            disc_image: false,
+            #[cfg(feature = "disc")]
+            cue: None,
+        });
+    };
+
+    crate::say!(
+        "Extracted {} from {} archive {}",
+        member.name,
+        member.kind.name(),
+        member.archive.display()
+    );
+    report::set_in(
+        key,
+        "archive",
+        serde_json::json!({
+            "path": report::path(&member.archive),
+            "format": member.kind.name(),
+            "member": member.name,
+        }),
+    );
+    Ok(Input {
+        data,
+        name_path: member.extracted_path(),
+        file: member.archive,
+        in_archive: true,
+        disc_image: false,
+        #[cfg(feature = "disc")]
+        cue: None,
+    })
+}
+
+#[cfg(not(feature = "archive"))]
+fn read(path: &Path, key: &str) -> Result<Input> {
+    let data = std::fs::read(path)?;
+    crate::utils::report::set(key, crate::utils::report::file(path, &data));
+    Ok(Input {
+        data,
+        file: path.to_path_buf(),
+        name_path: path.to_path_buf(),
+        in_archive: false,
+        disc_image: false,
+        #[cfg(feature = "disc")]
+        cue: None,
+    })
 }
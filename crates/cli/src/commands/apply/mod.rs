@@ -1,48 +1,132 @@
 //! Apply patch command with transactional safety
 
-mod input;
+mod directory;
+pub mod input;
 mod only;
-mod output;
+pub mod output;
+pub mod step;
 
 use anyhow::{Context, Result};
-use log::{debug, info, warn};
+use log::debug;
 use std::path::PathBuf;
-use stitchr_formats::detect_format;
+use stitchr_formats::copier_header::HeaderLayout;
+use stitchr_formats::n64_byte_order::OutputOrder;
+
+use crate::utils::atomic::WriteOptions;
+use crate::utils::paths::{OnExists, TemplateInput};
+use crate::utils::report::{self, FailureKind};
+use step::StepOptions;
+
+/// Options for the apply command
+pub struct ApplyOptions {
+    /// Verify source/target checksums (--verify)
+    pub verify: bool,
+    /// Operations to run instead of patching (--only)
+    pub only_modes: Vec<stitchr_cli::OnlyMode>,
+    /// SNES copier header layout to write (`None` disables reconciliation)
+    pub header_layout: Option<HeaderLayout>,
+    /// N64 byte order to write (`None` disables conversion)
+    pub byte_order: Option<OutputOrder>,
+    /// Recompute internal cartridge checksums after patching
+    pub fix_checksum: bool,
+    /// Regenerate EDC/ECC of the raw CD sectors the patch modified
+    /// (--fix-ecc)
+    pub fix_ecc: bool,
+    /// DAT files or directories used to name the input and output (--dat)
+    pub dats: Vec<PathBuf>,
+    /// Undo a UPS/RUP patch instead of applying it (--revert)
+    pub revert: bool,
+    /// Fail instead of skipping when the patch is already applied
+    pub refuse_applied: bool,
+    /// Output file name template, used when no output path is given
+    /// (--output-template)
+    pub output_template: Option<String>,
+    /// What to do when the output file already exists (--on-exists)
+    pub on_exists: OnExists,
+    /// Switch the output extension when the patched ROM is for another
+    /// console (--console-extension)
+    pub console_extension: bool,
+    /// Write over the input ROM, keeping a `.bak` copy (--in-place)
+    pub in_place: bool,
+    /// Copy the input's permissions and modification time (--preserve)
+    pub preserve: bool,
+    /// Write the output into a ZIP archive (--zip)
+    pub zip: bool,
+}
 
 /// Apply a patch to a ROM file with transactional safety
 ///
 /// Safety features:
 /// - Clones ROM data before patching (rollback on error)
 /// - Validates input != output paths
-/// - Writes to temp file first, then atomic rename
+/// - Writes to a unique, fsynced temp file first, then atomic rename
+/// - In-place patching keeps the original as `{rom}.bak`
 /// - Always shows CRC32 checksums for verification
 /// - Optional source/target checksum verification (--verify flag)
+/// - SNES copier header and N64 byte order matched to the patch
+/// - Input and output named from DAT files (--dat)
+/// - Already-applied patches skipped (or refused), UPS/RUP revertable
+/// - Output named from a template, existing outputs handled per --on-exists
+/// - ROM and patch read from ZIP, 7z and gzip archives, output optionally
+///   zipped
+/// - CUE sheets patched as one disc across their BIN files, or per track
+/// - EDC/ECC of modified raw CD sectors optionally regenerated (--fix-ecc)
 pub fn execute(
     rom_path: PathBuf,
     patch_path: Option<PathBuf>,
     output_path: Option<PathBuf>,
-    verify: bool,
-    only_modes: Vec<stitchr_cli::OnlyMode>,
+    options: ApplyOptions,
 ) -> Result<()> {
-    // Generate default output path if not specified (not needed for only-modes)
-    let output_path = if only_modes.is_empty() {
-        match output_path {
-            Some(path) => path,
-            None => crate::utils::paths::generate_default_output(&rom_path)?,
-        }
-    } else {
-        // Dummy path for only-modes (won't be used)
-        rom_path.with_extension("dummy")
-    };
-
-    debug!("Output path resolved to: {}", output_path.display());
+    let ApplyOptions {
+        verify,
+        only_modes,
+        header_layout,
+        byte_order,
+        fix_checksum,
+        fix_ecc,
+        dats,
+        revert,
+        refuse_applied,
+        output_template,
+        on_exists,
+        console_extension,
+        in_place,
+        preserve,
+        zip,
+    } = options;
 
     // Safety check: prevent overwriting input (skip in only-modes)
-    if only_modes.is_empty() && rom_path == output_path {
-        anyhow::bail!(
-            "Input and output paths cannot be the same. Use a different output path to preserve \
-             the original ROM."
-        );
+    if only_modes.is_empty() && output_path.as_ref() == Some(&rom_path) {
+        return Err(same_path_error());
+    }
+
+    #[cfg(not(feature = "archive"))]
+    if zip {
+        anyhow::bail!("--zip requires the archive feature");
+    }
+
+    // Directory input (multi-file patches)
+    if rom_path.is_dir() {
+        if !only_modes.is_empty() {
+            return Err(report::fail(
+                FailureKind::Usage,
+                "--only modes are not supported for directory input",
+            ));
+        }
+        if output_template.is_some() || in_place || zip {
+            return Err(report::fail(
+                FailureKind::Usage,
+                "--output-template, --in-place and --zip are not supported for directory input",
+            ));
+        }
+        let output_path = match output_path {
+            Some(path) => path,
+            None => crate::utils::paths::generate_default_output(&rom_path)?,
+        };
+        let output_path =
+            crate::utils::paths::resolve_existing(&output_path, on_exists, &Default::default())?;
+        let patch_path = patch_path.expect("Patch path should be validated in main");
+        return directory::execute(&rom_path, &patch_path, &output_path);
     }
 
     // Handle --only modes
@@ -71,14 +155,35 @@ pub fn execute(
     let patch_path = patch_path.expect("Patch path should be validated in main");
 
     // Load ROM and patch with checksum display
-    let original_rom = input::load_rom_with_checksum(&rom_path)?;
-    let patch_data = input::load_patch_with_checksum(&patch_path)?;
+    let rom_input = input::load_rom_with_checksum(&rom_path)?;
+    let patch_input = input::load_patch_with_checksum(&patch_path)?;
+    if in_place && rom_input.in_archive {
+        return Err(report::fail(
+            FailureKind::Usage,
+            "--in-place cannot write into an archive or compressed disc image; give an output path or use --zip",
+        ));
+    }
+    #[cfg(feature = "disc")]
+    if rom_input.cue.is_some() && (in_place || zip) {
+        return Err(report::fail(
+            FailureKind::Usage,
+            "--in-place and --zip are not supported for CUE sheets; give a .cue output path, or \
+             any other name for a merged BIN",
+        ));
+    }
+    #[cfg(feature = "disc")]
+    let cue = rom_input.cue;
+    let mut original_rom = rom_input.data;
+    let original_size = original_rom.len();
+    let patch_data = patch_input.data;
+    // Outputs are named after archive members rather than the archives
+    let (rom_path, rom_file) = (rom_input.name_path, rom_input.file);
+    let patch_path = patch_input.name_path;
 
     // Auto-detect patch format
-    let patch_type =
-        detect_format(&patch_data).context("Could not detect patch format from file header")?;
+    let patch_type = crate::commands::dispatch::detect_patch_format(&patch_data)?;
 
-    println!(
+    crate::say!(
         "Detected format: {} ({})",
         patch_type.name(),
         patch_type.extension()
@@ -86,49 +191,223 @@ pub fn execute(
 
     debug!("Internal patch type: {:?}", patch_type);
 
+    // Patch a single track when the patch was made against one, keeping the
+    // rest of the disc to write back around it
+    #[cfg(feature = "disc")]
+    let track = cue
+        .as_ref()
+        .and_then(|cue| {
+            let metadata = super::dispatch::patch_metadata(&patch_data, &patch_type).ok()?;
+            cue.target_track(metadata.source_size)
+        })
+        .map(|span| {
+            crate::say!(
+                "Patch targets track {:02} ({} bytes)",
+                span.number,
+                span.len()
+            );
+            report::set_in("rom", "track", span.number as u32);
+            let disc = std::mem::take(&mut original_rom);
+            original_rom = disc[span.start as usize..span.end as usize].to_vec();
+            (span, disc)
+        });
+
+    // Match the ROM's copier header and N64 byte order to the patch, and
+    // catch ROMs that already have it applied
+    let step = step::prepare(
+        &mut original_rom,
+        &patch_data,
+        patch_type,
+        StepOptions {
+            verify,
+            revert,
+            refuse_applied,
+            header_layout,
+            byte_order,
+            announce: true,
+        },
+    );
+    let state = step.state;
+    let revert_hint = step.revert_hint();
+
+    // Name the input ROM and the ROM the patch expects from DAT files
+    #[cfg(feature = "dat")]
+    let dat_index = crate::utils::dat::load(&dats);
+    #[cfg(feature = "dat")]
+    if let Some(index) = &dat_index {
+        let expected = super::dispatch::patch_metadata(&patch_data, &patch_type)
+            .ok()
+            .and_then(|m| m.source_checksum);
+        crate::utils::dat::report_input(index, &original_rom, expected.as_deref());
+    }
+    #[cfg(not(feature = "dat"))]
+    if !dats.is_empty() {
+        log::warn!("--dat requires the dat feature; ignoring DAT files");
+    }
+
     // Handle --only verify mode
     if only_modes
         .iter()
         .any(|m| matches!(m, stitchr_cli::OnlyMode::Verify))
     {
+        for adjustment in step.adjustments() {
+            crate::say!("{}", adjustment);
+            report::push("adjustments", adjustment);
+        }
+        report::set("already_applied", state.is_applied());
+        if state.is_applied() {
+            crate::say!("{}{}", state, revert_hint);
+        }
         return only::handle_verify_mode(&original_rom, &patch_data, &patch_type);
     }
 
-    // Normal mode: apply patch with optional verification
+    if state.is_applied() && !revert {
+        report::set("already_applied", true);
+    }
+    if !step.check_state()? {
+        crate::say!("{}, skipping{}", state, revert_hint);
+        report::set("status", "skipped");
+        return Ok(());
+    }
+    report::set("already_applied", revert);
 
-    // Verify source checksum if requested
-    if verify {
-        // IPS format has no embedded checksums - skip verification
-        if patch_type.name() == "International Patching System" {
-            warn!("IPS format does not support checksum verification (no embedded checksums)");
-        } else {
-            info!("Verifying source ROM checksum...");
-            super::verify::verify_source(&original_rom, &patch_data, &patch_type)
-                .context("Source ROM checksum verification failed")?;
-        }
+    // Normal mode: apply patch with optional verification, then restore the
+    // requested header layout and byte order
+    let patched = step.apply(&original_rom)?;
+    if patched.unverifiable {
+        report::set_in("verification", "source", "unsupported");
+    }
+    if revert {
+        crate::say!("Reverted patch; ROM matches the patch source");
     }
+    for adjustment in &patched.adjustments {
+        crate::say!("{}", adjustment);
+        report::push("adjustments", adjustment.as_str());
+    }
+    let mut patched_rom = patched.rom;
 
-    // Clone ROM data for transactional patching (rollback on error)
-    let mut patched_rom = original_rom.clone();
+    // Extension for outputs that are not named explicitly
+    let mut new_extension = patched.extension;
 
-    // Apply patch with format-specific handler
-    info!("Applying patch data to ROM buffer...");
-    super::dispatch::apply_patch(&mut patched_rom, &patch_data, &patch_type)
-        .context("Failed to apply patch")?;
+    // Repair internal cartridge checksums
+    if fix_checksum {
+        #[cfg(feature = "checksum_fix")]
+        crate::utils::checksum_fix::fix_and_report(&mut patched_rom);
+        #[cfg(not(feature = "checksum_fix"))]
+        anyhow::bail!("--fix-checksum requires the checksum_fix feature");
+    } else {
+        #[cfg(feature = "checksum_fix")]
+        crate::utils::checksum_fix::suggest_fix(&patched_rom);
+    }
 
-    // Verify target checksum if requested
-    if verify {
-        // IPS format has no embedded checksums - skip verification
-        if patch_type.name() != "International Patching System" {
-            info!("Verifying target ROM checksum...");
-            super::verify::verify_target(&original_rom, &patched_rom, &patch_data, &patch_type)
-                .context("Target ROM checksum verification failed")?;
+    // Regenerate EDC/ECC of raw CD sectors the patch changed
+    if fix_ecc {
+        #[cfg(feature = "disc")]
+        crate::utils::edc_ecc::fix_and_report(&original_rom, &mut patched_rom);
+        #[cfg(not(feature = "disc"))]
+        anyhow::bail!("--fix-ecc requires the disc feature");
+    } else {
+        #[cfg(feature = "disc")]
+        crate::utils::edc_ecc::suggest_fix(&original_rom, &patched_rom);
+    }
+
+    #[cfg(feature = "dat")]
+    if let Some(index) = &dat_index {
+        crate::utils::dat::report_output(index, &patched_rom);
+    }
+
+    if console_extension {
+        #[cfg(feature = "retroachievements")]
+        if let Some(extension) =
+            crate::utils::paths::console_change_extension(&original_rom, &patched_rom)
+        {
+            new_extension = Some(extension);
         }
+        #[cfg(not(feature = "retroachievements"))]
+        log::warn!("--console-extension requires the retroachievements feature; ignoring");
     }
 
+    // Name the output now that its contents are known
+    let output_path = match output_path {
+        Some(path) => path,
+        None if in_place => rom_path.clone(),
+        None => {
+            let mut path = match &output_template {
+                Some(template) => {
+                    let title = super::dispatch::patch_metadata(&patch_data, &patch_type)
+                        .ok()
+                        .and_then(|m| m.extra.into_iter().find(|(k, _)| k == "title"))
+                        .map(|(_, title)| title);
+                    let vars = crate::utils::paths::template_vars(&TemplateInput {
+                        rom_path: &rom_path,
+                        patch_path: &patch_path,
+                        patch_type,
+                        title,
+                        output: &patched_rom,
+                    });
+                    let path =
+                        PathBuf::from(crate::utils::paths::expand_template(template, &vars)?);
+                    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                        std::fs::create_dir_all(dir).with_context(|| {
+                            format!("Failed to create output directory: {}", dir.display())
+                        })?;
+                    }
+                    path
+                }
+                None => crate::utils::paths::generate_default_output(&rom_path)?,
+            };
+            if let Some(extension) = new_extension {
+                path.set_extension(extension);
+            }
+            path
+        }
+    };
+    let (output_path, zip_entry) = if zip {
+        let (archive, entry) = output::zip_target(&output_path, &rom_path);
+        (archive, Some(entry))
+    } else {
+        (output_path, None)
+    };
+    let output_path = if in_place {
+        output_path
+    } else if output_path == rom_file {
+        return Err(same_path_error());
+    } else {
+        crate::utils::paths::resolve_existing(&output_path, on_exists, &Default::default())?
+    };
+    debug!("Output path resolved to: {}", output_path.display());
+
     // Write output with checksum display
-    let original_size = original_rom.len();
-    output::write_patched_rom(&patched_rom, original_size, &output_path)?;
+    #[cfg(feature = "disc")]
+    if let Some(cue) = &cue {
+        return output::write_patched_disc(
+            cue,
+            track,
+            patched_rom,
+            &output_path,
+            on_exists,
+            preserve,
+        );
+    }
+    let write_options = WriteOptions {
+        preserve_from: preserve.then_some(rom_file),
+        backup: in_place,
+    };
+    output::write_patched_rom(
+        &patched_rom,
+        original_size,
+        &output_path,
+        zip_entry.as_deref(),
+        &write_options,
+    )?;
 
     Ok(())
 }
+
+fn same_path_error() -> anyhow::Error {
+    report::fail(
+        FailureKind::Usage,
+        "Input and output paths cannot be the same. Use a different output path to preserve the \
+         original ROM.",
+    )
+}
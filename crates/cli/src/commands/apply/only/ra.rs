@@ -10,6 +10,12 @@ use crate::commands::apply::input;
 pub fn handle_ra_mode(rom_path: &Path) -> Result<()> {
     info!("Running RetroAchievements check (ROM-only mode)");
     let rom = input::load_rom_with_checksum(rom_path)?;
-    crate::utils::retroachievements::check_and_display(&rom, rom_path);
+    // Disc hashes read the image from disk, where it is still compressed
+    let path = if rom.disc_image {
+        &rom.file
+    } else {
+        &rom.name_path
+    };
+    crate::utils::retroachievements::check_and_display(&rom.data, path);
     Ok(())
 }
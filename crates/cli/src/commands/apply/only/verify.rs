@@ -9,11 +9,14 @@ pub fn handle_verify_mode(
     patch_data: &[u8],
     patch_type: &PatchType,
 ) -> Result<()> {
-    println!("Running in verify-only mode (no patching will be performed)");
+    crate::say!("Running in verify-only mode (no patching will be performed)");
 
     // IPS format has no embedded checksums - skip verification
-    if patch_type.name() == "International Patching System" {
-        println!("Note: IPS format does not support checksum verification (no embedded checksums)");
+    if matches!(patch_type, PatchType::Ips | PatchType::Ips32) {
+        crate::say!(
+            "Note: IPS format does not support checksum verification (no embedded checksums)"
+        );
+        crate::utils::report::set_in("verification", "source", "unsupported");
         return Ok(());
     }
 
@@ -21,6 +24,6 @@ pub fn handle_verify_mode(
     crate::commands::verify::verify_source(original_rom, patch_data, patch_type)
         .context("Source ROM checksum verification failed")?;
 
-    println!("Verification completed successfully!");
+    crate::say!("Verification completed successfully!");
     Ok(())
 }
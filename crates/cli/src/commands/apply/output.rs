@@ -1,32 +1,84 @@
 //! Output file writing with atomic rename and checksum display
 
-use anyhow::{Context, Result};
-use log::{debug, info};
-use std::fs;
-use std::path::Path;
+use anyhow::Result;
+use log::info;
+use std::path::{Path, PathBuf};
+
+use crate::utils::atomic::{self, WriteOptions};
+
+/// The archive written for --zip and the name of the ROM inside it
+///
+/// `game.sfc` becomes `game.zip` holding `game.sfc`. An output already named
+/// `.zip` holds its stem with the input ROM's extension.
+pub fn zip_target(output_path: &Path, rom_path: &Path) -> (PathBuf, String) {
+    let is_zip = output_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    let entry = if is_zip {
+        let entry = output_path.with_extension("");
+        match rom_path.extension() {
+            Some(ext) => entry.with_extension(ext),
+            None => entry,
+        }
+    } else {
+        output_path.to_path_buf()
+    };
+    let entry = entry
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    (output_path.with_extension("zip"), entry)
+}
 
 /// Write patched ROM to output path with atomic rename
 ///
-/// Safety: Writes to temp file first, then atomically renames
+/// With `zip_entry`, the output is a ZIP archive holding the ROM under that
+/// name.
+///
+/// Safety: see [`atomic::write_file`]; the output is either the complete
+/// patched ROM or left as it was, even across a crash
 pub fn write_patched_rom(
     patched_rom: &[u8],
     original_size: usize,
     output_path: &Path,
+    zip_entry: Option<&str>,
+    options: &WriteOptions,
 ) -> Result<()> {
-    // Write to temp file first, then atomic rename
-    let temp_path = output_path.with_extension("tmp");
-    debug!("Writing temporary output: {}", temp_path.display());
-    fs::write(&temp_path, patched_rom).context("Failed to write temporary output file")?;
-    fs::rename(&temp_path, output_path).context("Failed to finalize output file")?;
-
-    println!("Successfully patched: {}", output_path.display());
+    match zip_entry {
+        #[cfg(feature = "archive")]
+        Some(entry) => {
+            let archive = stitchr_features::archive::zip::create(entry, patched_rom);
+            atomic::write_file(output_path, &archive, options)?;
+            crate::say!(
+                "Successfully patched: {} ({})",
+                output_path.display(),
+                entry
+            );
+        }
+        _ => {
+            atomic::write_file(output_path, patched_rom, options)?;
+            crate::say!("Successfully patched: {}", output_path.display());
+        }
+    }
     info!("ROM size: {} -> {} bytes", original_size, patched_rom.len());
+    crate::utils::report::set(
+        "output",
+        crate::utils::report::file(output_path, patched_rom),
+    );
+    if let Some(entry) = zip_entry {
+        crate::utils::report::set_in("output", "member", entry);
+    }
+    if options.backup {
+        let backup = atomic::backup_path(output_path);
+        crate::say!("Original kept as: {}", backup.display());
+        crate::utils::report::set_in("output", "backup", crate::utils::report::path(&backup));
+    }
 
     // Always show output checksum
     #[cfg(feature = "validation")]
     {
         let crc = crate::utils::validation::compute_crc32(patched_rom);
-        println!(
+        crate::say!(
             "Output ROM CRC32: {}",
             crate::utils::validation::format_crc32(crc)
         );
@@ -35,8 +87,62 @@ pub fn write_patched_rom(
     // RetroAchievements hash check (if enabled)
     #[cfg(feature = "retroachievements")]
     {
-        crate::utils::retroachievements::check_and_display(patched_rom, output_path);
+        let rom_path = zip_entry.map_or(output_path, Path::new);
+        crate::utils::retroachievements::check_and_display(patched_rom, rom_path);
     }
 
     Ok(())
 }
+
+/// Write the patched disc of a CUE sheet and report where it went
+///
+/// See [`crate::utils::cue::write_disc`] for the layout of the output.
+#[cfg(feature = "disc")]
+pub fn write_patched_disc(
+    cue: &crate::utils::cue::CueInput,
+    track: Option<(stitchr_features::disc::TrackSpan, Vec<u8>)>,
+    patched: Vec<u8>,
+    output_path: &Path,
+    on_exists: crate::utils::paths::OnExists,
+    preserve: bool,
+) -> Result<()> {
+    use crate::utils::report;
+
+    let written =
+        crate::utils::cue::write_disc(cue, track, patched, output_path, on_exists, preserve)?;
+    let disc = written.disc;
+    report::set("output", report::file(output_path, &disc));
+    if written.bins.is_empty() {
+        crate::say!(
+            "Successfully patched: {} (merged BIN)",
+            output_path.display()
+        );
+    } else {
+        for path in &written.bins {
+            info!("Wrote {}", path.display());
+        }
+        crate::say!(
+            "Successfully patched: {} ({} BIN file{})",
+            output_path.display(),
+            written.bins.len(),
+            if written.bins.len() == 1 { "" } else { "s" }
+        );
+        let files = written.bins.iter().map(|path| report::path(path));
+        report::set_in("output", "files", files.collect::<Vec<_>>());
+    }
+    info!("Disc size: {} -> {} bytes", cue.disc_size(), disc.len());
+
+    #[cfg(feature = "validation")]
+    {
+        let crc = crate::utils::validation::compute_crc32(&disc);
+        crate::say!(
+            "Output ROM CRC32: {}",
+            crate::utils::validation::format_crc32(crc)
+        );
+    }
+
+    #[cfg(feature = "retroachievements")]
+    crate::utils::retroachievements::check_and_display(&disc, output_path);
+
+    Ok(())
+}
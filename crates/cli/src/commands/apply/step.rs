@@ -0,0 +1,221 @@
+//! Applying one patch to a ROM buffer, shared by apply and batch
+//!
+//! [`prepare`] matches the ROM's copier header and N64 byte order to the
+//! patch and checks whether the patch is already applied;
+//! [`Prepared::apply`] verifies checksums, patches (or reverts) and restores
+//! the requested layout.
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use stitchr_core::PatchType;
+use stitchr_formats::copier_header::{self, HeaderFix, HeaderLayout};
+use stitchr_formats::n64_byte_order::{self, ByteOrderFix, OutputOrder};
+use stitchr_formats::patch_state::{self, PatchState};
+
+use crate::commands::{dispatch, verify};
+use crate::utils::report::{self, FailureKind};
+
+/// How to apply a patch
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StepOptions {
+    /// Verify source/target checksums
+    pub verify: bool,
+    /// Undo a UPS/RUP patch instead of applying it
+    pub revert: bool,
+    /// Fail instead of skipping when the patch is already applied
+    pub refuse_applied: bool,
+    /// SNES copier header layout to write (`None` disables reconciliation)
+    pub header_layout: Option<HeaderLayout>,
+    /// N64 byte order to write (`None` disables conversion)
+    pub byte_order: Option<OutputOrder>,
+    /// Print verification progress and record it in the command's report
+    /// (batch jobs report per job instead)
+    pub announce: bool,
+}
+
+/// A patch matched to a ROM, ready to apply
+pub struct Prepared<'a> {
+    patch: &'a [u8],
+    patch_type: PatchType,
+    options: StepOptions,
+    header_fix: Option<HeaderFix>,
+    order_fix: Option<ByteOrderFix>,
+    /// Whether the ROM already has the patch applied
+    pub state: PatchState,
+}
+
+/// A patched ROM
+pub struct Patched {
+    pub rom: Vec<u8>,
+    /// Header and byte order changes, for the user
+    pub adjustments: Vec<String>,
+    /// Whether checksums were requested but the format has none
+    pub unverifiable: bool,
+    /// Extension matching a changed N64 byte order
+    pub extension: Option<&'static str>,
+}
+
+/// Match `rom` to the layout `patch` expects and detect its patch state
+pub fn prepare<'a>(
+    rom: &mut Vec<u8>,
+    patch: &'a [u8],
+    patch_type: PatchType,
+    options: StepOptions,
+) -> Prepared<'a> {
+    // Strip or add an SNES copier header if the patch expects the other layout
+    let header_fix = options
+        .header_layout
+        .map(|_| copier_header::prepare(rom, patch, patch_type));
+
+    // Convert .v64/.n64 dumps to the byte order the patch expects
+    let order_fix = options
+        .byte_order
+        .map(|_| n64_byte_order::prepare(rom, patch, patch_type));
+
+    Prepared {
+        patch,
+        patch_type,
+        options,
+        header_fix,
+        order_fix,
+        state: patch_state::detect(rom, patch, patch_type),
+    }
+}
+
+impl Prepared<'_> {
+    /// Changes [`prepare`] made to the ROM
+    pub fn adjustments(&self) -> Vec<String> {
+        let header = self.header_fix.as_ref().filter(|f| f.change().is_some());
+        let order = self.order_fix.filter(|f| f.conversion().is_some());
+        header
+            .map(|f| f.to_string())
+            .into_iter()
+            .chain(order.map(|f| f.to_string()))
+            .collect()
+    }
+
+    /// Suffix pointing at `--revert` for patches that support it
+    pub fn revert_hint(&self) -> &'static str {
+        if patch_state::can_revert(self.patch_type) {
+            "; use --revert to restore the original ROM"
+        } else {
+            ""
+        }
+    }
+
+    /// Whether to go ahead with the patch
+    ///
+    /// `false` means the patch is already applied and should be skipped;
+    /// refused patches and impossible reverts are errors.
+    pub fn check_state(&self) -> Result<bool> {
+        let applied = self.state.is_applied();
+        if self.options.revert {
+            if !patch_state::can_revert(self.patch_type) {
+                return Err(report::fail(
+                    FailureKind::UnsupportedFormat,
+                    "--revert is only supported for UPS and RUP patches",
+                ));
+            }
+            if !applied {
+                return Err(report::fail(
+                    FailureKind::PatchState,
+                    "ROM does not have this patch applied; nothing to revert",
+                ));
+            }
+            return Ok(true);
+        }
+        if applied && self.options.refuse_applied {
+            return Err(report::fail(
+                FailureKind::PatchState,
+                format!("{}{}", self.state, self.revert_hint()),
+            ));
+        }
+        Ok(!applied)
+    }
+
+    /// Apply (or revert) the patch to `rom`, the buffer [`prepare`] matched
+    pub fn apply(self, rom: &[u8]) -> Result<Patched> {
+        let Self {
+            patch,
+            patch_type,
+            options,
+            header_fix,
+            order_fix,
+            ..
+        } = self;
+        // IPS format has no embedded checksums - skip verification
+        let unverifiable = options.verify
+            && !options.revert
+            && matches!(patch_type, PatchType::Ips | PatchType::Ips32);
+        let verify = options.verify && !options.revert && !unverifiable;
+        if unverifiable {
+            warn!("IPS format does not support checksum verification (no embedded checksums)");
+        }
+
+        // Verify source checksum if requested
+        if verify {
+            info!("Verifying source ROM checksum...");
+            if options.announce {
+                verify::verify_source(rom, patch, &patch_type)
+            } else {
+                dispatch::validate_patch(patch, &patch_type)
+                    .and_then(|_| verify::dispatch_verify(rom, patch, &patch_type, None))
+            }
+            .context("Source ROM checksum verification failed")?;
+        }
+
+        // Clone ROM data for transactional patching (rollback on error)
+        let mut patched = rom.to_vec();
+
+        // Apply patch with format-specific handler
+        if options.revert {
+            info!("Reverting patch on ROM buffer...");
+            patch_state::revert(&mut patched, patch, patch_type)
+                .context("Failed to revert patch")?;
+        } else {
+            info!("Applying patch data to ROM buffer...");
+            dispatch::apply_patch(&mut patched, patch, &patch_type)
+                .context("Failed to apply patch")?;
+        }
+
+        // Verify target checksum if requested
+        if verify {
+            info!("Verifying target ROM checksum...");
+            if options.announce {
+                verify::verify_target(rom, &patched, patch, &patch_type)
+            } else {
+                verify::dispatch_verify(rom, patch, &patch_type, Some(&patched))
+            }
+            .context("Target ROM checksum verification failed")?;
+        }
+
+        let mut adjustments = Vec::new();
+        let mut extension = None;
+
+        // Restore the requested N64 byte order
+        if let (Some(fix), Some(order)) = (order_fix, options.byte_order) {
+            let report = fix.finish(&mut patched, order);
+            if !report.is_empty() {
+                adjustments.push(report.to_string());
+            }
+            if let Some(output) = report.output.filter(|_| report.order_changed()) {
+                extension = Some(output.extension());
+            }
+        }
+
+        // Restore the requested copier header layout
+        if let (Some(fix), Some(layout)) = (header_fix, options.header_layout) {
+            let report = fix.finish(&mut patched, layout);
+            if !report.is_empty() {
+                adjustments.push(report.to_string());
+            }
+        }
+
+        Ok(Patched {
+            rom: patched,
+            adjustments,
+            unverifiable,
+            extension,
+        })
+    }
+}
@@ -0,0 +1,266 @@
+//! One batch job: a patch chain applied to one ROM
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use stitchr_core::PatchType;
+use stitchr_formats::copier_header::HeaderLayout;
+use stitchr_formats::detect_format;
+use stitchr_formats::n64_byte_order::OutputOrder;
+
+use super::manifest::Entry;
+use crate::commands::apply::step::{self, StepOptions};
+use crate::commands::dispatch;
+use crate::utils::atomic;
+use crate::utils::paths::{self, TemplateInput};
+use crate::utils::report::{self, FailureKind};
+
+/// A ROM to patch
+#[derive(Debug)]
+pub struct Job {
+    /// Index into the manifest entries
+    pub entry: usize,
+    pub rom: PathBuf,
+}
+
+/// What a successful job produced
+#[derive(Debug)]
+pub struct Outcome {
+    pub output: PathBuf,
+    pub formats: Vec<PatchType>,
+    pub input_crc32: u32,
+    pub output_crc32: u32,
+    pub output_size: usize,
+    /// Sectors whose EDC/ECC was regenerated, when `fix_ecc` applied
+    pub sectors_fixed: Option<usize>,
+    /// Copier header and N64 byte order changes made around the patches
+    pub adjustments: Vec<String>,
+    /// Patches the ROM already had applied
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Apply the entry's patches to the job's ROM and write the result
+///
+/// Each patch goes through the apply command's step: SNES copier headers and
+/// N64 byte order are matched to it and restored afterwards, and a patch the
+/// ROM already has applied is skipped. The output is named from
+/// the entry's template once the patched ROM is known (relative to `base`);
+/// `claimed` holds the outputs of the other jobs of this run.
+pub fn run(
+    job: &Job,
+    entry: &Entry,
+    base: &Path,
+    claimed: &Mutex<HashSet<PathBuf>>,
+) -> Result<Outcome> {
+    let (mut rom, rom_path) = read_input(&job.rom, false)
+        .with_context(|| format!("Failed to read ROM file: {}", job.rom.display()))?;
+    let original = rom.clone();
+
+    let mut formats = Vec::new();
+    let mut adjustments = Vec::new();
+    let mut skipped = Vec::new();
+    let mut title = None;
+    for patch_path in &entry.patches {
+        let (patch, _) = read_input(patch_path, true)
+            .with_context(|| format!("Failed to read patch file: {}", patch_path.display()))?;
+        let patch_type = detect_format(&patch).ok_or_else(|| {
+            report::fail(
+                FailureKind::UnsupportedFormat,
+                format!(
+                    "Could not detect patch format from file header: {}",
+                    patch_path.display()
+                ),
+            )
+        })?;
+        formats.push(patch_type);
+
+        let patch_title = dispatch::patch_metadata(&patch, &patch_type)
+            .ok()
+            .and_then(|m| m.extra.into_iter().find(|(k, _)| k == "title"));
+        if let Some((_, patch_title)) = patch_title {
+            title = Some(patch_title);
+        }
+
+        let mut matched = rom.clone();
+        let options = StepOptions {
+            verify: entry.verify,
+            header_layout: Some(HeaderLayout::Keep),
+            byte_order: Some(OutputOrder::Keep),
+            ..Default::default()
+        };
+        let step = step::prepare(&mut matched, &patch, patch_type, options);
+        if !step.check_state()? {
+            log::info!(
+                "{}: {}, skipping {}",
+                job.rom.display(),
+                step.state,
+                patch_path.display()
+            );
+            skipped.push(patch_path.clone());
+        } else {
+            let patched = step
+                .apply(&matched)
+                .with_context(|| format!("{}", patch_path.display()))?;
+            adjustments.extend(patched.adjustments);
+            rom = patched.rom;
+        }
+    }
+
+    if entry.fix_checksum {
+        #[cfg(feature = "checksum_fix")]
+        stitchr_features::checksum_fix::fix_checksums(&mut rom);
+        #[cfg(not(feature = "checksum_fix"))]
+        anyhow::bail!("fix_checksum requires the checksum_fix feature");
+    }
+
+    let sectors_fixed = if entry.fix_ecc {
+        fix_ecc(&job.rom, &original, &mut rom)?
+    } else {
+        None
+    };
+
+    let last_patch = entry.patches.last().expect("entries have patches");
+    let last_type = *formats.last().expect("entries have patches");
+    let mut vars = paths::template_vars(&TemplateInput {
+        rom_path: &rom_path,
+        patch_path: last_patch,
+        patch_type: last_type,
+        title,
+        output: &rom,
+    });
+    vars.push(("name", paths::sanitize(&entry.name)));
+    let output = base.join(paths::expand_template(&entry.output, &vars)?);
+    #[cfg(feature = "retroachievements")]
+    let output = match entry
+        .console_extension
+        .then(|| paths::console_change_extension(&original, &rom))
+        .flatten()
+    {
+        Some(extension) => output.with_extension(extension),
+        None => output,
+    };
+    if output == job.rom {
+        return Err(report::fail(
+            FailureKind::Usage,
+            "Output path is the input ROM",
+        ));
+    }
+
+    let output = {
+        let mut claimed = claimed.lock().unwrap_or_else(|e| e.into_inner());
+        let output = paths::resolve_existing(&output, entry.on_exists, &claimed)?;
+        claimed.insert(output.clone());
+        output
+    };
+    write(&output, &rom)?;
+
+    Ok(Outcome {
+        output,
+        formats,
+        input_crc32: crc32(&original),
+        output_crc32: crc32(&rom),
+        output_size: rom.len(),
+        sectors_fixed,
+        adjustments,
+        skipped,
+    })
+}
+
+/// Read a ROM or patch, extracting it when it is an archive
+///
+/// Also returns the path outputs are named after: an archive member's name
+/// next to its archive, otherwise `path`.
+fn read_input(path: &Path, is_patch: bool) -> Result<(Vec<u8>, PathBuf)> {
+    #[cfg(feature = "archive")]
+    {
+        use crate::utils::archive::{self, Role};
+
+        let role = if is_patch { Role::Patch } else { Role::Rom };
+        let (data, member) = archive::read(path, role)?;
+        let name_path = member.map_or_else(|| path.to_path_buf(), |m| m.extracted_path());
+        Ok((data, name_path))
+    }
+    #[cfg(not(feature = "archive"))]
+    {
+        let _ = is_patch;
+        Ok((fs::read(path)?, path.to_path_buf()))
+    }
+}
+
+/// Regenerate EDC/ECC of the sectors the patches modified
+///
+/// Returns how many were fixed, or `None` when the ROM is not a raw sector
+/// image and was left as is.
+#[cfg(feature = "disc")]
+fn fix_ecc(rom_path: &Path, original: &[u8], rom: &mut [u8]) -> Result<Option<usize>> {
+    let fixes = crate::utils::edc_ecc::fix(original, rom);
+    if fixes.is_none() {
+        log::warn!(
+            "{}: not a raw 2352-byte sector image; EDC/ECC left unchanged",
+            rom_path.display()
+        );
+    }
+    Ok(fixes.map(|f| f.fixed()))
+}
+
+#[cfg(not(feature = "disc"))]
+fn fix_ecc(_: &Path, _: &[u8], _: &mut [u8]) -> Result<Option<usize>> {
+    anyhow::bail!("fix_ecc requires the disc feature")
+}
+
+/// Create the output's directory, then write it atomically
+fn write(output: &Path, data: &[u8]) -> Result<()> {
+    if let Some(dir) = output.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+    atomic::write_file(output, data, &Default::default())
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    stitchr_features::validation::algorithms::crc32::compute(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::paths::OnExists;
+
+    #[test]
+    fn test_already_applied_patches_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("stitchr-job-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let rom_path = dir.join("game.bin");
+        fs::write(&rom_path, vec![0u8; 1024]).unwrap();
+        let patch_path = dir.join("fix.ips");
+        fs::write(&patch_path, b"PATCH\x00\x00\x10\x00\x02ABEOF").unwrap();
+
+        // The same patch twice: the second finds its bytes already there
+        let entry = Entry {
+            name: "twice".to_string(),
+            rom: rom_path.clone(),
+            patches: vec![patch_path.clone(), patch_path.clone()],
+            output: "{rom_stem}.out".to_string(),
+            verify: false,
+            fix_checksum: false,
+            fix_ecc: false,
+            on_exists: OnExists::Overwrite,
+            console_extension: false,
+        };
+        let job = Job {
+            entry: 0,
+            rom: rom_path,
+        };
+        let outcome = run(&job, &entry, &dir, &Mutex::default()).unwrap();
+
+        assert_eq!(outcome.skipped, vec![patch_path]);
+        assert!(outcome.adjustments.is_empty());
+        let output = fs::read(&outcome.output).unwrap();
+        assert_eq!(&output[0x10..0x12], b"AB");
+        assert_eq!(output.len(), 1024);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+//! Batch manifest loading
+//!
+//! ```toml
+//! jobs = 4                        # worker threads (default: CPU count)
+//! report = "batch.report.json"    # default: {manifest}.report.json
+//!
+//! [[entry]]
+//! rom = "roms/*.sfc"              # path or glob
+//! patches = ["fix.ips", "hack.bps"]
+//! output = "out/{title} ({console}).{ext}"
+//! on_exists = "suffix"            # fail, overwrite (default) or suffix
+//! console_extension = false       # rename when the console changes
+//! verify = true
+//! fix_checksum = false
+//! fix_ecc = false                 # regenerate EDC/ECC of raw CD sectors
+//! ```
+//!
+//! Relative paths are resolved against the manifest's directory.
+//!
+//! Manifests are read with the `toml` crate. Unknown keys and tables are
+//! rejected rather than ignored, and errors give the offending line.
+//!
+//! `rom` globs support `*`, `?` and `**` (see [`crate::utils::glob`]);
+//! brackets and braces match literally, since ROM names such as
+//! `Game (USA) [!].sfc` use them.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use toml::Spanned;
+
+use crate::utils::paths::{DEFAULT_OUTPUT_TEMPLATE, OnExists};
+
+/// A parsed batch manifest
+#[derive(Debug)]
+pub struct Manifest {
+    /// Worker threads
+    pub jobs: Option<usize>,
+    /// Report file
+    pub report: Option<PathBuf>,
+    pub entries: Vec<Entry>,
+}
+
+/// One `[[entry]]`: a ROM (or glob) and the patches to apply to it
+#[derive(Debug)]
+pub struct Entry {
+    /// Label for the summary (defaults to "entry N")
+    pub name: String,
+    /// ROM path or glob pattern
+    pub rom: PathBuf,
+    /// Patches applied in order
+    pub patches: Vec<PathBuf>,
+    /// Output path template
+    pub output: String,
+    /// Verify source/target checksums of every patch
+    pub verify: bool,
+    /// Recompute internal cartridge checksums after patching
+    pub fix_checksum: bool,
+    /// Regenerate EDC/ECC of the raw CD sectors the patches modified
+    pub fix_ecc: bool,
+    /// What to do when the output file already exists
+    pub on_exists: OnExists,
+    /// Use the patched console's extension when the patch changes it
+    pub console_extension: bool,
+}
+
+/// Manifest as written
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    jobs: Option<NonZeroUsize>,
+    report: Option<String>,
+    #[serde(default)]
+    entry: Vec<Spanned<RawEntry>>,
+}
+
+/// One `[[entry]]` as written
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawEntry {
+    name: Option<String>,
+    rom: String,
+    patch: Option<String>,
+    patches: Option<Vec<String>>,
+    output: Option<String>,
+    #[serde(default)]
+    verify: bool,
+    #[serde(default)]
+    fix_checksum: bool,
+    #[serde(default)]
+    fix_ecc: bool,
+    #[serde(default)]
+    on_exists: OnExists,
+    #[serde(default)]
+    console_extension: bool,
+}
+
+/// Read and check a manifest file
+pub fn load(path: &Path) -> Result<Manifest> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read manifest: {}", path.display()))?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    parse(&text, base).with_context(|| format!("Invalid manifest: {}", path.display()))
+}
+
+/// Parse manifest text; relative paths are joined to `base`
+pub fn parse(text: &str, base: &Path) -> Result<Manifest> {
+    let raw: RawManifest = toml::from_str(text)?;
+    if raw.entry.is_empty() {
+        bail!("no [[entry]] tables");
+    }
+
+    let mut entries = Vec::with_capacity(raw.entry.len());
+    for (index, entry) in raw.entry.into_iter().enumerate() {
+        let line = text[..entry.span().start].matches('\n').count() + 1;
+        entries.push(
+            parse_entry(entry.into_inner(), base, index + 1)
+                .with_context(|| format!("entry at line {}", line))?,
+        );
+    }
+
+    Ok(Manifest {
+        jobs: raw.jobs.map(NonZeroUsize::get),
+        report: raw.report.map(|p| base.join(p)),
+        entries,
+    })
+}
+
+fn parse_entry(raw: RawEntry, base: &Path, number: usize) -> Result<Entry> {
+    let patches = match (raw.patch, raw.patches) {
+        (Some(_), Some(_)) => bail!("use either 'patch' or 'patches', not both"),
+        (Some(patch), None) => vec![patch],
+        (None, Some(patches)) => patches,
+        (None, None) => bail!("missing 'patch' or 'patches'"),
+    };
+    if patches.is_empty() {
+        bail!("'patches' is empty");
+    }
+
+    Ok(Entry {
+        name: raw.name.unwrap_or_else(|| format!("entry {}", number)),
+        rom: base.join(raw.rom),
+        patches: patches.iter().map(|p| base.join(p)).collect(),
+        output: raw
+            .output
+            .unwrap_or_else(|| DEFAULT_OUTPUT_TEMPLATE.to_string()),
+        verify: raw.verify,
+        fix_checksum: raw.fix_checksum,
+        fix_ecc: raw.fix_ecc,
+        on_exists: raw.on_exists,
+        console_extension: raw.console_extension,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        format!("{:#}", parse(text, Path::new("")).unwrap_err())
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = parse(
+            r#"
+            # Top-level settings
+            jobs = 4
+            report = "out/report.json" # trailing comment
+
+            [[entry]]
+            rom = 'roms/*.sfc'
+            patches = [
+                "a.ips",  # first
+                "b # not a comment.bps",
+            ]
+            verify = true
+            on_exists = "suffix"
+
+            [[entry]]
+            name = "single"
+            rom = "x.gb"
+            patch = "x.ups"
+            "#,
+            Path::new("base"),
+        )
+        .unwrap();
+
+        assert_eq!(manifest.jobs, Some(4));
+        assert_eq!(manifest.report, Some(PathBuf::from("base/out/report.json")));
+        let [first, second] = &manifest.entries[..] else {
+            panic!("expected two entries");
+        };
+        assert_eq!(first.name, "entry 1");
+        assert_eq!(first.rom, PathBuf::from("base/roms/*.sfc"));
+        assert_eq!(
+            first.patches,
+            [
+                PathBuf::from("base/a.ips"),
+                PathBuf::from("base/b # not a comment.bps")
+            ]
+        );
+        assert!(first.verify && !first.fix_checksum);
+        assert_eq!(first.on_exists, OnExists::Suffix);
+        assert_eq!(first.output, DEFAULT_OUTPUT_TEMPLATE);
+        assert_eq!(second.name, "single");
+        assert_eq!(second.patches, [PathBuf::from("base/x.ups")]);
+        assert_eq!(second.on_exists, OnExists::Overwrite);
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        for (text, line, message) in [
+            ("jobs = 1\nreport = \"open", "line 2", "string"),
+            ("jobs = 1\njobs = 2", "line 2", "duplicate key"),
+            ("jobs = 0", "line 1", "nonzero"),
+            ("jobs = 1.5", "line 1", "float"),
+            ("colour = 1", "line 1", "unknown field `colour`"),
+            ("[entry]\nrom = \"a\"", "line 1", "sequence"),
+            ("[[entry]]\nrom = \"a\"\npatch = 1", "line 3", "string"),
+            (
+                "[[entry]]\nrom = \"a\"\nfix = true",
+                "line 3",
+                "unknown field `fix`",
+            ),
+            (
+                "[[entry]]\nrom = \"a\"\npatch = \"p\"\non_exists = \"skip\"",
+                "line 4",
+                "fail",
+            ),
+            ("[[rom]]\npath = \"a\"", "line 1", "unknown field `rom`"),
+        ] {
+            let err = error(text);
+            assert!(
+                err.contains(line) && err.contains(message),
+                "{:?}: {}",
+                text,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_entry_errors() {
+        assert!(error("jobs = 1").contains("no [[entry]] tables"));
+        for (text, message) in [
+            ("rom = \"a\"", "missing 'patch' or 'patches'"),
+            ("rom = \"a\"\npatch = \"p\"\npatches = [\"q\"]", "not both"),
+            ("rom = \"a\"\npatches = []", "'patches' is empty"),
+            ("patch = \"p\"", "missing field `rom`"),
+        ] {
+            let err = error(&format!("jobs = 1\n\n[[entry]]\n{}", text));
+            assert!(err.contains(message), "{:?}: {}", text, err);
+        }
+        let err = error("jobs = 1\n\n[[entry]]\nrom = \"a\"");
+        assert!(err.starts_with("entry at line 3"), "{}", err);
+    }
+}
@@ -0,0 +1,284 @@
+//! Batch command: apply patch chains to many ROMs from a manifest
+
+mod job;
+mod manifest;
+
+use anyhow::{Context, Result};
+use log::debug;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::utils::atomic;
+use crate::utils::glob;
+use crate::utils::report::{self, FailureKind};
+use job::{Job, Outcome};
+use manifest::Manifest;
+
+/// A planned job, or why an entry produced none
+type Planned = std::result::Result<Job, (usize, PathBuf, anyhow::Error)>;
+
+/// Run every entry of the manifest on a bounded pool of worker threads
+///
+/// Each ROM is its own job: a failure is recorded and the others continue.
+/// Prints a summary table and writes a JSON report; fails if any job did.
+pub fn execute(
+    manifest_path: PathBuf,
+    workers: Option<usize>,
+    report_path: Option<PathBuf>,
+) -> Result<()> {
+    let manifest = manifest::load(&manifest_path)
+        .map_err(|e| report::fail(FailureKind::Usage, format!("{:#}", e)))?;
+    let base = manifest_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .to_path_buf();
+
+    let planned = plan(&manifest);
+    let workers = workers
+        .or(manifest.jobs)
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, planned.len().max(1));
+    debug!("{} jobs on {} workers", planned.len(), workers);
+
+    let results = run_all(&manifest, &planned, &base, workers);
+
+    print_summary(&manifest, &planned, &results);
+
+    let report_path = report_path
+        .or_else(|| manifest.report.clone())
+        .unwrap_or_else(|| default_report_path(&manifest_path));
+    let document = build_report(&manifest_path, &manifest, &planned, &results);
+    atomic::write_file(
+        &report_path,
+        format!("{}\n", document).as_bytes(),
+        &Default::default(),
+    )
+    .with_context(|| format!("Failed to write report: {}", report_path.display()))?;
+    crate::say!("Report: {}", report_path.display());
+    if let Value::Object(fields) = document {
+        for (key, value) in fields {
+            report::set(&key, value);
+        }
+    }
+    report::set("report", report::path(&report_path));
+
+    let failed = results.iter().filter(|r| r.is_err()).count();
+    if failed > 0 {
+        return Err(report::fail(
+            FailureKind::Other,
+            format!("{} of {} batch jobs failed", failed, results.len()),
+        ));
+    }
+    Ok(())
+}
+
+/// Expand ROM globs into jobs; entries whose glob matches nothing fail
+///
+/// Outputs are named once each job has patched its ROM, since templates may
+/// use the patched data (`{crc32}`, `{console}`, ...).
+fn plan(manifest: &Manifest) -> Vec<Planned> {
+    let mut planned = Vec::new();
+    for (index, entry) in manifest.entries.iter().enumerate() {
+        match glob::expand(&entry.rom) {
+            Ok(roms) if roms.is_empty() => {
+                let err = anyhow::anyhow!("No files match {}", entry.rom.display());
+                planned.push(Err((index, entry.rom.clone(), err)));
+            }
+            Ok(roms) => planned.extend(roms.into_iter().map(|rom| Ok(Job { entry: index, rom }))),
+            Err(e) => planned.push(Err((index, entry.rom.clone(), e))),
+        }
+    }
+    planned
+}
+
+/// Run the planned jobs, `workers` at a time; results are in plan order
+fn run_all(
+    manifest: &Manifest,
+    planned: &[Planned],
+    base: &Path,
+    workers: usize,
+) -> Vec<Result<Outcome>> {
+    let claimed = Mutex::new(HashSet::new());
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<Outcome>>>> =
+        Mutex::new((0..planned.len()).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = planned.get(index) else {
+                        break;
+                    };
+                    let result = match item {
+                        Ok(job) => job::run(job, &manifest.entries[job.entry], base, &claimed),
+                        Err((_, _, e)) => {
+                            Err(report::fail(report::classify(e), format!("{:#}", e)))
+                        }
+                    };
+                    let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    let status = if result.is_ok() { "ok" } else { "FAILED" };
+                    crate::say!(
+                        "[{}/{}] {} {}",
+                        finished,
+                        planned.len(),
+                        status,
+                        rom_of(item).display()
+                    );
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err(anyhow::anyhow!("Job did not run"))))
+        .collect()
+}
+
+fn rom_of(item: &Planned) -> &Path {
+    match item {
+        Ok(job) => &job.rom,
+        Err((_, rom, _)) => rom,
+    }
+}
+
+fn entry_of(item: &Planned) -> usize {
+    match item {
+        Ok(job) => job.entry,
+        Err((entry, _, _)) => *entry,
+    }
+}
+
+fn print_summary(manifest: &Manifest, planned: &[Planned], results: &[Result<Outcome>]) {
+    let rom_width = planned
+        .iter()
+        .map(|item| rom_of(item).display().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(3);
+    let entry_width = manifest
+        .entries
+        .iter()
+        .map(|e| e.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+
+    crate::say!();
+    crate::say!(
+        "{:<6}  {:<entry_width$}  {:<rom_width$}  Result",
+        "Status",
+        "Entry",
+        "ROM"
+    );
+    for (item, result) in planned.iter().zip(results) {
+        let (status, detail) = match (item, result) {
+            (Ok(_), Ok(outcome)) => {
+                let mut detail = format!(
+                    "{} (CRC32 {:08x})",
+                    outcome.output.display(),
+                    outcome.output_crc32
+                );
+                if !outcome.skipped.is_empty() {
+                    detail.push_str(&format!(", {} already applied", outcome.skipped.len()));
+                }
+                ("ok", detail)
+            }
+            (_, Err(e)) => ("FAILED", format!("{:#}", e)),
+            (Err(_), Ok(_)) => unreachable!("planning failures never run"),
+        };
+        crate::say!(
+            "{:<6}  {:<entry_width$}  {:<rom_width$}  {}",
+            status,
+            manifest.entries[entry_of(item)].name,
+            rom_of(item).display(),
+            detail
+        );
+    }
+
+    let failed = results.iter().filter(|r| r.is_err()).count();
+    crate::say!();
+    crate::say!(
+        "{} jobs: {} succeeded, {} failed",
+        results.len(),
+        results.len() - failed,
+        failed
+    );
+}
+
+fn build_report(
+    manifest_path: &Path,
+    manifest: &Manifest,
+    planned: &[Planned],
+    results: &[Result<Outcome>],
+) -> Value {
+    let jobs: Vec<Value> = planned
+        .iter()
+        .zip(results)
+        .map(|(item, result)| {
+            let entry = &manifest.entries[entry_of(item)];
+            let patches: Vec<Value> = entry.patches.iter().map(|p| report::path(p)).collect();
+            let mut job = json!({
+                "entry": entry.name,
+                "rom": report::path(rom_of(item)),
+                "patches": patches,
+            });
+            match result {
+                Ok(outcome) => {
+                    let formats: Vec<Value> =
+                        outcome.formats.iter().map(|&f| report::format(f)).collect();
+                    job["status"] = json!("ok");
+                    job["output"] = report::path(&outcome.output);
+                    job["formats"] = formats.into();
+                    job["input_crc32"] = json!(format!("{:08x}", outcome.input_crc32));
+                    job["output_crc32"] = json!(format!("{:08x}", outcome.output_crc32));
+                    job["output_size"] = json!(outcome.output_size);
+                    if let Some(fixed) = outcome.sectors_fixed {
+                        job["sectors_fixed"] = json!(fixed);
+                    }
+                    if !outcome.adjustments.is_empty() {
+                        job["adjustments"] = json!(outcome.adjustments);
+                    }
+                    if !outcome.skipped.is_empty() {
+                        let skipped: Vec<Value> =
+                            outcome.skipped.iter().map(|p| report::path(p)).collect();
+                        job["skipped"] = skipped.into();
+                    }
+                }
+                Err(e) => {
+                    let kind = report::classify(e);
+                    job["status"] = json!("error");
+                    job["error"] = json!({ "kind": kind.id(), "message": format!("{:#}", e) });
+                }
+            }
+            job
+        })
+        .collect();
+
+    let failed = results.iter().filter(|r| r.is_err()).count();
+    json!({
+        "manifest": report::path(manifest_path),
+        "succeeded": results.len() - failed,
+        "failed": failed,
+        "jobs": jobs,
+    })
+}
+
+/// `{manifest_dir}/{manifest_stem}.report.json`
+fn default_report_path(manifest_path: &Path) -> PathBuf {
+    let stem = manifest_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "batch".to_string());
+    manifest_path.with_file_name(format!("{}.report.json", stem))
+}
@@ -0,0 +1,246 @@
+//! Disc command: list, extract and replace files inside ISO9660 disc images
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_features::disc::{
+    BlockDevice, DiscImage, ImageFormat, Iso9660Reader, Overlay, WritableDevice, copy_to,
+    replace_file,
+};
+
+use super::apply::input;
+use crate::utils::atomic;
+use crate::utils::cue;
+use crate::utils::paths::{self, OnExists};
+use crate::utils::report::{self, FailureKind};
+
+/// Print the directory tree of a disc image
+pub fn list(image_path: PathBuf) -> Result<()> {
+    let image = open(&image_path)?;
+    let iso =
+        Iso9660Reader::new(&image).map_err(|e| report::fail(FailureKind::UnsupportedFormat, e))?;
+    let entries = iso.list().map_err(anyhow::Error::msg)?;
+
+    crate::say!("{:>8}  {:>10}  Path", "LBA", "Size");
+    for entry in &entries {
+        if entry.is_dir {
+            crate::say!("{:>8}  {:>10}  {}/", entry.lba, "-", entry.path);
+        } else {
+            crate::say!("{:>8}  {:>10}  {}", entry.lba, entry.size, entry.path);
+        }
+        report::push(
+            "entries",
+            json!({
+                "path": entry.path,
+                "directory": entry.is_dir,
+                "lba": entry.lba,
+                "size": entry.size,
+            }),
+        );
+    }
+    let dirs = entries.iter().filter(|entry| entry.is_dir).count();
+    crate::say!(
+        "{} file(s) in {} director(ies)",
+        entries.len() - dirs,
+        dirs + 1
+    );
+    if entries.is_empty() {
+        report::set("entries", json!([]));
+    }
+    Ok(())
+}
+
+/// Copy a file out of a disc image
+///
+/// Written next to the image under its own name unless `output_path` is
+/// given; names read from the disc are sanitized so they cannot point
+/// elsewhere.
+pub fn extract(
+    image_path: PathBuf,
+    path: String,
+    output_path: Option<PathBuf>,
+    on_exists: OnExists,
+) -> Result<()> {
+    let image = open(&image_path)?;
+    let iso =
+        Iso9660Reader::new(&image).map_err(|e| report::fail(FailureKind::UnsupportedFormat, e))?;
+    let entry = iso
+        .find(&path)
+        .map_err(|e| report::fail(FailureKind::Usage, e))?;
+    let data = iso.read(&entry).map_err(anyhow::Error::msg)?;
+
+    let output_path = output_path.unwrap_or_else(|| {
+        let name = paths::sanitize(entry.name());
+        image_path.with_file_name(if name.is_empty() {
+            "_".to_string()
+        } else {
+            name
+        })
+    });
+    let output_path = paths::resolve_existing(&output_path, on_exists, &Default::default())?;
+    atomic::write_file(&output_path, &data, &Default::default())?;
+    crate::say!(
+        "Extracted {} ({} bytes) to {}",
+        entry.path,
+        data.len(),
+        output_path.display()
+    );
+    report::set("entry", entry.path.as_str());
+    report::set("output", report::file(&output_path, &data));
+    Ok(())
+}
+
+/// Replace a file inside a disc image with `file_path`
+pub fn replace(
+    image_path: PathBuf,
+    path: String,
+    file_path: PathBuf,
+    output_path: Option<PathBuf>,
+    on_exists: OnExists,
+) -> Result<()> {
+    let data = fs::read(&file_path)
+        .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+    report::set("file", report::file(&file_path, &data));
+    modify(&image_path, &path, output_path, on_exists, |_| Ok(data))
+}
+
+/// Apply a patch made against a single file of a disc image to that file
+pub fn patch(
+    image_path: PathBuf,
+    path: String,
+    patch_path: PathBuf,
+    output_path: Option<PathBuf>,
+    on_exists: OnExists,
+) -> Result<()> {
+    let patch = input::load_patch_with_checksum(&patch_path)?.data;
+    let patch_type = super::dispatch::detect_patch_format(&patch)?;
+    crate::say!(
+        "Detected format: {} ({})",
+        patch_type.name(),
+        patch_type.extension()
+    );
+    report::set("format", report::format(patch_type));
+
+    modify(&image_path, &path, output_path, on_exists, |file| {
+        let mut file = file.to_vec();
+        super::dispatch::apply_patch(&mut file, &patch, &patch_type)?;
+        Ok(file)
+    })
+}
+
+/// Open an image for reading, decoding CSO, ZSO and CHD block by block
+fn open(image_path: &Path) -> Result<DiscImage> {
+    report::set("image", report::path(image_path));
+    DiscImage::open_path(image_path).map_err(|e| report::fail(FailureKind::UnsupportedFormat, e))
+}
+
+/// Replace the file at `path` with what `contents` makes of it and write
+/// the disc out uncompressed
+///
+/// Only the rewritten sectors are held in memory; the rest of the disc is
+/// streamed from the input to the output.
+fn modify(
+    image_path: &Path,
+    path: &str,
+    output_path: Option<PathBuf>,
+    on_exists: OnExists,
+    contents: impl FnOnce(&[u8]) -> Result<Vec<u8>>,
+) -> Result<()> {
+    crate::say!("Loading disc: {}", image_path.display());
+    if cue::is_cue(image_path) {
+        report::set("image", report::path(image_path));
+        let (cue, device) =
+            cue::open(image_path).map_err(|e| report::fail(FailureKind::UnsupportedFormat, e))?;
+        let mut disc = Overlay::new(device);
+        replace_in(&mut disc, path, contents)?;
+        let output_path = resolve_output(output_path, image_path, on_exists)?;
+        let bins = cue::write_device(&cue, &disc, &output_path, on_exists)?;
+        return report_output(&output_path, disc.size(), &bins);
+    }
+
+    let image = open(image_path)?;
+    let name_path = match image.format {
+        ImageFormat::Plain => image_path.to_path_buf(),
+        _ => image_path.with_extension(image.extension),
+    };
+    let mut disc = Overlay::new(image);
+    replace_in(&mut disc, path, contents)?;
+    let output_path = resolve_output(output_path, &name_path, on_exists)?;
+    atomic::write_with(&output_path, &Default::default(), |file| {
+        copy_to(&disc, 0..disc.size(), file).map_err(anyhow::Error::msg)
+    })?;
+    report_output(&output_path, disc.size(), &[])
+}
+
+/// Replace the file at `path` of `disc` and report the change
+fn replace_in<D: WritableDevice>(
+    disc: &mut D,
+    path: &str,
+    contents: impl FnOnce(&[u8]) -> Result<Vec<u8>>,
+) -> Result<()> {
+    let current = {
+        let iso = Iso9660Reader::new(&*disc)
+            .map_err(|e| report::fail(FailureKind::UnsupportedFormat, e))?;
+        let entry = iso
+            .find(path)
+            .map_err(|e| report::fail(FailureKind::Usage, e))?;
+        iso.read(&entry)
+            .map_err(|e| report::fail(FailureKind::Usage, e))?
+    };
+    let new_contents = contents(&current)?;
+    let replaced = replace_file(disc, path, &new_contents)
+        .map_err(|e| report::fail(FailureKind::Verification, e))?;
+
+    crate::say!(
+        "Replaced {}: {} -> {} bytes ({} allocated, {} sector(s) rewritten)",
+        replaced.path,
+        replaced.old_size,
+        replaced.new_size,
+        replaced.allocated,
+        replaced.sectors
+    );
+    report::set(
+        "replaced",
+        json!({
+            "path": replaced.path,
+            "old_size": replaced.old_size,
+            "new_size": replaced.new_size,
+            "allocated": replaced.allocated,
+        }),
+    );
+    Ok(())
+}
+
+/// The output path, `{image_dir}/patched/{stem}.patched.{ext}` by default,
+/// with `--on-exists` applied
+fn resolve_output(
+    output_path: Option<PathBuf>,
+    name_path: &Path,
+    on_exists: OnExists,
+) -> Result<PathBuf> {
+    let output_path = match output_path {
+        Some(path) => path,
+        None => paths::generate_default_output(name_path)?,
+    };
+    paths::resolve_existing(&output_path, on_exists, &Default::default())
+}
+
+fn report_output(output_path: &Path, size: u64, bins: &[PathBuf]) -> Result<()> {
+    if bins.is_empty() {
+        crate::say!("Successfully patched: {}", output_path.display());
+    } else {
+        crate::say!(
+            "Successfully patched: {} ({} BIN file{})",
+            output_path.display(),
+            bins.len(),
+            if bins.len() == 1 { "" } else { "s" }
+        );
+    }
+    let mut output = json!({ "path": report::path(output_path), "size": size });
+    if !bins.is_empty() {
+        output["files"] = bins.iter().map(|path| report::path(path)).collect();
+    }
+    report::set("output", output);
+    Ok(())
+}
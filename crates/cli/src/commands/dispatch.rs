@@ -1,12 +1,36 @@
 //! Format dispatch logic for applying patches
 
+use crate::utils::report::{self, FailureKind};
 use anyhow::Result;
-use stitchr_core::{PatchFormat, PatchType};
+use stitchr_core::{LintWarning, PatchFormat, PatchMetadata, PatchType};
 use stitchr_formats::{
-    aps::ApsPatcher, bdf::BdfPatcher, bps::BpsPatcher, ebp::EbpPatcher, ips::IpsPatcher,
-    ppf::PpfPatcher, rup::RupPatcher, ups::UpsPatcher, xdelta::XdeltaPatcher,
+    aps::ApsPatcher,
+    bdf::BdfPatcher,
+    bpm::BpmPatcher,
+    bps::BpsPatcher,
+    detect_format,
+    ebp::EbpPatcher,
+    gdiff::GdiffPatcher,
+    ips::{Ips32Patcher, IpsPatcher},
+    pmsr::PmsrPatcher,
+    ppf::PpfPatcher,
+    rup::RupPatcher,
+    ups::UpsPatcher,
+    xdelta::XdeltaPatcher,
 };
 
+/// Detect the patch format and record it in the report
+pub fn detect_patch_format(patch: &[u8]) -> Result<PatchType> {
+    let patch_type = detect_format(patch).ok_or_else(|| {
+        report::fail(
+            FailureKind::UnsupportedFormat,
+            "Could not detect patch format from file header",
+        )
+    })?;
+    report::set("format", report::format(patch_type));
+    Ok(patch_type)
+}
+
 /// Apply patch based on detected format
 pub fn apply_patch(rom: &mut Vec<u8>, patch: &[u8], patch_type: &PatchType) -> Result<()> {
     match patch_type {
@@ -14,10 +38,18 @@ pub fn apply_patch(rom: &mut Vec<u8>, patch: &[u8], patch_type: &PatchType) -> R
             let patcher = IpsPatcher;
             patcher.apply(rom, patch)?;
         }
+        PatchType::Ips32 => {
+            let patcher = Ips32Patcher;
+            patcher.apply(rom, patch)?;
+        }
         PatchType::Bps => {
             let patcher = BpsPatcher;
             patcher.apply(rom, patch)?;
         }
+        PatchType::Bpm => {
+            let patcher = BpmPatcher;
+            patcher.apply(rom, patch)?;
+        }
         PatchType::Ups => {
             let patcher = UpsPatcher;
             patcher.apply(rom, patch)?;
@@ -46,7 +78,75 @@ pub fn apply_patch(rom: &mut Vec<u8>, patch: &[u8], patch_type: &PatchType) -> R
             let patcher = BdfPatcher;
             patcher.apply(rom, patch)?;
         }
+        PatchType::Pmsr => {
+            let patcher = PmsrPatcher;
+            patcher.apply(rom, patch)?;
+        }
+        PatchType::Gdiff => {
+            let patcher = GdiffPatcher;
+            patcher.apply(rom, patch)?;
+        }
     }
 
     Ok(())
 }
+
+/// Extract patch metadata based on detected format
+pub fn patch_metadata(patch: &[u8], patch_type: &PatchType) -> Result<PatchMetadata> {
+    let metadata = match patch_type {
+        PatchType::Ips => IpsPatcher::metadata(patch)?,
+        PatchType::Ips32 => Ips32Patcher::metadata(patch)?,
+        PatchType::Bps => BpsPatcher::metadata(patch)?,
+        PatchType::Bpm => BpmPatcher::metadata(patch)?,
+        PatchType::Ups => UpsPatcher::metadata(patch)?,
+        PatchType::Aps => ApsPatcher::metadata(patch)?,
+        PatchType::Ebp => EbpPatcher::metadata(patch)?,
+        PatchType::Rup => RupPatcher::metadata(patch)?,
+        PatchType::Ppf => PpfPatcher::metadata(patch)?,
+        PatchType::Xdelta => XdeltaPatcher::metadata(patch)?,
+        PatchType::Bdf => BdfPatcher::metadata(patch)?,
+        PatchType::Pmsr => PmsrPatcher::metadata(patch)?,
+        PatchType::Gdiff => GdiffPatcher::metadata(patch)?,
+    };
+    Ok(metadata)
+}
+
+/// Validate patch structure based on detected format
+pub fn validate_patch(patch: &[u8], patch_type: &PatchType) -> Result<()> {
+    match patch_type {
+        PatchType::Ips => IpsPatcher::validate(patch)?,
+        PatchType::Ips32 => Ips32Patcher::validate(patch)?,
+        PatchType::Bps => BpsPatcher::validate(patch)?,
+        PatchType::Bpm => BpmPatcher::validate(patch)?,
+        PatchType::Ups => UpsPatcher::validate(patch)?,
+        PatchType::Aps => ApsPatcher::validate(patch)?,
+        PatchType::Ebp => EbpPatcher::validate(patch)?,
+        PatchType::Rup => RupPatcher::validate(patch)?,
+        PatchType::Ppf => PpfPatcher::validate(patch)?,
+        PatchType::Xdelta => XdeltaPatcher::validate(patch)?,
+        PatchType::Bdf => BdfPatcher::validate(patch)?,
+        PatchType::Pmsr => PmsrPatcher::validate(patch)?,
+        PatchType::Gdiff => GdiffPatcher::validate(patch)?,
+    }
+    Ok(())
+}
+
+/// Lint a patch based on detected format
+pub fn lint_patch(patch: &[u8], patch_type: &PatchType) -> Result<Vec<LintWarning>> {
+    let warnings = match patch_type {
+        PatchType::Ips => IpsPatcher::lint(patch)?,
+        PatchType::Ips32 => Ips32Patcher::lint(patch)?,
+        PatchType::Bps => BpsPatcher::lint(patch)?,
+        PatchType::Bpm => BpmPatcher::lint(patch)?,
+        PatchType::Ups => UpsPatcher::lint(patch)?,
+        PatchType::Aps => ApsPatcher::lint(patch)?,
+        PatchType::Ebp => EbpPatcher::lint(patch)?,
+        PatchType::Rup => RupPatcher::lint(patch)?,
+        PatchType::Ppf => PpfPatcher::lint(patch)?,
+        PatchType::Xdelta => XdeltaPatcher::lint(patch)?,
+        PatchType::Bdf => BdfPatcher::lint(patch)?,
+        PatchType::Pmsr => PmsrPatcher::lint(patch)?,
+        PatchType::Gdiff => GdiffPatcher::lint(patch)?,
+    };
+    Ok(warnings)
+}
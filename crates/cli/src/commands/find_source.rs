@@ -0,0 +1,121 @@
+//! Find-source command: locate the ROM a patch was made for
+
+use anyhow::{Context, Result};
+use log::debug;
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use stitchr_formats::copier_header::HeaderLayout;
+use stitchr_formats::find_source::{Confidence, SourceMatch, find_source};
+use stitchr_formats::n64_byte_order::OutputOrder;
+
+use crate::utils::report::{self, FailureKind};
+
+/// Scan `dir` for ROMs matching the patch's recorded source and list them
+///
+/// With `apply`, the patch is applied to the match right away; this needs
+/// exactly one exact match so a plausible guess is never patched silently.
+pub fn execute(
+    patch_path: PathBuf,
+    dir: PathBuf,
+    apply: bool,
+    output_path: Option<PathBuf>,
+) -> Result<()> {
+    let patch_data = fs::read(&patch_path)
+        .with_context(|| format!("Failed to read patch file: {}", patch_path.display()))?;
+    report::set("patch", report::file(&patch_path, &patch_data));
+    let patch_type = super::dispatch::detect_patch_format(&patch_data)?;
+    debug!(
+        "Scanning {} for a {} source",
+        dir.display(),
+        patch_type.name()
+    );
+
+    let matches = find_source(&patch_data, patch_type, &dir)
+        .with_context(|| format!("Failed to search {}", dir.display()))?;
+    report::set(
+        "candidates",
+        matches
+            .iter()
+            .map(|found| {
+                json!({
+                    "path": report::path(&found.path),
+                    "confidence": confidence(found),
+                    "detail": found.detail,
+                })
+            })
+            .collect::<Vec<_>>(),
+    );
+    if matches.is_empty() {
+        return Err(report::fail(
+            FailureKind::Other,
+            format!("No source ROM for this patch found under {}", dir.display()),
+        ));
+    }
+
+    crate::say!("Source candidates for {}:", patch_path.display());
+    for found in &matches {
+        crate::say!("  {}", describe(found));
+    }
+
+    if !apply {
+        return Ok(());
+    }
+
+    let exact: Vec<&SourceMatch> = matches
+        .iter()
+        .filter(|m| m.confidence == Confidence::Exact)
+        .collect();
+    let [source] = exact.as_slice() else {
+        return Err(report::fail(
+            FailureKind::Other,
+            format!(
+                "--apply needs exactly one exact match, found {}; apply the patch to the right \
+                 ROM manually",
+                exact.len()
+            ),
+        ));
+    };
+
+    crate::say!();
+    super::apply::execute(
+        source.path.clone(),
+        Some(patch_path),
+        output_path,
+        super::apply::ApplyOptions {
+            verify: false,
+            only_modes: Vec::new(),
+            header_layout: Some(HeaderLayout::Keep),
+            byte_order: Some(OutputOrder::Keep),
+            fix_checksum: false,
+            fix_ecc: false,
+            dats: Vec::new(),
+            revert: false,
+            refuse_applied: false,
+            output_template: None,
+            on_exists: Default::default(),
+            console_extension: false,
+            in_place: false,
+            preserve: false,
+            zip: false,
+        },
+    )
+}
+
+/// "exact" or the plausibility percentage
+fn confidence(found: &SourceMatch) -> String {
+    match found.confidence {
+        Confidence::Exact => "exact".to_string(),
+        Confidence::Plausible(percent) => format!("{}%", percent),
+    }
+}
+
+/// One result line: confidence, path and what matched
+fn describe(found: &SourceMatch) -> String {
+    format!(
+        "[{}] {} ({})",
+        confidence(found),
+        found.path.display(),
+        found.detail
+    )
+}
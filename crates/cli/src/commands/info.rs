@@ -0,0 +1,160 @@
+//! Info command: show everything a patch says about itself
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use stitchr_core::PatchMetadata;
+use stitchr_formats::record_summary::{RecordSummary, summarize};
+
+use crate::utils::report;
+
+/// Print format, validation result, metadata and record summary of a patch
+///
+/// Fails if the patch does not pass its format's validation, after printing
+/// whatever could still be read.
+pub fn execute(patch_path: PathBuf) -> Result<()> {
+    #[cfg(feature = "archive")]
+    let patch = crate::utils::archive::read(&patch_path, crate::utils::archive::Role::Patch)
+        .map(|(patch, _)| patch);
+    #[cfg(not(feature = "archive"))]
+    let patch = std::fs::read(&patch_path);
+    let patch =
+        patch.with_context(|| format!("Failed to read patch file: {}", patch_path.display()))?;
+    report::set("patch", report::file(&patch_path, &patch));
+    let patch_type = super::dispatch::detect_patch_format(&patch)?;
+
+    crate::say!("{}", patch_path.display());
+    field("Format", patch_type.name());
+    field("Patch size", &format!("{} bytes", patch.len()));
+
+    let validation = super::dispatch::validate_patch(&patch, &patch_type);
+    report::set("valid", validation.is_ok());
+    match &validation {
+        Ok(()) => field("Valid", "yes"),
+        Err(e) => field("Valid", &format!("no ({:#})", e)),
+    }
+
+    match super::dispatch::patch_metadata(&patch, &patch_type) {
+        Ok(metadata) => {
+            print_metadata(&metadata);
+            report::set("metadata", metadata_json(&metadata));
+        }
+        Err(e) => field("Metadata", &format!("unavailable ({:#})", e)),
+    }
+
+    match summarize(&patch, patch_type) {
+        Ok(Some(summary)) => {
+            print_summary(&summary);
+            report::set(
+                "records",
+                json!({
+                    "count": summary.records,
+                    "bytes_changed": summary.bytes_changed,
+                    "highest_offset": summary.highest_offset,
+                }),
+            );
+        }
+        Ok(None) => field("Records", "not listed for this format"),
+        Err(e) => field("Records", &format!("unreadable ({})", e)),
+    }
+
+    validation.context("Patch failed validation")
+}
+
+fn print_metadata(metadata: &PatchMetadata) {
+    if let Some(size) = metadata.source_size {
+        field("Source size", &format!("{} bytes", size));
+    }
+    if let Some(size) = metadata.target_size {
+        field("Target size", &format!("{} bytes", size));
+    }
+    if let Some(checksum) = &metadata.source_checksum {
+        let (kind, value) = checksum_text(checksum);
+        field(&format!("Source {}", kind), &value);
+    }
+    if let Some(checksum) = &metadata.target_checksum {
+        let (kind, value) = checksum_text(checksum);
+        field(&format!("Target {}", kind), &value);
+    }
+
+    for (key, value) in &metadata.extra {
+        let value = value.trim_end();
+        if value.is_empty() {
+            continue;
+        }
+        if value.contains('\n') {
+            crate::say!("  {}:", label(key));
+            for line in value.lines() {
+                crate::say!("    {}", line.trim_end());
+            }
+        } else {
+            field(&label(key), value);
+        }
+    }
+}
+
+fn metadata_json(metadata: &PatchMetadata) -> Value {
+    let checksum = |checksum: &Option<Vec<u8>>| {
+        checksum.as_deref().map(|checksum| {
+            let (kind, value) = checksum_text(checksum);
+            json!({ "kind": kind, "value": value.to_lowercase() })
+        })
+    };
+    let extra = metadata
+        .extra
+        .iter()
+        .filter(|(_, value)| !value.trim_end().is_empty())
+        .map(|(key, value)| (key.clone(), Value::from(value.trim_end())))
+        .collect();
+    json!({
+        "source_size": metadata.source_size,
+        "target_size": metadata.target_size,
+        "source_checksum": checksum(&metadata.source_checksum),
+        "target_checksum": checksum(&metadata.target_checksum),
+        "extra": Value::Object(extra),
+    })
+}
+
+fn print_summary(summary: &RecordSummary) {
+    field("Records", &summary.records.to_string());
+    field("Bytes changed", &summary.bytes_changed.to_string());
+    if let Some(offset) = summary.highest_offset {
+        field("Highest offset", &format!("0x{:X}", offset));
+    }
+}
+
+fn field(name: &str, value: &str) {
+    crate::say!("  {:<18}{}", format!("{}:", name), value);
+}
+
+/// Checksum kind and value; 4-byte checksums are little-endian CRC32s
+fn checksum_text(checksum: &[u8]) -> (&'static str, String) {
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02X}", b)).collect();
+    match checksum.len() {
+        4 => (
+            "CRC32",
+            format!(
+                "{:08X}",
+                u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]])
+            ),
+        ),
+        16 => ("MD5", hex(checksum)),
+        _ => ("checksum", hex(checksum)),
+    }
+}
+
+/// Display label for a metadata key ("file_id_diz" -> "FILE_ID.DIZ")
+fn label(key: &str) -> String {
+    match key {
+        "metadata" => "Manifest".to_string(),
+        "file_id_diz" => "FILE_ID.DIZ".to_string(),
+        _ => {
+            let text = key.replace('_', " ");
+            let mut chars = text.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Lint command: report suspicious or non-canonical patch constructs
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::PathBuf;
+use stitchr_core::Severity;
+use stitchr_formats::detect_format;
+
+use crate::utils::report::{self, FailureKind};
+
+/// Lint each patch and print its findings
+///
+/// Fails when a patch is invalid or has error-level findings; warnings and
+/// notes alone do not.
+pub fn execute(patch_paths: Vec<PathBuf>) -> Result<()> {
+    let mut failed = 0;
+
+    for path in &patch_paths {
+        let patch = fs::read(path)
+            .with_context(|| format!("Failed to read patch file: {}", path.display()))?;
+        let mut entry = json!({ "path": report::path(path) });
+        let Some(patch_type) = detect_format(&patch) else {
+            crate::say!("{}: unknown patch format", path.display());
+            entry["format"] = Value::Null;
+            report::push("patches", entry);
+            failed += 1;
+            continue;
+        };
+        crate::say!("{} ({})", path.display(), patch_type.name());
+        entry["format"] = report::format(patch_type);
+
+        match super::dispatch::lint_patch(&patch, &patch_type) {
+            Ok(warnings) => {
+                if warnings.is_empty() {
+                    crate::say!("  No issues found");
+                }
+                for warning in &warnings {
+                    crate::say!("  {}", warning);
+                }
+                if warnings.iter().any(|w| w.severity == Severity::Error) {
+                    failed += 1;
+                }
+                let findings: Vec<Value> = warnings
+                    .iter()
+                    .map(|w| {
+                        json!({
+                            "offset": w.offset,
+                            "severity": w.severity.name(),
+                            "message": w.message,
+                        })
+                    })
+                    .collect();
+                entry["valid"] = json!(true);
+                entry["findings"] = findings.into();
+            }
+            Err(e) => {
+                crate::say!("  Invalid patch: {:#}", e);
+                entry["valid"] = json!(false);
+                entry["error"] = json!(format!("{:#}", e));
+                failed += 1;
+            }
+        }
+        report::push("patches", entry);
+    }
+
+    if failed > 0 {
+        return Err(report::fail(
+            FailureKind::InvalidPatch,
+            format!("{} of {} patches have errors", failed, patch_paths.len()),
+        ));
+    }
+    Ok(())
+}
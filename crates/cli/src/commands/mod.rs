@@ -1,7 +1,15 @@
 //! Command implementation
 //!
-//! CLI provides a single command for applying patches to ROMs.
+//! The default command applies a patch to a ROM; subcommands cover the
+//! other operations.
 
 pub mod apply;
+pub mod batch;
+#[cfg(feature = "disc")]
+pub mod disc;
 pub mod dispatch;
+pub mod find_source;
+pub mod info;
+pub mod lint;
+pub mod optimize;
 pub mod verify;
@@ -0,0 +1,102 @@
+//! Optimize command: re-encode a patch into its minimal form
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_core::PatchType;
+use stitchr_formats::optimize::{can_optimize, optimize};
+
+use crate::utils::atomic;
+use crate::utils::report::{self, FailureKind};
+
+/// Optimize a patch and write it next to the original (or to `output_path`)
+///
+/// The optimized patch is only written after applying both patches gave the
+/// same ROM.
+pub fn execute(
+    patch_path: PathBuf,
+    output_path: Option<PathBuf>,
+    source_path: Option<PathBuf>,
+    drop_undo: bool,
+) -> Result<()> {
+    let patch = fs::read(&patch_path)
+        .with_context(|| format!("Failed to read patch file: {}", patch_path.display()))?;
+    report::set("patch", report::file(&patch_path, &patch));
+    let patch_type = super::dispatch::detect_patch_format(&patch)?;
+    if !can_optimize(patch_type) {
+        return Err(report::fail(
+            FailureKind::UnsupportedFormat,
+            format!(
+                "{} patches cannot be optimized (supported: IPS, IPS32, PPF)",
+                patch_type.name()
+            ),
+        ));
+    }
+    if drop_undo && patch_type != PatchType::Ppf {
+        return Err(report::fail(
+            FailureKind::Usage,
+            "--drop-undo only applies to PPF patches",
+        ));
+    }
+
+    let source = source_path
+        .as_ref()
+        .map(|path| {
+            fs::read(path).with_context(|| format!("Failed to read source ROM: {}", path.display()))
+        })
+        .transpose()?;
+    if let (Some(path), Some(data)) = (&source_path, &source) {
+        report::set("source", report::file(path, data));
+    }
+
+    let optimized = optimize(&patch, patch_type, source.as_deref(), drop_undo)
+        .context("Failed to optimize patch")?;
+
+    report::set("original_size", optimized.original_size);
+    report::set("optimized_size", optimized.optimized_size());
+    report::set("saved", optimized.saved());
+    crate::say!(
+        "{} ({}): {} -> {} bytes",
+        patch_path.display(),
+        patch_type.name(),
+        optimized.original_size,
+        optimized.optimized_size()
+    );
+    if optimized.patch == patch {
+        crate::say!("Patch is already minimal, nothing written");
+        return Ok(());
+    }
+    crate::say!(
+        "Saved {} bytes ({:.1}%); output verified against the original patch",
+        optimized.saved(),
+        optimized.saved() as f64 * 100.0 / optimized.original_size as f64
+    );
+    if let Some(path) = &source_path {
+        crate::say!(
+            "Unchanged writes dropped: only valid for {}",
+            path.display()
+        );
+    }
+
+    let output_path = match output_path {
+        Some(path) => path,
+        None => default_output(&patch_path)?,
+    };
+    atomic::write_file(&output_path, &optimized.patch, &Default::default())?;
+    crate::say!("Wrote {}", output_path.display());
+    report::set("output", report::file(&output_path, &optimized.patch));
+    Ok(())
+}
+
+/// `{dir}/{stem}.optimized.{ext}` next to the patch
+fn default_output(patch_path: &Path) -> Result<PathBuf> {
+    let stem = patch_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .context("Patch file has invalid filename")?;
+    let name = match patch_path.extension().and_then(|s| s.to_str()) {
+        Some(ext) => format!("{}.optimized.{}", stem, ext),
+        None => format!("{}.optimized", stem),
+    };
+    Ok(patch_path.with_file_name(name))
+}
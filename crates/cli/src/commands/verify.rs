@@ -1,14 +1,21 @@
 //! Checksum verification for patches
 
+use crate::utils::report;
 use anyhow::Result;
 use stitchr_core::{PatchFormat, PatchType};
 use stitchr_formats::{
-    aps::ApsPatcher, bps::BpsPatcher, ebp::EbpPatcher, ips::IpsPatcher, rup::RupPatcher,
+    aps::ApsPatcher,
+    bpm::BpmPatcher,
+    bps::BpsPatcher,
+    ebp::EbpPatcher,
+    ips::{Ips32Patcher, IpsPatcher},
+    pmsr::PmsrPatcher,
+    rup::RupPatcher,
     ups::UpsPatcher,
 };
 
 /// Dispatch verify() calls to appropriate format
-fn dispatch_verify(
+pub fn dispatch_verify(
     rom: &[u8],
     patch: &[u8],
     patch_type: &PatchType,
@@ -16,11 +23,13 @@ fn dispatch_verify(
 ) -> Result<()> {
     match patch_type {
         PatchType::Ips => IpsPatcher::verify(rom, patch, target)?,
+        PatchType::Ips32 => Ips32Patcher::verify(rom, patch, target)?,
         PatchType::Bps => BpsPatcher::verify(rom, patch, target)?,
         PatchType::Ups => UpsPatcher::verify(rom, patch, target)?,
         PatchType::Aps => ApsPatcher::verify(rom, patch, target)?,
         PatchType::Ebp => EbpPatcher::verify(rom, patch, target)?,
         PatchType::Rup => RupPatcher::verify(rom, patch, target)?,
+        PatchType::Pmsr => PmsrPatcher::verify(rom, patch, target)?,
         _ => anyhow::bail!("Format {} does not support verification", patch_type.name()),
     }
     Ok(())
@@ -30,11 +39,14 @@ fn dispatch_verify(
 fn dispatch_validate(patch: &[u8], patch_type: &PatchType) -> Result<()> {
     match patch_type {
         PatchType::Ips => IpsPatcher::validate(patch)?,
+        PatchType::Ips32 => Ips32Patcher::validate(patch)?,
         PatchType::Bps => BpsPatcher::validate(patch)?,
+        PatchType::Bpm => BpmPatcher::validate(patch)?,
         PatchType::Ups => UpsPatcher::validate(patch)?,
         PatchType::Aps => ApsPatcher::validate(patch)?,
         PatchType::Ebp => EbpPatcher::validate(patch)?,
         PatchType::Rup => RupPatcher::validate(patch)?,
+        PatchType::Pmsr => PmsrPatcher::validate(patch)?,
         _ => anyhow::bail!("Format {} does not support verification", patch_type.name()),
     }
     Ok(())
@@ -42,13 +54,13 @@ fn dispatch_validate(patch: &[u8], patch_type: &PatchType) -> Result<()> {
 
 /// Verify source ROM checksum against patch
 pub fn verify_source(rom: &[u8], patch: &[u8], patch_type: &PatchType) -> Result<()> {
-    println!("Validating patch integrity...");
-    dispatch_validate(patch, patch_type)?;
-    println!("Patch integrity verified!");
+    crate::say!("Validating patch integrity...");
+    record("patch", dispatch_validate(patch, patch_type))?;
+    crate::say!("Patch integrity verified!");
 
-    println!("Verifying source ROM checksum...");
-    dispatch_verify(rom, patch, patch_type, None)?;
-    println!("Source ROM checksum verified!");
+    crate::say!("Verifying source ROM checksum...");
+    record("source", dispatch_verify(rom, patch, patch_type, None))?;
+    crate::say!("Source ROM checksum verified!");
     Ok(())
 }
 
@@ -59,8 +71,18 @@ pub fn verify_target(
     patch: &[u8],
     patch_type: &PatchType,
 ) -> Result<()> {
-    println!("Verifying target ROM checksum...");
-    dispatch_verify(source_rom, patch, patch_type, Some(target_rom))?;
-    println!("Target ROM checksum verified!");
+    crate::say!("Verifying target ROM checksum...");
+    record(
+        "target",
+        dispatch_verify(source_rom, patch, patch_type, Some(target_rom)),
+    )?;
+    crate::say!("Target ROM checksum verified!");
     Ok(())
 }
+
+/// Record the outcome of a check under `verification` in the report
+fn record(check: &str, result: Result<()>) -> Result<()> {
+    let outcome = if result.is_ok() { "passed" } else { "failed" };
+    report::set_in("verification", check, outcome);
+    result
+}
@@ -3,13 +3,17 @@
 //! A minimal CLI for applying ROM patches with automatic validation.
 
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 mod commands;
 mod utils;
 
 use stitchr_cli::OnlyMode as OnlyModeLib;
+use stitchr_formats::copier_header::HeaderLayout;
+use stitchr_formats::n64_byte_order::OutputOrder;
+use utils::paths::OnExists as OnExistsPolicy;
 
 /// Operation mode for --only flag
 #[derive(ValueEnum, Clone, Debug)]
@@ -29,21 +33,285 @@ impl From<OnlyMode> for OnlyModeLib {
     }
 }
 
+/// SNES copier header handling for --copier-header
+#[derive(ValueEnum, Clone, Debug)]
+enum CopierHeader {
+    /// Match the patch, then restore the input ROM's layout
+    Keep,
+    /// Match the patch, always write a 512-byte header
+    Headered,
+    /// Match the patch, never write a header
+    Headerless,
+    /// Leave the ROM exactly as loaded
+    Off,
+}
+
+impl CopierHeader {
+    fn layout(&self) -> Option<HeaderLayout> {
+        match self {
+            CopierHeader::Keep => Some(HeaderLayout::Keep),
+            CopierHeader::Headered => Some(HeaderLayout::Headered),
+            CopierHeader::Headerless => Some(HeaderLayout::Headerless),
+            CopierHeader::Off => None,
+        }
+    }
+}
+
+/// N64 byte order handling for --n64-byte-order
+#[derive(ValueEnum, Clone, Debug)]
+enum N64ByteOrder {
+    /// Convert for patching, then back to the input ROM's order
+    Keep,
+    /// Convert for patching, write big-endian (.z64)
+    Z64,
+    /// Leave the ROM exactly as loaded
+    Off,
+}
+
+impl N64ByteOrder {
+    fn output(&self) -> Option<OutputOrder> {
+        match self {
+            N64ByteOrder::Keep => Some(OutputOrder::Keep),
+            N64ByteOrder::Z64 => Some(OutputOrder::BigEndian),
+            N64ByteOrder::Off => None,
+        }
+    }
+}
+
+/// Subcommands besides the default apply
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Apply patch chains to many ROMs as listed in a TOML manifest
+    Batch {
+        /// Path to the manifest file
+        manifest: PathBuf,
+
+        /// Worker threads (overrides the manifest's `jobs`; default: CPU
+        /// count)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+
+        /// Report file (overrides the manifest's `report`; default:
+        /// {manifest}.report.json)
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
+
+    /// List, extract and replace files inside ISO9660 disc images
+    #[cfg(feature = "disc")]
+    Disc {
+        #[command(subcommand)]
+        action: DiscAction,
+    },
+
+    /// Find the ROM a patch was made for in a directory of ROMs
+    FindSource {
+        /// Path to the patch file
+        patch: PathBuf,
+
+        /// Directory to search (recursively)
+        dir: PathBuf,
+
+        /// Apply the patch to the match (requires exactly one exact match)
+        #[arg(long)]
+        apply: bool,
+
+        /// Output path for --apply
+        #[arg(short, long, requires = "apply")]
+        output: Option<PathBuf>,
+    },
+
+    /// Show a patch's format, metadata, checksums and record summary
+    Info {
+        /// Path to the patch file
+        patch: PathBuf,
+    },
+
+    /// Report suspicious or non-canonical constructs in patch files
+    Lint {
+        /// Patch files to check
+        #[arg(required = true)]
+        patches: Vec<PathBuf>,
+    },
+
+    /// Re-encode a patch into its smallest equivalent form (IPS, IPS32, PPF)
+    Optimize {
+        /// Path to the patch file
+        patch: PathBuf,
+
+        /// Output path (defaults to {patch_dir}/{patch}.optimized.{ext})
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Source ROM; writes that leave it unchanged are dropped (the result
+        /// then only works for this ROM)
+        #[arg(long)]
+        source: Option<PathBuf>,
+
+        /// Strip PPF3 undo data
+        #[arg(long)]
+        drop_undo: bool,
+    },
+}
+
+/// Operations of the disc subcommand
+#[cfg(feature = "disc")]
+#[derive(Subcommand, Debug)]
+enum DiscAction {
+    /// Print the directory tree with each entry's LBA and size
+    List {
+        /// Disc image (ISO, BIN, CUE, CSO, ZSO or CHD)
+        image: PathBuf,
+    },
+
+    /// Copy a file out of the disc
+    Extract {
+        /// Disc image (ISO, BIN, CUE, CSO, ZSO or CHD)
+        image: PathBuf,
+
+        /// Path of the file on the disc (e.g. PSP_GAME/SYSDIR/EBOOT.BIN)
+        path: String,
+
+        /// Output path (defaults to the file's name next to the image)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// What to do when an output file already exists
+        #[arg(long, value_enum, default_value = "overwrite")]
+        on_exists: OnExists,
+    },
+
+    /// Replace a file with one that fits the sectors allocated to it
+    Replace {
+        /// Disc image (ISO, BIN, CUE, CSO, ZSO or CHD)
+        image: PathBuf,
+
+        /// Path of the file on the disc
+        path: String,
+
+        /// New contents of the file
+        file: PathBuf,
+
+        /// Output path (defaults to {image_dir}/patched/{stem}.patched.{ext})
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// What to do when an output file already exists
+        #[arg(long, value_enum, default_value = "overwrite")]
+        on_exists: OnExists,
+    },
+
+    /// Apply a patch made against a single file of the disc
+    Patch {
+        /// Disc image (ISO, BIN, CUE, CSO, ZSO or CHD)
+        image: PathBuf,
+
+        /// Path of the file on the disc
+        path: String,
+
+        /// Patch for that file
+        patch: PathBuf,
+
+        /// Output path (defaults to {image_dir}/patched/{stem}.patched.{ext})
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// What to do when an output file already exists
+        #[arg(long, value_enum, default_value = "overwrite")]
+        on_exists: OnExists,
+    },
+}
+
+/// What to do when the ROM already has the patch applied (--on-applied)
+#[derive(ValueEnum, Clone, Debug)]
+enum OnApplied {
+    /// Report it and exit without writing anything
+    Skip,
+    /// Fail with an error
+    Error,
+}
+
+/// Existing output handling for --on-exists
+#[derive(ValueEnum, Clone, Debug)]
+enum OnExists {
+    /// Refuse to write
+    Fail,
+    /// Replace the existing file
+    Overwrite,
+    /// Write "{name} (1).{ext}", "{name} (2).{ext}", ... instead
+    Suffix,
+}
+
+impl From<OnExists> for OnExistsPolicy {
+    fn from(policy: OnExists) -> Self {
+        match policy {
+            OnExists::Fail => OnExistsPolicy::Fail,
+            OnExists::Overwrite => OnExistsPolicy::Overwrite,
+            OnExists::Suffix => OnExistsPolicy::Suffix,
+        }
+    }
+}
+
+/// Output style for --output-format
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable progress lines
+    Text,
+    /// One JSON document per operation on stdout
+    Json,
+}
+
 /// ROM Patcher - Apply patches to ROM files
 #[derive(Parser, Debug)]
 #[command(name = "stitchr")]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    /// Path to the ROM file
-    rom: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the ROM file (or source directory for BPM patches); ZIP, 7z
+    /// and gzip archives are read, `archive.zip:inner/path` picks a file
+    #[arg(required = true)]
+    rom: Option<PathBuf>,
 
-    /// Path to the patch file (not required for --only ra)
+    /// Path to the patch file (not required for --only ra); may be in an
+    /// archive like the ROM
     patch: Option<PathBuf>,
 
     /// Output path (optional, defaults to
     /// {rom_dir}/patched/{rom}.patched.{ext})
     output: Option<PathBuf>,
 
+    /// Output file name template when no output path is given; placeholders:
+    /// {rom_dir} {rom_stem} {ext} {patch_stem} {format} {crc32} {title}
+    /// {console}
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "output")]
+    output_template: Option<String>,
+
+    /// What to do when the output file already exists
+    #[arg(long, value_enum, default_value = "overwrite")]
+    on_exists: OnExists,
+
+    /// Use the usual extension of the patched ROM's console when the patch
+    /// changes it (e.g. .gb -> .gbc); not applied to explicit output paths
+    #[arg(long)]
+    console_extension: bool,
+
+    /// Write the patched ROM over the input, keeping the original as
+    /// {rom}.bak
+    #[arg(long, conflicts_with_all = ["output", "output_template", "console_extension"])]
+    in_place: bool,
+
+    /// Give the output the input ROM's permissions and modification time
+    #[arg(long)]
+    preserve: bool,
+
+    /// Write the output as a ZIP archive ({output} with a .zip extension)
+    /// holding the patched ROM
+    #[arg(long, conflicts_with = "in_place")]
+    zip: bool,
+
     /// Verify source/target checksums (slower, safer)
     #[arg(long)]
     verify: bool,
@@ -53,23 +321,161 @@ struct Cli {
     #[arg(long, value_enum, num_args = 1..)]
     only: Vec<OnlyMode>,
 
+    /// Recompute internal cartridge checksums after patching (SNES, GB/GBC,
+    /// GBA, Mega Drive, NDS, N64 CRC1/CRC2)
+    #[arg(long)]
+    fix_checksum: bool,
+
+    /// Regenerate EDC/ECC of raw 2352-byte CD sectors the patch modified
+    /// (Mode 1, Mode 2 Form 1; EDC only for Form 2)
+    #[arg(long)]
+    fix_ecc: bool,
+
+    /// SNES copier header: strip/add it to match the patch, and the layout
+    /// to write afterwards
+    #[arg(long, value_enum, default_value = "keep")]
+    copier_header: CopierHeader,
+
+    /// N64 byte order: convert .v64/.n64 to what the patch expects, and the
+    /// order to write afterwards
+    #[arg(long, value_enum, default_value = "keep")]
+    n64_byte_order: N64ByteOrder,
+
+    /// Undo a UPS or RUP patch on a ROM that already has it applied
+    #[arg(long)]
+    revert: bool,
+
+    /// What to do when the ROM already has the patch applied
+    #[arg(long, value_enum, default_value = "skip")]
+    on_applied: OnApplied,
+
+    /// No-Intro / Redump DAT file or directory used to name the input and
+    /// output ROMs (repeatable; also read from STITCHR_DAT)
+    #[arg(long = "dat", value_name = "PATH")]
+    dats: Vec<PathBuf>,
+
+    /// Output format (json: one machine-readable document, distinct exit
+    /// codes per failure class)
+    #[arg(long, value_enum, global = true, default_value = "text")]
+    output_format: OutputFormat,
+
     /// Verbose output (can be used multiple times)
-    #[arg(short, long, action = clap::ArgAction::Count)]
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
     verbose: u8,
 }
 
-fn main() -> Result<()> {
+impl Cli {
+    /// Operation name reported in JSON documents
+    fn operation(&self) -> &'static str {
+        match &self.command {
+            Some(Command::Batch { .. }) => "batch",
+            #[cfg(feature = "disc")]
+            Some(Command::Disc { .. }) => "disc",
+            Some(Command::FindSource { .. }) => "find-source",
+            Some(Command::Info { .. }) => "info",
+            Some(Command::Lint { .. }) => "lint",
+            Some(Command::Optimize { .. }) => "optimize",
+            None if self.revert => "revert",
+            None if self.only.iter().any(|m| matches!(m, OnlyMode::Verify)) => "verify",
+            None if !self.only.is_empty() => "ra",
+            None => "apply",
+        }
+    }
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
     // Initialize logger based on verbose level
     utils::logging::init(cli.verbose);
 
+    if cli.output_format == OutputFormat::Json {
+        utils::report::enable_json(cli.operation());
+    }
+
+    utils::report::finish(run(cli))
+}
+
+fn run(cli: Cli) -> Result<()> {
+    if let Some(command) = cli.command {
+        return match command {
+            Command::Batch {
+                manifest,
+                jobs,
+                report,
+            } => commands::batch::execute(manifest, jobs, report),
+            #[cfg(feature = "disc")]
+            Command::Disc { action } => match action {
+                DiscAction::List { image } => commands::disc::list(image),
+                DiscAction::Extract {
+                    image,
+                    path,
+                    output,
+                    on_exists,
+                } => commands::disc::extract(image, path, output, on_exists.into()),
+                DiscAction::Replace {
+                    image,
+                    path,
+                    file,
+                    output,
+                    on_exists,
+                } => commands::disc::replace(image, path, file, output, on_exists.into()),
+                DiscAction::Patch {
+                    image,
+                    path,
+                    patch,
+                    output,
+                    on_exists,
+                } => commands::disc::patch(image, path, patch, output, on_exists.into()),
+            },
+            Command::FindSource {
+                patch,
+                dir,
+                apply,
+                output,
+            } => commands::find_source::execute(patch, dir, apply, output),
+            Command::Info { patch } => commands::info::execute(patch),
+            Command::Lint { patches } => commands::lint::execute(patches),
+            Command::Optimize {
+                patch,
+                output,
+                source,
+                drop_undo,
+            } => commands::optimize::execute(patch, output, source, drop_undo),
+        };
+    }
+    let rom = cli.rom.expect("ROM path is required by clap");
+
     let only_modes: Vec<OnlyModeLib> = cli.only.into_iter().map(|m| m.into()).collect();
 
     // Validate: patch is required unless --only ra
     if cli.patch.is_none() && !only_modes.iter().any(|m| matches!(m, OnlyModeLib::Ra)) {
-        anyhow::bail!("Patch file is required (unless using --only ra)");
+        return Err(utils::report::fail(
+            utils::report::FailureKind::Usage,
+            "Patch file is required (unless using --only ra)",
+        ));
     }
 
-    commands::apply::execute(cli.rom, cli.patch, cli.output, cli.verify, only_modes)
+    commands::apply::execute(
+        rom,
+        cli.patch,
+        cli.output,
+        commands::apply::ApplyOptions {
+            verify: cli.verify,
+            only_modes,
+            header_layout: cli.copier_header.layout(),
+            byte_order: cli.n64_byte_order.output(),
+            fix_checksum: cli.fix_checksum,
+            fix_ecc: cli.fix_ecc,
+            dats: cli.dats,
+            revert: cli.revert,
+            refuse_applied: matches!(cli.on_applied, OnApplied::Error),
+            output_template: cli.output_template,
+            on_exists: cli.on_exists.into(),
+            console_extension: cli.console_extension,
+            in_place: cli.in_place,
+            preserve: cli.preserve,
+            zip: cli.zip,
+        },
+    )
 }
@@ -0,0 +1,191 @@
+//! ROMs and patches inside ZIP, 7z and gzip archives
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_features::archive::{self, ArchiveEntry, ArchiveKind};
+
+use super::report::{self, FailureKind};
+
+/// What an input file is used as, for picking it out of an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Rom,
+    Patch,
+}
+
+/// Extensions of the patch formats stitchr reads
+pub const PATCH_EXTENSIONS: &[&str] = &[
+    "ips", "bps", "bpm", "ups", "aps", "ebp", "rup", "ppf", "xdelta", "vcdiff", "delta", "bdf",
+    "pmsr", "gdiff",
+];
+
+/// Extensions that are never the ROM (documentation and artwork)
+const EXTRA_EXTENSIONS: &[&str] = &[
+    "txt", "nfo", "diz", "md", "pdf", "htm", "html", "jpg", "jpeg", "png", "gif", "bmp",
+];
+
+/// A file that was read from inside an archive
+#[derive(Debug, Clone)]
+pub struct Member {
+    /// The archive on disk
+    pub archive: PathBuf,
+    pub kind: ArchiveKind,
+    /// Path inside the archive
+    pub name: String,
+}
+
+impl Member {
+    /// Where the file would be if it were extracted next to the archive
+    ///
+    /// Outputs are named after this path rather than the archive's.
+    pub fn extracted_path(&self) -> PathBuf {
+        let file_name = self.name.rsplit('/').next().unwrap_or(&self.name);
+        self.archive.with_file_name(file_name)
+    }
+}
+
+/// Split `archive.zip:inner/path` into the archive and the inner path
+///
+/// Only applies when `path` itself does not exist and the part before the
+/// last `.zip:`, `.7z:` or `.gz:` (any case) is a file.
+pub fn split_member(path: &Path) -> Option<(PathBuf, String)> {
+    if path.exists() {
+        return None;
+    }
+    let text = path.to_str()?;
+    let lower = text.to_ascii_lowercase();
+    [".zip:", ".7z:", ".gz:"]
+        .iter()
+        .filter_map(|marker| lower.rfind(marker).map(|pos| pos + marker.len()))
+        .max()
+        .map(|split| (PathBuf::from(&text[..split - 1]), text[split..].to_string()))
+        .filter(|(archive, _)| archive.is_file())
+}
+
+/// Pick the ROM or patch among an archive's files
+///
+/// A patch is the single file with a patch extension; a ROM is the single
+/// file that is neither a patch nor documentation or artwork. An archive of
+/// one file always yields that file. Anything else is a usage error listing
+/// the files to choose from.
+pub fn select(entries: &[ArchiveEntry], role: Role, archive: &Path) -> Result<ArchiveEntry> {
+    if let [entry] = entries {
+        return Ok(entry.clone());
+    }
+    let extension = |entry: &ArchiveEntry| {
+        entry
+            .name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+            .unwrap_or_default()
+    };
+    let candidates: Vec<&ArchiveEntry> = entries
+        .iter()
+        .filter(|entry| {
+            let ext = extension(entry);
+            let is_patch = PATCH_EXTENSIONS.contains(&ext.as_str());
+            match role {
+                Role::Patch => is_patch,
+                Role::Rom => {
+                    !is_patch && !EXTRA_EXTENSIONS.contains(&ext.as_str()) && entry.size > 0
+                }
+            }
+        })
+        .collect();
+    if let [entry] = candidates.as_slice() {
+        return Ok((*entry).clone());
+    }
+
+    let what = match role {
+        Role::Rom => "ROM",
+        Role::Patch => "patch",
+    };
+    let listed = if candidates.is_empty() {
+        entries.iter().collect()
+    } else {
+        candidates
+    };
+    let names: Vec<&str> = listed.iter().map(|e| e.name.as_str()).collect();
+    let reason = if entries.is_empty() {
+        "is empty".to_string()
+    } else {
+        format!("has no single {}; it contains: {}", what, names.join(", "))
+    };
+    Err(report::fail(
+        FailureKind::Usage,
+        format!(
+            "{} {}. Pick one with {}:<name>",
+            archive.display(),
+            reason,
+            archive.display()
+        ),
+    ))
+}
+
+/// Read an input file, extracting it when it is (inside) an archive
+///
+/// Archives are recognized by their magic bytes. `archive.zip:inner/path`
+/// picks a file explicitly; otherwise [`select`] chooses one. The member is
+/// `None` for plain files.
+pub fn read(path: &Path, role: Role) -> Result<(Vec<u8>, Option<Member>)> {
+    let (archive_path, wanted) = match split_member(path) {
+        Some((archive, name)) => (archive, Some(name)),
+        None => (path.to_path_buf(), None),
+    };
+    let data = fs::read(&archive_path)
+        .with_context(|| format!("Failed to read {}", archive_path.display()))?;
+    let Some(kind) = ArchiveKind::detect(&data) else {
+        if wanted.is_some() {
+            return Err(report::fail(
+                FailureKind::UnsupportedFormat,
+                format!(
+                    "{} is not a ZIP, 7z or gzip archive",
+                    archive_path.display()
+                ),
+            ));
+        }
+        return Ok((data, None));
+    };
+
+    let mut entries = archive::list(kind, &data)
+        .with_context(|| format!("Failed to read {} archive", kind.name()))?;
+    // A gzip stream without a stored name is the archive minus `.gz`
+    if kind == ArchiveKind::Gzip
+        && let Some(entry) = entries.first_mut().filter(|e| e.name.is_empty())
+    {
+        entry.name = archive_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+    }
+
+    let entry = match wanted {
+        Some(name) => entries
+            .into_iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| {
+                report::fail(
+                    FailureKind::Usage,
+                    format!("No file named {} in {}", name, archive_path.display()),
+                )
+            })?,
+        None => select(&entries, role, &archive_path)?,
+    };
+    let contents = archive::extract(kind, &data, &entry.name).with_context(|| {
+        format!(
+            "Failed to extract {} from {}",
+            entry.name,
+            archive_path.display()
+        )
+    })?;
+
+    Ok((
+        contents,
+        Some(Member {
+            archive: archive_path,
+            kind,
+            name: entry.name,
+        }),
+    ))
+}
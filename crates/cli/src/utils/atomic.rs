@@ -0,0 +1,161 @@
+//! Crash-safe file writing
+//!
+//! Data goes to a uniquely named temporary file in the destination's
+//! directory and is fsynced before it is renamed over the destination; the
+//! directory is fsynced afterwards so the rename itself survives a crash. At
+//! any point the destination holds either the old or the new contents.
+
+use anyhow::{Context, Result};
+use log::debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How [`write_file`] treats the destination's attributes
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// Copy permissions and modification time from this file (--preserve)
+    pub preserve_from: Option<PathBuf>,
+    /// Keep the previous destination as [`backup_path`] (--in-place)
+    pub backup: bool,
+}
+
+/// `{path}.bak`
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Atomically replace `path` with `data`
+///
+/// An existing destination keeps its permissions unless
+/// [`WriteOptions::preserve_from`] names another file to take them from.
+pub fn write_file(path: &Path, data: &[u8], options: &WriteOptions) -> Result<()> {
+    write_with(path, options, |file| Ok(file.write_all(data)?))
+}
+
+/// Atomically replace `path` with what `fill` writes to the temporary file
+///
+/// Like [`write_file`], for outputs streamed rather than held in memory.
+pub fn write_with(
+    path: &Path,
+    options: &WriteOptions,
+    fill: impl FnOnce(&mut File) -> Result<()>,
+) -> Result<()> {
+    let existing = fs::metadata(path).ok().filter(|m| m.is_file());
+    let template = match &options.preserve_from {
+        Some(source) => Some(
+            fs::metadata(source)
+                .with_context(|| format!("Failed to read attributes of {}", source.display()))?,
+        ),
+        None => existing.clone(),
+    };
+
+    if options.backup && existing.is_some() {
+        let backup = backup_path(path);
+        debug!("Backing up {} to {}", path.display(), backup.display());
+        let (temp, mut file) = TempFile::create(&backup)?;
+        let mut source = File::open(path)
+            .with_context(|| format!("Failed to read {} for backup", path.display()))?;
+        std::io::copy(&mut source, &mut file)
+            .with_context(|| format!("Failed to write backup: {}", backup.display()))?;
+        temp.commit(file, existing.as_ref(), true)?;
+    }
+
+    let (temp, mut file) = TempFile::create(path)?;
+    debug!("Writing temporary output: {}", temp.path.display());
+    fill(&mut file)
+        .with_context(|| format!("Failed to write temporary output: {}", temp.path.display()))?;
+    temp.commit(file, template.as_ref(), options.preserve_from.is_some())
+}
+
+/// A temporary file next to its destination, removed unless committed
+struct TempFile {
+    path: PathBuf,
+    destination: PathBuf,
+    committed: bool,
+}
+
+impl TempFile {
+    /// Create `.{name}.{pid}-{n}.tmp` in the destination's directory
+    fn create(destination: &Path) -> Result<(Self, File)> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let name = destination
+            .file_name()
+            .with_context(|| format!("Invalid output path: {}", destination.display()))?
+            .to_string_lossy();
+        loop {
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path =
+                destination.with_file_name(format!(".{}.{}-{}.tmp", name, std::process::id(), n));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    let temp = TempFile {
+                        path,
+                        destination: destination.to_path_buf(),
+                        committed: false,
+                    };
+                    return Ok((temp, file));
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to create temporary file: {}", path.display())
+                    });
+                }
+            }
+        }
+    }
+
+    /// Apply attributes, fsync, rename over the destination, fsync the
+    /// directory
+    fn commit(mut self, file: File, template: Option<&fs::Metadata>, mtime: bool) -> Result<()> {
+        if let Some(template) = template {
+            file.set_permissions(template.permissions())
+                .context("Failed to set output permissions")?;
+            if mtime {
+                let modified = template
+                    .modified()
+                    .context("Failed to read modification time")?;
+                file.set_modified(modified)
+                    .context("Failed to set output modification time")?;
+            }
+        }
+        file.sync_all()
+            .with_context(|| format!("Failed to flush {}", self.path.display()))?;
+        drop(file);
+
+        fs::rename(&self.path, &self.destination).with_context(|| {
+            format!("Failed to finalize output: {}", self.destination.display())
+        })?;
+        self.committed = true;
+        match self.destination.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => sync_dir(dir),
+            _ => sync_dir(Path::new(".")),
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Persist a rename; directories cannot be opened for syncing on Windows
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    File::open(dir)
+        .and_then(|d| d.sync_all())
+        .with_context(|| format!("Failed to flush directory: {}", dir.display()))
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Internal cartridge checksum repair with before/after display
+
+use log::warn;
+use stitchr_features::checksum_fix::{self, System, n64};
+
+/// Recompute internal checksums and print each field's old and new value
+pub fn fix_and_report(rom: &mut [u8]) {
+    let system = checksum_fix::detect_system(rom);
+    let cic = system
+        .filter(|&s| s == System::N64)
+        .and_then(|_| n64::detect_cic(rom));
+
+    let fixes = checksum_fix::fix_checksums(rom);
+    if fixes.is_empty() {
+        match system {
+            Some(System::N64) => {
+                warn!("Unknown N64 CIC boot code; CRC1/CRC2 left unchanged")
+            }
+            _ => warn!("No supported cartridge header found; checksums left unchanged"),
+        }
+        return;
+    }
+
+    if let Some(cic) = cic {
+        crate::say!("Detected {}", cic);
+    }
+    for fix in &fixes {
+        crate::say!("Checksum: {}", fix);
+        crate::utils::report::push("adjustments", format!("Checksum: {}", fix));
+    }
+}
+
+/// Point at --fix-checksum when a patched N64 ROM no longer matches its
+/// boot checksums (it would not boot on real hardware)
+pub fn suggest_fix(rom: &[u8]) {
+    if n64::is_stale(rom) == Some(true) {
+        warn!(
+            "N64 CRC1/CRC2 no longer match the patched ROM; use --fix-checksum to recompute them"
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! CUE sheet inputs
+//!
+//! Patches apply to the disc formed by a sheet's BIN files read back to
+//! back, so a patch made against a merged BIN applies to a split-track dump
+//! and the reverse. A patch whose source size matches a single track (most
+//! often one made against `Track 1.bin`) applies to that track alone.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use stitchr_features::disc::{BlockDevice, CueDevice, CueSheet, TrackSpan, copy_to, read_all};
+
+use super::atomic::{self, WriteOptions};
+use super::paths::{self, OnExists};
+use super::report::{self, FailureKind};
+
+/// A CUE sheet and the layout of its files in the logical disc
+pub struct CueInput {
+    pub sheet: CueSheet,
+    /// The sheet being read
+    pub path: PathBuf,
+    /// Size of each BIN file, in sheet order
+    pub file_sizes: Vec<u64>,
+    pub spans: Vec<TrackSpan>,
+}
+
+/// Whether `path` names a CUE sheet
+pub fn is_cue(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cue"))
+}
+
+/// Read a CUE sheet and the disc formed by its BIN files
+pub fn load(path: &Path) -> Result<(CueInput, Vec<u8>), String> {
+    let (input, device) = open(path)?;
+    let disc = read_all(&device)?;
+    Ok((input, disc))
+}
+
+/// Read a CUE sheet and open its BIN files as one disc, read on demand
+pub fn open(path: &Path) -> Result<(CueInput, CueDevice), String> {
+    let sheet = CueSheet::load(path)?;
+    let device = CueDevice::open(&sheet, &dir(path))?;
+    let file_sizes = device.file_sizes();
+    let spans = sheet.spans(&file_sizes)?;
+    Ok((
+        CueInput {
+            sheet,
+            path: path.to_path_buf(),
+            file_sizes,
+            spans,
+        },
+        device,
+    ))
+}
+
+fn dir(path: &Path) -> PathBuf {
+    path.parent().unwrap_or(Path::new(".")).to_path_buf()
+}
+
+impl CueInput {
+    /// Size of the whole disc
+    pub fn disc_size(&self) -> u64 {
+        self.file_sizes.iter().sum()
+    }
+
+    /// Paths of the BIN files
+    pub fn file_paths(&self) -> Vec<PathBuf> {
+        self.sheet.file_paths(&dir(&self.path))
+    }
+
+    /// The single track a patch expecting a `source_size` byte input
+    /// applies to, when that is not the whole disc
+    pub fn target_track(&self, source_size: Option<usize>) -> Option<TrackSpan> {
+        let size = source_size? as u64;
+        if size == self.disc_size() {
+            return None;
+        }
+        let mut matching = self.spans.iter().filter(|span| span.len() == size);
+        match (matching.next(), matching.next()) {
+            (Some(span), None) => Some(*span),
+            _ => None,
+        }
+    }
+
+    /// Sizes of the BIN files once `span` (the whole disc when `None`) is
+    /// replaced by `patched_len` bytes
+    ///
+    /// A size change moves the end of the file holding the patched data,
+    /// the last file for whole-disc patches. Tracks after the change in the
+    /// same file would move too, which the sheet cannot express.
+    pub fn patched_file_sizes(
+        &self,
+        span: Option<&TrackSpan>,
+        patched_len: u64,
+    ) -> Result<Vec<u64>, String> {
+        let (start, end) = span.map_or((0, self.disc_size()), |s| (s.start, s.end));
+        let mut sizes = self.file_sizes.clone();
+        if patched_len == end - start {
+            return Ok(sizes);
+        }
+
+        // The file that ends where the patched data ends
+        let mut file_end = 0;
+        let file = self
+            .file_sizes
+            .iter()
+            .position(|size| {
+                file_end += size;
+                file_end == end
+            })
+            .ok_or_else(|| {
+                "Patch changes the size of a track that is followed by another track in the \
+                 same BIN file"
+                    .to_string()
+            })?;
+        let shrunk = (end - start).saturating_sub(patched_len);
+        if shrunk > self.file_sizes[file] {
+            return Err("Patched disc is too short for its CUE sheet".to_string());
+        }
+        sizes[file] = self.file_sizes[file] + patched_len.saturating_sub(end - start) - shrunk;
+        // The tracks must still start inside their files
+        self.sheet.spans(&sizes)?;
+        Ok(sizes)
+    }
+}
+
+/// Names of the BIN files written next to `output_cue`
+///
+/// The input sheet's name is swapped for the output's where the BIN names
+/// start with it (`Game (Track 1).bin` becomes `Game.patched (Track 1).bin`);
+/// other names get the output name as a prefix. BINs kept in a subdirectory
+/// of the input are written next to the output sheet.
+pub fn output_file_names(input: &CueInput, output_cue: &Path) -> Vec<String> {
+    let stem = |path: &Path| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let (input_stem, output_stem) = (stem(&input.path), stem(output_cue));
+    input
+        .sheet
+        .files
+        .iter()
+        .map(|file| {
+            let name = Path::new(&file.name)
+                .file_name()
+                .map_or_else(|| file.name.clone(), |n| n.to_string_lossy().into_owned());
+            match name.strip_prefix(&input_stem) {
+                Some(rest) if !input_stem.is_empty() => format!("{}{}", output_stem, rest),
+                _ => format!("{} {}", output_stem, name),
+            }
+        })
+        .collect()
+}
+
+/// A patched disc and the BIN files it went to
+#[derive(Debug)]
+pub struct WrittenDisc {
+    /// The whole patched disc
+    pub disc: Vec<u8>,
+    /// BIN files written next to a `.cue` output, in sheet order; empty when
+    /// the disc went to a single merged BIN
+    pub bins: Vec<PathBuf>,
+}
+
+/// Write the patched disc of `input` to `output_path`
+///
+/// A `.cue` output gets a sheet and BIN files laid out like the input's;
+/// any other name gets the whole disc as one merged BIN. With `track`, the
+/// patched data is that track, written back into the rest of the disc.
+/// `on_exists` applies to every BIN file (the caller has already resolved
+/// `output_path` itself), and no output may be one of the input BINs. All
+/// BIN names are settled before anything is written.
+pub fn write_disc(
+    input: &CueInput,
+    track: Option<(TrackSpan, Vec<u8>)>,
+    patched: Vec<u8>,
+    output_path: &Path,
+    on_exists: OnExists,
+    preserve: bool,
+) -> anyhow::Result<WrittenDisc> {
+    let patched_len = patched.len() as u64;
+    let (disc, span) = match track {
+        Some((span, mut disc)) => {
+            disc.splice(span.start as usize..span.end as usize, patched);
+            (disc, Some(span))
+        }
+        None => (patched, None),
+    };
+    let sizes = if is_cue(output_path) {
+        input
+            .patched_file_sizes(span.as_ref(), patched_len)
+            .map_err(|e| report::fail(FailureKind::UnsupportedFormat, e))?
+    } else {
+        Vec::new()
+    };
+    let bins = write_layout(input, &disc, &sizes, output_path, on_exists, preserve)?;
+    Ok(WrittenDisc { disc, bins })
+}
+
+/// Write `disc`, a disc the size of `input`'s, like [`write_disc`] does,
+/// streaming it rather than holding it in memory
+///
+/// Returns the BIN files written next to a `.cue` output.
+pub fn write_device(
+    input: &CueInput,
+    disc: &dyn BlockDevice,
+    output_path: &Path,
+    on_exists: OnExists,
+) -> anyhow::Result<Vec<PathBuf>> {
+    write_layout(
+        input,
+        disc,
+        &input.file_sizes,
+        output_path,
+        on_exists,
+        false,
+    )
+}
+
+/// Write `disc` as BIN files of `sizes` next to a `.cue` output, or as one
+/// merged BIN
+fn write_layout(
+    input: &CueInput,
+    disc: &dyn BlockDevice,
+    sizes: &[u64],
+    output_path: &Path,
+    on_exists: OnExists,
+    preserve: bool,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let options = |from: &Path| WriteOptions {
+        preserve_from: preserve.then(|| from.to_path_buf()),
+        backup: false,
+    };
+    let write = |path: &Path, range: std::ops::Range<u64>, from: &Path| {
+        atomic::write_with(path, &options(from), |file| {
+            copy_to(disc, range, file).map_err(anyhow::Error::msg)
+        })
+    };
+    let inputs = input.file_paths();
+    let check_not_input = |path: &Path| {
+        if inputs.iter().any(|input| paths::same_file(path, input)) {
+            return Err(report::fail(
+                FailureKind::Usage,
+                format!("Output would overwrite input BIN file: {}", path.display()),
+            ));
+        }
+        Ok(())
+    };
+
+    if !is_cue(output_path) {
+        check_not_input(output_path)?;
+        write(output_path, 0..disc.size(), &input.path)?;
+        return Ok(Vec::new());
+    }
+
+    let dir = output_path.parent().unwrap_or(Path::new(""));
+    let mut claimed = HashSet::from([output_path.to_path_buf()]);
+    let mut bins = Vec::with_capacity(sizes.len());
+    for name in output_file_names(input, output_path) {
+        let path = paths::resolve_existing(&dir.join(name), on_exists, &claimed)?;
+        check_not_input(&path)?;
+        claimed.insert(path.clone());
+        bins.push(path);
+    }
+
+    let mut offset = 0;
+    for ((path, size), from) in bins.iter().zip(sizes).zip(&inputs) {
+        write(path, offset..offset + size, from)?;
+        offset += size;
+    }
+    let names: Vec<String> = bins
+        .iter()
+        .map(|path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    let sheet = input.sheet.with_file_names(&names);
+    atomic::write_file(output_path, sheet.as_bytes(), &options(&input.path))?;
+    Ok(bins)
+}
@@ -0,0 +1,84 @@
+//! DAT-based ROM naming for input and output
+
+use log::{info, warn};
+use std::path::PathBuf;
+use stitchr_features::dat::{DatEntry, DatIndex};
+
+/// Environment variable listing DAT files or directories
+pub const DAT_ENV: &str = "STITCHR_DAT";
+
+/// Load DATs from `--dat` paths and `STITCHR_DAT`
+///
+/// Unreadable DATs are reported and skipped. Returns `None` when no DAT
+/// was given or none could be loaded.
+pub fn load(paths: &[PathBuf]) -> Option<DatIndex> {
+    let from_env: Vec<PathBuf> = std::env::var_os(DAT_ENV)
+        .map(|value| std::env::split_paths(&value).collect())
+        .unwrap_or_default();
+
+    let mut index = DatIndex::new();
+    for path in paths.iter().chain(&from_env) {
+        if let Err(e) = index.add_path(path) {
+            warn!("Could not load DAT {}: {}", path.display(), e);
+        }
+    }
+
+    if index.is_empty() {
+        return None;
+    }
+    info!("Loaded {} DAT entries", index.len());
+    Some(index)
+}
+
+/// Quoted game name for messages
+fn describe(entry: &DatEntry) -> String {
+    format!("'{}'", entry.game)
+}
+
+/// Name the input ROM and compare it with what the patch expects
+///
+/// `expected` is the source checksum recorded in the patch, if any.
+pub fn report_input(index: &DatIndex, rom: &[u8], expected: Option<&[u8]>) {
+    let input = index.identify(rom);
+    let wanted = expected
+        .map(|checksum| index.find_checksum(checksum))
+        .unwrap_or_default();
+
+    crate::utils::report::set_in("dat", "input", input.map(|e| e.game.clone()));
+    crate::utils::report::set_in(
+        "dat",
+        "patch_source",
+        wanted.first().map(|e| e.game.clone()),
+    );
+
+    match (input, wanted.first()) {
+        (Some(input), Some(_)) if wanted.iter().any(|w| w.game == input.game) => {
+            crate::say!("Input: {} (matches the patch source)", describe(input));
+        }
+        (Some(input), Some(wanted)) => {
+            warn!(
+                "Input is {}, patch expects {}",
+                describe(input),
+                describe(wanted)
+            );
+        }
+        (Some(input), None) => crate::say!("Input: {}", describe(input)),
+        (None, Some(wanted)) => {
+            warn!(
+                "Input is not in the loaded DATs, patch expects {}",
+                describe(wanted)
+            );
+        }
+        (None, None) => info!("Input ROM not found in the loaded DATs"),
+    }
+}
+
+/// Name the patched ROM, if it is a known dump
+pub fn report_output(index: &DatIndex, rom: &[u8]) {
+    let output = index.identify(rom);
+    crate::utils::report::set_in("dat", "output", output.map(|e| e.game.clone()));
+    match output {
+        Some(entry) => crate::say!("Output: {}", describe(entry)),
+        None => info!("Patched ROM not found in the loaded DATs"),
+    }
+}
@@ -0,0 +1,39 @@
+//! EDC/ECC regeneration for raw CD sectors with a summary display
+
+use log::{debug, warn};
+use stitchr_features::disc::ecc;
+
+/// Regenerate EDC/ECC of the raw sectors the patch modified
+///
+/// Returns `None`, leaving `patched` as is, when it is not a raw 2352-byte
+/// sector image.
+pub fn fix(original: &[u8], patched: &mut [u8]) -> Option<ecc::SectorFixes> {
+    ecc::is_raw_image(patched).then(|| ecc::fix_modified_sectors(original, patched))
+}
+
+/// Regenerate EDC/ECC of the raw sectors the patch modified and print how
+/// many were fixed
+pub fn fix_and_report(original: &[u8], patched: &mut [u8]) {
+    let Some(fixes) = fix(original, patched) else {
+        warn!("Not a raw 2352-byte sector image; EDC/ECC left unchanged");
+        return;
+    };
+    crate::say!("EDC/ECC: {}", fixes);
+    crate::utils::report::push("adjustments", format!("EDC/ECC: {}", fixes));
+    crate::utils::report::set("sectors_fixed", fixes.fixed());
+}
+
+/// Point at --fix-ecc when the patch left sectors with stale EDC/ECC
+pub fn suggest_fix(original: &[u8], patched: &[u8]) {
+    if !ecc::is_raw_image(patched) {
+        return;
+    }
+    let stale = ecc::stale_sectors(original, patched);
+    debug!("Modified sectors with stale EDC/ECC: {}", stale);
+    if stale > 0 {
+        warn!(
+            "{} patched CD sector(s) no longer match their EDC/ECC; use --fix-ecc to recompute them",
+            stale
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! File name globbing for batch manifests, with the `glob` crate
+//!
+//! `*` matches any run of characters within a path component, `?` a single
+//! character, and a `**` component any number of directories (including
+//! none). Hidden files and directories are skipped, even by patterns that
+//! start with a dot.
+//!
+//! There are no character classes or alternations: brackets are escaped
+//! before matching, so `[`, `]`, `{` and `}` match themselves, as they
+//! appear in ROM names like `Game (USA) [!].sfc`.
+
+use anyhow::{Context, Result};
+use glob::MatchOptions;
+use std::path::{Path, PathBuf};
+
+const OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: true,
+};
+
+/// Whether `pattern` contains wildcards
+pub fn is_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Files matching `pattern`, sorted
+///
+/// A pattern without wildcards is returned as is (whether or not it exists).
+/// Directories are never returned.
+pub fn expand(pattern: &Path) -> Result<Vec<PathBuf>> {
+    let text = pattern.to_string_lossy();
+    if !is_pattern(&text) {
+        return Ok(vec![pattern.to_path_buf()]);
+    }
+
+    let paths = glob::glob_with(&escape_brackets(&text), OPTIONS)
+        .with_context(|| format!("Invalid pattern {}", pattern.display()))?;
+    let mut found = Vec::new();
+    for path in paths {
+        let path = path.with_context(|| format!("Failed to expand {}", pattern.display()))?;
+        if path.is_file() {
+            found.push(path);
+        }
+    }
+    found.sort();
+    found.dedup();
+    Ok(found)
+}
+
+/// `pattern` with `[` and `]` made literal
+fn escape_brackets(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        match c {
+            '[' => out.push_str("[[]"),
+            ']' => out.push_str("[]]"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -1,10 +1,28 @@
 //! Utility functions
 
+pub mod atomic;
+pub mod glob;
 pub mod logging;
 pub mod paths;
+pub mod report;
 
 #[cfg(feature = "validation")]
 pub mod validation;
 
 #[cfg(feature = "retroachievements")]
 pub mod retroachievements;
+
+#[cfg(feature = "disc")]
+pub mod cue;
+
+#[cfg(feature = "disc")]
+pub mod edc_ecc;
+
+#[cfg(feature = "checksum_fix")]
+pub mod checksum_fix;
+
+#[cfg(feature = "dat")]
+pub mod dat;
+
+#[cfg(feature = "archive")]
+pub mod archive;
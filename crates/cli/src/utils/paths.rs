@@ -1,8 +1,13 @@
 //! Path utilities for output file generation
 
 use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use stitchr_core::PatchType;
+
+use super::report::{self, FailureKind};
 
 /// Generate default output path: {rom_dir}/patched/{stem}.patched.{ext}
 pub fn generate_default_output(rom_path: &Path) -> Result<PathBuf> {
@@ -28,3 +33,222 @@ pub fn generate_default_output(rom_path: &Path) -> Result<PathBuf> {
 
     Ok(patched_dir.join(output_filename))
 }
+
+/// What to do when the output file already exists (--on-exists)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnExists {
+    /// Refuse to write
+    Fail,
+    /// Replace the existing file
+    #[default]
+    Overwrite,
+    /// Write `{stem} (1).{ext}`, `{stem} (2).{ext}`, ... instead
+    Suffix,
+}
+
+/// Apply the `--on-exists` policy to `path`
+///
+/// `claimed` holds outputs already taken by other jobs of the same run; they
+/// count as existing, and even `Overwrite` will not write over them.
+pub fn resolve_existing(
+    path: &Path,
+    policy: OnExists,
+    claimed: &HashSet<PathBuf>,
+) -> Result<PathBuf> {
+    if claimed.contains(path) && policy != OnExists::Suffix {
+        return Err(report::fail(
+            FailureKind::OutputExists,
+            format!("Output {} is also written by another job", path.display()),
+        ));
+    }
+    let taken = |p: &Path| p.exists() || claimed.contains(p);
+    match policy {
+        OnExists::Overwrite => Ok(path.to_path_buf()),
+        OnExists::Fail if taken(path) => Err(report::fail(
+            FailureKind::OutputExists,
+            format!(
+                "Output file already exists: {} (use --on-exists overwrite or suffix)",
+                path.display()
+            ),
+        )),
+        OnExists::Fail => Ok(path.to_path_buf()),
+        OnExists::Suffix => Ok((0..)
+            .map(|n| suffixed(path, n))
+            .find(|p| !taken(p))
+            .expect("unbounded range")),
+    }
+}
+
+/// Whether `a` and `b` are the same existing file, however they are spelled
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// `path` with ` (n)` appended to the stem (`n = 0`: unchanged)
+fn suffixed(path: &Path, n: usize) -> PathBuf {
+    if n == 0 {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, n),
+    };
+    path.with_file_name(name)
+}
+
+/// What output templates can refer to
+pub struct TemplateInput<'a> {
+    pub rom_path: &'a Path,
+    /// Last patch applied
+    pub patch_path: &'a Path,
+    pub patch_type: PatchType,
+    /// Title from the patch metadata (EBP, RUP, BPS manifest)
+    pub title: Option<String>,
+    /// The patched ROM
+    pub output: &'a [u8],
+}
+
+/// Placeholder values for [`expand_template`]
+///
+/// `{rom_dir}`, `{rom_stem}`, `{ext}` (input extension), `{patch_stem}`,
+/// `{format}` (e.g. "bps"), `{crc32}` (of the output), `{title}` (falls back
+/// to the patch name) and `{console}` (detected from the output's header,
+/// "Unknown" otherwise). Values other than `{rom_dir}` are made safe for
+/// file names.
+pub fn template_vars(input: &TemplateInput) -> Vec<(&'static str, String)> {
+    let stem = |path: &Path| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let rom_dir = match input.rom_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let ext = input
+        .rom_path
+        .extension()
+        .map(|e| e.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    #[cfg(feature = "retroachievements")]
+    let console =
+        stitchr_features::rom_info::detect_console(input.output).map_or("Unknown", |c| c.name());
+    #[cfg(not(feature = "retroachievements"))]
+    let console = "Unknown";
+
+    vec![
+        ("rom_dir", rom_dir.display().to_string()),
+        ("rom_stem", sanitize(&stem(input.rom_path))),
+        ("ext", sanitize(&ext)),
+        ("patch_stem", sanitize(&stem(input.patch_path))),
+        ("format", format!("{:?}", input.patch_type).to_lowercase()),
+        (
+            "crc32",
+            format!(
+                "{:08x}",
+                stitchr_features::validation::algorithms::crc32::compute(input.output)
+            ),
+        ),
+        (
+            "title",
+            sanitize(input.title.as_deref().unwrap_or(&stem(input.patch_path))),
+        ),
+        ("console", sanitize(console)),
+    ]
+}
+
+/// Make a value usable as (part of) a file name
+///
+/// `.` and `..` become `_` so a value can never name a directory.
+pub fn sanitize(value: &str) -> String {
+    let name = value
+        .replace(": ", " - ")
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+    match name.trim() {
+        "." | ".." => "_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Extension of the console the patch turned the ROM into, if that differs
+/// from the input's console
+#[cfg(feature = "retroachievements")]
+pub fn console_change_extension(original: &[u8], patched: &[u8]) -> Option<&'static str> {
+    use stitchr_features::rom_info::detect_console;
+
+    let before = detect_console(original);
+    let after = detect_console(patched)?;
+    if before == Some(after) {
+        return None;
+    }
+    let extension = after.extension()?;
+    log::info!(
+        "Patched ROM is for {} (input: {}); using .{}",
+        after,
+        before.map_or("unknown".to_string(), |c| c.to_string()),
+        extension
+    );
+    Some(extension)
+}
+
+/// Output template matching [`generate_default_output`]
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "{rom_dir}/patched/{rom_stem}.patched.{ext}";
+
+/// Replace `{name}` placeholders in an output template
+///
+/// `{{` and `}}` produce literal braces; unknown placeholders are a usage
+/// error.
+/// A trailing `.` (left by an empty `{ext}`) is dropped.
+pub fn expand_template(template: &str, vars: &[(&str, String)]) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let Some(end) = tail.find('}').filter(|_| tail.starts_with('{')) else {
+            return Err(report::fail(
+                FailureKind::Usage,
+                format!("Unbalanced brace in output template: {}", template),
+            ));
+        };
+        let name = &tail[1..end];
+        let Some((_, value)) = vars.iter().find(|(key, _)| *key == name) else {
+            let known: Vec<&str> = vars.iter().map(|(key, _)| *key).collect();
+            return Err(report::fail(
+                FailureKind::Usage,
+                format!(
+                    "Unknown placeholder {{{}}} in output template (available: {})",
+                    name,
+                    known.join(", ")
+                ),
+            ));
+        };
+        out.push_str(value);
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    if out.ends_with('.') {
+        out.pop();
+    }
+    Ok(out)
+}
@@ -0,0 +1,249 @@
+//! Structured output for `--output-format json`
+//!
+//! In JSON mode the human-readable progress lines (printed through
+//! [`say!`](crate::say)) are suppressed, commands record what they did with
+//! [`set`], and `main` prints a single document per operation on stdout.
+//! Log messages still go to stderr.
+
+use std::fmt;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::{Value, json};
+use stitchr_core::{PatchError, PatchType};
+
+static JSON_MODE: AtomicBool = AtomicBool::new(false);
+static DOCUMENT: Mutex<Option<Value>> = Mutex::new(None);
+
+/// Print a progress line, unless JSON output is enabled
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::utils::report::is_json() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Failure classes, each with its own process exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// Anything not covered below
+    Other,
+    /// Invalid combination of arguments
+    Usage,
+    /// A file could not be read or written
+    Io,
+    /// The patch format was not recognized or is not supported here
+    UnsupportedFormat,
+    /// The patch is malformed
+    InvalidPatch,
+    /// Checksum or size verification failed, or the ROM does not fit the
+    /// patch
+    Verification,
+    /// The ROM already has the patch applied (or, for --revert, does not)
+    PatchState,
+    /// The output file exists and --on-exists forbids replacing it
+    OutputExists,
+}
+
+impl FailureKind {
+    /// Process exit code
+    pub fn exit_code(self) -> u8 {
+        match self {
+            FailureKind::Other => 1,
+            FailureKind::Usage => 2,
+            FailureKind::Io => 3,
+            FailureKind::UnsupportedFormat => 4,
+            FailureKind::InvalidPatch => 5,
+            FailureKind::Verification => 6,
+            FailureKind::PatchState => 7,
+            FailureKind::OutputExists => 8,
+        }
+    }
+
+    /// Identifier used in JSON documents
+    pub fn id(self) -> &'static str {
+        match self {
+            FailureKind::Other => "other",
+            FailureKind::Usage => "usage",
+            FailureKind::Io => "io",
+            FailureKind::UnsupportedFormat => "unsupported_format",
+            FailureKind::InvalidPatch => "invalid_patch",
+            FailureKind::Verification => "verification_failed",
+            FailureKind::PatchState => "patch_state",
+            FailureKind::OutputExists => "output_exists",
+        }
+    }
+}
+
+/// A CLI-level error with an explicit failure class
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: FailureKind,
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Build an [`anyhow::Error`] of the given class
+pub fn fail(kind: FailureKind, message: impl Into<String>) -> anyhow::Error {
+    Failure {
+        kind,
+        message: message.into(),
+    }
+    .into()
+}
+
+/// Failure class of an error: the outermost [`Failure`], [`PatchError`] or
+/// I/O error in its chain
+pub fn classify(err: &anyhow::Error) -> FailureKind {
+    for cause in err.chain() {
+        if let Some(failure) = cause.downcast_ref::<Failure>() {
+            return failure.kind;
+        }
+        if let Some(err) = cause.downcast_ref::<PatchError>() {
+            return match err {
+                PatchError::Io(_) => FailureKind::Io,
+                PatchError::UnsupportedVersion(_) => FailureKind::UnsupportedFormat,
+                PatchError::ChecksumMismatch { .. }
+                | PatchError::SizeMismatch { .. }
+                | PatchError::OutOfBounds { .. } => FailureKind::Verification,
+                PatchError::InvalidFormat(_)
+                | PatchError::CorruptedData
+                | PatchError::InvalidMagic { .. }
+                | PatchError::UnexpectedEof(_) => FailureKind::InvalidPatch,
+                PatchError::Other(_) => FailureKind::Other,
+            };
+        }
+        if cause.downcast_ref::<std::io::Error>().is_some() {
+            return FailureKind::Io;
+        }
+    }
+    FailureKind::Other
+}
+
+/// Switch to JSON output and start the document for `operation`
+pub fn enable_json(operation: &str) {
+    JSON_MODE.store(true, Ordering::Relaxed);
+    *document() = Some(json!({ "operation": operation }));
+}
+
+/// Whether JSON output is enabled
+pub fn is_json() -> bool {
+    JSON_MODE.load(Ordering::Relaxed)
+}
+
+fn document() -> std::sync::MutexGuard<'static, Option<Value>> {
+    DOCUMENT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Set a top-level field of the document (no-op in text mode)
+pub fn set(key: &str, value: impl Into<Value>) {
+    if let Some(doc) = document().as_mut().and_then(Value::as_object_mut) {
+        doc.insert(key.to_string(), value.into());
+    }
+}
+
+/// Append to a top-level array field of the document (no-op in text mode)
+pub fn push(key: &str, value: impl Into<Value>) {
+    if let Some(values) = document()
+        .as_mut()
+        .and_then(Value::as_object_mut)
+        .and_then(|doc| doc.entry(key).or_insert_with(|| json!([])).as_array_mut())
+    {
+        values.push(value.into());
+    }
+}
+
+/// Set a field of a top-level object field (no-op in text mode)
+pub fn set_in(object: &str, key: &str, value: impl Into<Value>) {
+    if let Some(target) = document()
+        .as_mut()
+        .and_then(Value::as_object_mut)
+        .and_then(|doc| {
+            doc.entry(object)
+                .or_insert_with(|| json!({}))
+                .as_object_mut()
+        })
+    {
+        target.insert(key.to_string(), value.into());
+    }
+}
+
+/// A path as a JSON string
+pub fn path(path: &Path) -> Value {
+    Value::String(path.display().to_string())
+}
+
+/// Path, size and CRC32/MD5/SHA-1 of a file's contents
+///
+/// Hashes are only computed in JSON mode.
+pub fn file(path: &Path, data: &[u8]) -> Value {
+    let file = json!({ "path": self::path(path), "size": data.len() });
+    #[cfg(feature = "validation")]
+    let file = if is_json() {
+        use stitchr_features::validation::algorithms::{md5, sha};
+        let mut file = file;
+        file["crc32"] =
+            super::validation::format_crc32(super::validation::compute_crc32(data)).into();
+        file["md5"] = md5::compute(data).into();
+        file["sha1"] = sha::compute_sha1(data).into();
+        file
+    } else {
+        file
+    };
+    file
+}
+
+/// Patch format as `{"id": "bps", "name": "Beat Patching System"}`
+pub fn format(patch_type: PatchType) -> Value {
+    json!({
+        "id": format!("{:?}", patch_type).to_lowercase(),
+        "name": patch_type.name(),
+    })
+}
+
+/// Finish the operation: print the JSON document (text mode: the error) and
+/// return the exit code for `result`
+pub fn finish(result: anyhow::Result<()>) -> ExitCode {
+    let doc = document().take();
+    let code = match &result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => ExitCode::from(classify(err).exit_code()),
+    };
+
+    let Some(mut doc) = doc else {
+        if let Err(err) = result {
+            eprintln!("Error: {:?}", err);
+        }
+        return code;
+    };
+
+    match result {
+        Ok(()) => {
+            if doc.get("status").is_none() {
+                doc["status"] = json!("ok");
+            }
+        }
+        Err(err) => {
+            let kind = classify(&err);
+            doc["status"] = json!("error");
+            doc["error"] = json!({
+                "kind": kind.id(),
+                "message": format!("{:#}", err),
+                "exit_code": kind.exit_code(),
+            });
+        }
+    }
+    println!("{}", doc);
+    code
+}
@@ -1,15 +1,23 @@
 //! RetroAchievements hash checking utilities
 
 use log::{debug, error, info, trace, warn};
+use serde_json::json;
 use std::path::Path;
+use stitchr_core::snes::has_copier_header;
 use stitchr_features::retroachievements::{
     Console, compute_n64_hash, compute_nds_hash, compute_nes_hash, compute_ps2_hash,
     compute_psp_hash, compute_psx_hash, compute_snes_hash, game_url, is_psp_iso,
     lookup_game_by_hash,
 };
+use stitchr_features::rom_info::{self, RomInfo};
 use stitchr_features::validation::algorithms::md5;
 
+use super::report;
+
 /// Detect console type from file extension
+///
+/// `.bin`/`.cue` read as PSX and `.iso` as PS2; prefer
+/// [`detect_console_from_rom`] when the data is available.
 pub fn detect_console(path: &Path) -> Option<Console> {
     let ext = path
         .extension()
@@ -30,11 +38,54 @@ pub fn detect_console(path: &Path) -> Option<Console> {
         "gg" => Some(Console::GameGear),
         "bin" | "cue" => Some(Console::Psx),
         "iso" => Some(Console::Ps2),
-        "cso" | "pbp" => Some(Console::Psp),
+        "cso" | "zso" | "pbp" => Some(Console::Psp),
         _ => None,
     }
 }
 
+/// Detect console type from the ROM's internal header, falling back to the
+/// file extension when the header is not recognized
+pub fn detect_console_from_rom(rom: &[u8], path: &Path) -> Option<Console> {
+    rom_info::identify(rom)
+        .and_then(|info| {
+            debug!("Internal header: {}", describe(&info));
+            ra_console(info.console)
+        })
+        .or_else(|| detect_console(path))
+}
+
+/// RetroAchievements console for an identified ROM
+fn ra_console(console: rom_info::Console) -> Option<Console> {
+    use rom_info::Console as Rom;
+
+    match console {
+        Rom::Nes => Some(Console::Nes),
+        Rom::Snes => Some(Console::Snes),
+        Rom::N64 => Some(Console::N64),
+        Rom::GameBoy => Some(Console::Gb),
+        Rom::GameBoyColor => Some(Console::Gbc),
+        Rom::Gba => Some(Console::Gba),
+        Rom::Nds => Some(Console::Nds),
+        Rom::MegaDrive => Some(Console::Genesis),
+        Rom::MasterSystem => Some(Console::MasterSystem),
+        Rom::GameGear => Some(Console::GameGear),
+        Rom::Psx => Some(Console::Psx),
+        Rom::Ps2 => Some(Console::Ps2),
+        Rom::Psp => Some(Console::Psp),
+        // No RetroAchievements hashing here; let the extension decide
+        Rom::PcEngine | Rom::SegaCd | Rom::Saturn | Rom::Iso9660 => None,
+    }
+}
+
+/// One-line summary of header info for logging
+fn describe(info: &RomInfo) -> String {
+    let mut parts = vec![info.console.to_string()];
+    parts.extend(info.title.as_ref().map(|t| format!("\"{}\"", t)));
+    parts.extend(info.game_code.clone());
+    parts.extend(info.region.map(|r| r.to_string()));
+    parts.join(", ")
+}
+
 /// Get human-readable hash algorithm description
 fn hash_algorithm_description(console: Console) -> &'static str {
     match console {
@@ -76,7 +127,7 @@ fn compute_ra_hash(rom: &[u8], path: &Path, console: Console) -> Result<String,
             Ok(compute_nes_hash(rom))
         }
         Console::Snes => {
-            let has_header = rom.len() > 512 && rom.len() % 8192 == 512;
+            let has_header = has_copier_header(rom.len());
             debug!(
                 "Copier header: {}",
                 if has_header {
@@ -151,12 +202,16 @@ fn compute_ra_hash(rom: &[u8], path: &Path, console: Console) -> Result<String,
 /// Compute and display RetroAchievements info for patched ROM
 pub fn check_and_display(rom: &[u8], output_path: &Path) {
     // Only check for supported consoles
-    let Some(mut console) = detect_console(output_path) else {
-        warn!("RetroAchievements: Unsupported file extension");
+    let Some(mut console) = detect_console_from_rom(rom, output_path) else {
+        warn!("RetroAchievements: Unrecognized ROM header and file extension");
+        report::set(
+            "retroachievements",
+            json!({ "status": "unrecognized_console" }),
+        );
         return;
     };
 
-    info!("Detected console from extension: {:?}", console);
+    info!("Detected console: {:?}", console);
 
     // For ISO files, distinguish between PS2 and PSP by checking content
     if console == Console::Ps2 {
@@ -174,31 +229,50 @@ pub fn check_and_display(rom: &[u8], output_path: &Path) {
     let md5_hash = match compute_ra_hash(rom, output_path, console) {
         Ok(hash) => hash,
         Err(e) => {
-            println!("\nRetroAchievements compatibility:");
-            println!("  Console: {:?}", console);
+            crate::say!("\nRetroAchievements compatibility:");
+            crate::say!("  Console: {:?}", console);
             error!("Hash computation failed: {}", e);
+            report::set(
+                "retroachievements",
+                json!({
+                    "console": format!("{:?}", console),
+                    "status": "hash_failed",
+                    "error": e,
+                }),
+            );
             return;
         }
     };
+    let mut ra = json!({
+        "console": format!("{:?}", console),
+        "hash": md5_hash,
+    });
 
-    println!("\nRetroAchievements compatibility:");
-    println!("  Console: {:?}", console);
-    println!("  MD5: {}", md5_hash);
+    crate::say!("\nRetroAchievements compatibility:");
+    crate::say!("  Console: {:?}", console);
+    crate::say!("  MD5: {}", md5_hash);
 
     // Look up game on RA
     debug!("Querying RetroAchievements API...");
     match lookup_game_by_hash(&md5_hash) {
         Ok(Some(game_id)) => {
-            println!("  Status: Recognized by RetroAchievements");
-            println!("  Game: {}", game_url(game_id));
+            crate::say!("  Status: Recognized by RetroAchievements");
+            crate::say!("  Game: {}", game_url(game_id));
             debug!("Game ID: {}", game_id);
+            ra["status"] = json!("recognized");
+            ra["game_id"] = json!(game_id);
+            ra["url"] = json!(game_url(game_id));
         }
         Ok(None) => {
-            println!("  Status: Not found in RetroAchievements database");
-            println!("  Note: This ROM may not have achievements");
+            crate::say!("  Status: Not found in RetroAchievements database");
+            crate::say!("  Note: This ROM may not have achievements");
+            ra["status"] = json!("not_found");
         }
         Err(e) => {
             error!("API lookup failed: {}", e);
+            ra["status"] = json!("lookup_failed");
+            ra["error"] = json!(e.to_string());
         }
     }
+    report::set("retroachievements", ra);
 }
@@ -0,0 +1,175 @@
+//! Reading ROMs and patches from archives
+
+#![cfg(feature = "archive")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_cli::utils::archive::{Role, read, select, split_member};
+use stitchr_cli::utils::report::{FailureKind, classify};
+use stitchr_features::archive::{ArchiveEntry, zip};
+
+const ROM: &[u8] = include_bytes!("../../../test_files/archive/rom.bin");
+const PATCH: &[u8] = include_bytes!("../../../test_files/archive/hack.ips");
+const MULTI_ZIP: &[u8] = include_bytes!("../../../test_files/archive/multi.zip");
+const SOLID_7Z: &[u8] = include_bytes!("../../../test_files/archive/solid.7z");
+const TWO_GZ: &[u8] = include_bytes!("../../../test_files/archive/two.gz");
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stitchr-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn entry(name: &str, size: u64) -> ArchiveEntry {
+    ArchiveEntry {
+        name: name.to_string(),
+        size,
+        crc32: None,
+    }
+}
+
+#[test]
+fn test_select_rom_skips_patches_and_docs() {
+    let entries = [
+        entry("readme.txt", 10),
+        entry("hack.bps", 100),
+        entry("cover.png", 100),
+        entry("empty.bin", 0),
+        entry("Game (USA).sfc", 1000),
+    ];
+    let archive = Path::new("hack.zip");
+    assert_eq!(
+        select(&entries, Role::Rom, archive).unwrap().name,
+        "Game (USA).sfc"
+    );
+    assert_eq!(
+        select(&entries, Role::Patch, archive).unwrap().name,
+        "hack.bps"
+    );
+}
+
+#[test]
+fn test_select_single_file_regardless_of_extension() {
+    let entries = [entry("notes.txt", 10)];
+    let archive = Path::new("a.zip");
+    assert_eq!(
+        select(&entries, Role::Patch, archive).unwrap().name,
+        "notes.txt"
+    );
+    assert_eq!(
+        select(&entries, Role::Rom, archive).unwrap().name,
+        "notes.txt"
+    );
+}
+
+#[test]
+fn test_select_ambiguous_lists_candidates() {
+    let entries = [
+        entry("v1.0/hack.ips", 10),
+        entry("v1.1/hack.ips", 10),
+        entry("readme.txt", 10),
+    ];
+    let err = select(&entries, Role::Patch, Path::new("hack.zip")).unwrap_err();
+    assert_eq!(classify(&err), FailureKind::Usage);
+    let message = err.to_string();
+    assert!(
+        message.contains("v1.0/hack.ips, v1.1/hack.ips"),
+        "{}",
+        message
+    );
+    assert!(!message.contains("readme.txt"), "{}", message);
+    assert!(message.contains("hack.zip:<name>"), "{}", message);
+}
+
+#[test]
+fn test_split_member() {
+    let dir = temp_dir("archive-split");
+    let archive = dir.join("Hack.ZIP");
+    fs::write(&archive, MULTI_ZIP).unwrap();
+
+    let path = PathBuf::from(format!("{}:docs/hack.ips", archive.display()));
+    assert_eq!(
+        split_member(&path),
+        Some((archive.clone(), "docs/hack.ips".to_string()))
+    );
+    // Existing files are taken literally, missing archives are not split
+    assert_eq!(split_member(&archive), None);
+    assert_eq!(split_member(&dir.join("missing.zip:a.ips")), None);
+}
+
+#[test]
+fn test_read_plain_file() {
+    let dir = temp_dir("archive-plain");
+    let path = dir.join("hack.ips");
+    fs::write(&path, PATCH).unwrap();
+
+    let (data, member) = read(&path, Role::Patch).unwrap();
+    assert_eq!(data, PATCH);
+    assert!(member.is_none());
+}
+
+#[test]
+fn test_read_picks_by_role() {
+    let dir = temp_dir("archive-role");
+    let path = dir.join("release.7z");
+    fs::write(&path, SOLID_7Z).unwrap();
+
+    let (rom, member) = read(&path, Role::Rom).unwrap();
+    assert_eq!(rom, ROM);
+    let member = member.unwrap();
+    assert_eq!(member.name, "Game (USA).sfc");
+    assert_eq!(member.extracted_path(), dir.join("Game (USA).sfc"));
+
+    let (patch, member) = read(&path, Role::Patch).unwrap();
+    assert_eq!(patch, PATCH);
+    assert_eq!(member.unwrap().extracted_path(), dir.join("hack.ips"));
+}
+
+#[test]
+fn test_read_explicit_member() {
+    let dir = temp_dir("archive-member");
+    let archive = dir.join("multi.zip");
+    fs::write(&archive, MULTI_ZIP).unwrap();
+
+    let path = PathBuf::from(format!("{}:docs/readme.txt", archive.display()));
+    let (data, _) = read(&path, Role::Rom).unwrap();
+    assert_eq!(data, b"Apply to the USA ROM.\n");
+
+    let path = PathBuf::from(format!("{}:docs/missing.ips", archive.display()));
+    let err = read(&path, Role::Patch).unwrap_err();
+    assert_eq!(classify(&err), FailureKind::Usage);
+}
+
+#[test]
+fn test_read_member_of_non_archive() {
+    let dir = temp_dir("archive-not");
+    let path = dir.join("rom.zip");
+    fs::write(&path, ROM).unwrap();
+
+    let member = PathBuf::from(format!("{}:rom.bin", path.display()));
+    let err = read(&member, Role::Rom).unwrap_err();
+    assert_eq!(classify(&err), FailureKind::UnsupportedFormat);
+}
+
+#[test]
+fn test_read_gzip_without_name() {
+    let dir = temp_dir("archive-gzip");
+    let path = dir.join("game.sfc.gz");
+    fs::write(&path, TWO_GZ).unwrap();
+
+    let (data, member) = read(&path, Role::Rom).unwrap();
+    assert_eq!(data, ROM);
+    assert_eq!(member.unwrap().extracted_path(), dir.join("game.sfc"));
+}
+
+#[test]
+fn test_read_written_zip() {
+    let dir = temp_dir("archive-written");
+    let path = dir.join("out.zip");
+    fs::write(&path, zip::create("out.sfc", ROM)).unwrap();
+
+    let (data, member) = read(&path, Role::Rom).unwrap();
+    assert_eq!(data, ROM);
+    assert_eq!(member.unwrap().name, "out.sfc");
+}
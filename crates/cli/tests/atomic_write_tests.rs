@@ -0,0 +1,130 @@
+//! Crash-safe output writing
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use stitchr_cli::utils::atomic::{WriteOptions, backup_path, write_file, write_with};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stitchr-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_write_leaves_no_temp_files() {
+    let dir = temp_dir("atomic-plain");
+    let path = dir.join("game.sfc");
+    // The old writer used `game.tmp` and would have replaced this file
+    fs::write(dir.join("game.tmp"), b"unrelated").unwrap();
+
+    write_file(&path, b"first", &WriteOptions::default()).unwrap();
+    write_file(&path, b"second", &WriteOptions::default()).unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), b"second");
+    assert_eq!(fs::read(dir.join("game.tmp")).unwrap(), b"unrelated");
+    assert_eq!(names(&dir), ["game.sfc", "game.tmp"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_failed_stream_keeps_previous_contents() {
+    let dir = temp_dir("atomic-stream");
+    let path = dir.join("game.iso");
+    fs::write(&path, b"old").unwrap();
+
+    let err = write_with(&path, &WriteOptions::default(), |file| {
+        std::io::Write::write_all(file, b"partial")?;
+        anyhow::bail!("read error")
+    })
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("read error"), "{:#}", err);
+    assert_eq!(fs::read(&path).unwrap(), b"old");
+    assert_eq!(names(&dir), ["game.iso"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_backup_keeps_previous_contents() {
+    let dir = temp_dir("atomic-backup");
+    let path = dir.join("game.gba");
+    let options = WriteOptions {
+        preserve_from: None,
+        backup: true,
+    };
+
+    // Nothing to back up yet
+    write_file(&path, b"original", &options).unwrap();
+    assert!(!backup_path(&path).exists());
+
+    write_file(&path, b"patched", &options).unwrap();
+    assert_eq!(backup_path(&path), dir.join("game.gba.bak"));
+    assert_eq!(fs::read(&path).unwrap(), b"patched");
+    assert_eq!(fs::read(dir.join("game.gba.bak")).unwrap(), b"original");
+    assert_eq!(names(&dir), ["game.gba", "game.gba.bak"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_preserve_modification_time() {
+    let dir = temp_dir("atomic-preserve");
+    let source = dir.join("game.nes");
+    fs::write(&source, b"rom").unwrap();
+    let then = SystemTime::UNIX_EPOCH + Duration::from_secs(1_577_836_800);
+    fs::File::options()
+        .write(true)
+        .open(&source)
+        .unwrap()
+        .set_modified(then)
+        .unwrap();
+
+    let output = dir.join("out.nes");
+    let options = WriteOptions {
+        preserve_from: Some(source.clone()),
+        backup: false,
+    };
+    write_file(&output, b"patched", &options).unwrap();
+    assert_eq!(fs::metadata(&output).unwrap().modified().unwrap(), then);
+
+    // Without --preserve the output is simply new
+    write_file(&output, b"again", &WriteOptions::default()).unwrap();
+    assert_ne!(fs::metadata(&output).unwrap().modified().unwrap(), then);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("atomic-mode");
+    let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    let source = dir.join("game.gb");
+    fs::write(&source, b"rom").unwrap();
+    fs::set_permissions(&source, fs::Permissions::from_mode(0o640)).unwrap();
+
+    // An existing output keeps its mode when replaced
+    let output = dir.join("out.gb");
+    fs::write(&output, b"old").unwrap();
+    fs::set_permissions(&output, fs::Permissions::from_mode(0o600)).unwrap();
+    write_file(&output, b"new", &WriteOptions::default()).unwrap();
+    assert_eq!(mode(&output), 0o600);
+
+    let options = WriteOptions {
+        preserve_from: Some(source.clone()),
+        backup: false,
+    };
+    write_file(&output, b"newer", &options).unwrap();
+    assert_eq!(mode(&output), 0o640);
+    fs::remove_dir_all(&dir).unwrap();
+}
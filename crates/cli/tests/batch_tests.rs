@@ -0,0 +1,97 @@
+//! Batch manifest building blocks: globbing, output templates
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_cli::utils::glob;
+use stitchr_cli::utils::paths::{DEFAULT_OUTPUT_TEMPLATE, expand_template};
+
+#[test]
+fn test_glob_expand() {
+    let dir = std::env::temp_dir().join(format!("stitchr-glob-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for file in [
+        "a.sfc",
+        "b.smc",
+        "sub/c.sfc",
+        "sub/deep/d.sfc",
+        ".hidden/e.sfc",
+        ".f.sfc",
+        "banana.gb",
+        "Game (USA) [!].sfc",
+        "{x,y}.gba",
+    ] {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"rom").unwrap();
+    }
+
+    let names = |pattern: &str| -> Vec<PathBuf> {
+        glob::expand(&dir.join(pattern))
+            .unwrap()
+            .into_iter()
+            .map(|p| p.strip_prefix(&dir).unwrap().to_path_buf())
+            .collect()
+    };
+    assert_eq!(
+        names("*.sfc"),
+        vec![PathBuf::from("Game (USA) [!].sfc"), PathBuf::from("a.sfc")]
+    );
+    assert!(names(".*.sfc").is_empty());
+    assert_eq!(names("*a*a*"), vec![PathBuf::from("banana.gb")]);
+    assert_eq!(
+        names("?.s?c"),
+        vec![PathBuf::from("a.sfc"), PathBuf::from("b.smc")]
+    );
+    assert_eq!(
+        names("**/*.sfc"),
+        vec![
+            PathBuf::from("Game (USA) [!].sfc"),
+            PathBuf::from("a.sfc"),
+            PathBuf::from("sub/c.sfc"),
+            PathBuf::from("sub/deep/d.sfc"),
+        ]
+    );
+    assert_eq!(names("s*/*"), vec![PathBuf::from("sub/c.sfc")]);
+    assert!(names("*.smd").is_empty());
+
+    // No character classes or alternations: brackets and braces are literal
+    assert_eq!(
+        names("* [!].sfc"),
+        vec![PathBuf::from("Game (USA) [!].sfc")]
+    );
+    assert!(names("[ab].s?c").is_empty());
+    assert_eq!(names("{x,y}*"), vec![PathBuf::from("{x,y}.gba")]);
+
+    // Literal paths are passed through
+    assert_eq!(
+        glob::expand(Path::new("missing.sfc")).unwrap(),
+        vec![PathBuf::from("missing.sfc")]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_expand_template() {
+    let vars = [
+        ("rom_dir", "roms".to_string()),
+        ("rom_stem", "game".to_string()),
+        ("ext", "sfc".to_string()),
+    ];
+    assert_eq!(
+        expand_template(DEFAULT_OUTPUT_TEMPLATE, &vars).unwrap(),
+        "roms/patched/game.patched.sfc"
+    );
+    assert_eq!(
+        expand_template("{{{rom_stem}}}.{ext}", &vars).unwrap(),
+        "{game}.sfc"
+    );
+
+    let no_ext = [("rom_stem", "game".to_string()), ("ext", String::new())];
+    assert_eq!(
+        expand_template("{rom_stem}.{ext}", &no_ext).unwrap(),
+        "game"
+    );
+
+    assert!(expand_template("{title}.sfc", &vars).is_err());
+    assert!(expand_template("{rom_stem", &vars).is_err());
+}
@@ -0,0 +1,293 @@
+//! Patching the disc of a CUE sheet
+
+#![cfg(feature = "disc")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_cli::utils::cue::{
+    CueInput, is_cue, load, open, output_file_names, write_device, write_disc,
+};
+use stitchr_cli::utils::paths::OnExists;
+
+const SECTOR: usize = 2352;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stitchr-cue-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A split dump: a 10-sector data track and a 4-sector audio track with a
+/// stored 2-sector pregap
+fn split_dump(name: &str) -> (PathBuf, CueInput, Vec<u8>) {
+    let dir = temp_dir(name);
+    fs::write(dir.join("Game (Track 1).bin"), vec![1u8; 10 * SECTOR]).unwrap();
+    fs::write(dir.join("Game (Track 2).bin"), vec![2u8; 4 * SECTOR]).unwrap();
+    fs::write(
+        dir.join("Game.cue"),
+        "FILE \"Game (Track 1).bin\" BINARY\n  TRACK 01 MODE2/2352\n    INDEX 01 00:00:00\n\
+         FILE \"Game (Track 2).bin\" BINARY\n  TRACK 02 AUDIO\n    INDEX 00 00:00:00\n    \
+         INDEX 01 00:00:02\n",
+    )
+    .unwrap();
+    let (cue, disc) = load(&dir.join("Game.cue")).unwrap();
+    (dir, cue, disc)
+}
+
+/// The same disc merged into one BIN
+fn merged_dump(name: &str) -> (PathBuf, CueInput) {
+    let dir = temp_dir(name);
+    let mut disc = vec![1u8; 10 * SECTOR];
+    disc.extend(vec![2u8; 4 * SECTOR]);
+    fs::write(dir.join("Game.bin"), disc).unwrap();
+    fs::write(
+        dir.join("Game.cue"),
+        "FILE \"Game.bin\" BINARY\n  TRACK 01 MODE2/2352\n    INDEX 01 00:00:00\n  \
+         TRACK 02 AUDIO\n    INDEX 00 00:00:10\n    INDEX 01 00:00:12\n",
+    )
+    .unwrap();
+    let (cue, _) = load(&dir.join("Game.cue")).unwrap();
+    (dir, cue)
+}
+
+#[test]
+fn test_is_cue() {
+    assert!(is_cue(Path::new("game.cue")));
+    assert!(is_cue(Path::new("GAME.CUE")));
+    assert!(!is_cue(Path::new("game.bin")));
+}
+
+#[test]
+fn test_load_concatenates_files() {
+    let (dir, cue, disc) = split_dump("load");
+    assert_eq!(disc.len(), 14 * SECTOR);
+    assert_eq!(disc[10 * SECTOR - 1], 1);
+    assert_eq!(disc[10 * SECTOR], 2);
+    assert_eq!(cue.file_sizes, vec![10 * SECTOR as u64, 4 * SECTOR as u64]);
+    assert_eq!(cue.disc_size(), disc.len() as u64);
+    assert_eq!(cue.file_paths()[1], dir.join("Game (Track 2).bin"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_target_track_by_source_size() {
+    let (dir, split, _) = split_dump("target-split");
+    let (merged_dir, merged) = merged_dump("target-merged");
+    for cue in [&split, &merged] {
+        // Patches without a size, or made against the whole disc
+        assert_eq!(cue.target_track(None), None);
+        assert_eq!(cue.target_track(Some(14 * SECTOR)), None);
+        // A patch made against Track 1.bin
+        let track = cue.target_track(Some(10 * SECTOR)).unwrap();
+        assert_eq!(
+            (track.number, track.start, track.end),
+            (1, 0, 10 * SECTOR as u64)
+        );
+        // Sizes that match no track
+        assert_eq!(cue.target_track(Some(123)), None);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+    fs::remove_dir_all(&merged_dir).unwrap();
+}
+
+#[test]
+fn test_patched_file_sizes() {
+    let (dir, split, _) = split_dump("sizes-split");
+    let (merged_dir, merged) = merged_dump("sizes-merged");
+    let track1 = split.target_track(Some(10 * SECTOR)).unwrap();
+    let sector = SECTOR as u64;
+
+    // Same size: files are unchanged
+    assert_eq!(
+        split.patched_file_sizes(None, 14 * sector).unwrap(),
+        vec![10 * sector, 4 * sector]
+    );
+    // A whole-disc patch that grows the disc grows the last file
+    assert_eq!(
+        split.patched_file_sizes(None, 15 * sector).unwrap(),
+        vec![10 * sector, 5 * sector]
+    );
+    // A grown track 1 grows its own file in a split dump
+    assert_eq!(
+        split
+            .patched_file_sizes(Some(&track1), 11 * sector)
+            .unwrap(),
+        vec![11 * sector, 4 * sector]
+    );
+    // ...but would move track 2 in a merged one
+    let err = merged
+        .patched_file_sizes(Some(&track1), 11 * sector)
+        .unwrap_err();
+    assert!(err.contains("followed by another track"), "{}", err);
+    // Shrinking past the start of the last track
+    assert!(split.patched_file_sizes(None, 10 * sector).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+    fs::remove_dir_all(&merged_dir).unwrap();
+}
+
+#[test]
+fn test_output_file_names() {
+    let (dir, split, _) = split_dump("names");
+    assert_eq!(
+        output_file_names(&split, Path::new("out/Game.patched.cue")),
+        vec![
+            "Game.patched (Track 1).bin".to_string(),
+            "Game.patched (Track 2).bin".to_string()
+        ]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+
+    // BINs not named after the sheet keep their name behind the output's
+    let dir = temp_dir("names-other");
+    fs::create_dir_all(dir.join("bins")).unwrap();
+    fs::write(dir.join("bins/data.bin"), vec![0u8; SECTOR]).unwrap();
+    fs::write(
+        dir.join("Game.cue"),
+        "FILE \"bins/data.bin\" BINARY\nTRACK 01 MODE1/2352\nINDEX 01 00:00:00\n",
+    )
+    .unwrap();
+    let (cue, _) = load(&dir.join("Game.cue")).unwrap();
+    assert_eq!(
+        output_file_names(&cue, Path::new("Hack.cue")),
+        vec!["Hack data.bin".to_string()]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_split_disc() {
+    let (dir, split, _) = split_dump("write-split");
+    // A whole-disc patch that grows the last track by a sector
+    let mut patched = vec![3u8; 10 * SECTOR];
+    patched.extend(vec![4u8; 5 * SECTOR]);
+    let output = dir.join("Game.patched.cue");
+    let written = write_disc(&split, None, patched, &output, OnExists::Fail, false).unwrap();
+
+    assert_eq!(
+        written.bins,
+        vec![
+            dir.join("Game.patched (Track 1).bin"),
+            dir.join("Game.patched (Track 2).bin")
+        ]
+    );
+    assert_eq!(fs::read(&written.bins[0]).unwrap(), vec![3u8; 10 * SECTOR]);
+    assert_eq!(fs::read(&written.bins[1]).unwrap(), vec![4u8; 5 * SECTOR]);
+    let sheet = fs::read_to_string(&output).unwrap();
+    assert!(sheet.contains("FILE \"Game.patched (Track 1).bin\" BINARY"));
+    assert!(sheet.contains("FILE \"Game.patched (Track 2).bin\" BINARY"));
+    // The input is untouched
+    assert_eq!(
+        fs::read(dir.join("Game (Track 2).bin")).unwrap(),
+        vec![2u8; 4 * SECTOR]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_patched_track_into_disc() {
+    let (dir, merged) = merged_dump("write-splice");
+    let (_, disc) = load(&dir.join("Game.cue")).unwrap();
+    let track1 = merged.target_track(Some(10 * SECTOR)).unwrap();
+    let output = dir.join("Hack.cue");
+    let written = write_disc(
+        &merged,
+        Some((track1, disc)),
+        vec![7u8; 10 * SECTOR],
+        &output,
+        OnExists::Fail,
+        false,
+    )
+    .unwrap();
+
+    let mut expected = vec![7u8; 10 * SECTOR];
+    expected.extend(vec![2u8; 4 * SECTOR]);
+    assert_eq!(written.disc, expected);
+    assert_eq!(written.bins, vec![dir.join("Hack.bin")]);
+    assert_eq!(fs::read(dir.join("Hack.bin")).unwrap(), expected);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_merged_bin() {
+    let (dir, split, disc) = split_dump("write-merged");
+    let output = dir.join("Game.merged.bin");
+    let written = write_disc(&split, None, disc.clone(), &output, OnExists::Fail, false).unwrap();
+    assert!(written.bins.is_empty());
+    assert_eq!(fs::read(&output).unwrap(), disc);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_applies_on_exists_to_bins() {
+    let (dir, split, disc) = split_dump("write-on-exists");
+    let output = dir.join("Game.patched.cue");
+    let existing = dir.join("Game.patched (Track 2).bin");
+    fs::write(&existing, b"keep").unwrap();
+
+    // Nothing is written when any BIN is refused
+    let err = write_disc(&split, None, disc.clone(), &output, OnExists::Fail, false).unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+    assert!(!dir.join("Game.patched (Track 1).bin").exists());
+    assert!(!output.exists());
+    assert_eq!(fs::read(&existing).unwrap(), b"keep");
+
+    let written = write_disc(&split, None, disc, &output, OnExists::Suffix, false).unwrap();
+    assert_eq!(written.bins[1], dir.join("Game.patched (Track 2) (1).bin"));
+    assert_eq!(fs::read(&existing).unwrap(), b"keep");
+    let sheet = fs::read_to_string(&output).unwrap();
+    assert!(sheet.contains("FILE \"Game.patched (Track 2) (1).bin\" BINARY"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_refuses_to_overwrite_input_bins() {
+    let (dir, split, disc) = split_dump("write-input");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    // The BIN names match the input's, spelled through another directory
+    let output = dir.join("sub/../Game.cue");
+    let err = write_disc(
+        &split,
+        None,
+        disc.clone(),
+        &output,
+        OnExists::Overwrite,
+        false,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("overwrite input BIN"), "{}", err);
+
+    let output = dir.join("sub/../Game (Track 1).bin");
+    let err = write_disc(&split, None, disc, &output, OnExists::Overwrite, false).unwrap_err();
+    assert!(err.to_string().contains("overwrite input BIN"), "{}", err);
+    assert_eq!(
+        fs::read(dir.join("Game (Track 1).bin")).unwrap(),
+        vec![1u8; 10 * SECTOR]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_device_streams_disc() {
+    let (dir, _, disc) = split_dump("write-device");
+    let (split, device) = open(&dir.join("Game.cue")).unwrap();
+    let output = dir.join("Game.patched.cue");
+    let bins = write_device(&split, &device, &output, OnExists::Fail).unwrap();
+    assert_eq!(bins.len(), 2);
+    let mut written = fs::read(&bins[0]).unwrap();
+    written.extend(fs::read(&bins[1]).unwrap());
+    assert_eq!(written, disc);
+
+    // A merged BIN, then an existing output refused
+    let output = dir.join("Game.merged.bin");
+    assert!(
+        write_device(&split, &device, &output, OnExists::Fail)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(fs::read(&output).unwrap(), disc);
+    let err = write_device(&split, &device, &dir.join("Game.cue"), OnExists::Fail).unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,31 @@
+//! EDC/ECC regeneration as --fix-ecc and batch `fix_ecc` apply it
+
+#![cfg(feature = "disc")]
+
+use stitchr_cli::utils::edc_ecc::fix;
+
+const PSX_BIN: &[u8] = include_bytes!("../../../test_files/disc/psx.bin");
+
+const SECTOR: usize = 2352;
+
+#[test]
+fn test_fix_counts_regenerated_sectors() {
+    let mut patched = PSX_BIN.to_vec();
+    patched[16 * SECTOR + 24] ^= 0xFF;
+    let fixes = fix(PSX_BIN, &mut patched).unwrap();
+    assert_eq!((fixes.modified, fixes.fixed()), (1, 1));
+    assert_ne!(
+        patched[16 * SECTOR + 0x818..17 * SECTOR],
+        PSX_BIN[16 * SECTOR + 0x818..17 * SECTOR]
+    );
+}
+
+#[test]
+fn test_fix_leaves_other_images_alone() {
+    let original = vec![0u8; 4 * 2048];
+    let mut patched = original.clone();
+    patched[100] = 1;
+    assert!(fix(&original, &mut patched).is_none());
+    assert_eq!(patched[100], 1);
+    assert_eq!(patched[..100], original[..100]);
+}
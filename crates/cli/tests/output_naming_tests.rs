@@ -0,0 +1,95 @@
+//! Output templates and the --on-exists policy
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_cli::utils::paths::{
+    OnExists, TemplateInput, expand_template, resolve_existing, sanitize, template_vars,
+};
+use stitchr_cli::utils::report::{FailureKind, classify};
+use stitchr_core::PatchType;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stitchr-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_template_vars() {
+    let input = TemplateInput {
+        rom_path: Path::new("roms/Game (USA).sfc"),
+        patch_path: Path::new("patches/hack v1.2.bps"),
+        patch_type: PatchType::Bps,
+        title: Some("Hack: Director's Cut".to_string()),
+        output: b"123456789",
+    };
+    let vars = template_vars(&input);
+    assert_eq!(
+        expand_template("{rom_dir}/{title} [{format}] {crc32}.{ext}", &vars).unwrap(),
+        "roms/Hack - Director's Cut [bps] cbf43926.sfc"
+    );
+    assert_eq!(
+        expand_template("{rom_stem} - {patch_stem} ({console})", &vars).unwrap(),
+        "Game (USA) - hack v1.2 (Unknown)"
+    );
+
+    // No title in the patch: fall back to its file name
+    let vars = template_vars(&TemplateInput {
+        title: None,
+        ..input
+    });
+    assert_eq!(expand_template("{title}", &vars).unwrap(), "hack v1.2");
+}
+
+#[test]
+fn test_sanitize() {
+    assert_eq!(sanitize("A: B"), "A - B");
+    assert_eq!(sanitize("a/b\\c*?\"<>|"), "a_b_c______");
+    assert_eq!(sanitize(" tab\there "), "tab_here");
+    assert_eq!(sanitize(".."), "_");
+    assert_eq!(sanitize(" . "), "_");
+    assert_eq!(sanitize("..."), "...");
+}
+
+#[test]
+fn test_on_exists_policies() {
+    let dir = temp_dir("on-exists");
+    let path = dir.join("game.sfc");
+    let none = HashSet::new();
+
+    // Nothing there yet: every policy keeps the path
+    for policy in [OnExists::Fail, OnExists::Overwrite, OnExists::Suffix] {
+        assert_eq!(resolve_existing(&path, policy, &none).unwrap(), path);
+    }
+
+    fs::write(&path, b"old").unwrap();
+    fs::write(dir.join("game (1).sfc"), b"old").unwrap();
+    let err = resolve_existing(&path, OnExists::Fail, &none).unwrap_err();
+    assert_eq!(classify(&err), FailureKind::OutputExists);
+    assert_eq!(FailureKind::OutputExists.exit_code(), 8);
+    assert_eq!(
+        resolve_existing(&path, OnExists::Overwrite, &none).unwrap(),
+        path
+    );
+    assert_eq!(
+        resolve_existing(&path, OnExists::Suffix, &none).unwrap(),
+        dir.join("game (2).sfc")
+    );
+
+    // Outputs claimed by other jobs count as taken, even for overwrite
+    let claimed = HashSet::from([dir.join("game (2).sfc"), dir.join("new")]);
+    assert_eq!(
+        resolve_existing(&path, OnExists::Suffix, &claimed).unwrap(),
+        dir.join("game (3).sfc")
+    );
+    let new = dir.join("new");
+    assert!(resolve_existing(&new, OnExists::Overwrite, &claimed).is_err());
+    assert_eq!(
+        resolve_existing(&new, OnExists::Suffix, &claimed).unwrap(),
+        dir.join("new (1)")
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,57 @@
+//! JSON output and failure classification tests
+
+use serde_json::json;
+use std::path::Path;
+use stitchr_cli::utils::report::{FailureKind, classify, fail, file, format};
+use stitchr_core::{PatchError, PatchType};
+
+#[test]
+fn test_json_keeps_field_order() {
+    // Documents list fields in the order commands set them
+    let mut doc = json!({ "operation": "apply", "rom": null, "status": "ok" });
+    doc["rom"] = json!({ "path": "rom.gb", "size": 16 });
+    assert_eq!(
+        doc.to_string(),
+        r#"{"operation":"apply","rom":{"path":"rom.gb","size":16},"status":"ok"}"#
+    );
+}
+
+#[test]
+fn test_classify_failure_and_context() {
+    let err = fail(FailureKind::PatchState, "already applied").context("outer");
+    assert_eq!(classify(&err), FailureKind::PatchState);
+    assert_eq!(FailureKind::PatchState.exit_code(), 7);
+}
+
+#[test]
+fn test_classify_patch_errors() {
+    let mismatch = anyhow::Error::from(PatchError::ChecksumMismatch {
+        expected: 1,
+        actual: 2,
+    })
+    .context("Source ROM checksum verification failed");
+    assert_eq!(classify(&mismatch), FailureKind::Verification);
+
+    let corrupt = anyhow::Error::from(PatchError::CorruptedData);
+    assert_eq!(classify(&corrupt), FailureKind::InvalidPatch);
+    assert_eq!(FailureKind::InvalidPatch.exit_code(), 5);
+}
+
+#[test]
+fn test_classify_io_and_other() {
+    let missing = std::fs::read("/nonexistent/stitchr/rom.bin")
+        .map_err(anyhow::Error::from)
+        .unwrap_err()
+        .context("Failed to read ROM file");
+    assert_eq!(classify(&missing), FailureKind::Io);
+    assert_eq!(classify(&anyhow::anyhow!("plain")), FailureKind::Other);
+    assert_eq!(FailureKind::Other.exit_code(), 1);
+}
+
+#[test]
+fn test_file_and_format_entries() {
+    // Hashes are only computed in JSON mode
+    let entry = file(Path::new("rom.gb"), &[0; 16]);
+    assert_eq!(entry.to_string(), r#"{"path":"rom.gb","size":16}"#);
+    assert_eq!(format(PatchType::Ips32).get("id"), Some(&json!("ips32")));
+}
@@ -3,7 +3,7 @@
 #[cfg(feature = "retroachievements")]
 use std::path::Path;
 #[cfg(feature = "retroachievements")]
-use stitchr_cli::utils::retroachievements::detect_console;
+use stitchr_cli::utils::retroachievements::{detect_console, detect_console_from_rom};
 #[cfg(feature = "retroachievements")]
 use stitchr_features::retroachievements::Console;
 
@@ -58,6 +58,7 @@ fn test_detect_console_playstation() {
     assert_eq!(detect_console(Path::new("game.cue")), Some(Console::Psx));
     assert_eq!(detect_console(Path::new("game.iso")), Some(Console::Ps2));
     assert_eq!(detect_console(Path::new("game.cso")), Some(Console::Psp));
+    assert_eq!(detect_console(Path::new("game.zso")), Some(Console::Psp));
     assert_eq!(detect_console(Path::new("game.pbp")), Some(Console::Psp));
 }
 
@@ -90,3 +91,34 @@ fn test_detect_console_with_path() {
         Some(Console::Nes)
     );
 }
+
+#[test]
+#[cfg(feature = "retroachievements")]
+fn test_detect_console_from_header_overrides_extension() {
+    // Mega Drive ROM with a .bin extension (previously read as PSX)
+    let mut rom = vec![0u8; 0x400];
+    rom[0x100..0x110].copy_from_slice(b"SEGA MEGA DRIVE ");
+    assert_eq!(
+        detect_console_from_rom(&rom, Path::new("sonic.bin")),
+        Some(Console::Genesis)
+    );
+
+    // iNES header in a file with no useful extension
+    let mut rom = vec![0u8; 0x4010];
+    rom[..4].copy_from_slice(b"NES\x1A");
+    assert_eq!(
+        detect_console_from_rom(&rom, Path::new("game.rom")),
+        Some(Console::Nes)
+    );
+}
+
+#[test]
+#[cfg(feature = "retroachievements")]
+fn test_detect_console_from_rom_falls_back_to_extension() {
+    let rom = vec![0u8; 0x100];
+    assert_eq!(
+        detect_console_from_rom(&rom, Path::new("game.gba")),
+        Some(Console::Gba)
+    );
+    assert_eq!(detect_console_from_rom(&rom, Path::new("game.txt")), None);
+}
@@ -1,6 +1,6 @@
 //! Core trait for patch format implementations
 
-use crate::{PatchMetadata, Result};
+use crate::{LintWarning, PatchMetadata, Result};
 
 /// Trait that all patch formats must implement
 ///
@@ -47,6 +47,24 @@ pub trait PatchFormat: Send + Sync {
     where
         Self: Sized;
 
+    /// Report suspicious or non-canonical constructs in a patch
+    ///
+    /// # Arguments
+    /// * `patch` - Patch data to inspect
+    ///
+    /// # Errors
+    /// Returns an error if the patch is too broken to inspect
+    ///
+    /// # Default Implementation
+    /// Runs `validate` and reports no findings
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>>
+    where
+        Self: Sized,
+    {
+        Self::validate(patch)?;
+        Ok(Vec::new())
+    }
+
     /// Verify checksums (source ROM and optionally target ROM)
     ///
     /// # Arguments
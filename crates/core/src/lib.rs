@@ -5,8 +5,11 @@
 
 pub mod error;
 pub mod format;
+pub mod lint;
+pub mod snes;
 pub mod types;
 
 pub use error::{PatchError, Result};
 pub use format::PatchFormat;
+pub use lint::{LintWarning, Severity};
 pub use types::{PatchMetadata, PatchType};
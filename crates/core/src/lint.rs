@@ -0,0 +1,65 @@
+//! Lint findings for patch files
+//!
+//! `validate` only answers "can this patch be applied". Linting reports
+//! constructs that apply fine but are suspicious or non-canonical, each with
+//! the patch offset it was found at.
+
+use std::fmt;
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Non-canonical but harmless
+    Info,
+    /// Likely a tooling bug; some patchers may behave differently
+    Warning,
+    /// The patch does not do what it appears to (ignored data, bad checksum)
+    Error,
+}
+
+impl Severity {
+    /// Lowercase name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// One lint finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    /// Offset in the patch file
+    pub offset: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl LintWarning {
+    /// Create a finding
+    pub fn new(offset: usize, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0x{:08X} {}: {}",
+            self.offset, self.severity, self.message
+        )
+    }
+}
@@ -0,0 +1,76 @@
+//! SNES ROM layout shared by the patch formats and the hashing features
+
+/// SMC/SWC copier header size
+pub const COPIER_HEADER_SIZE: usize = 512;
+
+/// SNES ROM block size (8KB)
+pub const BLOCK_SIZE: usize = 8 * 1024;
+
+/// Check if a ROM of this size has a copier header (512 bytes over a
+/// multiple of 8KB)
+#[inline]
+pub fn has_copier_header(size: usize) -> bool {
+    size > COPIER_HEADER_SIZE && size % BLOCK_SIZE == COPIER_HEADER_SIZE
+}
+
+/// Internal header size
+pub const INTERNAL_HEADER_SIZE: usize = 0x20;
+
+/// Internal header locations (LoROM, HiROM, ExHiROM) with the map modes
+/// (low nibble) valid at each
+const INTERNAL_HEADER_LOCATIONS: [(usize, &[u8]); 3] = [
+    (0x7FC0, &[0x0, 0x2, 0x3]),
+    (0xFFC0, &[0x1, 0xA]),
+    (0x40FFC0, &[0x5]),
+];
+
+const TITLE_LEN: usize = 21;
+const MAP_MODE: usize = 0x15;
+const COMPLEMENT: usize = 0x1C;
+const CHECKSUM: usize = 0x1E;
+
+/// ROM data without its copier header, if it has one
+pub fn strip_copier_header(rom: &[u8]) -> &[u8] {
+    if has_copier_header(rom.len()) {
+        &rom[COPIER_HEADER_SIZE..]
+    } else {
+        rom
+    }
+}
+
+/// Locate the internal header in a ROM without a copier header
+///
+/// A location qualifies when its map mode byte fits the layout and its title
+/// is printable (ASCII or half-width katakana); a consistent
+/// checksum/complement pair wins ties.
+pub fn find_internal_header(rom: &[u8]) -> Option<usize> {
+    let mut best = None;
+    let mut best_score = 0;
+
+    for (offset, modes) in INTERNAL_HEADER_LOCATIONS {
+        let Some(header) = rom.get(offset..offset + INTERNAL_HEADER_SIZE) else {
+            continue;
+        };
+
+        let mode = header[MAP_MODE];
+        if mode & 0xE0 != 0x20 || !modes.contains(&(mode & 0x0F)) {
+            continue;
+        }
+        if !header[..TITLE_LEN]
+            .iter()
+            .all(|&b| (0x20..0x7F).contains(&b) || (0xA0..0xE0).contains(&b))
+        {
+            continue;
+        }
+
+        let complement = u16::from_le_bytes([header[COMPLEMENT], header[COMPLEMENT + 1]]);
+        let checksum = u16::from_le_bytes([header[CHECKSUM], header[CHECKSUM + 1]]);
+        let score = 1 + (complement ^ checksum == 0xFFFF) as u32;
+        if score > best_score {
+            best = Some(offset);
+            best_score = score;
+        }
+    }
+
+    best
+}
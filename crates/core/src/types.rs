@@ -5,8 +5,12 @@
 pub enum PatchType {
     /// International Patching System
     Ips,
+    /// IPS with 32-bit offsets
+    Ips32,
     /// Beat Patching System (byuu)
     Bps,
+    /// Beat multi-file (directory) patch
+    Bpm,
     /// Universal Patching System
     Ups,
     /// Nintendo 64 APS format
@@ -21,6 +25,10 @@ pub enum PatchType {
     Xdelta,
     /// Binary Diff Format (bsdiff)
     Bdf,
+    /// Paper Mario Star Rod
+    Pmsr,
+    /// W3C Generic Diff Format
+    Gdiff,
 }
 
 impl PatchType {
@@ -28,7 +36,9 @@ impl PatchType {
     pub fn extension(&self) -> &'static str {
         match self {
             Self::Ips => "ips",
+            Self::Ips32 => "ips",
             Self::Bps => "bps",
+            Self::Bpm => "bpm",
             Self::Ups => "ups",
             Self::Aps => "aps",
             Self::Ebp => "ebp",
@@ -36,6 +46,8 @@ impl PatchType {
             Self::Ppf => "ppf",
             Self::Xdelta => "xdelta",
             Self::Bdf => "bdf",
+            Self::Pmsr => "pmsr",
+            Self::Gdiff => "gdiff",
         }
     }
 
@@ -43,7 +55,9 @@ impl PatchType {
     pub fn name(&self) -> &'static str {
         match self {
             Self::Ips => "International Patching System",
+            Self::Ips32 => "International Patching System (32-bit)",
             Self::Bps => "Beat Patching System",
+            Self::Bpm => "Beat Multi-file Patch",
             Self::Ups => "Universal Patching System",
             Self::Aps => "Nintendo 64 APS Format",
             Self::Ebp => "Extended Binary Patch",
@@ -51,6 +65,8 @@ impl PatchType {
             Self::Ppf => "PlayStation Patch Format",
             Self::Xdelta => "xdelta Binary Diff",
             Self::Bdf => "Binary Diff Format",
+            Self::Pmsr => "Paper Mario Star Rod",
+            Self::Gdiff => "Generic Diff Format",
         }
     }
 }
@@ -0,0 +1,102 @@
+//! gzip files (RFC 1952), including concatenated members, read with `flate2`
+
+use std::io::{self, Read};
+
+use flate2::bufread::GzDecoder;
+use stitchr_core::{PatchError, Result};
+
+use super::{ArchiveEntry, MAX_EXTRACT_SIZE};
+
+/// The single file of a gzip stream
+///
+/// The name comes from the header's FNAME field; without one it is empty
+/// and callers name the file after the archive. The size and CRC32 are from
+/// the last member's trailer (the size is modulo 4 GiB).
+pub fn entry(data: &[u8]) -> Result<ArchiveEntry> {
+    let decoder = GzDecoder::new(data);
+    let header = decoder.header().ok_or_else(invalid_header)?;
+    let (crc32, size) = trailer(data).ok_or_else(|| eof("gzip trailer"))?;
+    Ok(ArchiveEntry {
+        // ISO 8859-1 per the RFC
+        name: header
+            .filename()
+            .map(|name| name.iter().map(|&b| b as char).collect())
+            .unwrap_or_default(),
+        size: size as u64,
+        crc32: Some(crc32),
+    })
+}
+
+/// Decompress every member and check their CRC32 and size
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let start = out.len();
+        let mut decoder = GzDecoder::new(rest);
+        if decoder.header().is_none() {
+            return Err(invalid_header());
+        }
+        let limit = (MAX_EXTRACT_SIZE - start) as u64 + 1;
+        let result = (&mut decoder).take(limit).read_to_end(&mut out);
+        rest = decoder.into_inner();
+        if out.len() > MAX_EXTRACT_SIZE {
+            return Err(PatchError::Other(format!(
+                "gzip stream is too large to extract (max {} bytes)",
+                MAX_EXTRACT_SIZE
+            )));
+        }
+
+        if let Err(err) = result {
+            let consumed = &data[..data.len() - rest.len()];
+            return Err(member_error(err, consumed, &out[start..]));
+        }
+
+        // Trailing zero padding is tolerated, as gzip itself does
+        if rest.iter().all(|&b| b == 0) {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+/// CRC32 and size from the trailer ending `data`
+fn trailer(data: &[u8]) -> Option<(u32, u32)> {
+    let trailer = data.last_chunk::<8>()?;
+    Some((
+        u32::from_le_bytes(trailer[..4].try_into().expect("4 bytes")),
+        u32::from_le_bytes(trailer[4..].try_into().expect("4 bytes")),
+    ))
+}
+
+/// Explain a failed member: `flate2` checks the trailer but reports a
+/// mismatch without the values, so they are recovered from the trailer it
+/// consumed when that trailer's size matches the decoded data
+fn member_error(err: io::Error, consumed: &[u8], contents: &[u8]) -> PatchError {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        return eof("gzip stream");
+    }
+    if err.kind() == io::ErrorKind::InvalidInput
+        && let Some((expected, size)) = trailer(consumed)
+    {
+        let actual = crc32fast::hash(contents);
+        if size == contents.len() as u32 && actual != expected {
+            return PatchError::ChecksumMismatch { expected, actual };
+        }
+        if size != contents.len() as u32 && actual == expected {
+            return PatchError::SizeMismatch {
+                expected: size as usize,
+                actual: contents.len(),
+            };
+        }
+    }
+    PatchError::InvalidFormat(format!("Corrupt gzip stream: {}", err))
+}
+
+fn invalid_header() -> PatchError {
+    PatchError::InvalidFormat("Invalid gzip header".to_string())
+}
+
+fn eof(what: &str) -> PatchError {
+    PatchError::UnexpectedEof(what.to_string())
+}
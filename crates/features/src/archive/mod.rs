@@ -0,0 +1,76 @@
+//! Reading ROMs and patches from ZIP, 7z and gzip archives
+//!
+//! ZIP and gzip are read with the `zip` and `flate2` crates, 7z with
+//! `sevenz-rust`. Extracted files are checked against the CRC32 the archive
+//! records. ZIP archives of a single file can also be written.
+
+pub mod gzip;
+pub mod sevenz;
+pub mod zip;
+
+use stitchr_core::Result;
+
+/// Largest file decoded from an archive, the patch formats' target size limit
+pub const MAX_EXTRACT_SIZE: usize = 512 * 1024 * 1024;
+
+/// Archive container formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    SevenZip,
+    Gzip,
+}
+
+impl ArchiveKind {
+    /// Detect an archive from its magic bytes
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+            Some(Self::Zip)
+        } else if data.starts_with(&[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C]) {
+            Some(Self::SevenZip)
+        } else if data.starts_with(&[0x1F, 0x8B]) {
+            Some(Self::Gzip)
+        } else {
+            None
+        }
+    }
+
+    /// Human-readable format name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Zip => "ZIP",
+            Self::SevenZip => "7z",
+            Self::Gzip => "gzip",
+        }
+    }
+}
+
+/// A file inside an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path inside the archive, `/`-separated (empty for a gzip stream
+    /// without a stored name)
+    pub name: String,
+    /// Uncompressed size
+    pub size: u64,
+    /// CRC32 recorded by the archive, if any
+    pub crc32: Option<u32>,
+}
+
+/// List the files of an archive
+pub fn list(kind: ArchiveKind, data: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    match kind {
+        ArchiveKind::Zip => zip::list(data),
+        ArchiveKind::SevenZip => sevenz::list(data),
+        ArchiveKind::Gzip => Ok(vec![gzip::entry(data)?]),
+    }
+}
+
+/// Extract one file by its [`ArchiveEntry::name`]
+pub fn extract(kind: ArchiveKind, data: &[u8], name: &str) -> Result<Vec<u8>> {
+    match kind {
+        ArchiveKind::Zip => zip::extract(data, name),
+        ArchiveKind::SevenZip => sevenz::extract(data, name),
+        ArchiveKind::Gzip => gzip::decompress(data),
+    }
+}
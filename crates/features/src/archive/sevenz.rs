@@ -0,0 +1,126 @@
+//! 7z archives, read with the `sevenz-rust` crate
+//!
+//! LZMA, LZMA2, BCJ, Delta and stored folders are decoded. Deflate folders
+//! and encrypted archives are reported as unsupported.
+
+use std::io::{self, Cursor, Read};
+
+use sevenz_rust::{Archive, BlockDecoder};
+use stitchr_core::{PatchError, Result};
+
+use super::{ArchiveEntry, MAX_EXTRACT_SIZE};
+
+/// List the files of a 7z archive (directories are left out)
+pub fn list(data: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    Ok(read_archive(data)?
+        .files
+        .into_iter()
+        .filter(|file| !file.is_directory && !file.is_anti_item)
+        .map(|file| ArchiveEntry {
+            crc32: match file.has_stream {
+                true => file.has_crc.then_some(file.crc as u32),
+                false => Some(0),
+            },
+            size: file.size,
+            name: file.name,
+        })
+        .collect())
+}
+
+/// Extract and CRC-check one file
+pub fn extract(data: &[u8], name: &str) -> Result<Vec<u8>> {
+    let mut archive = read_archive(data)?;
+    let index = archive
+        .files
+        .iter()
+        .position(|f| f.name == name && !f.is_directory && !f.is_anti_item)
+        .ok_or_else(|| PatchError::Other(format!("No file named {} in the 7z archive", name)))?;
+    let file = &archive.files[index];
+    if !file.has_stream {
+        return Ok(Vec::new());
+    }
+    if file.size > MAX_EXTRACT_SIZE as u64 {
+        return Err(PatchError::Other(format!(
+            "{} is too large to extract ({} bytes, max {})",
+            name, file.size, MAX_EXTRACT_SIZE
+        )));
+    }
+    let expected = file.has_crc.then_some(file.crc as u32);
+    let folder = archive.stream_map.file_folder_index[index]
+        .ok_or_else(|| corrupt("file without a folder"))?;
+
+    // `BlockDecoder` walks a folder's files as one run of the file list,
+    // which breaks when empty files sit between them: keep only the files
+    // with data, so folder N starts after the substreams of folders before it
+    let wanted = archive.files[..index]
+        .iter()
+        .filter(|f| f.has_stream)
+        .count();
+    archive.files.retain(|f| f.has_stream);
+    archive.stream_map.folder_first_file_index[folder] = archive.folders[..folder]
+        .iter()
+        .map(|f| f.num_unpack_sub_streams)
+        .sum();
+
+    // The CRC is checked here so a mismatch reports both values; a single
+    // file's folder CRC is also its own
+    for file in &mut archive.files {
+        file.has_crc = false;
+    }
+    archive.folders[folder].has_crc = false;
+
+    let mut position = archive.stream_map.folder_first_file_index[folder];
+    let mut contents = None;
+    let mut source = Cursor::new(data);
+    BlockDecoder::new(folder, &archive, &[], &mut source)
+        .for_each_entries(&mut |_, reader: &mut dyn Read| {
+            if position == wanted {
+                let mut out = Vec::new();
+                reader.read_to_end(&mut out)?;
+                contents = Some(out);
+                return Ok(false);
+            }
+            position += 1;
+            io::copy(reader, &mut io::sink())?;
+            Ok(true)
+        })
+        .map_err(error)?;
+
+    let contents = contents.ok_or_else(|| corrupt("file missing from its folder"))?;
+    if let Some(expected) = expected {
+        let actual = crc32fast::hash(&contents);
+        if actual != expected {
+            return Err(PatchError::ChecksumMismatch { expected, actual });
+        }
+    }
+    Ok(contents)
+}
+
+fn read_archive(data: &[u8]) -> Result<Archive> {
+    Archive::read(&mut Cursor::new(data), data.len() as u64, &[]).map_err(error)
+}
+
+fn corrupt(what: &str) -> PatchError {
+    PatchError::InvalidFormat(format!("Corrupt 7z archive: {}", what))
+}
+
+fn error(err: sevenz_rust::Error) -> PatchError {
+    use sevenz_rust::Error;
+
+    match err {
+        Error::UnsupportedCompressionMethod(method) => {
+            PatchError::UnsupportedVersion(format!("7z compression method {}", method))
+        }
+        Error::Unsupported(what) => PatchError::UnsupportedVersion(format!("7z {}", what)),
+        Error::PasswordRequired | Error::MaybeBadPassword(_) => {
+            PatchError::UnsupportedVersion("encrypted 7z archive".to_string())
+        }
+        Error::UnsupportedVersion { major, minor } => {
+            PatchError::UnsupportedVersion(format!("7z format version {}.{}", major, minor))
+        }
+        Error::Io(e, _) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            PatchError::UnexpectedEof("7z archive".to_string())
+        }
+        other => corrupt(&other.to_string()),
+    }
+}
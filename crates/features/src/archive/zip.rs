@@ -0,0 +1,104 @@
+//! ZIP archives, read and written with the `zip` crate
+
+use std::io::{self, Cursor, Read, Write};
+
+use stitchr_core::{PatchError, Result};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipReadOptions, ZipWriter};
+
+use super::{ArchiveEntry, MAX_EXTRACT_SIZE};
+
+/// List the files of a ZIP archive (directories are left out)
+pub fn list(data: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    let mut archive = open(data)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index).map_err(error)?;
+        if file.is_dir() {
+            continue;
+        }
+        entries.push(ArchiveEntry {
+            name: file.name().to_string(),
+            size: file.size(),
+            crc32: Some(file.crc32()),
+        });
+    }
+    Ok(entries)
+}
+
+/// Extract and CRC-check one file
+pub fn extract(data: &[u8], name: &str) -> Result<Vec<u8>> {
+    let mut archive = open(data)?;
+    let index = archive
+        .index_for_name(name)
+        .ok_or_else(|| PatchError::Other(format!("No file named {} in the ZIP archive", name)))?;
+
+    let (size, expected) = {
+        let file = archive.by_index_raw(index).map_err(error)?;
+        if file.encrypted() {
+            return Err(PatchError::Other(format!("{} is encrypted", name)));
+        }
+        (file.size(), file.crc32())
+    };
+    if size > MAX_EXTRACT_SIZE as u64 {
+        return Err(PatchError::Other(format!(
+            "{} is too large to extract ({} bytes, max {})",
+            name, size, MAX_EXTRACT_SIZE
+        )));
+    }
+
+    // The CRC is checked here so a mismatch reports both values
+    let options = ZipReadOptions::new().ignore_crc32(true);
+    let file = archive
+        .by_index_with_options(index, options)
+        .map_err(error)?;
+    let mut contents = Vec::with_capacity(size as usize);
+    file.take(size + 1)
+        .read_to_end(&mut contents)
+        .map_err(|e| error(ZipError::Io(e)))?;
+    if contents.len() as u64 != size {
+        return Err(PatchError::SizeMismatch {
+            expected: size as usize,
+            actual: contents.len(),
+        });
+    }
+    let actual = crc32fast::hash(&contents);
+    if actual != expected {
+        return Err(PatchError::ChecksumMismatch { expected, actual });
+    }
+    Ok(contents)
+}
+
+/// Build a ZIP archive holding one deflated file
+pub fn create(name: &str, contents: &[u8]) -> Vec<u8> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(contents.len() as u64 >= u32::MAX as u64);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    writer
+        .start_file(name, options)
+        .and_then(|()| writer.write_all(contents).map_err(ZipError::Io))
+        .and_then(|()| writer.finish())
+        .expect("writing a ZIP archive to memory")
+        .into_inner()
+}
+
+fn open(data: &[u8]) -> Result<ZipArchive<Cursor<&[u8]>>> {
+    ZipArchive::new(Cursor::new(data)).map_err(error)
+}
+
+fn error(err: ZipError) -> PatchError {
+    match err {
+        ZipError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            PatchError::UnexpectedEof("ZIP archive".to_string())
+        }
+        ZipError::UnsupportedArchive(what) => {
+            PatchError::UnsupportedVersion(format!("ZIP archive: {}", what))
+        }
+        ZipError::CompressionMethodNotSupported(method) => {
+            PatchError::UnsupportedVersion(format!("ZIP compression method {}", method))
+        }
+        other => PatchError::InvalidFormat(format!("Corrupt ZIP archive: {}", other)),
+    }
+}
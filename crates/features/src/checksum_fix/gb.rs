@@ -0,0 +1,68 @@
+//! Game Boy / Game Boy Color header and global checksums
+//!
+//! - Header checksum (0x14D): `x = x - byte - 1` over 0x134..=0x14C
+//! - Global checksum (0x14E, big-endian): sum of every byte except itself
+
+use super::types::{ChecksumFix, System};
+
+/// Start of the Nintendo logo in the cartridge header
+const LOGO_OFFSET: usize = 0x104;
+/// First bytes of the Nintendo logo
+const LOGO_START: [u8; 4] = [0xCE, 0xED, 0x66, 0x66];
+
+const HEADER_CHECKSUM_OFFSET: usize = 0x14D;
+const GLOBAL_CHECKSUM_OFFSET: usize = 0x14E;
+
+/// Check for the Game Boy Nintendo logo
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= 0x150 && rom[LOGO_OFFSET..LOGO_OFFSET + 4] == LOGO_START
+}
+
+/// Header checksum over 0x134..=0x14C
+pub fn header_checksum(rom: &[u8]) -> u8 {
+    rom[0x134..HEADER_CHECKSUM_OFFSET]
+        .iter()
+        .fold(0u8, |x, &b| x.wrapping_sub(b).wrapping_sub(1))
+}
+
+/// Global checksum: 16-bit sum of all bytes except the checksum itself
+pub fn global_checksum(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|(i, _)| *i != GLOBAL_CHECKSUM_OFFSET && *i != GLOBAL_CHECKSUM_OFFSET + 1)
+        .fold(0u16, |sum, (_, &b)| sum.wrapping_add(b as u16))
+}
+
+/// Rewrite both checksums (header first, since the global sum covers it)
+pub fn fix(rom: &mut [u8]) -> Vec<ChecksumFix> {
+    if !detect(rom) {
+        return Vec::new();
+    }
+
+    let before = rom[HEADER_CHECKSUM_OFFSET];
+    let after = header_checksum(rom);
+    rom[HEADER_CHECKSUM_OFFSET] = after;
+    let header = ChecksumFix {
+        system: System::GameBoy,
+        field: "header checksum",
+        offset: HEADER_CHECKSUM_OFFSET,
+        width: 1,
+        before: before as u32,
+        after: after as u32,
+    };
+
+    let range = GLOBAL_CHECKSUM_OFFSET..GLOBAL_CHECKSUM_OFFSET + 2;
+    let before = u16::from_be_bytes([rom[range.start], rom[range.start + 1]]);
+    let after = global_checksum(rom);
+    rom[range].copy_from_slice(&after.to_be_bytes());
+    let global = ChecksumFix {
+        system: System::GameBoy,
+        field: "global checksum",
+        offset: GLOBAL_CHECKSUM_OFFSET,
+        width: 2,
+        before: before as u32,
+        after: after as u32,
+    };
+
+    vec![header, global]
+}
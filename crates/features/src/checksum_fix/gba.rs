@@ -0,0 +1,50 @@
+//! GBA header complement
+//!
+//! The byte at 0xBD is `-(sum of 0xA0..=0xBC) - 0x19`.
+
+use super::types::{ChecksumFix, System};
+
+/// Start of the Nintendo logo in the cartridge header
+const LOGO_OFFSET: usize = 0x04;
+/// First bytes of the Nintendo logo
+const LOGO_START: [u8; 4] = [0x24, 0xFF, 0xAE, 0x51];
+/// Fixed value required at 0xB2
+const FIXED_VALUE_OFFSET: usize = 0xB2;
+const FIXED_VALUE: u8 = 0x96;
+
+const COMPLEMENT_OFFSET: usize = 0xBD;
+
+/// Check for the GBA Nintendo logo and fixed header value
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= 0xC0
+        && rom[LOGO_OFFSET..LOGO_OFFSET + 4] == LOGO_START
+        && rom[FIXED_VALUE_OFFSET] == FIXED_VALUE
+}
+
+/// Header complement over 0xA0..=0xBC
+pub fn header_complement(rom: &[u8]) -> u8 {
+    rom[0xA0..COMPLEMENT_OFFSET]
+        .iter()
+        .fold(0u8, |sum, &b| sum.wrapping_sub(b))
+        .wrapping_sub(0x19)
+}
+
+/// Rewrite the header complement
+pub fn fix(rom: &mut [u8]) -> Vec<ChecksumFix> {
+    if !detect(rom) {
+        return Vec::new();
+    }
+
+    let before = rom[COMPLEMENT_OFFSET];
+    let after = header_complement(rom);
+    rom[COMPLEMENT_OFFSET] = after;
+
+    vec![ChecksumFix {
+        system: System::Gba,
+        field: "header complement",
+        offset: COMPLEMENT_OFFSET,
+        width: 1,
+        before: before as u32,
+        after: after as u32,
+    }]
+}
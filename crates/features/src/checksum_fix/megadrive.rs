@@ -0,0 +1,47 @@
+//! Mega Drive / Genesis checksum
+//!
+//! The big-endian word at 0x18E is the 16-bit sum of all big-endian words
+//! from 0x200 to the end of the ROM.
+
+use super::types::{ChecksumFix, System};
+
+/// System name field ("SEGA MEGA DRIVE", "SEGA GENESIS", ...)
+const SYSTEM_OFFSET: usize = 0x100;
+const CHECKSUM_OFFSET: usize = 0x18E;
+const DATA_START: usize = 0x200;
+
+/// Check for "SEGA" in the system name field
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() > DATA_START
+        && (&rom[SYSTEM_OFFSET..SYSTEM_OFFSET + 4] == b"SEGA"
+            || &rom[SYSTEM_OFFSET + 1..SYSTEM_OFFSET + 5] == b"SEGA")
+}
+
+/// Sum of big-endian words from 0x200 (an odd trailing byte counts as the
+/// high byte of a word)
+pub fn checksum(rom: &[u8]) -> u16 {
+    rom[DATA_START..]
+        .chunks(2)
+        .map(|w| u16::from_be_bytes([w[0], w.get(1).copied().unwrap_or(0)]))
+        .fold(0u16, u16::wrapping_add)
+}
+
+/// Rewrite the checksum word
+pub fn fix(rom: &mut [u8]) -> Vec<ChecksumFix> {
+    if !detect(rom) {
+        return Vec::new();
+    }
+
+    let before = u16::from_be_bytes([rom[CHECKSUM_OFFSET], rom[CHECKSUM_OFFSET + 1]]);
+    let after = checksum(rom);
+    rom[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 2].copy_from_slice(&after.to_be_bytes());
+
+    vec![ChecksumFix {
+        system: System::MegaDrive,
+        field: "checksum",
+        offset: CHECKSUM_OFFSET,
+        width: 2,
+        before: before as u32,
+        after: after as u32,
+    }]
+}
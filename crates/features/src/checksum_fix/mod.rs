@@ -0,0 +1,58 @@
+//! Internal cartridge checksum repair
+//!
+//! Many hacks leave the checksum in the cartridge header stale. This module
+//! detects the system from the header itself and rewrites:
+//! - SNES: checksum and complement (LoROM, HiROM, ExHiROM)
+//! - Game Boy / Color: header checksum and global checksum
+//! - GBA: header complement
+//! - Mega Drive: checksum at 0x18E
+//! - NDS: header CRC16
+//! - N64: boot checksums CRC1/CRC2, per CIC variant
+
+pub mod gb;
+pub mod gba;
+pub mod megadrive;
+pub mod n64;
+pub mod nds;
+pub mod snes;
+
+mod types;
+
+pub use types::{ChecksumFix, System};
+
+/// Detect the system from the cartridge header
+///
+/// Checks run from the most to the least specific signature.
+pub fn detect_system(rom: &[u8]) -> Option<System> {
+    if n64::detect(rom) {
+        Some(System::N64)
+    } else if nds::detect(rom) {
+        Some(System::Nds)
+    } else if gba::detect(rom) {
+        Some(System::Gba)
+    } else if gb::detect(rom) {
+        Some(System::GameBoy)
+    } else if megadrive::detect(rom) {
+        Some(System::MegaDrive)
+    } else if snes::detect(rom) {
+        Some(System::Snes)
+    } else {
+        None
+    }
+}
+
+/// Recompute and write the internal checksums of `rom`
+///
+/// Returns one entry per checksum field (including fields that were already
+/// correct), or an empty list if the system (or N64 CIC) is not recognized.
+pub fn fix_checksums(rom: &mut [u8]) -> Vec<ChecksumFix> {
+    match detect_system(rom) {
+        Some(System::Snes) => snes::fix(rom),
+        Some(System::GameBoy) => gb::fix(rom),
+        Some(System::Gba) => gba::fix(rom),
+        Some(System::MegaDrive) => megadrive::fix(rom),
+        Some(System::Nds) => nds::fix(rom),
+        Some(System::N64) => n64::fix(rom),
+        None => Vec::new(),
+    }
+}
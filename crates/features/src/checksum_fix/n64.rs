@@ -0,0 +1,218 @@
+//! N64 boot checksums (CRC1/CRC2)
+//!
+//! The big-endian words at 0x10 and 0x14 are checked by the CIC lockout chip
+//! against the first MiB of game data after the boot code (0x1000..0x101000).
+//! Each CIC variant seeds and folds the sum differently, so the variant is
+//! identified from the CRC32 of the IPL3 boot code (0x40..0x1000) first.
+//!
+//! All three dump byte orders (.z64, .v64, .n64) are read and written in
+//! place.
+
+use std::fmt;
+
+use super::types::{ChecksumFix, System};
+
+const CRC1_OFFSET: usize = 0x10;
+const CRC2_OFFSET: usize = 0x14;
+
+/// IPL3 boot code range
+const IPL3_START: usize = 0x40;
+const IPL3_END: usize = 0x1000;
+
+/// Checksummed data range
+const CHECKSUM_START: usize = 0x1000;
+const CHECKSUM_END: usize = 0x101000;
+
+/// CIC lockout chip variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cic {
+    /// CIC-NUS-6101 (Star Fox 64 NTSC)
+    Cic6101,
+    /// CIC-NUS-6102 / 7101, the most common
+    Cic6102,
+    /// CIC-NUS-6103 / 7103
+    Cic6103,
+    /// CIC-NUS-6105 / 7105
+    Cic6105,
+    /// CIC-NUS-6106 / 7106
+    Cic6106,
+    /// CIC-NUS-7102 (Lylat Wars PAL)
+    Cic7102,
+}
+
+impl Cic {
+    /// Identify the CIC from the CRC32 of the IPL3 boot code
+    pub fn from_ipl3_crc(crc: u32) -> Option<Self> {
+        match crc {
+            0x6170_A4A1 => Some(Self::Cic6101),
+            0x90BB_6CB5 => Some(Self::Cic6102),
+            0x0B05_0EE0 => Some(Self::Cic6103),
+            0x98BC_2C86 => Some(Self::Cic6105),
+            0xACC8_580A => Some(Self::Cic6106),
+            0x009E_9EA3 => Some(Self::Cic7102),
+            _ => None,
+        }
+    }
+
+    /// Chip name (e.g. "6102")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cic6101 => "6101",
+            Self::Cic6102 => "6102",
+            Self::Cic6103 => "6103",
+            Self::Cic6105 => "6105",
+            Self::Cic6106 => "6106",
+            Self::Cic7102 => "7102",
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        match self {
+            Self::Cic6101 | Self::Cic6102 | Self::Cic7102 => 0xF8CA_4DDC,
+            Self::Cic6103 => 0xA388_6759,
+            Self::Cic6105 => 0xDF26_F436,
+            Self::Cic6106 => 0x1FEA_617A,
+        }
+    }
+}
+
+impl fmt::Display for Cic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CIC-NUS-{}", self.name())
+    }
+}
+
+/// Dump byte order, detected from the first word
+#[derive(Debug, Clone, Copy)]
+enum Order {
+    BigEndian,
+    ByteSwapped,
+    LittleEndian,
+}
+
+impl Order {
+    fn detect(rom: &[u8]) -> Option<Self> {
+        match rom.get(..4)? {
+            [0x80, 0x37, 0x12, 0x40] => Some(Self::BigEndian),
+            [0x37, 0x80, 0x40, 0x12] => Some(Self::ByteSwapped),
+            [0x40, 0x12, 0x37, 0x80] => Some(Self::LittleEndian),
+            _ => None,
+        }
+    }
+
+    /// Reorder the four bytes of a word between this order and big-endian
+    fn swap(&self, [a, b, c, d]: [u8; 4]) -> [u8; 4] {
+        match self {
+            Self::BigEndian => [a, b, c, d],
+            Self::ByteSwapped => [b, a, d, c],
+            Self::LittleEndian => [d, c, b, a],
+        }
+    }
+
+    fn read(&self, rom: &[u8], offset: usize) -> u32 {
+        let bytes = rom[offset..offset + 4].try_into().unwrap();
+        u32::from_be_bytes(self.swap(bytes))
+    }
+
+    fn write(&self, rom: &mut [u8], offset: usize, value: u32) {
+        rom[offset..offset + 4].copy_from_slice(&self.swap(value.to_be_bytes()));
+    }
+}
+
+/// Check for an N64 header in any byte order, with the full checksummed
+/// range present
+pub fn detect(rom: &[u8]) -> bool {
+    Order::detect(rom).is_some() && rom.len() >= CHECKSUM_END
+}
+
+/// Identify the CIC from the IPL3 boot code
+pub fn detect_cic(rom: &[u8]) -> Option<Cic> {
+    let order = Order::detect(rom)?;
+    let ipl3 = rom.get(IPL3_START..IPL3_END)?;
+    let mut hasher = crc32fast::Hasher::new();
+    for word in ipl3.chunks_exact(4) {
+        hasher.update(&order.swap(word.try_into().unwrap()));
+    }
+    Cic::from_ipl3_crc(hasher.finalize())
+}
+
+/// Compute CRC1 and CRC2 for `cic`
+///
+/// Returns `None` if the ROM is not an N64 ROM or is shorter than the
+/// checksummed range.
+pub fn crc(rom: &[u8], cic: Cic) -> Option<(u32, u32)> {
+    let order = Order::detect(rom)?;
+    if rom.len() < CHECKSUM_END {
+        return None;
+    }
+
+    let seed = cic.seed();
+    let (mut t1, mut t2, mut t3, mut t4, mut t5, mut t6) = (seed, seed, seed, seed, seed, seed);
+
+    for i in (CHECKSUM_START..CHECKSUM_END).step_by(4) {
+        let d = order.read(rom, i);
+        let (sum, carry) = t6.overflowing_add(d);
+        if carry {
+            t4 = t4.wrapping_add(1);
+        }
+        t6 = sum;
+        t3 ^= d;
+        let r = d.rotate_left(d & 0x1F);
+        t5 = t5.wrapping_add(r);
+        if t2 > d {
+            t2 ^= r;
+        } else {
+            t2 ^= t6 ^ d;
+        }
+        t1 = t1.wrapping_add(match cic {
+            Cic::Cic6105 => order.read(rom, IPL3_START + 0x0710 + (i & 0xFF)) ^ d,
+            _ => t5 ^ d,
+        });
+    }
+
+    Some(match cic {
+        Cic::Cic6103 => ((t6 ^ t4).wrapping_add(t3), (t5 ^ t2).wrapping_add(t1)),
+        Cic::Cic6106 => (
+            t6.wrapping_mul(t4).wrapping_add(t3),
+            t5.wrapping_mul(t2).wrapping_add(t1),
+        ),
+        _ => (t6 ^ t4 ^ t3, t5 ^ t2 ^ t1),
+    })
+}
+
+/// Whether CRC1/CRC2 disagree with the ROM contents
+///
+/// `None` if the ROM or its CIC is not recognized.
+pub fn is_stale(rom: &[u8]) -> Option<bool> {
+    let order = Order::detect(rom)?;
+    let (crc1, crc2) = crc(rom, detect_cic(rom)?)?;
+    Some(order.read(rom, CRC1_OFFSET) != crc1 || order.read(rom, CRC2_OFFSET) != crc2)
+}
+
+/// Rewrite CRC1 and CRC2
+///
+/// Returns an empty list if the CIC cannot be identified.
+pub fn fix(rom: &mut [u8]) -> Vec<ChecksumFix> {
+    let Some(order) = Order::detect(rom) else {
+        return Vec::new();
+    };
+    let Some((crc1, crc2)) = detect_cic(rom).and_then(|cic| crc(rom, cic)) else {
+        return Vec::new();
+    };
+
+    [("CRC1", CRC1_OFFSET, crc1), ("CRC2", CRC2_OFFSET, crc2)]
+        .into_iter()
+        .map(|(field, offset, after)| {
+            let before = order.read(rom, offset);
+            order.write(rom, offset, after);
+            ChecksumFix {
+                system: System::N64,
+                field,
+                offset,
+                width: 4,
+                before,
+                after,
+            }
+        })
+        .collect()
+}
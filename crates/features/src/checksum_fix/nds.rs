@@ -0,0 +1,52 @@
+//! NDS header CRC16
+//!
+//! The little-endian word at 0x15E is CRC-16 (MODBUS: reflected 0x8005,
+//! initial 0xFFFF) over header bytes 0x000..0x15E.
+
+use super::types::{ChecksumFix, System};
+
+/// Nintendo logo CRC16 field, always 0xCF56 on retail cartridges
+const LOGO_CRC_OFFSET: usize = 0x15C;
+const LOGO_CRC: u16 = 0xCF56;
+const HEADER_CRC_OFFSET: usize = 0x15E;
+
+/// Check for the fixed Nintendo logo CRC
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= 0x160
+        && u16::from_le_bytes([rom[LOGO_CRC_OFFSET], rom[LOGO_CRC_OFFSET + 1]]) == LOGO_CRC
+}
+
+/// CRC-16/MODBUS
+pub fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xFFFF, |mut crc, &b| {
+        crc ^= b as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+        crc
+    })
+}
+
+/// Rewrite the header CRC16
+pub fn fix(rom: &mut [u8]) -> Vec<ChecksumFix> {
+    if !detect(rom) {
+        return Vec::new();
+    }
+
+    let before = u16::from_le_bytes([rom[HEADER_CRC_OFFSET], rom[HEADER_CRC_OFFSET + 1]]);
+    let after = crc16(&rom[..HEADER_CRC_OFFSET]);
+    rom[HEADER_CRC_OFFSET..HEADER_CRC_OFFSET + 2].copy_from_slice(&after.to_le_bytes());
+
+    vec![ChecksumFix {
+        system: System::Nds,
+        field: "header CRC16",
+        offset: HEADER_CRC_OFFSET,
+        width: 2,
+        before: before as u32,
+        after: after as u32,
+    }]
+}
@@ -0,0 +1,89 @@
+//! SNES internal checksum and complement
+//!
+//! The checksum is the 16-bit sum of every ROM byte. ROMs whose size is not
+//! a power of two mirror their last part up to the next power of two, so
+//! that part is counted repeatedly. The complement is `checksum ^ 0xFFFF`;
+//! both are little-endian words in the internal header (complement at
+//! +0x1C, checksum at +0x1E).
+
+use stitchr_core::snes::{find_internal_header, strip_copier_header};
+
+use super::types::{ChecksumFix, System};
+
+/// Checksum field offsets within the internal header
+const COMPLEMENT: usize = 0x1C;
+const CHECKSUM: usize = 0x1E;
+
+/// Check for a plausible internal header at any known location
+pub fn detect(rom: &[u8]) -> bool {
+    find_internal_header(strip_copier_header(rom)).is_some()
+}
+
+/// Compute the checksum with the header's checksum fields counted as a
+/// valid pair (0x0000 / 0xFFFF), so the result does not depend on them
+pub fn checksum(rom: &[u8], header: usize) -> u16 {
+    let fields = header + COMPLEMENT..header + CHECKSUM + 2;
+    let byte = |i: usize| -> u32 {
+        if fields.contains(&i) {
+            // complement 0xFFFF then checksum 0x0000
+            if i < header + CHECKSUM { 0xFF } else { 0x00 }
+        } else {
+            rom[i] as u32
+        }
+    };
+    let sum = |range: std::ops::Range<usize>| -> u32 { range.map(byte).sum() };
+
+    let size = rom.len();
+    let base = if size.is_power_of_two() {
+        size
+    } else {
+        size.next_power_of_two() / 2
+    };
+
+    let mut total = sum(0..base);
+    if size > base {
+        let remainder = size - base;
+        // Mirror the remainder (repeatedly if needed) up to `base` bytes
+        let repeats = (base / remainder) as u32;
+        total += sum(base..size) * repeats;
+    }
+    total as u16
+}
+
+/// Rewrite the checksum and complement
+pub fn fix(rom: &mut [u8]) -> Vec<ChecksumFix> {
+    let skip = rom.len() - strip_copier_header(rom).len();
+    let data = &mut rom[skip..];
+    let Some(header) = find_internal_header(data) else {
+        return Vec::new();
+    };
+
+    let read = |data: &[u8], at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let old_complement = read(data, header + COMPLEMENT);
+    let old_checksum = read(data, header + CHECKSUM);
+
+    let new_checksum = checksum(data, header);
+    let new_complement = new_checksum ^ 0xFFFF;
+    data[header + COMPLEMENT..header + COMPLEMENT + 2]
+        .copy_from_slice(&new_complement.to_le_bytes());
+    data[header + CHECKSUM..header + CHECKSUM + 2].copy_from_slice(&new_checksum.to_le_bytes());
+
+    vec![
+        ChecksumFix {
+            system: System::Snes,
+            field: "checksum",
+            offset: skip + header + CHECKSUM,
+            width: 2,
+            before: old_checksum as u32,
+            after: new_checksum as u32,
+        },
+        ChecksumFix {
+            system: System::Snes,
+            field: "complement",
+            offset: skip + header + COMPLEMENT,
+            width: 2,
+            before: old_complement as u32,
+            after: new_complement as u32,
+        },
+    ]
+}
@@ -0,0 +1,77 @@
+//! Checksum fix types
+
+use std::fmt;
+
+/// Systems with a repairable internal checksum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum System {
+    Snes,
+    GameBoy,
+    Gba,
+    MegaDrive,
+    Nds,
+    N64,
+}
+
+impl System {
+    /// Human-readable system name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Snes => "SNES",
+            Self::GameBoy => "Game Boy",
+            Self::Gba => "GBA",
+            Self::MegaDrive => "Mega Drive",
+            Self::Nds => "NDS",
+            Self::N64 => "N64",
+        }
+    }
+}
+
+/// One checksum field, before and after repair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumFix {
+    pub system: System,
+    /// Field name (e.g. "checksum", "complement")
+    pub field: &'static str,
+    /// Offset of the field in the ROM
+    pub offset: usize,
+    /// Field width in bytes
+    pub width: usize,
+    pub before: u32,
+    pub after: u32,
+}
+
+impl ChecksumFix {
+    /// Whether the field was rewritten
+    pub fn changed(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+impl fmt::Display for ChecksumFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.width * 2;
+        if self.changed() {
+            write!(
+                f,
+                "{} {} at {:#x}: {:#0w$x} -> {:#0w$x}",
+                self.system.name(),
+                self.field,
+                self.offset,
+                self.before,
+                self.after,
+                w = digits + 2
+            )
+        } else {
+            write!(
+                f,
+                "{} {} at {:#x}: {:#0w$x} (already correct)",
+                self.system.name(),
+                self.field,
+                self.offset,
+                self.before,
+                w = digits + 2
+            )
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! clrmamepro DAT parser
+//!
+//! The format is a list of parenthesized blocks:
+//!
+//! ```text
+//! clrmamepro (
+//!     name "Nintendo - Super Nintendo Entertainment System"
+//! )
+//! game (
+//!     name "Chrono Trigger (USA)"
+//!     rom ( name "Chrono Trigger (USA).sfc" size 4194304 crc 2D206BF7 )
+//! )
+//! ```
+
+use stitchr_core::{PatchError, Result};
+
+use super::types::{Dat, DatEntry};
+
+/// Lexical token
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+/// Split into parentheses, quoted strings and bare words
+fn tokenize(text: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '(' => {
+                tokens.push(Token::Open);
+                rest = &rest[1..];
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                rest = &rest[1..];
+            }
+            '"' => {
+                let close = rest[1..].find('"').ok_or_else(|| {
+                    PatchError::InvalidFormat("Unterminated string in clrmamepro DAT".to_string())
+                })?;
+                tokens.push(Token::Word(&rest[1..close + 1]));
+                rest = &rest[close + 2..];
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Word(&rest[..end]));
+                rest = &rest[end..];
+            }
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// Parse a clrmamepro DAT
+pub fn parse(text: &str) -> Result<Dat> {
+    let tokens = tokenize(text)?;
+    let mut dat = Dat::default();
+    let mut pos = 0;
+
+    while pos < tokens.len() {
+        let Token::Word(block) = tokens[pos] else {
+            return Err(unexpected(pos));
+        };
+        if tokens.get(pos + 1) != Some(&Token::Open) {
+            return Err(unexpected(pos + 1));
+        }
+        let end = matching_close(&tokens, pos + 1)?;
+        let body = &tokens[pos + 2..end];
+
+        match block {
+            "clrmamepro" => dat.name = field(body, "name").map(str::to_string),
+            "game" | "machine" | "resource" => {
+                let game = field(body, "name").unwrap_or_default();
+                for rom in sub_blocks(body, "rom") {
+                    let mut entry = DatEntry::new(game, "");
+                    for pair in rom.chunks_exact(2) {
+                        if let [Token::Word(key), Token::Word(value)] = pair {
+                            entry.set(key, value);
+                        }
+                    }
+                    dat.entries.push(entry);
+                }
+            }
+            _ => {}
+        }
+        pos = end + 1;
+    }
+
+    Ok(dat)
+}
+
+fn unexpected(pos: usize) -> PatchError {
+    PatchError::InvalidFormat(format!("Unexpected token {} in clrmamepro DAT", pos))
+}
+
+/// Index of the `)` matching the `(` at `open`
+fn matching_close(tokens: &[Token], open: usize) -> Result<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+            Token::Word(_) => {}
+        }
+    }
+    Err(PatchError::InvalidFormat(
+        "Unbalanced parentheses in clrmamepro DAT".to_string(),
+    ))
+}
+
+/// Value of a top-level `key value` pair in a block body
+fn field<'a>(body: &[Token<'a>], key: &str) -> Option<&'a str> {
+    let mut pos = 0;
+    while pos + 1 < body.len() {
+        match (&body[pos], &body[pos + 1]) {
+            (Token::Word(k), Token::Word(v)) if *k == key => return Some(v),
+            (Token::Word(_), Token::Open) => {
+                pos = matching_close(body, pos + 1).ok()? + 1;
+            }
+            _ => pos += 2,
+        }
+    }
+    None
+}
+
+/// Bodies of nested `key ( ... )` blocks
+fn sub_blocks<'t, 'a>(body: &'t [Token<'a>], key: &str) -> Vec<&'t [Token<'a>]> {
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while pos + 1 < body.len() {
+        match (&body[pos], &body[pos + 1]) {
+            (Token::Word(k), Token::Open) => {
+                let Ok(end) = matching_close(body, pos + 1) else {
+                    break;
+                };
+                if *k == key {
+                    blocks.push(&body[pos + 2..end]);
+                }
+                pos = end + 1;
+            }
+            _ => pos += 2,
+        }
+    }
+    blocks
+}
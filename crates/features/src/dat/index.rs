@@ -0,0 +1,119 @@
+//! Hash index over DAT entries
+
+use std::collections::HashMap;
+
+use crate::validation::{HashAlgorithm, Validator};
+
+use super::types::{Dat, DatEntry, parse_hex};
+
+/// DAT entries indexed by CRC32, MD5 and SHA-1
+#[derive(Debug, Default)]
+pub struct DatIndex {
+    entries: Vec<DatEntry>,
+    by_crc32: HashMap<u32, Vec<usize>>,
+    by_md5: HashMap<[u8; 16], Vec<usize>>,
+    by_sha1: HashMap<[u8; 20], Vec<usize>>,
+}
+
+impl DatIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every entry of `dat`
+    pub fn add(&mut self, dat: Dat) {
+        for entry in dat.entries {
+            let index = self.entries.len();
+            if let Some(crc) = entry.crc32 {
+                self.by_crc32.entry(crc).or_default().push(index);
+            }
+            if let Some(md5) = entry.md5 {
+                self.by_md5.entry(md5).or_default().push(index);
+            }
+            if let Some(sha1) = entry.sha1 {
+                self.by_sha1.entry(sha1).or_default().push(index);
+            }
+            self.entries.push(entry);
+        }
+    }
+
+    /// Number of indexed ROM entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the index holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn collect<'a>(&'a self, hits: Option<&Vec<usize>>) -> Vec<&'a DatEntry> {
+        hits.map(|hits| hits.iter().map(|&i| &self.entries[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Entries with this CRC32
+    pub fn find_crc32(&self, crc: u32) -> Vec<&DatEntry> {
+        self.collect(self.by_crc32.get(&crc))
+    }
+
+    /// Entries with this MD5
+    pub fn find_md5(&self, md5: &[u8; 16]) -> Vec<&DatEntry> {
+        self.collect(self.by_md5.get(md5))
+    }
+
+    /// Entries with this SHA-1
+    pub fn find_sha1(&self, sha1: &[u8; 20]) -> Vec<&DatEntry> {
+        self.collect(self.by_sha1.get(sha1))
+    }
+
+    /// Entries matching a checksum as stored in patch metadata: 4 bytes
+    /// (little-endian CRC32), 16 bytes (MD5) or 20 bytes (SHA-1)
+    pub fn find_checksum(&self, checksum: &[u8]) -> Vec<&DatEntry> {
+        match checksum.len() {
+            4 => self.find_crc32(u32::from_le_bytes(checksum.try_into().unwrap())),
+            16 => self.find_md5(checksum.try_into().unwrap()),
+            20 => self.find_sha1(checksum.try_into().unwrap()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Identify `data` by its hashes
+    ///
+    /// CRC32 narrows the candidates; when they carry a SHA-1 (or MD5) it is
+    /// computed and must match too, so a CRC collision is not reported as
+    /// a match. Falls back to MD5 for entries without a CRC32.
+    pub fn identify(&self, data: &[u8]) -> Option<&DatEntry> {
+        let candidates = self.find_crc32(Validator::crc32(data));
+        if candidates.is_empty() {
+            if self.by_md5.is_empty() {
+                return None;
+            }
+            let md5 = digest(data, HashAlgorithm::Md5)?;
+            return self.find_md5(&md5).into_iter().next();
+        }
+
+        let mut sha1 = None;
+        let mut md5 = None;
+        candidates.into_iter().find(|entry| {
+            if entry.size.is_some_and(|size| size != data.len() as u64) {
+                return false;
+            }
+            if let Some(expected) = entry.sha1 {
+                let actual = sha1.get_or_insert_with(|| digest(data, HashAlgorithm::Sha1));
+                return *actual == Some(expected);
+            }
+            if let Some(expected) = entry.md5 {
+                let actual = md5.get_or_insert_with(|| digest(data, HashAlgorithm::Md5));
+                return *actual == Some(expected);
+            }
+            true
+        })
+    }
+}
+
+/// MD5 / SHA-1 digest bytes (the validator returns them as hex text)
+fn digest<const N: usize>(data: &[u8], algorithm: HashAlgorithm) -> Option<[u8; N]> {
+    parse_hex(std::str::from_utf8(&Validator::compute(data, algorithm)).ok()?)
+}
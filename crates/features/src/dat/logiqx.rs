@@ -0,0 +1,139 @@
+//! Logiqx XML DAT parser
+//!
+//! Handles the subset No-Intro and Redump use: a `<header>` with a `<name>`
+//! and `<game>` (or `<machine>`) elements holding `<rom>` elements whose
+//! attributes carry the name, size and hashes. Anything else is skipped, so
+//! no general XML parser is needed.
+
+use stitchr_core::{PatchError, Result};
+
+use super::types::{Dat, DatEntry};
+
+/// Parse a Logiqx XML DAT
+pub fn parse(text: &str) -> Result<Dat> {
+    if !text.contains("<datafile") {
+        return Err(PatchError::InvalidFormat(
+            "Not a Logiqx DAT: <datafile> element not found".to_string(),
+        ));
+    }
+
+    let mut dat = Dat::default();
+    let mut game: Option<String> = None;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = tag_end(rest).ok_or_else(|| {
+            PatchError::InvalidFormat("Unterminated tag in Logiqx DAT".to_string())
+        })?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            if matches!(name.trim(), "game" | "machine") {
+                game = None;
+            }
+            continue;
+        }
+
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let name = name.trim_end_matches('/');
+        match name {
+            "game" | "machine" => {
+                game = attribute(attributes, "name");
+            }
+            "rom" => {
+                let Some(game) = &game else { continue };
+                let mut entry = DatEntry::new(game.clone(), "");
+                for key in ["name", "size", "crc", "md5", "sha1"] {
+                    if let Some(value) = attribute(attributes, key) {
+                        entry.set(key, &value);
+                    }
+                }
+                dat.entries.push(entry);
+            }
+            "name" if game.is_none() && dat.name.is_none() => {
+                // Header name: text up to the closing tag
+                if let Some(close) = rest.find("</name>") {
+                    dat.name = Some(unescape(rest[..close].trim()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(dat)
+}
+
+/// Index of the `>` closing a tag, skipping quoted attribute values
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Value of attribute `key` in an attribute list
+fn attribute(attributes: &str, key: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let close = value[1..].find(quote)? + 1;
+        if name == key {
+            return Some(unescape(&value[1..close]));
+        }
+        rest = &value[close + 1..];
+    }
+    None
+}
+
+/// Decode the predefined XML entities and numeric character references
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else { break };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,67 @@
+//! No-Intro / Redump DAT file matching
+//!
+//! Loads DAT files offline (Logiqx XML or clrmamepro text, detected from
+//! the content) and indexes their ROM entries by CRC32, MD5 and SHA-1, so a
+//! ROM can be named from its hashes and a patch's expected source checksum
+//! can be named too.
+
+pub mod clrmamepro;
+pub mod logiqx;
+
+mod index;
+mod types;
+
+pub use index::DatIndex;
+pub use types::{Dat, DatEntry};
+
+use std::path::Path;
+
+use stitchr_core::Result;
+
+impl Dat {
+    /// Parse a DAT, picking the format from the content
+    pub fn parse(text: &str) -> Result<Self> {
+        if text.trim_start().starts_with('<') {
+            logiqx::parse(text)
+        } else {
+            clrmamepro::parse(text)
+        }
+    }
+
+    /// Read and parse a DAT file
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path)?;
+        Self::parse(&String::from_utf8_lossy(&data))
+    }
+}
+
+impl DatIndex {
+    /// Index a DAT file, or every `.dat` / `.xml` file in a directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut index = Self::new();
+        index.add_path(path)?;
+        Ok(index)
+    }
+
+    /// Add a DAT file, or every `.dat` / `.xml` file in a directory
+    pub fn add_path(&mut self, path: &Path) -> Result<()> {
+        if !path.is_dir() {
+            self.add(Dat::load(path)?);
+            return Ok(());
+        }
+
+        let mut files: Vec<_> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case("dat") || e.eq_ignore_ascii_case("xml"))
+            })
+            .collect();
+        files.sort();
+        for file in files {
+            self.add(Dat::load(&file)?);
+        }
+        Ok(())
+    }
+}
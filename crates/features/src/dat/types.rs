@@ -0,0 +1,62 @@
+//! DAT file types
+
+/// One ROM of a DAT game entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatEntry {
+    /// Game (set) name, e.g. "Chrono Trigger (USA)"
+    pub game: String,
+    /// ROM file name within the set
+    pub rom: String,
+    pub size: Option<u64>,
+    pub crc32: Option<u32>,
+    pub md5: Option<[u8; 16]>,
+    pub sha1: Option<[u8; 20]>,
+}
+
+impl DatEntry {
+    /// Entry with only the names set
+    pub fn new(game: impl Into<String>, rom: impl Into<String>) -> Self {
+        Self {
+            game: game.into(),
+            rom: rom.into(),
+            size: None,
+            crc32: None,
+            md5: None,
+            sha1: None,
+        }
+    }
+
+    /// Set a field from a DAT attribute (`size`, `crc`, `md5`, `sha1`);
+    /// unknown keys and malformed values are ignored
+    pub(crate) fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "name" => self.rom = value.to_string(),
+            "size" => self.size = value.parse().ok(),
+            "crc" => self.crc32 = u32::from_str_radix(value, 16).ok(),
+            "md5" => self.md5 = parse_hex(value),
+            "sha1" => self.sha1 = parse_hex(value),
+            _ => {}
+        }
+    }
+}
+
+/// A parsed DAT file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dat {
+    /// DAT name from the header (e.g. "Nintendo - Super Nintendo
+    /// Entertainment System")
+    pub name: Option<String>,
+    pub entries: Vec<DatEntry>,
+}
+
+/// Parse a fixed-length hex digest
+pub(crate) fn parse_hex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    if value.len() != N * 2 {
+        return None;
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(value.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(out)
+}
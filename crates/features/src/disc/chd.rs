@@ -0,0 +1,874 @@
+//! CHD v5 (`.chd`) compressed disc images
+//!
+//! A CHD stores its data in fixed-size hunks, each compressed with one of up
+//! to four codecs named in the header. The hunk map is itself compressed: a
+//! Huffman-coded stream of compression types, then per-hunk lengths, CRCs
+//! and references as packed bit fields.
+//!
+//! CD images store 2448-byte frames (sector plus subcode), with each track
+//! padded to a multiple of four frames. Like `chdman extractcd`, the device
+//! exposes the tracks back to back, each sector cut to its track's data
+//! size, so a single-track MODE1 CHD reads as an ISO and a MODE2_RAW one as
+//! a BIN. Other CHDs (DVDs) expose their logical bytes directly.
+//!
+//! Supported codecs are `zlib`, `lzma`, `flac`, `cdzl`, `cdlz` and `cdfl`;
+//! images that depend on a parent CHD are rejected.
+
+use std::io::Cursor;
+
+use super::device::{BlockCache, BlockDevice};
+use super::{ecc, inflate};
+
+const HEADER_SIZE: usize = 124;
+/// Largest hunk accepted (chdman uses 4 KiB to 19.5 KiB)
+const MAX_HUNK_BYTES: u32 = 1 << 24;
+
+const CD_FRAME_SIZE: usize = 2448;
+const CD_SECTOR_SIZE: usize = 2352;
+const CD_SUBCODE_SIZE: usize = 96;
+/// Padding between tracks, in frames
+const CD_TRACK_PADDING: u64 = 4;
+
+/// LZMA `lc=3, lp=0, pb=2`, which chdman always uses
+const LZMA_PROPERTIES: u8 = 93;
+
+const fn tag(name: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*name)
+}
+
+const CODEC_ZLIB: u32 = tag(b"zlib");
+const CODEC_LZMA: u32 = tag(b"lzma");
+const CODEC_CD_ZLIB: u32 = tag(b"cdzl");
+const CODEC_CD_LZMA: u32 = tag(b"cdlz");
+const CODEC_FLAC: u32 = tag(b"flac");
+const CODEC_CD_FLAC: u32 = tag(b"cdfl");
+
+const META_CD_TRACK: u32 = tag(b"CHTR");
+const META_CD_TRACK2: u32 = tag(b"CHT2");
+const META_GD_TRACK: u32 = tag(b"CHGD");
+
+/// Compression types in the map
+const TYPE_CODEC_3: u8 = 3;
+const TYPE_NONE: u8 = 4;
+const TYPE_SELF: u8 = 5;
+const TYPE_PARENT: u8 = 6;
+const TYPE_RLE_SMALL: u8 = 7;
+const TYPE_RLE_LARGE: u8 = 8;
+const TYPE_SELF_0: u8 = 9;
+const TYPE_SELF_1: u8 = 10;
+const TYPE_PARENT_SELF: u8 = 11;
+const TYPE_PARENT_0: u8 = 12;
+const TYPE_PARENT_1: u8 = 13;
+
+/// Where a hunk's data comes from
+#[derive(Debug, Clone, Copy)]
+enum Hunk {
+    /// Compressed with the header's codec `codec`
+    Compressed {
+        codec: usize,
+        offset: u64,
+        length: u32,
+        crc: u16,
+    },
+    /// Stored as is; compressed images record a CRC
+    Stored { offset: u64, crc: Option<u16> },
+    /// All zeros (an unallocated hunk of an uncompressed image)
+    Zero,
+    /// Same data as an earlier hunk
+    Copy(u64),
+    /// Data lives in the parent image
+    Parent,
+}
+
+/// A CD track as laid out in the CHD and in the exposed image
+#[derive(Debug, Clone, Copy)]
+struct Track {
+    /// First frame in the CHD
+    chd_frame: u64,
+    frames: u64,
+    /// Bytes of each sector exposed (2048 for MODE1, 2352 for raw, ...)
+    data_size: usize,
+    /// Audio is stored big-endian and swapped back on read
+    audio: bool,
+    /// Offset of the track in the exposed image
+    offset: u64,
+}
+
+/// A CHD v5 image, exposing the disc it compresses
+pub struct ChdDevice {
+    inner: Box<dyn BlockDevice>,
+    compressors: [u32; 4],
+    hunk_bytes: u32,
+    map: Vec<Hunk>,
+    /// CD tracks; empty for non-CD images
+    tracks: Vec<Track>,
+    size: u64,
+    cache: BlockCache,
+}
+
+fn be_u16(data: &[u8]) -> u16 {
+    u16::from_be_bytes([data[0], data[1]])
+}
+
+fn be_u32(data: &[u8]) -> u32 {
+    u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+}
+
+fn be_u64(data: &[u8]) -> u64 {
+    u64::from_be_bytes(data[..8].try_into().unwrap())
+}
+
+/// CRC-16/CCITT-FALSE, which CHD uses for the map and each hunk
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn codec_name(codec: u32) -> String {
+    codec
+        .to_be_bytes()
+        .iter()
+        .map(|&b| if b.is_ascii_graphic() { b as char } else { '?' })
+        .collect()
+}
+
+impl ChdDevice {
+    /// Parse the header, hunk map and CD track metadata of `inner`
+    pub fn open(inner: Box<dyn BlockDevice>) -> Result<Self, String> {
+        let mut header = [0u8; HEADER_SIZE];
+        if inner.size() < 16 {
+            return Err("Truncated CHD header".to_string());
+        }
+        inner.read_at(0, &mut header[..16])?;
+        if &header[0..8] != b"MComprHD" {
+            return Err("Not a CHD image".to_string());
+        }
+        let version = be_u32(&header[12..]);
+        if version != 5 {
+            return Err(format!(
+                "CHD version {} is not supported (only version 5)",
+                version
+            ));
+        }
+        inner
+            .read_at(0, &mut header)
+            .map_err(|_| "Truncated CHD header".to_string())?;
+
+        let compressors = [
+            be_u32(&header[16..]),
+            be_u32(&header[20..]),
+            be_u32(&header[24..]),
+            be_u32(&header[28..]),
+        ];
+        let logical_bytes = be_u64(&header[32..]);
+        let map_offset = be_u64(&header[40..]);
+        let meta_offset = be_u64(&header[48..]);
+        let hunk_bytes = be_u32(&header[56..]);
+        let unit_bytes = be_u32(&header[60..]);
+        if header[104..124].iter().any(|&b| b != 0) {
+            return Err("CHD images that depend on a parent are not supported".to_string());
+        }
+        if hunk_bytes == 0 || hunk_bytes > MAX_HUNK_BYTES || unit_bytes == 0 {
+            return Err(format!("Invalid CHD hunk size {}", hunk_bytes));
+        }
+        let hunk_count = logical_bytes.div_ceil(hunk_bytes as u64);
+
+        let map = if compressors[0] == 0 {
+            read_raw_map(inner.as_ref(), map_offset, hunk_count, hunk_bytes)?
+        } else {
+            read_compressed_map(
+                inner.as_ref(),
+                map_offset,
+                hunk_count,
+                hunk_bytes,
+                unit_bytes,
+            )?
+        };
+
+        let tracks = read_cd_tracks(inner.as_ref(), meta_offset)?;
+        let size = if tracks.is_empty() {
+            logical_bytes
+        } else {
+            if !(hunk_bytes as usize).is_multiple_of(CD_FRAME_SIZE) {
+                return Err(format!("Invalid CD CHD hunk size {}", hunk_bytes));
+            }
+            let last = tracks[tracks.len() - 1];
+            let end = (last.chd_frame.checked_add(last.frames))
+                .and_then(|frames| frames.checked_mul(CD_FRAME_SIZE as u64));
+            if end.is_none_or(|end| end > logical_bytes) {
+                return Err("CHD tracks extend past the end of the image".to_string());
+            }
+            last.offset + last.frames * last.data_size as u64
+        };
+
+        Ok(Self {
+            inner,
+            compressors,
+            hunk_bytes,
+            map,
+            tracks,
+            size,
+            cache: BlockCache::new(),
+        })
+    }
+
+    /// Whether this is a CD image
+    pub fn is_cd(&self) -> bool {
+        !self.tracks.is_empty()
+    }
+
+    /// Sector data size of the first CD track (2048 for an ISO-like image)
+    pub fn first_track_sector_size(&self) -> Option<usize> {
+        self.tracks.first().map(|track| track.data_size)
+    }
+
+    /// Decode hunk `index`
+    fn load(&self, index: u64) -> Result<Vec<u8>, String> {
+        let hunk_bytes = self.hunk_bytes as usize;
+        let mut index = index;
+        // A copy refers to an earlier hunk, which is never itself a copy in
+        // images chdman writes; allow a short chain regardless
+        for _ in 0..16 {
+            let (data, crc) = match self.map[index as usize] {
+                Hunk::Compressed {
+                    codec,
+                    offset,
+                    length,
+                    crc,
+                } => {
+                    let mut src = vec![0u8; length as usize];
+                    self.inner.read_at(offset, &mut src)?;
+                    (
+                        decompress(self.compressors[codec], &src, hunk_bytes)
+                            .map_err(|e| format!("CHD hunk {}: {}", index, e))?,
+                        Some(crc),
+                    )
+                }
+                Hunk::Stored { offset, crc } => {
+                    let mut data = vec![0u8; hunk_bytes];
+                    self.inner.read_at(offset, &mut data)?;
+                    (data, crc)
+                }
+                Hunk::Zero => (vec![0u8; hunk_bytes], None),
+                Hunk::Copy(source) if (source as usize) < self.map.len() => {
+                    index = source;
+                    continue;
+                }
+                Hunk::Copy(_) => {
+                    return Err(format!("CHD hunk {} has an invalid reference", index));
+                }
+                Hunk::Parent => {
+                    return Err("CHD images that depend on a parent are not supported".to_string());
+                }
+            };
+            if crc.is_some_and(|crc| crc16(&data) != crc) {
+                return Err(format!("CHD hunk {} failed its CRC check", index));
+            }
+            return Ok(data);
+        }
+        Err(format!("CHD hunk {} has a circular reference", index))
+    }
+
+    /// Read from the concatenated hunks
+    fn read_hunks(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        let hunk_bytes = self.hunk_bytes as u64;
+        if offset.saturating_add(buf.len() as u64) > self.map.len() as u64 * hunk_bytes {
+            return Err(format!(
+                "Read of {} bytes at {} out of bounds",
+                buf.len(),
+                offset
+            ));
+        }
+        let mut pos = offset;
+        let mut done = 0;
+        while done < buf.len() {
+            let hunk = pos / hunk_bytes;
+            let within = (pos % hunk_bytes) as usize;
+            let n = (hunk_bytes as usize - within).min(buf.len() - done);
+            self.cache
+                .read(hunk, within, &mut buf[done..done + n], |h| self.load(h))?;
+            done += n;
+            pos += n as u64;
+        }
+        Ok(())
+    }
+}
+
+impl BlockDevice for ChdDevice {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        if offset.saturating_add(buf.len() as u64) > self.size {
+            return Err(format!(
+                "Read of {} bytes at {} out of bounds",
+                buf.len(),
+                offset
+            ));
+        }
+        if self.tracks.is_empty() {
+            return self.read_hunks(offset, buf);
+        }
+
+        let mut pos = offset;
+        let mut done = 0;
+        let mut sector = [0u8; CD_SECTOR_SIZE];
+        while done < buf.len() {
+            let track = self
+                .tracks
+                .iter()
+                .rev()
+                .find(|track| track.offset <= pos && track.frames > 0)
+                .expect("tracks cover the whole image");
+            let size = track.data_size as u64;
+            let frame = track.chd_frame + (pos - track.offset) / size;
+            let within = ((pos - track.offset) % size) as usize;
+            let sector = &mut sector[..track.data_size];
+            self.read_hunks(frame * CD_FRAME_SIZE as u64, sector)?;
+            if track.audio {
+                for pair in sector.chunks_exact_mut(2) {
+                    pair.swap(0, 1);
+                }
+            }
+            let n = (track.data_size - within).min(buf.len() - done);
+            buf[done..done + n].copy_from_slice(&sector[within..within + n]);
+            done += n;
+            pos += n as u64;
+        }
+        Ok(())
+    }
+}
+
+/// Map of an uncompressed CHD: one big-endian hunk number per hunk
+fn read_raw_map(
+    inner: &dyn BlockDevice,
+    offset: u64,
+    hunk_count: u64,
+    hunk_bytes: u32,
+) -> Result<Vec<Hunk>, String> {
+    if offset.saturating_add(hunk_count.saturating_mul(4)) > inner.size() {
+        return Err("Truncated CHD hunk map".to_string());
+    }
+    let mut raw = vec![0u8; hunk_count as usize * 4];
+    inner.read_at(offset, &mut raw)?;
+    Ok(raw
+        .chunks_exact(4)
+        .map(|entry| match be_u32(entry) {
+            0 => Hunk::Zero,
+            n => Hunk::Stored {
+                offset: n as u64 * hunk_bytes as u64,
+                crc: None,
+            },
+        })
+        .collect())
+}
+
+/// MSB-first bit reader; reads past the end yield zeros
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Bits<'_> {
+    fn read(&mut self, count: u32) -> u64 {
+        let mut value = 0u64;
+        for _ in 0..count {
+            let byte = self.data.get(self.pos / 8).copied().unwrap_or(0);
+            value = (value << 1) | ((byte >> (7 - self.pos % 8)) & 1) as u64;
+            self.pos += 1;
+        }
+        value
+    }
+
+    fn overflowed(&self) -> bool {
+        self.pos > self.data.len() * 8
+    }
+}
+
+/// The map's Huffman decoder: 16 symbols, codes of at most 8 bits
+struct Huffman {
+    /// Symbol and code length for every 8-bit prefix
+    lookup: [(u8, u8); 256],
+}
+
+impl Huffman {
+    const CODES: usize = 16;
+    const MAX_BITS: u32 = 8;
+
+    /// Read code lengths stored as 4-bit fields with run-length escapes and
+    /// assign canonical codes (longest codes first)
+    fn import(bits: &mut Bits) -> Result<Self, String> {
+        let invalid = || "Invalid CHD map Huffman table".to_string();
+        let mut lengths = [0u8; Self::CODES];
+        let mut node = 0;
+        while node < Self::CODES {
+            let value = bits.read(4) as u8;
+            if value != 1 {
+                lengths[node] = value;
+                node += 1;
+                continue;
+            }
+            let value = bits.read(4) as u8;
+            if value == 1 {
+                lengths[node] = 1;
+                node += 1;
+                continue;
+            }
+            let repeat = bits.read(4) as usize + 3;
+            if node + repeat > Self::CODES {
+                return Err(invalid());
+            }
+            lengths[node..node + repeat].fill(value);
+            node += repeat;
+        }
+
+        let mut start = [0u32; 33];
+        let mut histogram = [0u32; 33];
+        for &len in &lengths {
+            if len as u32 > Self::MAX_BITS {
+                return Err(invalid());
+            }
+            histogram[len as usize] += 1;
+        }
+        let mut current = 0u32;
+        for len in (1..=32).rev() {
+            let next = (current + histogram[len]) >> 1;
+            if len != 1 && next * 2 != current + histogram[len] {
+                return Err(invalid());
+            }
+            start[len] = current;
+            current = next;
+        }
+
+        let mut lookup = [(0u8, 0u8); 256];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len == 0 {
+                continue;
+            }
+            let code = start[len as usize];
+            start[len as usize] += 1;
+            let shift = Self::MAX_BITS - len as u32;
+            let first = (code << shift) as usize;
+            let slots = lookup
+                .get_mut(first..first + (1 << shift))
+                .ok_or_else(invalid)?;
+            slots.fill((symbol as u8, len));
+        }
+        Ok(Self { lookup })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u8, String> {
+        let start = bits.pos;
+        let (symbol, len) = self.lookup[bits.read(Self::MAX_BITS) as usize];
+        if len == 0 {
+            return Err("Invalid code in CHD hunk map".to_string());
+        }
+        bits.pos = start + len as usize;
+        Ok(symbol)
+    }
+}
+
+/// Map of a compressed CHD
+fn read_compressed_map(
+    inner: &dyn BlockDevice,
+    offset: u64,
+    hunk_count: u64,
+    hunk_bytes: u32,
+    unit_bytes: u32,
+) -> Result<Vec<Hunk>, String> {
+    let truncated = || "Truncated CHD hunk map".to_string();
+    let mut header = [0u8; 16];
+    inner
+        .read_at(offset, &mut header)
+        .map_err(|_| truncated())?;
+    let map_bytes = be_u32(&header) as u64;
+    let mut first_offset = [0u8; 8];
+    first_offset[2..].copy_from_slice(&header[4..10]);
+    let first_offset = u64::from_be_bytes(first_offset);
+    let map_crc = be_u16(&header[10..]);
+    let length_bits = header[12] as u32;
+    let self_bits = header[13] as u32;
+    let parent_bits = header[14] as u32;
+    if length_bits > 32 || self_bits > 64 || parent_bits > 64 {
+        return Err("Invalid CHD hunk map header".to_string());
+    }
+    if (offset + 16).saturating_add(map_bytes) > inner.size() {
+        return Err(truncated());
+    }
+    let mut compressed = vec![0u8; map_bytes as usize];
+    inner.read_at(offset + 16, &mut compressed)?;
+    let mut bits = Bits {
+        data: &compressed,
+        pos: 0,
+    };
+
+    // Compression types, with runs of the previous type
+    let huffman = Huffman::import(&mut bits)?;
+    let mut types = Vec::new();
+    let mut last = 0u8;
+    let mut repeat = 0u64;
+    for _ in 0..hunk_count {
+        if repeat > 0 {
+            types.push(last);
+            repeat -= 1;
+            continue;
+        }
+        match huffman.decode(&mut bits)? {
+            TYPE_RLE_SMALL => {
+                types.push(last);
+                repeat = 2 + huffman.decode(&mut bits)? as u64;
+            }
+            TYPE_RLE_LARGE => {
+                types.push(last);
+                repeat = 2 + 16 + ((huffman.decode(&mut bits)? as u64) << 4);
+                repeat += huffman.decode(&mut bits)? as u64;
+            }
+            value => {
+                types.push(value);
+                last = value;
+            }
+        }
+        if bits.overflowed() {
+            return Err(truncated());
+        }
+    }
+
+    // Lengths, CRCs and references; the map CRC covers 12-byte entries of
+    // type, 24-bit length, 48-bit offset and CRC
+    let mut map = Vec::with_capacity(types.len());
+    let mut raw = Vec::with_capacity(types.len() * 12);
+    let mut current = first_offset;
+    let mut last_self = 0u64;
+    let mut last_parent = 0u64;
+    for (hunk, &kind) in types.iter().enumerate() {
+        let mut kind = kind;
+        let mut offset = current;
+        let mut length = 0u32;
+        let mut crc = 0u16;
+        let entry = match kind {
+            0..=TYPE_CODEC_3 => {
+                length = bits.read(length_bits) as u32;
+                crc = bits.read(16) as u16;
+                current += length as u64;
+                Hunk::Compressed {
+                    codec: kind as usize,
+                    offset,
+                    length,
+                    crc,
+                }
+            }
+            TYPE_NONE => {
+                length = hunk_bytes;
+                crc = bits.read(16) as u16;
+                current += length as u64;
+                Hunk::Stored {
+                    offset,
+                    crc: Some(crc),
+                }
+            }
+            TYPE_SELF | TYPE_SELF_0 | TYPE_SELF_1 => {
+                if kind == TYPE_SELF {
+                    last_self = bits.read(self_bits);
+                } else if kind == TYPE_SELF_1 {
+                    last_self += 1;
+                }
+                kind = TYPE_SELF;
+                offset = last_self;
+                Hunk::Copy(last_self)
+            }
+            TYPE_PARENT | TYPE_PARENT_SELF | TYPE_PARENT_0 | TYPE_PARENT_1 => {
+                match kind {
+                    TYPE_PARENT => last_parent = bits.read(parent_bits),
+                    TYPE_PARENT_SELF => {
+                        last_parent = hunk as u64 * hunk_bytes as u64 / unit_bytes as u64
+                    }
+                    TYPE_PARENT_1 => last_parent += (hunk_bytes / unit_bytes) as u64,
+                    _ => {}
+                }
+                kind = TYPE_PARENT;
+                offset = last_parent;
+                Hunk::Parent
+            }
+            _ => return Err("Invalid compression type in CHD hunk map".to_string()),
+        };
+        if bits.overflowed() {
+            return Err(truncated());
+        }
+        raw.push(kind);
+        raw.extend_from_slice(&length.to_be_bytes()[1..]);
+        raw.extend_from_slice(&offset.to_be_bytes()[2..]);
+        raw.extend_from_slice(&crc.to_be_bytes());
+        map.push(entry);
+    }
+
+    if crc16(&raw) != map_crc {
+        return Err("CHD hunk map failed its CRC check".to_string());
+    }
+    Ok(map)
+}
+
+/// Decompress one hunk of `size` bytes
+fn decompress(codec: u32, src: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    let data = match codec {
+        CODEC_ZLIB => inflate(src, size)?,
+        CODEC_LZMA => decompress_lzma(src, size)?,
+        CODEC_FLAC => decompress_flac(src, size)?,
+        CODEC_CD_ZLIB | CODEC_CD_LZMA => decompress_cd(codec, src, size)?,
+        CODEC_CD_FLAC => decompress_cd_flac(src, size)?,
+        _ => {
+            return Err(format!(
+                "compression '{}' is not supported",
+                codec_name(codec)
+            ));
+        }
+    };
+    if data.len() != size {
+        return Err("decompressed to the wrong size".to_string());
+    }
+    Ok(data)
+}
+
+/// Decompress a raw LZMA hunk (no header, no end marker)
+fn decompress_lzma(src: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    // lzma-rs reads the properties and dictionary size from a header; any
+    // dictionary at least as large as the hunk decodes it
+    let mut input = Vec::with_capacity(5 + src.len());
+    input.push(LZMA_PROPERTIES);
+    input.extend_from_slice(&(size.max(4096) as u32).to_le_bytes());
+    input.extend_from_slice(src);
+
+    let options = lzma_rs::decompress::Options {
+        unpacked_size: lzma_rs::decompress::UnpackedSize::UseProvided(Some(size as u64)),
+        ..Default::default()
+    };
+    let mut out = Vec::with_capacity(size);
+    lzma_rs::lzma_decompress_with_options(&mut input.as_slice(), &mut out, &options)
+        .map_err(|e| format!("Corrupt LZMA hunk: {}", e))?;
+    Ok(out)
+}
+
+/// Decompress a CD hunk: sector data and subcode are compressed separately,
+/// and sectors flagged in the header get their sync and ECC regenerated
+fn decompress_cd(codec: u32, src: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    let truncated = || "truncated CD hunk".to_string();
+    let frames = size / CD_FRAME_SIZE;
+    let ecc_bytes = frames.div_ceil(8);
+    let length_bytes = if size < 65536 { 2 } else { 3 };
+    let header = src.get(..ecc_bytes + length_bytes).ok_or_else(truncated)?;
+    let base_length = header[ecc_bytes..]
+        .iter()
+        .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+    let base_src = src
+        .get(header.len()..header.len() + base_length)
+        .ok_or_else(truncated)?;
+    let subcode_src = &src[header.len() + base_length..];
+
+    let base_codec = if codec == CODEC_CD_LZMA {
+        CODEC_LZMA
+    } else {
+        CODEC_ZLIB
+    };
+    let base = decompress(base_codec, base_src, frames * CD_SECTOR_SIZE)?;
+    let subcode = decompress(CODEC_ZLIB, subcode_src, frames * CD_SUBCODE_SIZE)?;
+
+    let mut out = vec![0u8; size];
+    for (i, frame) in out.chunks_exact_mut(CD_FRAME_SIZE).enumerate() {
+        frame[..CD_SECTOR_SIZE].copy_from_slice(&base[i * CD_SECTOR_SIZE..][..CD_SECTOR_SIZE]);
+        frame[CD_SECTOR_SIZE..].copy_from_slice(&subcode[i * CD_SUBCODE_SIZE..][..CD_SUBCODE_SIZE]);
+        if header[i / 8] & (1 << (i % 8)) != 0 {
+            frame[..ecc::SYNC.len()].copy_from_slice(&ecc::SYNC);
+            ecc::generate(&mut frame[..CD_SECTOR_SIZE]);
+        }
+    }
+    Ok(out)
+}
+
+/// Decompress a `flac` hunk: a byte giving the output byte order (`L` or
+/// `B`), then FLAC frames of 16-bit stereo samples
+fn decompress_flac(src: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    let big_endian = match src.first() {
+        Some(b'L') => false,
+        Some(b'B') => true,
+        _ => return Err("invalid FLAC hunk byte order".to_string()),
+    };
+    let (data, _) = decode_flac(&src[1..], size, big_endian)?;
+    Ok(data)
+}
+
+/// Decompress a `cdfl` hunk: every sector is FLAC audio with big-endian
+/// samples (as CHD stores audio tracks), followed by the deflated subcode
+/// where the FLAC frames end
+fn decompress_cd_flac(src: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    let frames = size / CD_FRAME_SIZE;
+    let (base, used) = decode_flac(src, frames * CD_SECTOR_SIZE, true)?;
+    if base.len() != frames * CD_SECTOR_SIZE {
+        return Err("decompressed to the wrong size".to_string());
+    }
+    let subcode = decompress(CODEC_ZLIB, &src[used..], frames * CD_SUBCODE_SIZE)?;
+
+    let mut out = vec![0u8; size];
+    for (i, frame) in out.chunks_exact_mut(CD_FRAME_SIZE).enumerate() {
+        frame[..CD_SECTOR_SIZE].copy_from_slice(&base[i * CD_SECTOR_SIZE..][..CD_SECTOR_SIZE]);
+        frame[CD_SECTOR_SIZE..].copy_from_slice(&subcode[i * CD_SUBCODE_SIZE..][..CD_SUBCODE_SIZE]);
+    }
+    Ok(out)
+}
+
+/// Decode FLAC frames (no stream header) until `size` bytes of interleaved
+/// 16-bit stereo samples are out; also returns how many bytes were read
+fn decode_flac(src: &[u8], size: usize, big_endian: bool) -> Result<(Vec<u8>, usize), String> {
+    let mut input = Cursor::new(src);
+    let mut reader = claxon::frame::FrameReader::new(&mut input);
+    let mut out = Vec::with_capacity(size);
+    let mut buffer = Vec::new();
+    while out.len() < size {
+        let block = reader
+            .read_next_or_eof(buffer)
+            .map_err(|e| format!("Corrupt FLAC hunk: {}", e))?
+            .ok_or_else(|| "truncated FLAC hunk".to_string())?;
+        if block.channels() != 2 {
+            return Err("FLAC hunk is not stereo".to_string());
+        }
+        for (left, right) in block.stereo_samples() {
+            for sample in [left as i16, right as i16] {
+                out.extend_from_slice(&match big_endian {
+                    true => sample.to_be_bytes(),
+                    false => sample.to_le_bytes(),
+                });
+            }
+        }
+        buffer = block.into_buffer();
+    }
+    Ok((out, input.position() as usize))
+}
+
+/// Sector data size of a CD track type
+fn track_data_size(kind: &str) -> Option<usize> {
+    Some(match kind {
+        "MODE1" | "MODE2_FORM1" => 2048,
+        "MODE2" | "MODE2_FORM_MIX" => 2336,
+        "MODE2_FORM2" => 2324,
+        "MODE1_RAW" | "MODE2_RAW" | "AUDIO" => 2352,
+        _ => return None,
+    })
+}
+
+/// Read the CD track list from the metadata chain; empty for non-CD images
+fn read_cd_tracks(inner: &dyn BlockDevice, mut offset: u64) -> Result<Vec<Track>, String> {
+    // (number, frames, padding frames, type)
+    let mut found: Vec<(u32, u64, Option<u64>, String)> = Vec::new();
+    let mut entries = 0;
+    while offset != 0 {
+        entries += 1;
+        if entries > 4096 {
+            return Err("CHD metadata chain does not end".to_string());
+        }
+        let mut header = [0u8; 16];
+        inner
+            .read_at(offset, &mut header)
+            .map_err(|_| "Truncated CHD metadata".to_string())?;
+        let tag = be_u32(&header);
+        let length = (be_u32(&header[4..]) & 0x00FF_FFFF) as usize;
+        let next = be_u64(&header[8..]);
+
+        if matches!(tag, META_CD_TRACK | META_CD_TRACK2 | META_GD_TRACK) {
+            let mut text = vec![0u8; length];
+            inner
+                .read_at(offset + 16, &mut text)
+                .map_err(|_| "Truncated CHD metadata".to_string())?;
+            let text = String::from_utf8_lossy(&text);
+            let field = |name: &str| {
+                text.split_whitespace()
+                    .find_map(|pair| pair.strip_prefix(name)?.strip_prefix(':'))
+                    .map(|value| value.trim_end_matches('\0').to_string())
+            };
+            let number = |name: &str| field(name).and_then(|v| v.parse::<u64>().ok());
+            let invalid = || {
+                format!(
+                    "Invalid CHD track metadata: {}",
+                    text.trim_end_matches('\0')
+                )
+            };
+            let track = number("TRACK").ok_or_else(invalid)? as u32;
+            let frames = number("FRAMES").ok_or_else(invalid)?;
+            let kind = field("TYPE").ok_or_else(invalid)?;
+            let padding = if tag == META_GD_TRACK {
+                Some(number("PAD").unwrap_or(0))
+            } else {
+                None
+            };
+            found.push((track, frames, padding, kind));
+        }
+        offset = next;
+    }
+
+    found.sort_by_key(|(track, ..)| *track);
+    let mut tracks = Vec::with_capacity(found.len());
+    let mut chd_frame = 0u64;
+    let mut logical = 0u64;
+    for (number, frames, padding, kind) in found {
+        let data_size = track_data_size(&kind)
+            .ok_or_else(|| format!("Unknown CD track type {} in track {}", kind, number))?;
+        tracks.push(Track {
+            chd_frame,
+            frames,
+            data_size,
+            audio: kind == "AUDIO",
+            offset: logical,
+        });
+        let padding =
+            padding.unwrap_or((CD_TRACK_PADDING - frames % CD_TRACK_PADDING) % CD_TRACK_PADDING);
+        chd_frame = chd_frame
+            .checked_add(frames)
+            .and_then(|f| f.checked_add(padding))
+            .ok_or_else(|| "Invalid CHD track length".to_string())?;
+        logical = logical.saturating_add(frames.saturating_mul(data_size as u64));
+    }
+    Ok(tracks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_huffman_run_of_equal_lengths() {
+        // Escape, length 4, repeat 13 + 3: sixteen 4-bit codes, code = symbol
+        let data = [0x14, 0xD0, 0x5A];
+        let mut bits = Bits {
+            data: &data,
+            pos: 0,
+        };
+        let huffman = Huffman::import(&mut bits).unwrap();
+        assert_eq!(huffman.decode(&mut bits).unwrap(), 0);
+        assert_eq!(huffman.decode(&mut bits).unwrap(), 5);
+        assert_eq!(huffman.decode(&mut bits).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_huffman_rejects_invalid_tree() {
+        // A single 2-bit code, then fifteen unused symbols
+        let data = [0x21, 0x0C];
+        let mut bits = Bits {
+            data: &data,
+            pos: 0,
+        };
+        assert!(Huffman::import(&mut bits).is_err());
+    }
+}
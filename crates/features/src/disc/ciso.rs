@@ -0,0 +1,141 @@
+//! CISO (`.cso`) and ZISO (`.zso`) compressed ISOs
+//!
+//! Both share one layout: a 24-byte header, then one little-endian `u32`
+//! index entry per block plus a final end marker. An entry shifted left by
+//! the header's alignment is the block's file offset; its top bit marks a
+//! stored block in CISO v1 and ZISO, and an LZ4 block in CISO v2.
+
+use super::device::{BlockCache, BlockDevice};
+use super::inflate;
+
+const HEADER_SIZE: u64 = 24;
+/// Largest block size accepted (real images use 2 KiB)
+const MAX_BLOCK_SIZE: u32 = 1 << 20;
+
+/// A CISO or ZISO image, exposing the ISO it compresses
+pub struct CisoDevice {
+    inner: Box<dyn BlockDevice>,
+    zso: bool,
+    version: u8,
+    block_size: u32,
+    align: u8,
+    size: u64,
+    index: Vec<u32>,
+    cache: BlockCache,
+}
+
+impl CisoDevice {
+    /// Parse the header and block index of `inner`
+    pub fn open(inner: Box<dyn BlockDevice>) -> Result<Self, String> {
+        let mut header = [0u8; HEADER_SIZE as usize];
+        inner
+            .read_at(0, &mut header)
+            .map_err(|_| "Truncated CSO header".to_string())?;
+        let zso = match &header[0..4] {
+            b"CISO" => false,
+            b"ZISO" => true,
+            _ => return Err("Not a CSO or ZSO image".to_string()),
+        };
+        let size = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let block_size = u32::from_le_bytes(header[16..20].try_into().unwrap());
+        let version = header[20];
+        let align = header[21];
+
+        if block_size == 0 || block_size > MAX_BLOCK_SIZE || align > 31 {
+            return Err(format!("Invalid CSO block size {}", block_size));
+        }
+        let blocks = size.div_ceil(block_size as u64);
+        let index_bytes = blocks
+            .checked_add(1)
+            .and_then(|entries| entries.checked_mul(4))
+            .filter(|&bytes| bytes <= inner.size().saturating_sub(HEADER_SIZE))
+            .ok_or_else(|| "Truncated CSO block index".to_string())?;
+        let mut raw = vec![0u8; index_bytes as usize];
+        inner.read_at(HEADER_SIZE, &mut raw)?;
+        let index = raw
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        Ok(Self {
+            inner,
+            zso,
+            version,
+            block_size,
+            align,
+            size,
+            index,
+            cache: BlockCache::new(),
+        })
+    }
+
+    /// Decode block `block`
+    fn load(&self, block: u64) -> Result<Vec<u8>, String> {
+        let i = block as usize;
+        let entry = self.index[i];
+        let start = ((entry & 0x7FFF_FFFF) as u64) << self.align;
+        let end = ((self.index[i + 1] & 0x7FFF_FFFF) as u64) << self.align;
+        if end < start || end - start > 2 * self.block_size as u64 || end > self.inner.size() {
+            return Err(format!("Invalid CSO index entry for block {}", block));
+        }
+        let expected = (self.size - block * self.block_size as u64).min(self.block_size as u64);
+
+        let mut stored = vec![0u8; (end - start) as usize];
+        self.inner.read_at(start, &mut stored)?;
+
+        let flag = entry & 0x8000_0000 != 0;
+        let plain = if self.version >= 2 && !self.zso {
+            stored.len() >= self.block_size as usize
+        } else {
+            flag
+        };
+        let lz4 = self.zso || (self.version >= 2 && flag);
+
+        let mut data = if plain {
+            stored
+        } else if lz4 {
+            let mut out = vec![0u8; self.block_size as usize];
+            let len = lz4_flex::block::decompress_into(&stored, &mut out)
+                .map_err(|e| format!("CSO block {}: {}", block, e))?;
+            out.truncate(len);
+            out
+        } else {
+            inflate(&stored, self.block_size as usize)
+                .map_err(|e| format!("CSO block {}: {}", block, e))?
+        };
+        if data.len() < expected as usize {
+            return Err(format!("CSO block {} is truncated", block));
+        }
+        data.truncate(expected as usize);
+        Ok(data)
+    }
+}
+
+impl BlockDevice for CisoDevice {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        if offset.saturating_add(buf.len() as u64) > self.size {
+            return Err(format!(
+                "Read of {} bytes at {} out of bounds",
+                buf.len(),
+                offset
+            ));
+        }
+        let block_size = self.block_size as u64;
+        let mut pos = offset;
+        let mut done = 0;
+        while done < buf.len() {
+            let block = pos / block_size;
+            let within = (pos % block_size) as usize;
+            let n = (block_size as usize - within).min(buf.len() - done);
+            self.cache
+                .read(block, within, &mut buf[done..done + n], |b| self.load(b))?;
+            done += n;
+            pos += n as u64;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,462 @@
+//! CUE sheets and multi-file BIN images
+//!
+//! A CUE sheet lists the BIN files of a disc and where each track starts
+//! inside them. Redump-style dumps keep every track in its own file, while
+//! merged dumps put all tracks in one; either way the files concatenated in
+//! sheet order form the same logical disc, which is what patches apply to.
+//!
+//! `PREGAP` and `POSTGAP` sectors are generated by the drive and not stored
+//! in any file, so they only shift disc addresses, never image offsets.
+
+use std::path::{Path, PathBuf};
+
+use super::device::{BlockDevice, FileDevice};
+
+/// Frames (sectors) per second of CD audio time
+const FRAMES_PER_SECOND: u32 = 75;
+
+/// A `FILE` entry of a CUE sheet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueFile {
+    /// File name, relative to the sheet
+    pub name: String,
+    /// File type (`BINARY`, `WAVE`, ...)
+    pub kind: String,
+}
+
+/// A `TRACK` entry of a CUE sheet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueTrack {
+    pub number: u8,
+    /// Track mode as written in the sheet (`MODE2/2352`, `AUDIO`, ...)
+    pub mode: String,
+    /// Index into [`CueSheet::files`] of the file holding the track
+    pub file: usize,
+    /// `INDEX` numbers and their positions in frames from the start of
+    /// the file
+    pub indexes: Vec<(u8, u32)>,
+    /// Frames of `PREGAP` before the track, not stored in the file
+    pub pregap: u32,
+    /// Frames of `POSTGAP` after the track, not stored in the file
+    pub postgap: u32,
+}
+
+impl CueTrack {
+    /// Bytes per sector in the file
+    pub fn sector_size(&self) -> usize {
+        match self.mode.as_str() {
+            "AUDIO" => 2352,
+            "CDG" => 2448,
+            mode => mode
+                .split_once('/')
+                .and_then(|(_, size)| size.parse().ok())
+                .unwrap_or(2352),
+        }
+    }
+
+    /// Whether the track holds data rather than audio
+    pub fn is_data(&self) -> bool {
+        self.mode.starts_with("MODE")
+    }
+
+    /// Position of `INDEX number` in frames from the start of the file
+    pub fn index(&self, number: u8) -> Option<u32> {
+        self.indexes
+            .iter()
+            .find(|(n, _)| *n == number)
+            .map(|(_, frames)| *frames)
+    }
+
+    /// Where the track's data starts in its file: its pregap (`INDEX 00`)
+    /// when the file holds one, otherwise `INDEX 01`
+    fn first_frame(&self) -> u32 {
+        self.indexes.first().map_or(0, |(_, frames)| *frames)
+    }
+}
+
+/// Where a track lies in the logical disc
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackSpan {
+    pub number: u8,
+    /// Byte offset of the track, including a stored `INDEX 00` pregap
+    pub start: u64,
+    /// Byte offset just past the track
+    pub end: u64,
+    /// Disc address of `INDEX 01`, counting sectors from the start of the
+    /// first track (generated pregaps and postgaps included)
+    pub lba: u32,
+}
+
+impl TrackSpan {
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A parsed CUE sheet
+#[derive(Debug, Clone)]
+pub struct CueSheet {
+    pub files: Vec<CueFile>,
+    pub tracks: Vec<CueTrack>,
+    /// The sheet as read, kept so it can be rewritten with other file names
+    text: String,
+    /// Line number of each `FILE` command
+    file_lines: Vec<usize>,
+}
+
+impl CueSheet {
+    /// Parse the text of a CUE sheet
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let mut files = Vec::new();
+        let mut file_lines = Vec::new();
+        let mut tracks: Vec<CueTrack> = Vec::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+            let err = |message: &str| format!("CUE line {}: {}", line_number + 1, message);
+
+            match command.to_uppercase().as_str() {
+                "FILE" => {
+                    let (name, kind) = parse_file(rest).ok_or_else(|| err("malformed FILE"))?;
+                    files.push(CueFile { name, kind });
+                    file_lines.push(line_number);
+                }
+                "TRACK" => {
+                    let file = files
+                        .len()
+                        .checked_sub(1)
+                        .ok_or_else(|| err("TRACK before FILE"))?;
+                    let (number, mode) = rest
+                        .split_once(char::is_whitespace)
+                        .ok_or_else(|| err("malformed TRACK"))?;
+                    let number: u8 = number.parse().map_err(|_| err("bad track number"))?;
+                    if tracks.last().is_some_and(|t| t.number >= number) {
+                        return Err(err("track numbers must increase"));
+                    }
+                    tracks.push(CueTrack {
+                        number,
+                        mode: mode.trim().to_uppercase(),
+                        file,
+                        indexes: Vec::new(),
+                        pregap: 0,
+                        postgap: 0,
+                    });
+                }
+                "INDEX" => {
+                    let track = tracks.last_mut().ok_or_else(|| err("INDEX before TRACK"))?;
+                    // Sheets with gaps appended to the previous track start
+                    // a new FILE between INDEX 00 and INDEX 01; the pregap
+                    // then belongs to the previous file
+                    if track.file != files.len() - 1 {
+                        track.file = files.len() - 1;
+                        track.indexes.clear();
+                    }
+                    let (number, time) = rest
+                        .split_once(char::is_whitespace)
+                        .ok_or_else(|| err("malformed INDEX"))?;
+                    let number: u8 = number.parse().map_err(|_| err("bad index number"))?;
+                    let frames = parse_msf(time.trim()).ok_or_else(|| err("bad INDEX time"))?;
+                    if track
+                        .indexes
+                        .last()
+                        .is_some_and(|&(n, f)| n >= number || f > frames)
+                    {
+                        return Err(err("indexes must increase"));
+                    }
+                    track.indexes.push((number, frames));
+                }
+                "PREGAP" | "POSTGAP" => {
+                    let track = tracks
+                        .last_mut()
+                        .ok_or_else(|| err(&format!("{} before TRACK", command)))?;
+                    let frames = parse_msf(rest).ok_or_else(|| err("bad gap time"))?;
+                    if command.eq_ignore_ascii_case("PREGAP") {
+                        track.pregap = frames;
+                    } else {
+                        track.postgap = frames;
+                    }
+                }
+                // Metadata that does not affect the layout
+                _ => {}
+            }
+        }
+
+        if tracks.is_empty() {
+            return Err("CUE sheet has no tracks".to_string());
+        }
+        if let Some(track) = tracks.iter().find(|t| t.index(1).is_none()) {
+            return Err(format!("Track {} has no INDEX 01", track.number));
+        }
+        if let Some(file) = (0..files.len()).find(|&f| !tracks.iter().any(|t| t.file == f)) {
+            return Err(format!("FILE \"{}\" has no tracks", files[file].name));
+        }
+
+        Ok(Self {
+            files,
+            tracks,
+            text: text.to_string(),
+            file_lines,
+        })
+    }
+
+    /// Read and parse a CUE sheet file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read(path)
+            .map_err(|e| format!("Failed to read CUE file {}: {}", path.display(), e))?;
+        Self::parse(&String::from_utf8_lossy(&text))
+    }
+
+    /// Paths of the sheet's files, relative to the directory holding it
+    pub fn file_paths(&self, dir: &Path) -> Vec<PathBuf> {
+        self.files.iter().map(|f| dir.join(&f.name)).collect()
+    }
+
+    /// Where each track lies in the logical disc, given the size of each
+    /// file
+    pub fn spans(&self, file_sizes: &[u64]) -> Result<Vec<TrackSpan>, String> {
+        if file_sizes.len() != self.files.len() {
+            return Err(format!(
+                "CUE sheet lists {} files, got {} sizes",
+                self.files.len(),
+                file_sizes.len()
+            ));
+        }
+        let file_starts: Vec<u64> = file_sizes
+            .iter()
+            .scan(0u64, |offset, size| {
+                let start = *offset;
+                *offset += size;
+                Some(start)
+            })
+            .collect();
+
+        let mut spans = Vec::with_capacity(self.tracks.len());
+        // Disc address of the start of the current file
+        let mut file_lba = 0u32;
+        let mut gaps = 0u32;
+        for (i, track) in self.tracks.iter().enumerate() {
+            let sector_size = track.sector_size() as u64;
+            let file_start = file_starts[track.file];
+            let file_end = file_start + file_sizes[track.file];
+            if i > 0 && self.tracks[i - 1].file != track.file {
+                let previous = &self.tracks[i - 1];
+                file_lba = u32::try_from(frames(file_sizes[previous.file], previous.sector_size()))
+                    .ok()
+                    .and_then(|frames| file_lba.checked_add(frames))
+                    .ok_or_else(|| too_long(track.number))?;
+            }
+
+            let start = file_start + u64::from(track.first_frame()) * sector_size;
+            let end = match self.tracks.get(i + 1) {
+                Some(next) if next.file == track.file => {
+                    file_start + u64::from(next.first_frame()) * sector_size
+                }
+                _ => file_end,
+            };
+            let index1 = track.index(1).expect("checked when parsed");
+            // INDEX 01 must leave at least one sector of the track itself
+            if file_start + u64::from(index1) * sector_size >= end || end > file_end {
+                return Err(format!(
+                    "Track {} starts beyond the end of \"{}\"",
+                    track.number, self.files[track.file].name
+                ));
+            }
+
+            gaps = gaps
+                .checked_add(track.pregap)
+                .ok_or_else(|| too_long(track.number))?;
+            let lba = file_lba
+                .checked_add(gaps)
+                .and_then(|lba| lba.checked_add(index1))
+                .ok_or_else(|| too_long(track.number))?;
+            spans.push(TrackSpan {
+                number: track.number,
+                start,
+                end,
+                lba,
+            });
+            gaps = gaps
+                .checked_add(track.postgap)
+                .ok_or_else(|| too_long(track.number))?;
+        }
+        Ok(spans)
+    }
+
+    /// The sheet with its `FILE` commands pointing at `names`
+    ///
+    /// Everything else, including comments and metadata, is kept as is.
+    pub fn with_file_names(&self, names: &[String]) -> String {
+        let newline = if self.text.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let mut out = String::with_capacity(self.text.len());
+        for (line_number, line) in self.text.lines().enumerate() {
+            match self.file_lines.iter().position(|&l| l == line_number) {
+                Some(file) => {
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    let name = names.get(file).unwrap_or(&self.files[file].name);
+                    out.push_str(&format!(
+                        "{}FILE \"{}\" {}",
+                        indent, name, self.files[file].kind
+                    ));
+                }
+                None => out.push_str(line),
+            }
+            out.push_str(newline);
+        }
+        out
+    }
+}
+
+/// Number of whole sectors in a file
+fn frames(size: u64, sector_size: usize) -> u64 {
+    size / sector_size as u64
+}
+
+/// The error for a track whose disc address does not fit in 32 bits
+fn too_long(track: u8) -> String {
+    format!("Track {} lies beyond the largest disc address", track)
+}
+
+/// Parse `"name with spaces.bin" BINARY` or `name.bin BINARY`
+fn parse_file(rest: &str) -> Option<(String, String)> {
+    let (name, kind) = if let Some(quoted) = rest.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        rest.rsplit_once(char::is_whitespace)?
+    };
+    let kind = kind.trim();
+    if name.is_empty() || kind.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), kind.to_uppercase()))
+}
+
+/// Parse `mm:ss:ff` into frames
+fn parse_msf(time: &str) -> Option<u32> {
+    let mut parts = time.split(':').map(|p| p.parse::<u32>().ok());
+    let (m, s, f) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || s >= 60 || f >= FRAMES_PER_SECOND {
+        return None;
+    }
+    m.checked_mul(60)?
+        .checked_add(s)?
+        .checked_mul(FRAMES_PER_SECOND)?
+        .checked_add(f)
+}
+
+/// The files of a CUE sheet read back to back as one disc
+pub struct CueDevice {
+    parts: Vec<(u64, Box<dyn BlockDevice>)>,
+    size: u64,
+}
+
+impl CueDevice {
+    /// Concatenate `parts` in order
+    pub fn new(parts: Vec<Box<dyn BlockDevice>>) -> Self {
+        let mut size = 0;
+        let parts = parts
+            .into_iter()
+            .map(|part| {
+                let start = size;
+                size += part.size();
+                (start, part)
+            })
+            .collect();
+        Self { parts, size }
+    }
+
+    /// Open the files of `sheet`, which lives in `dir`
+    ///
+    /// Only `BINARY` files hold raw sectors; WAVE and other audio files
+    /// are rejected.
+    pub fn open(sheet: &CueSheet, dir: &Path) -> Result<Self, String> {
+        let mut parts: Vec<Box<dyn BlockDevice>> = Vec::with_capacity(sheet.files.len());
+        for (file, path) in sheet.files.iter().zip(sheet.file_paths(dir)) {
+            if file.kind != "BINARY" {
+                return Err(format!(
+                    "Unsupported {} file in CUE sheet: {}",
+                    file.kind, file.name
+                ));
+            }
+            parts.push(Box::new(FileDevice::open(&path)?));
+        }
+        Ok(Self::new(parts))
+    }
+
+    /// Size of each file, in sheet order
+    pub fn file_sizes(&self) -> Vec<u64> {
+        self.parts.iter().map(|(_, part)| part.size()).collect()
+    }
+}
+
+impl BlockDevice for CueDevice {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        if offset.saturating_add(buf.len() as u64) > self.size {
+            return Err(format!(
+                "Read of {} bytes at {} out of bounds",
+                buf.len(),
+                offset
+            ));
+        }
+        let mut done = 0;
+        for (start, part) in &self.parts {
+            if done == buf.len() {
+                break;
+            }
+            let end = start + part.size();
+            let position = offset + done as u64;
+            if position >= end {
+                continue;
+            }
+            let n = ((end - position) as usize).min(buf.len() - done);
+            part.read_at(position - start, &mut buf[done..done + n])?;
+            done += n;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_msf() {
+        assert_eq!(parse_msf("00:02:00"), Some(150));
+        assert_eq!(parse_msf("01:00:74"), Some(4574));
+        assert_eq!(parse_msf("00:60:00"), None);
+        assert_eq!(parse_msf("00:00:75"), None);
+        assert_eq!(parse_msf("00:00"), None);
+        assert_eq!(parse_msf("954437:00:00"), Some(4_294_966_500));
+        assert_eq!(parse_msf("954438:00:00"), None);
+        assert_eq!(parse_msf("4294967295:00:00"), None);
+    }
+
+    #[test]
+    fn test_parse_file() {
+        assert_eq!(
+            parse_file("\"Game (Track 1).bin\" BINARY"),
+            Some(("Game (Track 1).bin".to_string(), "BINARY".to_string()))
+        );
+        assert_eq!(
+            parse_file("game.bin binary"),
+            Some(("game.bin".to_string(), "BINARY".to_string()))
+        );
+        assert_eq!(parse_file("\"game.bin\""), None);
+    }
+}
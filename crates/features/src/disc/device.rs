@@ -0,0 +1,285 @@
+//! Random-access byte sources for disc images
+//!
+//! Compressed images decode only the blocks a read touches, so hashing a
+//! disc reads a few megabytes instead of the whole image.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+
+/// A read-only, randomly addressable sequence of bytes
+pub trait BlockDevice {
+    /// Total size in bytes
+    fn size(&self) -> u64;
+
+    /// Fill `buf` with the bytes starting at `offset`
+    ///
+    /// Reading past [`size`](Self::size) is an error.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String>;
+}
+
+fn read_slice(data: &[u8], offset: u64, buf: &mut [u8]) -> Result<(), String> {
+    let start = usize::try_from(offset).map_err(|_| "Read offset out of range".to_string())?;
+    let bytes = start
+        .checked_add(buf.len())
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| format!("Read of {} bytes at {} out of bounds", buf.len(), offset))?;
+    buf.copy_from_slice(bytes);
+    Ok(())
+}
+
+impl BlockDevice for &[u8] {
+    fn size(&self) -> u64 {
+        self.len() as u64
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        read_slice(self, offset, buf)
+    }
+}
+
+impl BlockDevice for Vec<u8> {
+    fn size(&self) -> u64 {
+        self.len() as u64
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        read_slice(self, offset, buf)
+    }
+}
+
+/// A [`BlockDevice`] whose bytes can also be overwritten in place
+pub trait WritableDevice: BlockDevice {
+    /// Overwrite the bytes starting at `offset` with `data`
+    ///
+    /// Devices never grow: writing past [`size`](BlockDevice::size) is an
+    /// error.
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<(), String>;
+}
+
+impl WritableDevice for Vec<u8> {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<(), String> {
+        let bytes = usize::try_from(offset)
+            .ok()
+            .and_then(|start| Some(start..start.checked_add(data.len())?))
+            .and_then(|range| self.get_mut(range))
+            .ok_or_else(|| format!("Write of {} bytes at {} out of bounds", data.len(), offset))?;
+        bytes.copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// Writes held in memory on top of a read-only device
+///
+/// Rewriting a file inside a compressed or multi-gigabyte image only keeps
+/// the pages it touched; the rest is read from `base` when the result is
+/// copied out with [`copy_to`].
+pub struct Overlay<D> {
+    base: D,
+    pages: BTreeMap<u64, Vec<u8>>,
+}
+
+impl<D: BlockDevice> Overlay<D> {
+    /// Granularity of the copies taken of `base`
+    const PAGE_SIZE: u64 = 4096;
+
+    pub fn new(base: D) -> Self {
+        Self {
+            base,
+            pages: BTreeMap::new(),
+        }
+    }
+
+    /// Bytes of `page` within the device
+    fn page_range(&self, page: u64) -> Range<u64> {
+        let start = page * Self::PAGE_SIZE;
+        start..(start + Self::PAGE_SIZE).min(self.base.size())
+    }
+
+    /// Call `visit` with each page overlapping `len` bytes at `offset`, the
+    /// range of those bytes within the page, and where they start in the
+    /// caller's buffer
+    fn for_each_page(
+        &self,
+        offset: u64,
+        len: usize,
+        mut visit: impl FnMut(u64, Range<usize>, usize) -> Result<(), String>,
+    ) -> Result<(), String> {
+        if offset.saturating_add(len as u64) > self.base.size() {
+            return Err(format!(
+                "Access of {} bytes at {} out of bounds",
+                len, offset
+            ));
+        }
+        let mut done = 0;
+        while done < len {
+            let position = offset + done as u64;
+            let page = position / Self::PAGE_SIZE;
+            let start = (position % Self::PAGE_SIZE) as usize;
+            let n = (Self::PAGE_SIZE as usize - start).min(len - done);
+            visit(page, start..start + n, done)?;
+            done += n;
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for Overlay<D> {
+    fn size(&self) -> u64 {
+        self.base.size()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        self.for_each_page(offset, buf.len(), |page, range, at| {
+            let out = &mut buf[at..at + range.len()];
+            match self.pages.get(&page) {
+                Some(data) => out.copy_from_slice(&data[range]),
+                None => self
+                    .base
+                    .read_at(page * Self::PAGE_SIZE + range.start as u64, out)?,
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<D: BlockDevice> WritableDevice for Overlay<D> {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<(), String> {
+        let mut touched = Vec::new();
+        self.for_each_page(offset, data.len(), |page, range, at| {
+            touched.push((page, range, at));
+            Ok(())
+        })?;
+        for (page, range, at) in touched {
+            if !self.pages.contains_key(&page) {
+                let bytes = self.page_range(page);
+                let mut copy = vec![0u8; (bytes.end - bytes.start) as usize];
+                self.base.read_at(bytes.start, &mut copy)?;
+                self.pages.insert(page, copy);
+            }
+            let copy = self.pages.get_mut(&page).expect("page was just copied");
+            copy[range.clone()].copy_from_slice(&data[at..at + range.len()]);
+        }
+        Ok(())
+    }
+}
+
+/// A file on disk, read on demand
+pub struct FileDevice {
+    file: RefCell<File>,
+    size: u64,
+}
+
+impl FileDevice {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let size = file
+            .metadata()
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .len();
+        Ok(Self {
+            file: RefCell::new(file),
+            size,
+        })
+    }
+}
+
+impl BlockDevice for FileDevice {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        if offset.saturating_add(buf.len() as u64) > self.size {
+            return Err(format!(
+                "Read of {} bytes at {} out of bounds",
+                buf.len(),
+                offset
+            ));
+        }
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(buf))
+            .map_err(|e| format!("Failed to read image: {}", e))
+    }
+}
+
+/// Stream the bytes of `range` to `out`, a megabyte at a time
+pub fn copy_to(
+    device: &dyn BlockDevice,
+    range: Range<u64>,
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    const CHUNK: u64 = 1 << 20;
+
+    let mut chunk = vec![0u8; CHUNK.min(range.end.saturating_sub(range.start)) as usize];
+    let mut position = range.start;
+    while position < range.end {
+        let n = CHUNK.min(range.end - position) as usize;
+        device.read_at(position, &mut chunk[..n])?;
+        out.write_all(&chunk[..n])
+            .map_err(|e| format!("Failed to write image: {}", e))?;
+        position += n as u64;
+    }
+    Ok(())
+}
+
+/// Read a whole device into memory
+pub fn read_all(device: &dyn BlockDevice) -> Result<Vec<u8>, String> {
+    const CHUNK: usize = 1 << 20;
+
+    let too_large = || format!("Image too large to load ({} bytes)", device.size());
+    let size = usize::try_from(device.size()).map_err(|_| too_large())?;
+    let mut data = Vec::new();
+    data.try_reserve_exact(size).map_err(|_| too_large())?;
+    // Fill as we go, so a corrupt image fails before the whole size is
+    // touched
+    let mut chunk = vec![0u8; CHUNK.min(size)];
+    while data.len() < size {
+        let n = CHUNK.min(size - data.len());
+        device.read_at(data.len() as u64, &mut chunk[..n])?;
+        data.extend_from_slice(&chunk[..n]);
+    }
+    Ok(data)
+}
+
+/// Cache of the most recently decoded block of a compressed image
+///
+/// Filesystem reads go sector by sector, so consecutive reads nearly always
+/// hit the same block.
+pub(super) struct BlockCache {
+    block: RefCell<Option<(u64, Vec<u8>)>>,
+}
+
+impl BlockCache {
+    pub(super) fn new() -> Self {
+        Self {
+            block: RefCell::new(None),
+        }
+    }
+
+    /// Copy `buf.len()` bytes at `offset` within block `index`, decoding it
+    /// with `load` unless it is cached
+    pub(super) fn read(
+        &self,
+        index: u64,
+        offset: usize,
+        buf: &mut [u8],
+        load: impl FnOnce(u64) -> Result<Vec<u8>, String>,
+    ) -> Result<(), String> {
+        let mut cached = self.block.borrow_mut();
+        if cached.as_ref().is_none_or(|(i, _)| *i != index) {
+            *cached = Some((index, load(index)?));
+        }
+        let (_, block) = cached.as_ref().expect("block was just cached");
+        let bytes = block
+            .get(offset..offset + buf.len())
+            .ok_or_else(|| format!("Block {} is shorter than expected", index))?;
+        buf.copy_from_slice(bytes);
+        Ok(())
+    }
+}
@@ -0,0 +1,306 @@
+//! CD-ROM sector sync pattern, EDC and Reed-Solomon P/Q parity
+//!
+//! Raw 2352-byte data sectors carry an EDC (a CRC32 variant) over their
+//! header and user data, and Mode 1 and Mode 2 Form 1 sectors add P/Q
+//! parity. Patches that change user data leave both stale, which some
+//! emulators and optical drive emulators reject; [`fix_modified_sectors`]
+//! regenerates them for the sectors a patch touched.
+
+use std::fmt;
+
+use super::iso9660::SECTOR_SIZE_RAW;
+
+/// Sync pattern opening every raw data sector
+pub const SYNC: [u8; 12] = [
+    0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00,
+];
+
+/// Offset of the mode byte in a raw sector
+const MODE_OFFSET: usize = 0xF;
+/// Offset of the Mode 2 subheader's submode byte
+const SUBMODE_OFFSET: usize = 0x12;
+/// Submode flag marking a Mode 2 Form 2 sector
+const SUBMODE_FORM2: u8 = 0x20;
+/// Offset of the 172 P parity bytes in a raw sector
+const P_OFFSET: usize = 0x81C;
+/// Offset of the 104 Q parity bytes in a raw sector
+const Q_OFFSET: usize = 0x8C8;
+
+/// GF(2^8) multiply-by-two table and its inverse-of-(x ^ 2x) table
+const fn tables() -> ([u8; 256], [u8; 256]) {
+    let mut f = [0u8; 256];
+    let mut b = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        let j = ((i << 1) ^ if i & 0x80 != 0 { 0x11D } else { 0 }) as u8;
+        f[i] = j;
+        b[i ^ j as usize] = i as u8;
+        i += 1;
+    }
+    (f, b)
+}
+
+const LUT: ([u8; 256], [u8; 256]) = tables();
+
+/// Compute one parity block over `sector[0xC..]` into `sector[dest..]`
+fn compute_block(
+    sector: &mut [u8],
+    major_count: usize,
+    minor_count: usize,
+    major_mult: usize,
+    minor_inc: usize,
+    dest: usize,
+) {
+    let (f_lut, b_lut) = &LUT;
+    let size = major_count * minor_count;
+    for major in 0..major_count {
+        let mut index = (major >> 1) * major_mult + (major & 1);
+        let mut ecc_a = 0u8;
+        let mut ecc_b = 0u8;
+        for _ in 0..minor_count {
+            let byte = sector[0xC + index];
+            index += minor_inc;
+            if index >= size {
+                index -= size;
+            }
+            ecc_a ^= byte;
+            ecc_b ^= byte;
+            ecc_a = f_lut[ecc_a as usize];
+        }
+        ecc_a = b_lut[(f_lut[ecc_a as usize] ^ ecc_b) as usize];
+        sector[dest + major] = ecc_a;
+        sector[dest + major + major_count] = ecc_a ^ ecc_b;
+    }
+}
+
+/// Regenerate the P and Q parity of a raw 2352-byte sector from its header
+/// and user data, exactly as stored (no address zeroing)
+pub fn generate(sector: &mut [u8]) {
+    compute_block(sector, 86, 24, 2, 86, P_OFFSET);
+    compute_block(sector, 52, 43, 86, 88, Q_OFFSET);
+}
+
+/// Regenerate P and Q parity of a Mode 2 Form 1 sector, which is computed
+/// with the header (address and mode) taken as zero
+fn generate_mode2(sector: &mut [u8]) {
+    let mut header = [0u8; 4];
+    header.copy_from_slice(&sector[0xC..0x10]);
+    sector[0xC..0x10].fill(0);
+    generate(sector);
+    sector[0xC..0x10].copy_from_slice(&header);
+}
+
+/// EDC lookup table (CRC32 variant, polynomial 0xD8018001, reflected)
+const fn edc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut edc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            edc = (edc >> 1) ^ if edc & 1 != 0 { 0xD801_8001 } else { 0 };
+            bit += 1;
+        }
+        table[i] = edc;
+        i += 1;
+    }
+    table
+}
+
+const EDC_LUT: [u32; 256] = edc_table();
+
+/// EDC of `data`
+pub fn edc(data: &[u8]) -> u32 {
+    data.iter().fold(0, |edc, &byte| {
+        (edc >> 8) ^ EDC_LUT[((edc ^ u32::from(byte)) & 0xFF) as usize]
+    })
+}
+
+/// Layout of a raw data sector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectorMode {
+    /// 2048 bytes of data, EDC and P/Q parity
+    Mode1,
+    /// XA, 2048 bytes of data, EDC and P/Q parity
+    Mode2Form1,
+    /// XA, 2324 bytes of data and an optional EDC
+    Mode2Form2,
+}
+
+impl SectorMode {
+    /// Mode of a raw sector, or `None` for audio, Mode 0 and anything
+    /// without a sync pattern
+    pub fn detect(sector: &[u8]) -> Option<Self> {
+        if sector.len() < SECTOR_SIZE_RAW || sector[..SYNC.len()] != SYNC {
+            return None;
+        }
+        match sector[MODE_OFFSET] {
+            1 => Some(Self::Mode1),
+            2 if sector[SUBMODE_OFFSET] & SUBMODE_FORM2 != 0 => Some(Self::Mode2Form2),
+            2 => Some(Self::Mode2Form1),
+            _ => None,
+        }
+    }
+
+    /// Bytes covered by the EDC and where it is stored
+    fn edc_range(self) -> (std::ops::Range<usize>, usize) {
+        match self {
+            Self::Mode1 => (0..0x810, 0x810),
+            Self::Mode2Form1 => (0x10..0x818, 0x818),
+            Self::Mode2Form2 => (0x10..0x92C, 0x92C),
+        }
+    }
+}
+
+/// Regenerate the EDC and P/Q parity of a raw sector in place
+///
+/// Form 2 sectors only get an EDC, and only if they had one: a zero EDC
+/// means the disc does not use it. Returns the sector's mode, or `None` if
+/// it is not a data sector.
+pub fn regenerate(sector: &mut [u8]) -> Option<SectorMode> {
+    let mode = SectorMode::detect(sector)?;
+    let (covered, at) = mode.edc_range();
+    if mode == SectorMode::Mode2Form2 && sector[at..at + 4] == [0; 4] {
+        return Some(mode);
+    }
+    let value = edc(&sector[covered]);
+    sector[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    match mode {
+        SectorMode::Mode1 => {
+            // Reserved bytes between EDC and parity
+            sector[0x814..P_OFFSET].fill(0);
+            generate(sector);
+        }
+        SectorMode::Mode2Form1 => generate_mode2(sector),
+        SectorMode::Mode2Form2 => {}
+    }
+    Some(mode)
+}
+
+/// Sectors whose EDC/ECC [`fix_modified_sectors`] regenerated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SectorFixes {
+    /// Data sectors that differ from the original image
+    pub modified: usize,
+    pub mode1: usize,
+    pub mode2_form1: usize,
+    pub mode2_form2: usize,
+}
+
+impl SectorFixes {
+    /// Sectors whose EDC or ECC changed
+    pub fn fixed(&self) -> usize {
+        self.mode1 + self.mode2_form1 + self.mode2_form2
+    }
+}
+
+impl fmt::Display for SectorFixes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fixed {} of {} modified data sectors",
+            self.fixed(),
+            self.modified
+        )?;
+        let parts: Vec<String> = [
+            ("Mode 1", self.mode1),
+            ("Mode 2 Form 1", self.mode2_form1),
+            ("Mode 2 Form 2", self.mode2_form2),
+        ]
+        .iter()
+        .filter(|(_, n)| *n > 0)
+        .map(|(mode, n)| format!("{} {}", n, mode))
+        .collect();
+        if !parts.is_empty() {
+            write!(f, " ({})", parts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `image` is made of raw 2352-byte sectors
+pub fn is_raw_image(image: &[u8]) -> bool {
+    image.len().is_multiple_of(SECTOR_SIZE_RAW)
+        && image
+            .chunks_exact(SECTOR_SIZE_RAW)
+            .take(PROBE_SECTORS)
+            .any(|sector| SectorMode::detect(sector).is_some())
+}
+
+/// Sectors [`is_raw_image`] looks at for a data sector
+const PROBE_SECTORS: usize = 32;
+
+/// Regenerate EDC and ECC of the raw data sectors of `patched` that differ
+/// from `original`
+///
+/// Sectors past the end of `original` count as modified. Unchanged sectors
+/// are left alone, so deliberately bad ECC (copy protection) survives.
+pub fn fix_modified_sectors(original: &[u8], patched: &mut [u8]) -> SectorFixes {
+    let mut fixes = SectorFixes::default();
+    for (index, sector) in patched.chunks_exact_mut(SECTOR_SIZE_RAW).enumerate() {
+        let start = index * SECTOR_SIZE_RAW;
+        if original.get(start..start + SECTOR_SIZE_RAW) == Some(&*sector) {
+            continue;
+        }
+        let before = sector.to_vec();
+        let Some(mode) = regenerate(sector) else {
+            continue;
+        };
+        fixes.modified += 1;
+        if *sector != before[..] {
+            match mode {
+                SectorMode::Mode1 => fixes.mode1 += 1,
+                SectorMode::Mode2Form1 => fixes.mode2_form1 += 1,
+                SectorMode::Mode2Form2 => fixes.mode2_form2 += 1,
+            }
+        }
+    }
+    fixes
+}
+
+/// Number of modified data sectors whose EDC or ECC no longer matches
+pub fn stale_sectors(original: &[u8], patched: &[u8]) -> usize {
+    patched
+        .chunks_exact(SECTOR_SIZE_RAW)
+        .enumerate()
+        .filter(|&(index, sector)| {
+            let start = index * SECTOR_SIZE_RAW;
+            if original.get(start..start + SECTOR_SIZE_RAW) == Some(sector) {
+                return false;
+            }
+            let mut fixed = sector.to_vec();
+            regenerate(&mut fixed).is_some() && fixed != sector
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edc_check_value() {
+        // The EDC of its own little-endian encoding appended is zero, like
+        // any reflected CRC without a final XOR
+        let data = b"123456789";
+        let value = edc(data);
+        let mut with_edc = data.to_vec();
+        with_edc.extend_from_slice(&value.to_le_bytes());
+        assert_eq!(edc(&with_edc), 0);
+    }
+
+    #[test]
+    fn test_mode_detection() {
+        let mut sector = vec![0u8; SECTOR_SIZE_RAW];
+        assert_eq!(SectorMode::detect(&sector), None);
+        sector[..12].copy_from_slice(&SYNC);
+        sector[MODE_OFFSET] = 1;
+        assert_eq!(SectorMode::detect(&sector), Some(SectorMode::Mode1));
+        sector[MODE_OFFSET] = 2;
+        assert_eq!(SectorMode::detect(&sector), Some(SectorMode::Mode2Form1));
+        sector[SUBMODE_OFFSET] = SUBMODE_FORM2;
+        assert_eq!(SectorMode::detect(&sector), Some(SectorMode::Mode2Form2));
+        sector[MODE_OFFSET] = 0;
+        assert_eq!(SectorMode::detect(&sector), None);
+    }
+}
@@ -0,0 +1,523 @@
+//! ISO9660 filesystem
+//!
+//! Walks the directory tree of standard ISOs (2048-byte sectors) and raw CD
+//! images (2352-byte Mode 2 Form 1 sectors), reads files, and replaces a
+//! file in place when the new contents fit the sectors already allocated to
+//! it.
+
+use super::device::{BlockDevice, WritableDevice};
+use super::ecc::{self, SectorMode};
+
+/// Standard ISO sector size
+pub(crate) const SECTOR_SIZE_ISO: usize = 2048;
+/// Raw CD sector size (Mode 2)
+pub(crate) const SECTOR_SIZE_RAW: usize = 2352;
+/// Data offset in raw sector (sync + header + subheader)
+const RAW_DATA_OFFSET: usize = 24;
+/// Primary Volume Descriptor location
+const PVD_SECTOR: usize = 16;
+/// Volume descriptor types
+const DESCRIPTOR_SUPPLEMENTARY: u8 = 2;
+const DESCRIPTOR_TERMINATOR: u8 = 255;
+/// Volume descriptors looked at past the primary one for a Joliet tree
+const MAX_DESCRIPTORS: usize = 16;
+/// Escape sequences marking a Supplementary Volume Descriptor as Joliet
+/// (UCS-2 levels 1 to 3)
+const JOLIET_ESCAPES: [&[u8]; 3] = [b"%/@", b"%/C", b"%/E"];
+/// Root directory record in the Primary Volume Descriptor
+const ROOT_RECORD_OFFSET: usize = 156;
+/// Directory record flag: the entry is a directory
+const FLAG_DIRECTORY: u8 = 0x02;
+/// Directory record flag: the file continues in the next record
+const FLAG_MULTI_EXTENT: u8 = 0x80;
+/// Deepest directory nesting walked, so a looping tree cannot recurse forever
+const MAX_DEPTH: usize = 64;
+
+/// ISO image format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsoFormat {
+    /// Standard ISO (2048-byte sectors)
+    Standard,
+    /// Raw CD image (2352-byte sectors, Mode 2 Form 1)
+    Raw,
+}
+
+impl IsoFormat {
+    /// Byte offset of the user data of a logical sector
+    fn data_offset(self, sector: usize) -> usize {
+        match self {
+            Self::Standard => sector * SECTOR_SIZE_ISO,
+            Self::Raw => sector * SECTOR_SIZE_RAW + RAW_DATA_OFFSET,
+        }
+    }
+
+    /// Bytes of a whole logical sector, headers and parity included
+    fn sector_range(self, sector: usize) -> std::ops::Range<u64> {
+        let size = match self {
+            Self::Standard => SECTOR_SIZE_ISO,
+            Self::Raw => SECTOR_SIZE_RAW,
+        } as u64;
+        sector as u64 * size..(sector as u64 + 1) * size
+    }
+}
+
+/// A file or directory of the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Path from the root, `/`-separated, without the `;1` version suffix
+    pub path: String,
+    pub is_dir: bool,
+    /// First sector of the extent
+    pub lba: u32,
+    /// Size in bytes
+    pub size: u32,
+    flags: u8,
+    /// Sector and byte offset of the directory record describing the entry
+    record: (usize, usize),
+}
+
+impl Entry {
+    /// Last component of the path
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Bytes allocated to the entry, its size rounded up to whole sectors
+    pub fn allocated(&self) -> usize {
+        (self.size as usize).div_ceil(SECTOR_SIZE_ISO) * SECTOR_SIZE_ISO
+    }
+
+    fn sectors(&self) -> usize {
+        (self.size as usize).div_ceil(SECTOR_SIZE_ISO)
+    }
+
+    /// Parse the directory record at the start of `data`
+    ///
+    /// Returns `None` for the `.` and `..` records.
+    fn parse(data: &[u8], parent: &str, record: (usize, usize)) -> Option<Self> {
+        let name_len = *data.get(32)? as usize;
+        let raw_name = data.get(33..33 + name_len)?;
+        if name_len == 0 || raw_name == [0] || raw_name == [1] {
+            return None;
+        }
+        let flags = data[25];
+        let is_dir = flags & FLAG_DIRECTORY != 0;
+        let name = normalize(&String::from_utf8_lossy(raw_name), is_dir);
+        Some(Self {
+            path: if parent.is_empty() {
+                name
+            } else {
+                format!("{}/{}", parent, name)
+            },
+            is_dir,
+            lba: u32::from_le_bytes(data[2..6].try_into().unwrap()),
+            size: u32::from_le_bytes(data[10..14].try_into().unwrap()),
+            flags,
+            record,
+        })
+    }
+}
+
+/// Strip the version suffix, and the dot of file names without extension
+fn normalize(name: &str, is_dir: bool) -> String {
+    let name = name.split(';').next().unwrap_or(name);
+    match name.strip_suffix('.') {
+        Some(stem) if !is_dir => stem.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// ISO9660 filesystem reader
+pub struct Iso9660Reader<'a> {
+    device: &'a dyn BlockDevice,
+    format: IsoFormat,
+}
+
+impl<'a> Iso9660Reader<'a> {
+    /// Create a new ISO9660 reader, auto-detecting format
+    pub fn new(device: &'a dyn BlockDevice) -> Result<Self, String> {
+        let format = Self::detect_format(device)?;
+        Ok(Self { device, format })
+    }
+
+    /// Sector layout of the image
+    pub fn format(&self) -> IsoFormat {
+        self.format
+    }
+
+    /// Detect ISO format by checking for CD001 signature
+    fn detect_format(device: &dyn BlockDevice) -> Result<IsoFormat, String> {
+        let has_signature = |offset: usize| {
+            let mut id = [0u8; 6];
+            (device.size() > (offset + id.len()) as u64)
+                && device.read_at(offset as u64, &mut id).is_ok()
+                && &id[1..6] == b"CD001"
+        };
+
+        // Try standard ISO first (sector 16 at 16 * 2048)
+        if has_signature(IsoFormat::Standard.data_offset(PVD_SECTOR)) {
+            return Ok(IsoFormat::Standard);
+        }
+
+        // Try raw CD image (sector 16 at 16 * 2352 + 24)
+        if has_signature(IsoFormat::Raw.data_offset(PVD_SECTOR)) {
+            return Ok(IsoFormat::Raw);
+        }
+
+        Err("Cannot detect ISO format: CD001 signature not found".to_string())
+    }
+
+    /// Read a single sector
+    fn read_sector(&self, sector: usize) -> Result<Vec<u8>, String> {
+        let mut data = vec![0u8; SECTOR_SIZE_ISO];
+        self.device
+            .read_at(self.format.data_offset(sector) as u64, &mut data)
+            .map_err(|_| format!("Sector {} out of bounds", sector))?;
+        Ok(data)
+    }
+
+    /// Read multiple consecutive sectors
+    fn read_sectors(&self, start_sector: usize, count: usize) -> Result<Vec<u8>, String> {
+        let mut data = Vec::with_capacity(count.min(1 << 16) * SECTOR_SIZE_ISO);
+        for i in 0..count {
+            data.extend_from_slice(&self.read_sector(start_sector + i)?);
+        }
+        Ok(data)
+    }
+
+    /// The root directory
+    pub fn root(&self) -> Result<Entry, String> {
+        let pvd = self.read_sector(PVD_SECTOR)?;
+        if pvd[0] != 1 || &pvd[1..6] != b"CD001" {
+            return Err("Invalid Primary Volume Descriptor".to_string());
+        }
+        Ok(root_entry(&pvd, PVD_SECTOR))
+    }
+
+    /// The root directory of the Joliet tree, when the disc has one
+    ///
+    /// Joliet records carry UCS-2 names, so the paths of entries read from
+    /// it are not meaningful; they point at the same extents as the primary
+    /// tree.
+    pub fn joliet_root(&self) -> Result<Option<Entry>, String> {
+        for sector in PVD_SECTOR + 1..PVD_SECTOR + 1 + MAX_DESCRIPTORS {
+            let Ok(descriptor) = self.read_sector(sector) else {
+                break;
+            };
+            if &descriptor[1..6] != b"CD001" || descriptor[0] == DESCRIPTOR_TERMINATOR {
+                break;
+            }
+            if descriptor[0] == DESCRIPTOR_SUPPLEMENTARY
+                && JOLIET_ESCAPES.contains(&&descriptor[88..91])
+            {
+                return Ok(Some(root_entry(&descriptor, sector)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Entries of a directory, without `.` and `..`
+    pub fn read_dir(&self, dir: &Entry) -> Result<Vec<Entry>, String> {
+        if !dir.is_dir {
+            return Err(format!("Not a directory: {}", dir.path));
+        }
+        let dir_data = self.read_sectors(dir.lba as usize, dir.sectors())?;
+
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset < dir_data.len() {
+            let record_len = dir_data[offset] as usize;
+            if record_len == 0 {
+                // Records never cross sectors; the rest of this one is padding
+                offset = (offset / SECTOR_SIZE_ISO + 1) * SECTOR_SIZE_ISO;
+                continue;
+            }
+            if offset + record_len > dir_data.len() {
+                break;
+            }
+            let location = (
+                dir.lba as usize + offset / SECTOR_SIZE_ISO,
+                offset % SECTOR_SIZE_ISO,
+            );
+            if let Some(entry) =
+                Entry::parse(&dir_data[offset..offset + record_len], &dir.path, location)
+            {
+                entries.push(entry);
+            }
+            offset += record_len;
+        }
+        Ok(entries)
+    }
+
+    /// Every file and directory of the disc, each directory followed by its
+    /// contents
+    pub fn list(&self) -> Result<Vec<Entry>, String> {
+        self.list_from(&self.root()?)
+    }
+
+    /// Every file and directory below `root`
+    fn list_from(&self, root: &Entry) -> Result<Vec<Entry>, String> {
+        let mut entries = Vec::new();
+        self.walk(root, 0, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn walk(&self, dir: &Entry, depth: usize, entries: &mut Vec<Entry>) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("Directory tree too deep at {}", dir.path));
+        }
+        for entry in self.read_dir(dir)? {
+            let is_dir = entry.is_dir;
+            entries.push(entry);
+            if is_dir {
+                let dir = entries.last().unwrap().clone();
+                self.walk(&dir, depth + 1, entries)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up a file or directory by path (e.g., "PSP_GAME/SYSDIR/EBOOT.BIN")
+    ///
+    /// Components are matched case-insensitively, with or without their
+    /// `;1` version suffix, and may be separated by `/` or `\`.
+    pub fn find(&self, path: &str) -> Result<Entry, String> {
+        let parts: Vec<&str> = path.split(['/', '\\']).filter(|s| !s.is_empty()).collect();
+        if parts.is_empty() {
+            return Err("Empty path".to_string());
+        }
+
+        let mut current = self.root()?;
+        for (i, part) in parts.iter().enumerate() {
+            let last = i == parts.len() - 1;
+            let found = self.read_dir(&current)?.into_iter().find(|entry| {
+                (last || entry.is_dir)
+                    && entry
+                        .name()
+                        .eq_ignore_ascii_case(&normalize(part, entry.is_dir))
+            });
+            current = match found {
+                Some(entry) => entry,
+                None if last => return Err(format!("File not found: {}", path)),
+                None => return Err(format!("Directory not found: {}", part)),
+            };
+        }
+        Ok(current)
+    }
+
+    /// Read the contents of a file
+    pub fn read(&self, entry: &Entry) -> Result<Vec<u8>, String> {
+        check_plain_file(entry)?;
+        let mut data = self.read_sectors(entry.lba as usize, entry.sectors())?;
+        data.truncate(entry.size as usize);
+        Ok(data)
+    }
+
+    /// Read a file from the root directory
+    pub fn read_file(&self, filename: &str) -> Result<Vec<u8>, String> {
+        self.read_file_path(filename)
+    }
+
+    /// Read a file from a subdirectory path (e.g., "PSP_GAME/SYSDIR/EBOOT.BIN")
+    pub fn read_file_path(&self, path: &str) -> Result<Vec<u8>, String> {
+        self.read(&self.find(path)?)
+    }
+}
+
+/// The root directory record of a volume descriptor read from `sector`
+fn root_entry(descriptor: &[u8], sector: usize) -> Entry {
+    let record = &descriptor[ROOT_RECORD_OFFSET..];
+    Entry {
+        path: String::new(),
+        is_dir: true,
+        lba: u32::from_le_bytes(record[2..6].try_into().unwrap()),
+        size: u32::from_le_bytes(record[10..14].try_into().unwrap()),
+        flags: FLAG_DIRECTORY,
+        record: (sector, ROOT_RECORD_OFFSET),
+    }
+}
+
+/// Files the reader and [`replace_file`] handle: one extent, not a directory
+fn check_plain_file(entry: &Entry) -> Result<(), String> {
+    if entry.is_dir {
+        return Err(format!("{} is a directory", entry.path));
+    }
+    if entry.flags & FLAG_MULTI_EXTENT != 0 {
+        return Err(format!(
+            "{} is stored in several extents, which is not supported",
+            entry.path
+        ));
+    }
+    Ok(())
+}
+
+/// A file replaced by [`replace_file`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub path: String,
+    pub old_size: u32,
+    pub new_size: u32,
+    /// Bytes allocated to the file, the most it can hold
+    pub allocated: usize,
+    /// Sectors rewritten, including those holding its directory records
+    pub sectors: usize,
+}
+
+/// Replace the contents of a file inside an uncompressed disc image
+///
+/// The new contents must fit the sectors already allocated to the file, so
+/// nothing else on the disc moves. Only those sectors and the ones holding
+/// the file's directory records are rewritten: the slack after the new
+/// contents is zeroed and the size updated in the primary record and, on
+/// discs with a Joliet tree, in the Joliet record. Sectors of raw images get
+/// their EDC/ECC regenerated. UDF descriptors of UDF-bridge discs are not
+/// touched; consoles that read the ISO9660 tree are unaffected.
+pub fn replace_file<D: WritableDevice>(
+    device: &mut D,
+    path: &str,
+    data: &[u8],
+) -> Result<Replacement, String> {
+    let (format, entry, records) = {
+        let reader = Iso9660Reader::new(&*device)?;
+        let entry = reader.find(path)?;
+        check_plain_file(&entry)?;
+        let mut records = vec![entry.record];
+        if let Some(root) = reader.joliet_root()? {
+            let joliet = reader.list_from(&root)?;
+            records.extend(
+                joliet
+                    .iter()
+                    .filter(|other| !other.is_dir && other.lba == entry.lba)
+                    .map(|other| other.record)
+                    .filter(|record| *record != entry.record),
+            );
+        }
+        (reader.format(), entry, records)
+    };
+
+    let allocated = entry.allocated();
+    if data.len() > allocated {
+        return Err(format!(
+            "{} bytes do not fit {}: {} bytes are allocated to it ({} used)",
+            data.len(),
+            entry.path,
+            allocated,
+            entry.size
+        ));
+    }
+    let sectors = entry.lba as usize..entry.lba as usize + entry.sectors();
+    if sectors
+        .clone()
+        .any(|sector| format.sector_range(sector).end > device.size())
+    {
+        return Err(format!("{} extends past the end of the image", entry.path));
+    }
+    if format == IsoFormat::Raw {
+        let mut raw = vec![0u8; SECTOR_SIZE_RAW];
+        for sector in sectors.clone() {
+            device.read_at(format.sector_range(sector).start, &mut raw)?;
+            if SectorMode::detect(&raw) != Some(SectorMode::Mode2Form1) {
+                return Err(format!(
+                    "{} is not stored in Mode 2 Form 1 sectors, which is not supported",
+                    entry.path
+                ));
+            }
+        }
+    }
+
+    for (chunk, sector) in data
+        .chunks(SECTOR_SIZE_ISO)
+        .chain(std::iter::repeat(&[][..]))
+        .zip(sectors.clone())
+    {
+        rewrite_sector(device, format, sector, |user_data| {
+            user_data[..chunk.len()].copy_from_slice(chunk);
+            user_data[chunk.len()..].fill(0);
+        })?;
+    }
+
+    let size = data.len() as u32;
+    let mut record_sectors: Vec<usize> = records.iter().map(|(sector, _)| *sector).collect();
+    record_sectors.sort_unstable();
+    record_sectors.dedup();
+    for &sector in &record_sectors {
+        rewrite_sector(device, format, sector, |user_data| {
+            for &(_, offset) in records.iter().filter(|(s, _)| *s == sector) {
+                user_data[offset + 10..offset + 14].copy_from_slice(&size.to_le_bytes());
+                user_data[offset + 14..offset + 18].copy_from_slice(&size.to_be_bytes());
+            }
+        })?;
+    }
+
+    Ok(Replacement {
+        path: entry.path,
+        old_size: entry.size,
+        new_size: size,
+        allocated,
+        sectors: sectors.len() + record_sectors.len(),
+    })
+}
+
+/// Edit the user data of `sector` and write it back, regenerating the
+/// EDC/ECC of raw images
+fn rewrite_sector<D: WritableDevice>(
+    device: &mut D,
+    format: IsoFormat,
+    sector: usize,
+    edit: impl FnOnce(&mut [u8]),
+) -> Result<(), String> {
+    let range = format.sector_range(sector);
+    let mut bytes = vec![0u8; (range.end - range.start) as usize];
+    device.read_at(range.start, &mut bytes)?;
+    let user_data = format.data_offset(0);
+    edit(&mut bytes[user_data..user_data + SECTOR_SIZE_ISO]);
+    if format == IsoFormat::Raw {
+        ecc::regenerate(&mut bytes);
+    }
+    device.write_at(range.start, &bytes)
+}
+
+/// Check if an ISO file is a PSP game (has PSP_GAME directory)
+pub fn is_psp_iso(data: &[u8]) -> bool {
+    let Ok(iso) = Iso9660Reader::new(&data) else {
+        return false;
+    };
+    // Try to find PSP_GAME directory
+    iso.read_file_path("PSP_GAME/PARAM.SFO").is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iso_format_detection() {
+        // Create minimal standard ISO PVD at sector 16
+        let mut iso_data = vec![0u8; 17 * 2048 + 10];
+        let pvd_offset = 16 * 2048;
+        iso_data[pvd_offset] = 1; // Type
+        iso_data[pvd_offset + 1..pvd_offset + 6].copy_from_slice(b"CD001");
+
+        let format = Iso9660Reader::detect_format(&iso_data).unwrap();
+        assert_eq!(format, IsoFormat::Standard);
+    }
+
+    #[test]
+    fn test_raw_format_detection() {
+        // Create minimal raw CD PVD at sector 16
+        let mut raw_data = vec![0u8; 17 * 2352 + 30];
+        let pvd_offset = 16 * 2352 + 24; // Raw sector + data offset
+        raw_data[pvd_offset] = 1;
+        raw_data[pvd_offset + 1..pvd_offset + 6].copy_from_slice(b"CD001");
+
+        let format = Iso9660Reader::detect_format(&raw_data).unwrap();
+        assert_eq!(format, IsoFormat::Raw);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("SYSTEM.CNF;1", false), "SYSTEM.CNF");
+        assert_eq!(normalize("README.;1", false), "README");
+        assert_eq!(normalize("DATA", true), "DATA");
+    }
+}
@@ -0,0 +1,166 @@
+//! Disc images
+//!
+//! Standard ISOs (2048-byte sectors) and raw CD images (2352-byte sectors)
+//! are read through a [`BlockDevice`], so CSO, ZSO and CHD images are decoded
+//! block by block as the filesystem is walked, and CUE sheets are read across
+//! all of their BIN files. [`iso9660`] lists, extracts and replaces files
+//! inside the disc.
+
+mod chd;
+mod ciso;
+mod cue;
+mod device;
+pub mod ecc;
+pub mod iso9660;
+
+use std::path::Path;
+
+pub use chd::ChdDevice;
+pub use ciso::CisoDevice;
+pub use cue::{CueDevice, CueFile, CueSheet, CueTrack, TrackSpan};
+pub use device::{BlockDevice, FileDevice, Overlay, WritableDevice, copy_to, read_all};
+pub use iso9660::{Entry, Iso9660Reader, IsoFormat, Replacement, is_psp_iso, replace_file};
+
+use iso9660::SECTOR_SIZE_ISO;
+
+/// Container of a disc image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Uncompressed ISO or BIN
+    Plain,
+    /// CISO, deflate-compressed blocks
+    Cso,
+    /// ZISO, LZ4-compressed blocks
+    Zso,
+    /// MAME CHD v5
+    Chd,
+}
+
+impl ImageFormat {
+    /// Detect a compressed image from its magic bytes
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(b"CISO") {
+            Self::Cso
+        } else if data.starts_with(b"ZISO") {
+            Self::Zso
+        } else if data.starts_with(b"MComprHD") {
+            Self::Chd
+        } else {
+            Self::Plain
+        }
+    }
+
+    /// Human-readable format name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Cso => "CSO",
+            Self::Zso => "ZSO",
+            Self::Chd => "CHD",
+        }
+    }
+}
+
+/// A disc image, decompressed on the fly
+pub struct DiscImage {
+    pub format: ImageFormat,
+    /// Extension of the uncompressed image: `bin` for raw CD sectors,
+    /// otherwise `iso`
+    pub extension: &'static str,
+    device: Box<dyn BlockDevice>,
+}
+
+impl DiscImage {
+    /// Open the image stored in `inner`, detecting its container
+    pub fn open(inner: Box<dyn BlockDevice>) -> Result<Self, String> {
+        let mut magic = [0u8; 8];
+        let len = inner.size().min(magic.len() as u64) as usize;
+        inner.read_at(0, &mut magic[..len])?;
+
+        let format = ImageFormat::detect(&magic[..len]);
+        let (device, extension): (Box<dyn BlockDevice>, _) = match format {
+            ImageFormat::Plain => (inner, "iso"),
+            ImageFormat::Cso | ImageFormat::Zso => (Box::new(CisoDevice::open(inner)?), "iso"),
+            ImageFormat::Chd => {
+                let chd = ChdDevice::open(inner)?;
+                let extension = match chd.first_track_sector_size() {
+                    Some(size) if size != SECTOR_SIZE_ISO => "bin",
+                    _ => "iso",
+                };
+                (Box::new(chd), extension)
+            }
+        };
+        Ok(Self {
+            format,
+            extension,
+            device,
+        })
+    }
+
+    /// Open an image file
+    ///
+    /// A `.cue` sheet opens the disc formed by all of its BIN files.
+    pub fn open_path(path: &Path) -> Result<Self, String> {
+        let is_cue = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cue"));
+        if !is_cue {
+            return Self::open(Box::new(FileDevice::open(path)?));
+        }
+
+        let sheet = CueSheet::load(path)?;
+        let device = CueDevice::open(&sheet, path.parent().unwrap_or(Path::new(".")))?;
+        let extension = match sheet.tracks[0].sector_size() {
+            SECTOR_SIZE_ISO => "iso",
+            _ => "bin",
+        };
+        Ok(Self {
+            format: ImageFormat::Plain,
+            extension,
+            device: Box::new(device),
+        })
+    }
+}
+
+impl BlockDevice for DiscImage {
+    fn size(&self) -> u64 {
+        self.device.size()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), String> {
+        self.device.read_at(offset, buf)
+    }
+}
+
+/// Check that `path` is a disc image the hashers can open
+///
+/// CUE sheets are returned as is: [`DiscImage::open_path`] reads the disc
+/// across all of their files.
+pub fn resolve_image_path(path: &Path) -> Result<std::path::PathBuf, String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "bin" | "iso" | "cso" | "zso" | "chd" | "cue" => Ok(path.to_path_buf()),
+        _ => Err(format!("Unsupported file type: {}", ext)),
+    }
+}
+
+/// Inflate a raw Deflate stream (CSO blocks, CHD hunks) of at most `limit`
+/// bytes
+fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut out = Vec::with_capacity(limit);
+    flate2::read::DeflateDecoder::new(data)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| format!("Corrupt deflate stream: {}", e))?;
+    if out.len() > limit {
+        return Err(format!("Deflate stream expands beyond {} bytes", limit));
+    }
+    Ok(out)
+}
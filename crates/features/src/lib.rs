@@ -4,6 +4,12 @@
 //! functionality:
 //! - Validation: Checksum verification and integrity checks
 //! - RetroAchievements: Hash checking against RetroAchievements database
+//! - Checksum fix: Repair internal cartridge header checksums
+//! - ROM info: Identify console, title and region from internal headers
+//! - DAT: Name ROMs from No-Intro / Redump DAT files
+//! - Archive: Read ROMs and patches from ZIP, 7z and gzip archives
+//! - Disc: Read CSO, CHD and CUE disc images; list, extract and replace
+//!   files in their ISO9660 filesystem
 
 #[cfg(feature = "validation")]
 pub mod validation;
@@ -11,4 +17,19 @@ pub mod validation;
 #[cfg(feature = "retroachievements")]
 pub mod retroachievements;
 
+#[cfg(feature = "checksum_fix")]
+pub mod checksum_fix;
+
+#[cfg(feature = "rom_info")]
+pub mod rom_info;
+
+#[cfg(feature = "dat")]
+pub mod dat;
+
+#[cfg(feature = "archive")]
+pub mod archive;
+
+#[cfg(feature = "disc")]
+pub mod disc;
+
 pub use stitchr_core::*;
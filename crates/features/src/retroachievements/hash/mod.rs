@@ -2,7 +2,6 @@
 //!
 //! Each console has specific hashing requirements per RA documentation.
 
-mod iso9660;
 mod n64;
 mod nds;
 mod nes;
@@ -11,7 +10,7 @@ mod psp;
 mod psx;
 mod snes;
 
-pub use iso9660::is_psp_iso;
+pub use crate::disc::is_psp_iso;
 pub use n64::compute_n64_hash;
 pub use nds::compute_nds_hash;
 pub use nes::compute_nes_hash;
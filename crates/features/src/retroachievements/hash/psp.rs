@@ -8,15 +8,14 @@
 
 use std::path::Path;
 
-use super::iso9660::{Iso9660Reader, resolve_image_path};
+use crate::disc::{DiscImage, Iso9660Reader, resolve_image_path};
 
 /// Compute RetroAchievements hash for a PSP disc image
 pub fn compute_psp_hash(path: &Path) -> Result<String, String> {
     let image_path = resolve_image_path(path)?;
-    let image_data =
-        std::fs::read(&image_path).map_err(|e| format!("Failed to read image: {}", e))?;
+    let image = DiscImage::open_path(&image_path)?;
 
-    let iso = Iso9660Reader::new(&image_data)?;
+    let iso = Iso9660Reader::new(&image)?;
 
     // Read PARAM.SFO
     let param_sfo = iso.read_file_path("PSP_GAME/PARAM.SFO")?;
@@ -9,30 +9,22 @@
 
 use std::path::Path;
 
-/// PSX CD sector size (Mode 2 Form 1)
-const SECTOR_SIZE_RAW: usize = 2352;
-/// Data offset within raw sector (sync + header + subheader)
-const DATA_OFFSET: usize = 24;
-/// User data size per sector
-const DATA_SIZE: usize = 2048;
-/// Primary Volume Descriptor location
-const PVD_SECTOR: usize = 16;
+use crate::disc::{DiscImage, Iso9660Reader, resolve_image_path};
 
 /// Compute RetroAchievements hash for a PSX disc image
 ///
-/// Accepts either a .cue file path (will find associated .bin) or direct .bin
-/// path
+/// Accepts a .cue sheet (read across all of its BIN files), a .bin path, or
+/// a compressed CHD image
 pub fn compute_psx_hash(path: &Path) -> Result<String, String> {
-    // Get the BIN file path
-    let bin_path = resolve_bin_path(path)?;
+    let image_path = resolve_image_path(path)?;
+    let image = DiscImage::open_path(&image_path)?;
 
-    // Read the entire BIN file
-    let bin_data =
-        std::fs::read(&bin_path).map_err(|e| format!("Failed to read BIN file: {}", e))?;
+    let iso = Iso9660Reader::new(&image)?;
 
     // Read SYSTEM.CNF
-    let system_cnf = read_file_from_iso(&bin_data, "SYSTEM.CNF")
-        .or_else(|_| read_file_from_iso(&bin_data, "SYSTEM.CNF;1"))?;
+    let system_cnf = iso
+        .read_file("SYSTEM.CNF")
+        .or_else(|_| iso.read_file("SYSTEM.CNF;1"))?;
 
     // Parse BOOT= line
     let boot_path = parse_boot_path(&system_cnf)?;
@@ -42,8 +34,9 @@ pub fn compute_psx_hash(path: &Path) -> Result<String, String> {
     let exe_name = extract_exe_name(&boot_path)?;
 
     // Read the executable
-    let exe_data = read_file_from_iso(&bin_data, &exe_name)
-        .or_else(|_| read_file_from_iso(&bin_data, &format!("{};1", exe_name)))?;
+    let exe_data = iso
+        .read_file(&exe_name)
+        .or_else(|_| iso.read_file(&format!("{};1", exe_name)))?;
 
     // Build hash buffer: exe_name + exe_contents
     let mut buffer = Vec::with_capacity(exe_name.len() + exe_data.len());
@@ -55,145 +48,6 @@ pub fn compute_psx_hash(path: &Path) -> Result<String, String> {
     Ok(format!("{:x}", digest))
 }
 
-/// Resolve .cue to .bin path, or return .bin path directly
-fn resolve_bin_path(path: &Path) -> Result<std::path::PathBuf, String> {
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .unwrap_or_default();
-
-    if ext == "bin" {
-        return Ok(path.to_path_buf());
-    }
-
-    if ext == "cue" {
-        // Parse CUE to find BIN file
-        let cue_content =
-            std::fs::read_to_string(path).map_err(|e| format!("Failed to read CUE file: {}", e))?;
-
-        // Find FILE "xxx.bin" line
-        for line in cue_content.lines() {
-            let line = line.trim();
-            if line.to_uppercase().starts_with("FILE") {
-                // Extract filename between quotes
-                if let Some(start) = line.find('"')
-                    && let Some(end) = line[start + 1..].find('"')
-                {
-                    let bin_name = &line[start + 1..start + 1 + end];
-                    let bin_path = path.parent().unwrap_or(Path::new(".")).join(bin_name);
-                    return Ok(bin_path);
-                }
-            }
-        }
-        return Err("No BIN file found in CUE".to_string());
-    }
-
-    Err(format!("Unsupported file type: {}", ext))
-}
-
-/// Read a file from ISO9660 filesystem in a raw BIN image
-fn read_file_from_iso(bin_data: &[u8], filename: &str) -> Result<Vec<u8>, String> {
-    // Read Primary Volume Descriptor (sector 16)
-    let pvd = read_sector(bin_data, PVD_SECTOR)?;
-
-    // Verify this is a PVD (type 1, "CD001")
-    if pvd[0] != 1 || &pvd[1..6] != b"CD001" {
-        return Err("Invalid Primary Volume Descriptor".to_string());
-    }
-
-    // Root directory record is at offset 156, 34 bytes
-    let root_dir_record = &pvd[156..190];
-    let root_lba = u32::from_le_bytes([
-        root_dir_record[2],
-        root_dir_record[3],
-        root_dir_record[4],
-        root_dir_record[5],
-    ]) as usize;
-    let root_size = u32::from_le_bytes([
-        root_dir_record[10],
-        root_dir_record[11],
-        root_dir_record[12],
-        root_dir_record[13],
-    ]) as usize;
-
-    // Read root directory
-    let root_dir = read_sectors(bin_data, root_lba, root_size.div_ceil(DATA_SIZE))?;
-
-    // Search for file in directory
-    let filename_upper = filename.to_uppercase();
-    let filename_no_version = filename_upper.trim_end_matches(";1");
-
-    let mut offset = 0;
-    while offset < root_dir.len() {
-        let record_len = root_dir[offset] as usize;
-        if record_len == 0 {
-            // Move to next sector boundary
-            let next_sector = ((offset / DATA_SIZE) + 1) * DATA_SIZE;
-            if next_sector >= root_dir.len() {
-                break;
-            }
-            offset = next_sector;
-            continue;
-        }
-
-        if offset + record_len > root_dir.len() {
-            break;
-        }
-
-        let name_len = root_dir[offset + 32] as usize;
-        if name_len > 0 && offset + 33 + name_len <= root_dir.len() {
-            let name_bytes = &root_dir[offset + 33..offset + 33 + name_len];
-            let name = String::from_utf8_lossy(name_bytes).to_uppercase();
-            let name_no_version = name.trim_end_matches(";1");
-
-            if name_no_version == filename_no_version || name == filename_upper {
-                // Found it! Get LBA and size
-                let file_lba = u32::from_le_bytes([
-                    root_dir[offset + 2],
-                    root_dir[offset + 3],
-                    root_dir[offset + 4],
-                    root_dir[offset + 5],
-                ]) as usize;
-                let file_size = u32::from_le_bytes([
-                    root_dir[offset + 10],
-                    root_dir[offset + 11],
-                    root_dir[offset + 12],
-                    root_dir[offset + 13],
-                ]) as usize;
-
-                // Read file data
-                let sectors_needed = file_size.div_ceil(DATA_SIZE);
-                let mut data = read_sectors(bin_data, file_lba, sectors_needed)?;
-                data.truncate(file_size);
-                return Ok(data);
-            }
-        }
-
-        offset += record_len;
-    }
-
-    Err(format!("File not found: {}", filename))
-}
-
-/// Read a single sector from raw BIN data
-fn read_sector(bin_data: &[u8], sector: usize) -> Result<Vec<u8>, String> {
-    let offset = sector * SECTOR_SIZE_RAW + DATA_OFFSET;
-    if offset + DATA_SIZE > bin_data.len() {
-        return Err(format!("Sector {} out of bounds", sector));
-    }
-    Ok(bin_data[offset..offset + DATA_SIZE].to_vec())
-}
-
-/// Read multiple consecutive sectors
-fn read_sectors(bin_data: &[u8], start_sector: usize, count: usize) -> Result<Vec<u8>, String> {
-    let mut data = Vec::with_capacity(count * DATA_SIZE);
-    for i in 0..count {
-        data.extend(read_sector(bin_data, start_sector + i)?);
-    }
-    Ok(data)
-}
-
 /// Parse BOOT= line from SYSTEM.CNF content
 fn parse_boot_path(system_cnf: &[u8]) -> Result<String, String> {
     let content = String::from_utf8_lossy(system_cnf);
@@ -4,10 +4,7 @@
 //! skip the first 512 bytes (SMC/SWC copier header).
 //! Otherwise, hash the entire file.
 
-/// SMC/SWC copier header size
-const COPIER_HEADER_SIZE: usize = 512;
-/// SNES ROM block size (8KB)
-const BLOCK_SIZE: usize = 8 * 1024;
+use stitchr_core::snes::{COPIER_HEADER_SIZE, has_copier_header};
 
 /// Compute RetroAchievements hash for a SNES ROM
 pub fn compute_snes_hash(rom: &[u8]) -> String {
@@ -22,15 +19,10 @@ pub fn compute_snes_hash(rom: &[u8]) -> String {
     format!("{:x}", digest)
 }
 
-/// Check if ROM has a copier header (512 bytes over a multiple of 8KB)
-#[inline]
-fn has_copier_header(size: usize) -> bool {
-    size > COPIER_HEADER_SIZE && size % BLOCK_SIZE == COPIER_HEADER_SIZE
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use stitchr_core::snes::BLOCK_SIZE;
 
     #[test]
     fn test_snes_with_copier_header() {
@@ -0,0 +1,96 @@
+//! Game Boy / Game Boy Color cartridge header
+//!
+//! 0x134..0x144 holds the title. On later carts the title shrinks to 11
+//! bytes, followed by a 4-character manufacturer (game) code and the CGB
+//! flag at 0x143. Cartridge type, destination and version follow at 0x147,
+//! 0x14A and 0x14C.
+
+use super::types::{Console, Region, RomInfo, header_text};
+
+const LOGO_OFFSET: usize = 0x104;
+/// First bytes of the Nintendo logo
+const LOGO_START: [u8; 4] = [0xCE, 0xED, 0x66, 0x66];
+
+const TITLE: usize = 0x134;
+const GAME_CODE: usize = 0x13F;
+const CGB_FLAG: usize = 0x143;
+const CARTRIDGE_TYPE: usize = 0x147;
+const DESTINATION: usize = 0x14A;
+const VERSION: usize = 0x14C;
+
+/// Check for the Nintendo logo
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= 0x150 && rom[LOGO_OFFSET..LOGO_OFFSET + 4] == LOGO_START
+}
+
+/// Parse the cartridge header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    if !detect(rom) {
+        return None;
+    }
+
+    let cgb = rom[CGB_FLAG] & 0x80 != 0;
+    let mut info = RomInfo::new(if cgb {
+        Console::GameBoyColor
+    } else {
+        Console::GameBoy
+    });
+
+    let code = &rom[GAME_CODE..CGB_FLAG];
+    if cgb
+        && code
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+    {
+        info.title = header_text(&rom[TITLE..GAME_CODE]);
+        info.game_code = header_text(code);
+    } else {
+        let end = if cgb { CGB_FLAG } else { CGB_FLAG + 1 };
+        info.title = header_text(&rom[TITLE..end]);
+    }
+
+    info.region = Some(match rom[DESTINATION] {
+        0x00 => Region::Japan,
+        _ => Region::Other("Overseas"),
+    });
+    info.revision = Some(rom[VERSION]);
+    info.mapper = Some(cartridge_type(rom[CARTRIDGE_TYPE]));
+
+    Some(info)
+}
+
+/// Cartridge type byte as a name
+fn cartridge_type(value: u8) -> String {
+    let name = match value {
+        0x00 => "ROM ONLY",
+        0x01 => "MBC1",
+        0x02 => "MBC1+RAM",
+        0x03 => "MBC1+RAM+BATTERY",
+        0x05 => "MBC2",
+        0x06 => "MBC2+BATTERY",
+        0x08 => "ROM+RAM",
+        0x09 => "ROM+RAM+BATTERY",
+        0x0B => "MMM01",
+        0x0C => "MMM01+RAM",
+        0x0D => "MMM01+RAM+BATTERY",
+        0x0F => "MBC3+TIMER+BATTERY",
+        0x10 => "MBC3+TIMER+RAM+BATTERY",
+        0x11 => "MBC3",
+        0x12 => "MBC3+RAM",
+        0x13 => "MBC3+RAM+BATTERY",
+        0x19 => "MBC5",
+        0x1A => "MBC5+RAM",
+        0x1B => "MBC5+RAM+BATTERY",
+        0x1C => "MBC5+RUMBLE",
+        0x1D => "MBC5+RUMBLE+RAM",
+        0x1E => "MBC5+RUMBLE+RAM+BATTERY",
+        0x20 => "MBC6",
+        0x22 => "MBC7+SENSOR+RUMBLE+RAM+BATTERY",
+        0xFC => "POCKET CAMERA",
+        0xFD => "BANDAI TAMA5",
+        0xFE => "HuC3",
+        0xFF => "HuC1+RAM+BATTERY",
+        _ => return format!("Unknown ({:#04x})", value),
+    };
+    name.to_string()
+}
@@ -0,0 +1,38 @@
+//! Game Boy Advance cartridge header
+//!
+//! 12-byte title at 0xA0, 4-character game code at 0xAC (its last letter is
+//! the region) and software version at 0xBC.
+
+use super::types::{Console, Region, RomInfo, header_text};
+
+const LOGO_OFFSET: usize = 0x04;
+/// First bytes of the Nintendo logo
+const LOGO_START: [u8; 4] = [0x24, 0xFF, 0xAE, 0x51];
+/// Fixed value required at 0xB2
+const FIXED_VALUE_OFFSET: usize = 0xB2;
+const FIXED_VALUE: u8 = 0x96;
+
+const TITLE: usize = 0xA0;
+const GAME_CODE: usize = 0xAC;
+const VERSION: usize = 0xBC;
+
+/// Check for the Nintendo logo and fixed header value
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= 0xC0
+        && rom[LOGO_OFFSET..LOGO_OFFSET + 4] == LOGO_START
+        && rom[FIXED_VALUE_OFFSET] == FIXED_VALUE
+}
+
+/// Parse the cartridge header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    if !detect(rom) {
+        return None;
+    }
+
+    let mut info = RomInfo::new(Console::Gba);
+    info.title = header_text(&rom[TITLE..GAME_CODE]);
+    info.game_code = header_text(&rom[GAME_CODE..GAME_CODE + 4]);
+    info.region = Region::from_game_code_letter(rom[GAME_CODE + 3]);
+    info.revision = Some(rom[VERSION]);
+    Some(info)
+}
@@ -0,0 +1,244 @@
+//! Disc images: ISO9660 system identifiers and Sega system areas
+//!
+//! The Primary Volume Descriptor at sector 16 names the platform in its
+//! system identifier ("PLAYSTATION", "PSP GAME") and the disc in its volume
+//! identifier. PlayStation discs are told apart by SYSTEM.CNF (`BOOT2` on
+//! PS2), which also names the executable, i.e. the serial. Sega CD and
+//! Saturn discs are recognized from the header in sector 0 instead.
+//!
+//! Both 2048-byte sector images and raw 2352-byte images (Mode 1 and
+//! Mode 2 Form 1) are read.
+
+use super::megadrive;
+use super::types::{Console, Region, RomInfo, header_text};
+
+const SECTOR_SIZE: usize = 2048;
+const RAW_SECTOR_SIZE: usize = 2352;
+const PVD_SECTOR: usize = 16;
+
+/// PVD fields
+const SYSTEM_ID: std::ops::Range<usize> = 8..40;
+const VOLUME_ID: std::ops::Range<usize> = 40..72;
+const ROOT_RECORD: usize = 156;
+
+/// Largest root file read (SYSTEM.CNF, UMD_DATA.BIN are tiny)
+const MAX_FILE_SIZE: usize = 64 * 1024;
+
+/// Sector layout of an image
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    sector_size: usize,
+    data_offset: usize,
+}
+
+const LAYOUTS: [Layout; 3] = [
+    Layout {
+        sector_size: SECTOR_SIZE,
+        data_offset: 0,
+    },
+    // Mode 2 Form 1: sync + header + subheader
+    Layout {
+        sector_size: RAW_SECTOR_SIZE,
+        data_offset: 24,
+    },
+    // Mode 1: sync + header
+    Layout {
+        sector_size: RAW_SECTOR_SIZE,
+        data_offset: 16,
+    },
+];
+
+/// A disc image with a known sector layout
+struct Disc<'a> {
+    data: &'a [u8],
+    layout: Layout,
+}
+
+impl<'a> Disc<'a> {
+    /// Find the layout whose sector 16 holds a volume descriptor
+    fn open(data: &'a [u8]) -> Option<Self> {
+        LAYOUTS
+            .into_iter()
+            .map(|layout| Disc { data, layout })
+            .find(|disc| {
+                disc.sector(PVD_SECTOR)
+                    .is_some_and(|pvd| pvd[0] == 1 && &pvd[1..6] == b"CD001")
+            })
+    }
+
+    /// User data of a sector
+    fn sector(&self, index: usize) -> Option<&'a [u8]> {
+        let start = index * self.layout.sector_size + self.layout.data_offset;
+        self.data.get(start..start + SECTOR_SIZE)
+    }
+
+    /// Read `size` bytes starting at sector `lba`
+    fn read(&self, lba: usize, size: usize) -> Option<Vec<u8>> {
+        let mut out = Vec::with_capacity(size);
+        for index in lba..lba + size.div_ceil(SECTOR_SIZE) {
+            out.extend_from_slice(self.sector(index)?);
+        }
+        out.truncate(size);
+        Some(out)
+    }
+
+    /// Read a file from the root directory (names compared without the
+    /// `;1` version suffix, case-insensitively)
+    fn root_file(&self, name: &str) -> Option<Vec<u8>> {
+        let pvd = self.sector(PVD_SECTOR)?;
+        let record = &pvd[ROOT_RECORD..ROOT_RECORD + 34];
+        let (lba, size) = extent(record);
+        let directory = self.read(lba, size.min(MAX_FILE_SIZE))?;
+
+        let mut pos = 0;
+        while pos < directory.len() {
+            let len = directory[pos] as usize;
+            if len == 0 {
+                // Records never cross sectors; skip the padding
+                pos = (pos / SECTOR_SIZE + 1) * SECTOR_SIZE;
+                continue;
+            }
+            let record = directory.get(pos..pos + len)?;
+            if len > 33 {
+                let name_len = record[32] as usize;
+                let entry = record.get(33..33 + name_len)?;
+                let entry = entry.split(|&b| b == b';').next().unwrap_or(entry);
+                if entry.eq_ignore_ascii_case(name.as_bytes()) {
+                    let (lba, size) = extent(record);
+                    return self.read(lba, size.min(MAX_FILE_SIZE));
+                }
+            }
+            pos += len;
+        }
+        None
+    }
+}
+
+/// Extent LBA and size of a directory record (little-endian halves)
+fn extent(record: &[u8]) -> (usize, usize) {
+    let lba = u32::from_le_bytes(record[2..6].try_into().unwrap());
+    let size = u32::from_le_bytes(record[10..14].try_into().unwrap());
+    (lba as usize, size as usize)
+}
+
+/// Check for a Sega system area or an ISO9660 volume descriptor
+pub fn detect(data: &[u8]) -> bool {
+    sega_system_area(data).is_some() || Disc::open(data).is_some()
+}
+
+/// Parse a disc image
+pub fn parse(data: &[u8]) -> Option<RomInfo> {
+    if let Some(info) = sega_system_area(data) {
+        return Some(info);
+    }
+
+    let disc = Disc::open(data)?;
+    let pvd = disc.sector(PVD_SECTOR)?;
+    let system = header_text(&pvd[SYSTEM_ID]).unwrap_or_default();
+
+    let mut info = match system.as_str() {
+        "PLAYSTATION" => playstation(&disc),
+        "PSP GAME" => psp(&disc),
+        _ => RomInfo::new(Console::Iso9660),
+    };
+    info.title = header_text(&pvd[VOLUME_ID]);
+    Some(info)
+}
+
+/// PlayStation 1/2: SYSTEM.CNF names the boot executable
+fn playstation(disc: &Disc) -> RomInfo {
+    let config = disc
+        .root_file("SYSTEM.CNF")
+        .map(|data| String::from_utf8_lossy(&data).into_owned())
+        .unwrap_or_default();
+
+    let boot = config.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        matches!(key.trim(), "BOOT" | "BOOT2").then(|| (key.trim() == "BOOT2", value.trim()))
+    });
+
+    let mut info = RomInfo::new(match boot {
+        Some((true, _)) => Console::Ps2,
+        _ => Console::Psx,
+    });
+    if let Some((_, path)) = boot {
+        // cdrom:\SLUS_007.11;1 -> SLUS-00711
+        let file = path.rsplit(['\\', '/', ':']).next().unwrap_or(path);
+        let file = file.split(';').next().unwrap_or(file);
+        let code: String = file
+            .chars()
+            .filter(|&c| c != '.')
+            .map(|c| if c == '_' { '-' } else { c })
+            .collect();
+        info.region = serial_region(&code);
+        info.game_code = (!code.is_empty()).then_some(code);
+    }
+    info
+}
+
+/// PSP: UMD_DATA.BIN starts with the serial ("ULUS-10041|...")
+fn psp(disc: &Disc) -> RomInfo {
+    let mut info = RomInfo::new(Console::Psp);
+    if let Some(data) = disc.root_file("UMD_DATA.BIN") {
+        let end = data.iter().position(|&b| b == b'|').unwrap_or(data.len());
+        info.game_code = header_text(&data[..end]);
+        info.region = info.game_code.as_deref().and_then(serial_region);
+    }
+    info
+}
+
+/// Region from the third letter of a Sony serial (SLUS, SCES, SLPM, ULJS...)
+fn serial_region(code: &str) -> Option<Region> {
+    Some(match code.as_bytes().get(2)? {
+        b'U' => Region::Usa,
+        b'E' => Region::Europe,
+        b'J' | b'P' => Region::Japan,
+        b'K' => Region::Korea,
+        b'A' => Region::Asia,
+        b'C' => Region::China,
+        _ => return None,
+    })
+}
+
+/// Sega CD and Saturn headers in sector 0
+fn sega_system_area(data: &[u8]) -> Option<RomInfo> {
+    // Sector 0 user data: at 0 for 2048-byte images, 16 for raw Mode 1
+    [0, 16].into_iter().find_map(|offset| {
+        let area = data.get(offset..offset + 0x200)?;
+        if area.starts_with(b"SEGADISCSYSTEM") {
+            Some(megadrive::parse_header(area, Console::SegaCd))
+        } else if area.starts_with(b"SEGA SEGASATURN") {
+            Some(saturn(area))
+        } else {
+            None
+        }
+    })
+}
+
+/// Saturn system ID: product number at 0x20, version ("V1.002") at 0x2A,
+/// area symbols at 0x40, title at 0x60
+fn saturn(area: &[u8]) -> RomInfo {
+    let mut info = RomInfo::new(Console::Saturn);
+    info.game_code = header_text(&area[0x20..0x2A]);
+    info.revision = header_text(&area[0x2A..0x30])
+        .and_then(|v| v.split_once('.').and_then(|(_, minor)| minor.parse().ok()));
+    info.title = header_text(&area[0x60..0xD0]);
+
+    let areas: Vec<u8> = area[0x40..0x50]
+        .iter()
+        .copied()
+        .filter(u8::is_ascii_uppercase)
+        .collect();
+    info.region = match areas.as_slice() {
+        [] => None,
+        [b'J'] => Some(Region::Japan),
+        [b'U'] => Some(Region::Usa),
+        [b'E'] => Some(Region::Europe),
+        [b'T'] => Some(Region::Asia),
+        [b'K'] => Some(Region::Korea),
+        [b'B'] => Some(Region::Brazil),
+        [_] => None,
+        _ => Some(Region::World),
+    };
+    info
+}
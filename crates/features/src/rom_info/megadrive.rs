@@ -0,0 +1,78 @@
+//! Mega Drive / Genesis cartridge header
+//!
+//! 0x100 starts with "SEGA"; domestic and overseas titles follow at 0x120
+//! and 0x150, the serial ("GM 00001009-00": type, product number, revision)
+//! at 0x180 and the region codes at 0x1F0. Sega CD discs carry the same
+//! header at 0x100 of their first sector.
+
+use super::types::{Console, Region, RomInfo, header_text};
+
+const SYSTEM: usize = 0x100;
+const DOMESTIC_TITLE: usize = 0x120;
+const OVERSEAS_TITLE: usize = 0x150;
+const SERIAL: usize = 0x180;
+const SERIAL_END: usize = 0x18E;
+const REGION: usize = 0x1F0;
+const HEADER_END: usize = 0x200;
+
+/// Check for "SEGA" at 0x100 (some carts start it at 0x101)
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= HEADER_END
+        && (&rom[SYSTEM..SYSTEM + 4] == b"SEGA" || &rom[SYSTEM + 1..SYSTEM + 5] == b"SEGA")
+}
+
+/// Parse the cartridge header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    detect(rom).then(|| parse_header(rom, Console::MegaDrive))
+}
+
+/// Parse a Sega header block (0x000..0x200) for `console`
+pub(super) fn parse_header(data: &[u8], console: Console) -> RomInfo {
+    let mut info = RomInfo::new(console);
+    info.title = header_text(&data[OVERSEAS_TITLE..SERIAL])
+        .or_else(|| header_text(&data[DOMESTIC_TITLE..OVERSEAS_TITLE]));
+
+    let serial = String::from_utf8_lossy(&data[SERIAL..SERIAL_END]).into_owned();
+    let (code, revision) = match serial.split_once('-') {
+        Some((code, revision)) => (code, revision.trim().parse().ok()),
+        None => (serial.as_str(), None),
+    };
+    info.game_code = header_text(code.as_bytes());
+    info.revision = revision;
+    info.region = region(&data[REGION..REGION + 3]);
+
+    if &data[SYSTEM..SYSTEM + 8] == b"SEGA SSF" {
+        info.mapper = Some("Sega SSF".to_string());
+    }
+    info
+}
+
+/// Region codes: letters J/U/E, or else a single hex digit with bit 0 =
+/// Japan, bit 2 = Americas and bit 3 = Europe. More than one region reads
+/// as World.
+fn region(codes: &[u8]) -> Option<Region> {
+    let (mut japan, mut usa, mut europe) = (false, false, false);
+    if codes.iter().any(|b| b"JUE".contains(b)) {
+        for code in codes {
+            match code {
+                b'J' => japan = true,
+                b'U' => usa = true,
+                b'E' => europe = true,
+                _ => {}
+            }
+        }
+    } else {
+        let bits = (codes[0] as char).to_digit(16)?;
+        japan = bits & 0x1 != 0;
+        usa = bits & 0x4 != 0;
+        europe = bits & 0x8 != 0;
+    }
+
+    match (japan, usa, europe) {
+        (true, false, false) => Some(Region::Japan),
+        (false, true, false) => Some(Region::Usa),
+        (false, false, true) => Some(Region::Europe),
+        (false, false, false) => None,
+        _ => Some(Region::World),
+    }
+}
@@ -0,0 +1,47 @@
+//! ROM identification from internal headers
+//!
+//! Reads the header each platform stores in the image itself rather than
+//! trusting the file extension:
+//! - NES: iNES / NES 2.0 (mapper, submapper, TV system)
+//! - SNES: LoROM / HiROM / ExHiROM internal header
+//! - Game Boy / Color, GBA, NDS, N64 cartridge headers
+//! - Mega Drive, Master System / Game Gear ("TMR SEGA")
+//! - PC Engine HuCards (reset vector heuristic)
+//! - Disc images: ISO9660 system identifier, Sega CD / Saturn system area
+
+pub mod gb;
+pub mod gba;
+pub mod iso9660;
+pub mod megadrive;
+pub mod n64;
+pub mod nds;
+pub mod nes;
+pub mod pce;
+pub mod sms;
+pub mod snes;
+
+mod types;
+
+pub use types::{Console, Region, RomInfo};
+
+/// Identify a ROM or disc image from its internal header
+///
+/// Signatures are tried from the most to the least specific; the SNES and
+/// PC Engine checks are heuristics and run last.
+pub fn identify(rom: &[u8]) -> Option<RomInfo> {
+    nes::parse(rom)
+        .or_else(|| n64::parse(rom))
+        .or_else(|| iso9660::parse(rom))
+        .or_else(|| nds::parse(rom))
+        .or_else(|| gba::parse(rom))
+        .or_else(|| gb::parse(rom))
+        .or_else(|| megadrive::parse(rom))
+        .or_else(|| sms::parse(rom))
+        .or_else(|| snes::parse(rom))
+        .or_else(|| pce::parse(rom))
+}
+
+/// Identify just the console
+pub fn detect_console(rom: &[u8]) -> Option<Console> {
+    identify(rom).map(|info| info.console)
+}
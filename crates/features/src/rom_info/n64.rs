@@ -0,0 +1,51 @@
+//! Nintendo 64 ROM header
+//!
+//! 20-byte title at 0x20 and a 4-character game code at 0x3B (media type,
+//! two-letter ID, region letter) followed by the version byte at 0x3F. The
+//! header is read in whichever byte order the dump uses.
+
+use super::types::{Console, Region, RomInfo, header_text};
+
+const HEADER_SIZE: usize = 0x40;
+const TITLE: usize = 0x20;
+const TITLE_LEN: usize = 20;
+const GAME_CODE: usize = 0x3B;
+const VERSION: usize = 0x3F;
+
+/// Check for the N64 header magic in any byte order
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= HEADER_SIZE && big_endian_header(rom).is_some()
+}
+
+/// The 64-byte header converted to big-endian
+fn big_endian_header(rom: &[u8]) -> Option<Vec<u8>> {
+    let header = rom.get(..HEADER_SIZE)?;
+    match header[..4] {
+        [0x80, 0x37, 0x12, 0x40] => Some(header.to_vec()),
+        [0x37, 0x80, 0x40, 0x12] => {
+            Some(header.chunks_exact(2).flat_map(|w| [w[1], w[0]]).collect())
+        }
+        [0x40, 0x12, 0x37, 0x80] => Some(
+            header
+                .chunks_exact(4)
+                .flat_map(|w| [w[3], w[2], w[1], w[0]])
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Parse the ROM header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    let header = big_endian_header(rom)?;
+
+    let mut info = RomInfo::new(Console::N64);
+    info.title = header_text(&header[TITLE..TITLE + TITLE_LEN]);
+    let code = &header[GAME_CODE..GAME_CODE + 4];
+    if code.iter().all(|b| b.is_ascii_alphanumeric()) {
+        info.game_code = header_text(code);
+    }
+    info.region = Region::from_game_code_letter(header[GAME_CODE + 3]);
+    info.revision = Some(header[VERSION]);
+    Some(info)
+}
@@ -0,0 +1,32 @@
+//! Nintendo DS cartridge header
+//!
+//! 12-byte title at 0x00, 4-character game code at 0x0C (last letter is the
+//! region) and ROM version at 0x1E. Retail headers carry the fixed logo
+//! CRC16 0xCF56 at 0x15C.
+
+use super::types::{Console, Region, RomInfo, header_text};
+
+const GAME_CODE: usize = 0x0C;
+const VERSION: usize = 0x1E;
+const LOGO_CRC_OFFSET: usize = 0x15C;
+const LOGO_CRC: u16 = 0xCF56;
+
+/// Check for the fixed Nintendo logo CRC
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= 0x160
+        && u16::from_le_bytes([rom[LOGO_CRC_OFFSET], rom[LOGO_CRC_OFFSET + 1]]) == LOGO_CRC
+}
+
+/// Parse the cartridge header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    if !detect(rom) {
+        return None;
+    }
+
+    let mut info = RomInfo::new(Console::Nds);
+    info.title = header_text(&rom[..GAME_CODE]);
+    info.game_code = header_text(&rom[GAME_CODE..GAME_CODE + 4]);
+    info.region = Region::from_game_code_letter(rom[GAME_CODE + 3]);
+    info.revision = Some(rom[VERSION]);
+    Some(info)
+}
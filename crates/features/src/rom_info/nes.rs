@@ -0,0 +1,76 @@
+//! iNES / NES 2.0 header
+//!
+//! 16 bytes starting with `NES\x1A`. The mapper number is split across the
+//! high nibbles of bytes 6 and 7; NES 2.0 (byte 7 bits 2-3 = `10`) adds
+//! four more bits and a submapper in byte 8, and a timing field in byte 12.
+
+use super::types::{Console, Region, RomInfo};
+
+const MAGIC: &[u8; 4] = b"NES\x1A";
+const HEADER_SIZE: usize = 16;
+
+/// Check for the iNES magic
+pub fn detect(rom: &[u8]) -> bool {
+    rom.len() >= HEADER_SIZE && rom.starts_with(MAGIC)
+}
+
+/// Parse the iNES / NES 2.0 header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    if !detect(rom) {
+        return None;
+    }
+    let header = &rom[..HEADER_SIZE];
+
+    let nes2 = header[7] & 0x0C == 0x08;
+    let mut mapper = ((header[6] >> 4) | (header[7] & 0xF0)) as u16;
+    let mut info = RomInfo::new(Console::Nes);
+
+    if nes2 {
+        mapper |= ((header[8] & 0x0F) as u16) << 8;
+        let submapper = header[8] >> 4;
+        info.mapper = Some(match mapper_name(mapper) {
+            Some(name) => format!("{}.{} ({})", mapper, submapper, name),
+            None => format!("{}.{}", mapper, submapper),
+        });
+        info.region = match header[12] & 0x03 {
+            1 => Some(Region::Europe),
+            2 => Some(Region::World),
+            3 => Some(Region::Other("Dendy")),
+            _ => None,
+        };
+    } else {
+        info.mapper = Some(match mapper_name(mapper) {
+            Some(name) => format!("{} ({})", mapper, name),
+            None => mapper.to_string(),
+        });
+        // TV system bit: PAL dumps are European releases
+        if header[9] & 0x01 != 0 {
+            info.region = Some(Region::Europe);
+        }
+    }
+
+    Some(info)
+}
+
+/// Board names for the common mappers
+fn mapper_name(mapper: u16) -> Option<&'static str> {
+    Some(match mapper {
+        0 => "NROM",
+        1 => "MMC1",
+        2 => "UxROM",
+        3 => "CNROM",
+        4 => "MMC3",
+        5 => "MMC5",
+        7 => "AxROM",
+        9 => "MMC2",
+        10 => "MMC4",
+        11 => "Color Dreams",
+        19 => "Namco 163",
+        24 | 26 => "VRC6",
+        66 => "GxROM",
+        69 => "Sunsoft FME-7",
+        71 => "Camerica",
+        85 => "VRC7",
+        _ => return None,
+    })
+}
@@ -0,0 +1,37 @@
+//! PC Engine / TurboGrafx-16 HuCard
+//!
+//! HuCards have no header, so detection is a heuristic: the reset vector at
+//! the end of the first 8KB bank must point into that bank as mapped at
+//! 0xE000, and the code there must start with SEI or CSH, as every HuC6280
+//! startup routine does. A 512-byte copier header is skipped.
+
+use stitchr_core::snes::strip_copier_header;
+
+use super::types::{Console, RomInfo};
+
+const BANK_SIZE: usize = 0x2000;
+/// Reset vector location within the first bank
+const RESET_VECTOR: usize = 0x1FFE;
+const BANK_BASE: u16 = 0xE000;
+
+/// SEI and CSH opcodes
+const STARTUP_OPCODES: [u8; 2] = [0x78, 0xD4];
+
+/// Check the reset vector heuristic
+pub fn detect(rom: &[u8]) -> bool {
+    // Copier headers on HuCards follow the SNES layout (512 bytes over 8KB)
+    let rom = strip_copier_header(rom);
+    if rom.len() < BANK_SIZE || !rom.len().is_multiple_of(BANK_SIZE) {
+        return false;
+    }
+
+    let vector = u16::from_le_bytes([rom[RESET_VECTOR], rom[RESET_VECTOR + 1]]);
+    vector >= BANK_BASE
+        && (vector as usize - BANK_BASE as usize) < RESET_VECTOR
+        && STARTUP_OPCODES.contains(&rom[(vector - BANK_BASE) as usize])
+}
+
+/// Identify a HuCard (no title, code or region is stored)
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    detect(rom).then(|| RomInfo::new(Console::PcEngine))
+}
@@ -0,0 +1,59 @@
+//! Master System / Game Gear "TMR SEGA" header
+//!
+//! 16 bytes at 0x7FF0 (or 0x3FF0 / 0x1FF0 on small carts): the signature,
+//! a checksum, a BCD product code, the version in the low nibble of byte
+//! 0x0E and the region code in the high nibble of byte 0x0F. The region
+//! code also tells Master System and Game Gear apart.
+
+use super::types::{Console, Region, RomInfo};
+
+const SIGNATURE: &[u8; 8] = b"TMR SEGA";
+const LOCATIONS: [usize; 3] = [0x7FF0, 0x3FF0, 0x1FF0];
+
+const PRODUCT_CODE: usize = 0x0C;
+const VERSION: usize = 0x0E;
+const REGION: usize = 0x0F;
+
+/// Locate the header
+fn find_header(rom: &[u8]) -> Option<usize> {
+    LOCATIONS
+        .into_iter()
+        .find(|&offset| rom.get(offset..offset + 8) == Some(SIGNATURE.as_slice()))
+        .filter(|&offset| rom.len() >= offset + 0x10)
+}
+
+/// Check for the "TMR SEGA" signature
+pub fn detect(rom: &[u8]) -> bool {
+    find_header(rom).is_some()
+}
+
+/// Parse the header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    let offset = find_header(rom)?;
+    let header = &rom[offset..offset + 0x10];
+
+    let (console, region) = match header[REGION] >> 4 {
+        0x3 => (Console::MasterSystem, Some(Region::Japan)),
+        0x4 => (Console::MasterSystem, Some(Region::Other("Export"))),
+        0x5 => (Console::GameGear, Some(Region::Japan)),
+        0x6 => (Console::GameGear, Some(Region::Other("Export"))),
+        0x7 => (Console::GameGear, Some(Region::World)),
+        _ => (Console::MasterSystem, None),
+    };
+
+    let mut info = RomInfo::new(console);
+    info.region = region;
+    info.revision = Some(header[VERSION] & 0x0F);
+
+    // Two BCD bytes (little-endian) plus the high nibble of the next byte as
+    // the leading digit(s)
+    let bcd = |b: u8| (b >> 4) as u32 * 10 + (b & 0x0F) as u32;
+    let product = (header[VERSION] >> 4) as u32 * 10_000
+        + bcd(header[PRODUCT_CODE + 1]) * 100
+        + bcd(header[PRODUCT_CODE]);
+    if product != 0 {
+        info.game_code = Some(product.to_string());
+    }
+
+    Some(info)
+}
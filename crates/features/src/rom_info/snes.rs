@@ -0,0 +1,88 @@
+//! SNES internal header
+//!
+//! 32 bytes at 0x7FC0 (LoROM), 0xFFC0 (HiROM) or 0x40FFC0 (ExHiROM), after
+//! any 512-byte copier header: 21-byte title, map mode, cartridge type,
+//! sizes, destination code, developer ID, version and checksums. Games with
+//! developer ID 0x33 also carry a 4-character game code 14 bytes before it.
+
+use stitchr_core::snes::{INTERNAL_HEADER_SIZE, find_internal_header, strip_copier_header};
+
+use super::types::{Console, Region, RomInfo, header_text};
+
+const TITLE_LEN: usize = 21;
+const MAP_MODE: usize = 0x15;
+const DESTINATION: usize = 0x19;
+const DEVELOPER_ID: usize = 0x1A;
+const VERSION: usize = 0x1B;
+/// Extended header game code, relative to the internal header
+const GAME_CODE: usize = 0x10;
+
+/// Check for a plausible internal header
+pub fn detect(rom: &[u8]) -> bool {
+    find_internal_header(strip_copier_header(rom)).is_some()
+}
+
+/// Parse the internal header
+pub fn parse(rom: &[u8]) -> Option<RomInfo> {
+    let rom = strip_copier_header(rom);
+    let offset = find_internal_header(rom)?;
+    let header = &rom[offset..offset + INTERNAL_HEADER_SIZE];
+
+    let mut info = RomInfo::new(Console::Snes);
+    info.title = header_text(&header[..TITLE_LEN]);
+    info.region = region(header[DESTINATION]);
+    info.revision = Some(header[VERSION]);
+    info.mapper = Some(mapper(header[MAP_MODE]));
+
+    if header[DEVELOPER_ID] == 0x33 {
+        let code = &rom[offset - GAME_CODE + 2..offset - GAME_CODE + 6];
+        if code.iter().all(|b| b.is_ascii_alphanumeric()) {
+            info.game_code = header_text(code);
+        }
+    }
+
+    Some(info)
+}
+
+/// Memory map name from the map mode byte
+fn mapper(mode: u8) -> String {
+    let layout = match mode & 0x0F {
+        0x0 => "LoROM",
+        0x1 => "HiROM",
+        0x2 => "LoROM (S-DD1)",
+        0x3 => "LoROM (SA-1)",
+        0x5 => "ExHiROM",
+        0xA => "HiROM (SPC7110)",
+        _ => "Unknown",
+    };
+    if mode & 0x10 != 0 {
+        format!("{}, FastROM", layout)
+    } else {
+        layout.to_string()
+    }
+}
+
+/// Destination code
+fn region(code: u8) -> Option<Region> {
+    Some(match code {
+        0x00 => Region::Japan,
+        0x01 => Region::Usa,
+        0x02 => Region::Europe,
+        0x03 => Region::Other("Scandinavia"),
+        0x04 => Region::Other("Finland"),
+        0x05 => Region::Other("Denmark"),
+        0x06 => Region::Other("France"),
+        0x07 => Region::Other("Netherlands"),
+        0x08 => Region::Other("Spain"),
+        0x09 => Region::Other("Germany"),
+        0x0A => Region::Other("Italy"),
+        0x0B => Region::China,
+        0x0C => Region::Other("Indonesia"),
+        0x0D => Region::Korea,
+        0x0E => Region::World,
+        0x0F => Region::Canada,
+        0x10 => Region::Brazil,
+        0x11 => Region::Australia,
+        _ => return None,
+    })
+}
@@ -0,0 +1,192 @@
+//! ROM identification types
+
+use std::fmt;
+
+/// Console a ROM or disc image belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Console {
+    Nes,
+    Snes,
+    N64,
+    GameBoy,
+    GameBoyColor,
+    Gba,
+    Nds,
+    MegaDrive,
+    MasterSystem,
+    GameGear,
+    PcEngine,
+    SegaCd,
+    Saturn,
+    Psx,
+    Ps2,
+    Psp,
+    /// ISO9660 image with an unrecognized system identifier
+    Iso9660,
+}
+
+impl Console {
+    /// Human-readable console name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Nes => "NES",
+            Self::Snes => "SNES",
+            Self::N64 => "Nintendo 64",
+            Self::GameBoy => "Game Boy",
+            Self::GameBoyColor => "Game Boy Color",
+            Self::Gba => "Game Boy Advance",
+            Self::Nds => "Nintendo DS",
+            Self::MegaDrive => "Mega Drive",
+            Self::MasterSystem => "Master System",
+            Self::GameGear => "Game Gear",
+            Self::PcEngine => "PC Engine",
+            Self::SegaCd => "Sega CD",
+            Self::Saturn => "Saturn",
+            Self::Psx => "PlayStation",
+            Self::Ps2 => "PlayStation 2",
+            Self::Psp => "PlayStation Portable",
+            Self::Iso9660 => "ISO9660 disc",
+        }
+    }
+
+    /// Usual file extension for a dump, or `None` for disc images (whose
+    /// extension depends on the image format)
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Self::Nes => Some("nes"),
+            Self::Snes => Some("sfc"),
+            Self::N64 => Some("z64"),
+            Self::GameBoy => Some("gb"),
+            Self::GameBoyColor => Some("gbc"),
+            Self::Gba => Some("gba"),
+            Self::Nds => Some("nds"),
+            Self::MegaDrive => Some("md"),
+            Self::MasterSystem => Some("sms"),
+            Self::GameGear => Some("gg"),
+            Self::PcEngine => Some("pce"),
+            Self::SegaCd | Self::Saturn | Self::Psx | Self::Ps2 | Self::Psp | Self::Iso9660 => None,
+        }
+    }
+}
+
+impl fmt::Display for Console {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Release region named in a header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Japan,
+    Usa,
+    Europe,
+    World,
+    Korea,
+    China,
+    Asia,
+    Brazil,
+    Australia,
+    Canada,
+    /// Country or market without its own variant (e.g. "France", "Export")
+    Other(&'static str),
+}
+
+impl Region {
+    /// Region from the last letter of a Nintendo game code (e.g. `E` in
+    /// `AGBE`), as used by GBA, NDS and N64 headers
+    pub fn from_game_code_letter(letter: u8) -> Option<Self> {
+        Some(match letter {
+            b'A' => Self::Asia,
+            b'B' => Self::Brazil,
+            b'C' => Self::China,
+            b'D' => Self::Other("Germany"),
+            b'E' => Self::Usa,
+            b'F' => Self::Other("France"),
+            b'H' => Self::Other("Netherlands"),
+            b'I' => Self::Other("Italy"),
+            b'J' => Self::Japan,
+            b'K' => Self::Korea,
+            b'N' => Self::Canada,
+            b'P' | b'X' | b'Y' | b'Z' => Self::Europe,
+            b'S' => Self::Other("Spain"),
+            b'U' => Self::Australia,
+            b'W' => Self::Other("Scandinavia"),
+            _ => return None,
+        })
+    }
+
+    /// Human-readable region name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Japan => "Japan",
+            Self::Usa => "USA",
+            Self::Europe => "Europe",
+            Self::World => "World",
+            Self::Korea => "Korea",
+            Self::China => "China",
+            Self::Asia => "Asia",
+            Self::Brazil => "Brazil",
+            Self::Australia => "Australia",
+            Self::Canada => "Canada",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Identification read from a ROM's internal header
+///
+/// Fields the header does not carry (e.g. a title in an iNES header) are
+/// `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomInfo {
+    pub console: Console,
+    /// Internal title, trimmed
+    pub title: Option<String>,
+    /// Product or serial code (e.g. "AGBE", "SLUS-01234")
+    pub game_code: Option<String>,
+    pub region: Option<Region>,
+    /// Revision / version number
+    pub revision: Option<u8>,
+    /// Mapper, memory map or cartridge type (e.g. "4 (MMC3)", "HiROM")
+    pub mapper: Option<String>,
+}
+
+impl RomInfo {
+    /// Info with only the console set
+    pub fn new(console: Console) -> Self {
+        Self {
+            console,
+            title: None,
+            game_code: None,
+            region: None,
+            revision: None,
+            mapper: None,
+        }
+    }
+}
+
+/// Header text as a trimmed string
+///
+/// Stops at the first NUL, maps half-width katakana (JIS X 0201, used by
+/// SNES and Game Boy titles) to Unicode and drops other non-printable
+/// bytes. Returns `None` for an empty result.
+pub(crate) fn header_text(bytes: &[u8]) -> Option<String> {
+    let text: String = bytes
+        .iter()
+        .take_while(|&&b| b != 0)
+        .filter_map(|&b| match b {
+            0x20..=0x7E => Some(b as char),
+            0xA1..=0xDF => char::from_u32(0xFF61 + (b - 0xA1) as u32),
+            _ => None,
+        })
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
@@ -0,0 +1,211 @@
+//! Archive reading and ZIP writing tests
+//!
+//! The fixtures in `test_files/archive/` hold `rom.bin` and `hack.ips`
+//! under various container formats and compression methods.
+
+#![cfg(feature = "archive")]
+
+use stitchr_core::PatchError;
+use stitchr_features::archive::{self, ArchiveKind, gzip, sevenz, zip};
+
+const ROM: &[u8] = include_bytes!("../../../test_files/archive/rom.bin");
+const PATCH: &[u8] = include_bytes!("../../../test_files/archive/hack.ips");
+
+const SINGLE_7Z: &[u8] = include_bytes!("../../../test_files/archive/single.7z");
+const SOLID_7Z: &[u8] = include_bytes!("../../../test_files/archive/solid.7z");
+const DEFLATE_7Z: &[u8] = include_bytes!("../../../test_files/archive/deflate.7z");
+const COPY_7Z: &[u8] = include_bytes!("../../../test_files/archive/copy.7z");
+const MULTI_ZIP: &[u8] = include_bytes!("../../../test_files/archive/multi.zip");
+const GAME_GZ: &[u8] = include_bytes!("../../../test_files/archive/game.sfc.gz");
+const TWO_GZ: &[u8] = include_bytes!("../../../test_files/archive/two.gz");
+
+fn names(entries: &[archive::ArchiveEntry]) -> Vec<&str> {
+    entries.iter().map(|e| e.name.as_str()).collect()
+}
+
+#[test]
+fn test_detect() {
+    assert_eq!(ArchiveKind::detect(MULTI_ZIP), Some(ArchiveKind::Zip));
+    assert_eq!(ArchiveKind::detect(SINGLE_7Z), Some(ArchiveKind::SevenZip));
+    assert_eq!(ArchiveKind::detect(GAME_GZ), Some(ArchiveKind::Gzip));
+    assert_eq!(ArchiveKind::detect(ROM), None);
+    assert_eq!(ArchiveKind::detect(PATCH), None);
+}
+
+#[test]
+fn test_zip_list_skips_directories() {
+    let entries = zip::list(MULTI_ZIP).unwrap();
+    assert_eq!(
+        names(&entries),
+        ["docs/readme.txt", "docs/hack.ips", "Game (USA).sfc"]
+    );
+    let rom = &entries[2];
+    assert_eq!(rom.size, ROM.len() as u64);
+    assert_eq!(rom.crc32, Some(crc32fast::hash(ROM)));
+}
+
+#[test]
+fn test_zip_extract_stored_and_deflated() {
+    assert_eq!(zip::extract(MULTI_ZIP, "Game (USA).sfc").unwrap(), ROM);
+    assert_eq!(zip::extract(MULTI_ZIP, "docs/hack.ips").unwrap(), PATCH);
+    assert!(zip::extract(MULTI_ZIP, "missing.sfc").is_err());
+}
+
+#[test]
+fn test_zip_corrupt_crc() {
+    let mut data = MULTI_ZIP.to_vec();
+    // The stored patch's bytes follow its local header and name
+    let pos = data
+        .windows(13)
+        .position(|w| w == b"docs/hack.ips")
+        .unwrap()
+        + 13;
+    data[pos] ^= 0xFF;
+    assert!(matches!(
+        zip::extract(&data, "docs/hack.ips"),
+        Err(PatchError::ChecksumMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_zip_rejects_oversized_entry() {
+    let mut data = zip::create("rom.bin", ROM);
+    // Claim 1 GiB in the central directory's uncompressed size
+    let central = data.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+    data[central + 24..central + 28].copy_from_slice(&(1u32 << 30).to_le_bytes());
+
+    let err = zip::extract(&data, "rom.bin").unwrap_err();
+    assert!(err.to_string().contains("too large"), "{}", err);
+}
+
+#[test]
+fn test_zip_create_roundtrip() {
+    for contents in [ROM, PATCH, b"".as_slice()] {
+        let data = zip::create("dir/Game (USA).sfc", contents);
+        assert_eq!(ArchiveKind::detect(&data), Some(ArchiveKind::Zip));
+        let entries = zip::list(&data).unwrap();
+        assert_eq!(names(&entries), ["dir/Game (USA).sfc"]);
+        assert_eq!(entries[0].size, contents.len() as u64);
+        assert_eq!(zip::extract(&data, "dir/Game (USA).sfc").unwrap(), contents);
+    }
+}
+
+#[test]
+fn test_zip_create_compresses() {
+    let data = zip::create("rom.bin", ROM);
+    assert!(data.len() < ROM.len() / 2);
+}
+
+#[test]
+fn test_sevenz_lzma2() {
+    let entries = sevenz::list(SINGLE_7Z).unwrap();
+    assert_eq!(names(&entries), ["Game (USA).sfc"]);
+    assert_eq!(entries[0].crc32, Some(crc32fast::hash(ROM)));
+    assert_eq!(sevenz::extract(SINGLE_7Z, "Game (USA).sfc").unwrap(), ROM);
+}
+
+#[test]
+fn test_sevenz_solid_with_encoded_header() {
+    let entries = sevenz::list(SOLID_7Z).unwrap();
+    assert_eq!(
+        names(&entries),
+        [
+            "docs/readme.txt",
+            "empty.txt",
+            "docs/hack.ips",
+            "Game (USA).sfc"
+        ]
+    );
+    assert_eq!(entries[1].size, 0);
+    assert_eq!(sevenz::extract(SOLID_7Z, "docs/hack.ips").unwrap(), PATCH);
+    assert_eq!(sevenz::extract(SOLID_7Z, "Game (USA).sfc").unwrap(), ROM);
+    assert_eq!(
+        sevenz::extract(SOLID_7Z, "docs/readme.txt").unwrap(),
+        b"Apply to the USA ROM.\n"
+    );
+    assert!(sevenz::extract(SOLID_7Z, "empty.txt").unwrap().is_empty());
+    assert!(sevenz::extract(SOLID_7Z, "docs").is_err());
+}
+
+#[test]
+fn test_sevenz_copy() {
+    assert_eq!(sevenz::extract(COPY_7Z, "a.bin").unwrap(), ROM);
+}
+
+#[test]
+fn test_sevenz_deflate_unsupported() {
+    assert_eq!(names(&sevenz::list(DEFLATE_7Z).unwrap()), ["a.bin"]);
+    assert!(matches!(
+        sevenz::extract(DEFLATE_7Z, "a.bin"),
+        Err(PatchError::UnsupportedVersion(_))
+    ));
+}
+
+#[test]
+fn test_sevenz_corrupt_data() {
+    let mut data = COPY_7Z.to_vec();
+    data[32 + 100] ^= 0xFF;
+    assert!(matches!(
+        sevenz::extract(&data, "a.bin"),
+        Err(PatchError::ChecksumMismatch { .. })
+    ));
+
+    let mut data = SINGLE_7Z.to_vec();
+    // Start header CRC
+    data[12] ^= 0xFF;
+    assert!(sevenz::list(&data).is_err());
+}
+
+#[test]
+fn test_gzip_entry_and_decompress() {
+    let entry = gzip::entry(GAME_GZ).unwrap();
+    assert_eq!(entry.name, "Game (USA).sfc");
+    assert_eq!(entry.size, ROM.len() as u64);
+    assert_eq!(gzip::decompress(GAME_GZ).unwrap(), ROM);
+}
+
+#[test]
+fn test_gzip_concatenated_members() {
+    assert_eq!(gzip::entry(TWO_GZ).unwrap().name, "");
+    assert_eq!(gzip::decompress(TWO_GZ).unwrap(), ROM);
+}
+
+#[test]
+fn test_gzip_corrupt_trailer() {
+    let mut data = GAME_GZ.to_vec();
+    let crc = data.len() - 8;
+    data[crc] ^= 0xFF;
+    assert!(matches!(
+        gzip::decompress(&data),
+        Err(PatchError::ChecksumMismatch { .. })
+    ));
+    assert!(gzip::decompress(&GAME_GZ[..GAME_GZ.len() - 4]).is_err());
+}
+
+#[test]
+fn test_archive_dispatch() {
+    for (data, name) in [
+        (MULTI_ZIP, "Game (USA).sfc"),
+        (SINGLE_7Z, "Game (USA).sfc"),
+        (GAME_GZ, "Game (USA).sfc"),
+    ] {
+        let kind = ArchiveKind::detect(data).unwrap();
+        let entries = archive::list(kind, data).unwrap();
+        assert!(entries.iter().any(|e| e.name == name), "{}", kind.name());
+        assert_eq!(archive::extract(kind, data, name).unwrap(), ROM);
+    }
+}
+
+#[test]
+fn test_truncated_archives() {
+    for data in [MULTI_ZIP, SINGLE_7Z, SOLID_7Z, GAME_GZ] {
+        let kind = ArchiveKind::detect(data).unwrap();
+        let cut = &data[..data.len() / 2];
+        assert!(
+            archive::list(kind, cut).is_err()
+                || archive::extract(kind, cut, "Game (USA).sfc").is_err(),
+            "{}",
+            kind.name()
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Internal checksum repair tests
+
+#![cfg(feature = "checksum_fix")]
+
+use stitchr_features::checksum_fix::n64::{self, Cic};
+use stitchr_features::checksum_fix::{self, System, gb, gba, megadrive, nds, snes};
+
+/// 32 KiB Game Boy ROM with the logo start and a zeroed header
+fn gb_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x104..0x108].copy_from_slice(&[0xCE, 0xED, 0x66, 0x66]);
+    rom
+}
+
+fn gba_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x200];
+    rom[0x04..0x08].copy_from_slice(&[0x24, 0xFF, 0xAE, 0x51]);
+    rom[0xB2] = 0x96;
+    rom
+}
+
+/// LoROM image with a valid-looking internal header at 0x7FC0
+fn snes_rom(size: usize) -> Vec<u8> {
+    let mut rom: Vec<u8> = (0..size).map(|i| (i % 7) as u8).collect();
+    rom[0x7FC0..0x7FD5].copy_from_slice(b"TEST GAME            ");
+    rom[0x7FD5] = 0x20;
+    rom[0x7FDC..0x7FE0].fill(0);
+    rom
+}
+
+/// CRC32 (reflected 0xEDB88320) table entry
+fn crc32_entry(index: u32) -> u32 {
+    (0..8).fold(index, |c, _| {
+        if c & 1 != 0 {
+            (c >> 1) ^ 0xEDB8_8320
+        } else {
+            c >> 1
+        }
+    })
+}
+
+/// Overwrite the last four bytes of `data` so its CRC32 becomes `target`
+fn forge_crc32(data: &mut [u8], target: u32) {
+    let len = data.len();
+    let prefix = !crc32fast::hash(&data[..len - 4]);
+    let mut reg = !target;
+    for _ in 0..4 {
+        let index = (0..256)
+            .find(|&j| crc32_entry(j) >> 24 == reg >> 24)
+            .unwrap();
+        reg = ((reg ^ crc32_entry(index)) << 8) | index;
+    }
+    data[len - 4..].copy_from_slice(&(reg ^ prefix).to_le_bytes());
+    assert_eq!(crc32fast::hash(data), target);
+}
+
+/// Big-endian N64 ROM whose boot code identifies as `ipl3_crc`
+fn n64_rom(ipl3_crc: u32) -> Vec<u8> {
+    let mut rom: Vec<u8> = (0..0x101000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    rom[..4].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+    forge_crc32(&mut rom[0x40..0x1000], ipl3_crc);
+    rom
+}
+
+/// Byte-swap every 16-bit word (.z64 -> .v64)
+fn to_v64(rom: &[u8]) -> Vec<u8> {
+    rom.chunks_exact(2).flat_map(|w| [w[1], w[0]]).collect()
+}
+
+/// Reference SNES checksum: physically mirror to a power of two and sum
+fn reference_snes_checksum(rom: &[u8]) -> u16 {
+    let mut rom = rom.to_vec();
+    rom[0x7FDC..0x7FE0].copy_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+    let base = if rom.len().is_power_of_two() {
+        rom.len()
+    } else {
+        rom.len().next_power_of_two() / 2
+    };
+    let mut mirrored = rom[..base].to_vec();
+    while mirrored.len() < base * 2 && rom.len() > base {
+        mirrored.extend_from_slice(&rom[base..]);
+    }
+    mirrored.iter().map(|&b| b as u32).sum::<u32>() as u16
+}
+
+#[test]
+fn test_detect_system() {
+    assert_eq!(
+        checksum_fix::detect_system(&gb_rom()),
+        Some(System::GameBoy)
+    );
+    assert_eq!(checksum_fix::detect_system(&gba_rom()), Some(System::Gba));
+    assert_eq!(
+        checksum_fix::detect_system(&snes_rom(0x10000)),
+        Some(System::Snes)
+    );
+    assert_eq!(checksum_fix::detect_system(&[0u8; 0x10000]), None);
+}
+
+#[test]
+fn test_gb_checksums() {
+    let mut rom = gb_rom();
+    let fixes = gb::fix(&mut rom);
+
+    assert_eq!(fixes.len(), 2);
+    // 25 zero bytes: x = -25
+    assert_eq!(rom[0x14D], 0xE7);
+    assert_eq!(fixes[0].after, 0xE7);
+    assert!(fixes[0].changed());
+
+    let expected: u16 = rom
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 0x14E && *i != 0x14F)
+        .map(|(_, &b)| b as u16)
+        .fold(0, u16::wrapping_add);
+    assert_eq!(u16::from_be_bytes([rom[0x14E], rom[0x14F]]), expected);
+
+    // Second pass finds nothing to change
+    assert!(gb::fix(&mut rom).iter().all(|f| !f.changed()));
+}
+
+#[test]
+fn test_gba_complement() {
+    let mut rom = gba_rom();
+    let fixes = gba::fix(&mut rom);
+    // -(0x96) - 0x19
+    assert_eq!(rom[0xBD], 0x51);
+    assert_eq!(fixes[0].before, 0);
+    assert_eq!(fixes[0].after, 0x51);
+}
+
+#[test]
+fn test_megadrive_checksum() {
+    let mut rom = vec![0u8; 0x205];
+    rom[0x100..0x110].copy_from_slice(b"SEGA MEGA DRIVE ");
+    rom[0x200..0x205].copy_from_slice(&[0x01, 0x02, 0x03, 0x04, 0x05]);
+
+    let fixes = megadrive::fix(&mut rom);
+    // 0x0102 + 0x0304 + 0x0500
+    assert_eq!(u16::from_be_bytes([rom[0x18E], rom[0x18F]]), 0x0906);
+    assert_eq!(fixes[0].system, System::MegaDrive);
+}
+
+#[test]
+fn test_nds_crc16() {
+    assert_eq!(nds::crc16(b"123456789"), 0x4B37);
+
+    let mut rom = vec![0u8; 0x200];
+    rom[0x15C..0x15E].copy_from_slice(&0xCF56u16.to_le_bytes());
+    let fixes = nds::fix(&mut rom);
+    let expected = nds::crc16(&rom[..0x15E]);
+    assert_eq!(u16::from_le_bytes([rom[0x15E], rom[0x15F]]), expected);
+    assert_eq!(fixes[0].after, expected as u32);
+}
+
+#[test]
+fn test_snes_power_of_two() {
+    let mut rom = snes_rom(0x10000);
+    let expected = reference_snes_checksum(&rom);
+
+    let fixes = snes::fix(&mut rom);
+    assert_eq!(fixes[0].after, expected as u32);
+    assert_eq!(u16::from_le_bytes([rom[0x7FDE], rom[0x7FDF]]), expected);
+    assert_eq!(u16::from_le_bytes([rom[0x7FDC], rom[0x7FDD]]), !expected);
+    assert!(snes::fix(&mut rom).iter().all(|f| !f.changed()));
+}
+
+#[test]
+fn test_snes_mirrored_size() {
+    // 96 KiB: last 32 KiB counted twice
+    let mut rom = snes_rom(0x18000);
+    let expected = reference_snes_checksum(&rom);
+    snes::fix(&mut rom);
+    assert_eq!(u16::from_le_bytes([rom[0x7FDE], rom[0x7FDF]]), expected);
+}
+
+#[test]
+fn test_snes_copier_header() {
+    let clean = snes_rom(0x10000);
+    let mut headered = vec![0xAAu8; 512];
+    headered.extend_from_slice(&clean);
+
+    let fixes = checksum_fix::fix_checksums(&mut headered);
+    assert_eq!(fixes[0].offset, 512 + 0x7FDE);
+    assert!(headered[..512].iter().all(|&b| b == 0xAA));
+
+    let mut clean_fixed = clean.clone();
+    snes::fix(&mut clean_fixed);
+    assert_eq!(&headered[512..], &clean_fixed[..]);
+}
+
+#[test]
+fn test_snes_copier_header_needs_8k_blocks() {
+    // 512 bytes over a multiple of 1 KiB but not of 8 KiB: no copier header
+    let mut rom = snes_rom(0x10000 + 0x600);
+    let fixes = checksum_fix::fix_checksums(&mut rom);
+    assert_eq!(fixes[0].offset, 0x7FDE);
+}
+
+#[test]
+fn test_report_format() {
+    let mut rom = gba_rom();
+    let fix = &checksum_fix::fix_checksums(&mut rom)[0];
+    assert_eq!(
+        fix.to_string(),
+        "GBA header complement at 0xbd: 0x00 -> 0x51"
+    );
+}
+
+#[test]
+fn test_unknown_rom_untouched() {
+    let mut rom = vec![0x5Au8; 0x8000];
+    assert!(checksum_fix::fix_checksums(&mut rom).is_empty());
+    assert!(rom.iter().all(|&b| b == 0x5A));
+}
+
+#[test]
+fn test_n64_cic_detection() {
+    let cases = [
+        (0x6170_A4A1, Cic::Cic6101),
+        (0x90BB_6CB5, Cic::Cic6102),
+        (0x0B05_0EE0, Cic::Cic6103),
+        (0x98BC_2C86, Cic::Cic6105),
+        (0xACC8_580A, Cic::Cic6106),
+        (0x009E_9EA3, Cic::Cic7102),
+    ];
+    for (crc, cic) in cases {
+        let rom = n64_rom(crc);
+        assert_eq!(n64::detect_cic(&rom), Some(cic));
+        assert_eq!(n64::detect_cic(&to_v64(&rom)), Some(cic));
+    }
+    assert_eq!(n64::detect_cic(&n64_rom(0x1234_5678)), None);
+}
+
+#[test]
+fn test_n64_crc_depends_on_cic() {
+    let rom = n64_rom(0x90BB_6CB5);
+    let results: Vec<_> = [Cic::Cic6102, Cic::Cic6103, Cic::Cic6105, Cic::Cic6106]
+        .iter()
+        .map(|&cic| n64::crc(&rom, cic).unwrap())
+        .collect();
+    for (i, a) in results.iter().enumerate() {
+        for b in &results[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+    // 6101 and 7102 share the 6102 seed and fold
+    assert_eq!(n64::crc(&rom, Cic::Cic6101), n64::crc(&rom, Cic::Cic6102));
+    assert_eq!(n64::crc(&rom[..0x100000], Cic::Cic6102), None);
+}
+
+#[test]
+fn test_n64_fix() {
+    let mut rom = n64_rom(0x90BB_6CB5);
+    assert_eq!(checksum_fix::detect_system(&rom), Some(System::N64));
+    assert_eq!(n64::is_stale(&rom), Some(true));
+
+    let (crc1, crc2) = n64::crc(&rom, Cic::Cic6102).unwrap();
+    let fixes = checksum_fix::fix_checksums(&mut rom);
+    assert_eq!(fixes.len(), 2);
+    assert_eq!((fixes[0].field, fixes[0].offset), ("CRC1", 0x10));
+    assert_eq!(rom[0x10..0x14], crc1.to_be_bytes());
+    assert_eq!(rom[0x14..0x18], crc2.to_be_bytes());
+    assert_eq!(n64::is_stale(&rom), Some(false));
+
+    // Patching data inside the checksummed range makes it stale again
+    rom[0x2000] ^= 0xFF;
+    assert_eq!(n64::is_stale(&rom), Some(true));
+}
+
+#[test]
+fn test_n64_fix_keeps_byte_order() {
+    let mut z64 = n64_rom(0x98BC_2C86);
+    let mut v64 = to_v64(&z64);
+
+    checksum_fix::fix_checksums(&mut z64);
+    let fixes = checksum_fix::fix_checksums(&mut v64);
+
+    assert_eq!(v64, to_v64(&z64));
+    assert_eq!(
+        fixes[1].after,
+        u32::from_be_bytes(z64[0x14..0x18].try_into().unwrap())
+    );
+}
+
+#[test]
+fn test_n64_unknown_cic_untouched() {
+    let mut rom = n64_rom(0x1234_5678);
+    let before = rom.clone();
+    assert!(checksum_fix::fix_checksums(&mut rom).is_empty());
+    assert_eq!(rom, before);
+    assert_eq!(n64::is_stale(&rom), None);
+}
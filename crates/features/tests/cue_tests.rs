@@ -0,0 +1,250 @@
+//! Tests for CUE sheets and multi-file BIN images
+
+#![cfg(feature = "retroachievements")]
+
+use std::path::{Path, PathBuf};
+use stitchr_features::disc::{BlockDevice, CueDevice, CueSheet, DiscImage, read_all};
+use stitchr_features::retroachievements::compute_psx_hash;
+
+const PSX_BIN: &[u8] = include_bytes!("../../../test_files/disc/psx.bin");
+const PSX_CHD: &[u8] = include_bytes!("../../../test_files/disc/psx.chd");
+
+const PSX_HASH: &str = "0e713b6b1b9548c071b95d8da29bccaf";
+
+const SPLIT_CUE: &str = "REM COMMENT \"split dump\"\r\n\
+    FILE \"game (Track 1).bin\" BINARY\r\n  \
+    TRACK 01 MODE2/2352\r\n    \
+    INDEX 01 00:00:00\r\n\
+    FILE \"game (Track 2).bin\" BINARY\r\n  \
+    TRACK 02 AUDIO\r\n    \
+    INDEX 00 00:00:00\r\n    \
+    INDEX 01 00:00:02\r\n";
+
+const MERGED_CUE: &str = "FILE \"game.bin\" BINARY\n  \
+    TRACK 01 MODE2/2352\n    \
+    INDEX 01 00:00:00\n  \
+    TRACK 02 AUDIO\n    \
+    INDEX 00 00:00:27\n    \
+    INDEX 01 00:00:29\n";
+
+/// The PSX test disc: a data track followed by 16 sectors of audio
+fn psx_disc() -> Vec<u8> {
+    read_all(&DiscImage::open(Box::new(PSX_CHD.to_vec())).unwrap()).unwrap()
+}
+
+/// Write the PSX disc as a split-track dump and as a merged one
+fn write_dumps(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stitchr_cue_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("merged")).unwrap();
+    let disc = psx_disc();
+    let (track1, track2) = disc.split_at(PSX_BIN.len());
+    std::fs::write(dir.join("game (Track 1).bin"), track1).unwrap();
+    std::fs::write(dir.join("game (Track 2).bin"), track2).unwrap();
+    std::fs::write(dir.join("game.cue"), SPLIT_CUE).unwrap();
+    std::fs::write(dir.join("merged/game.bin"), &disc).unwrap();
+    std::fs::write(dir.join("merged/game.cue"), MERGED_CUE).unwrap();
+    dir
+}
+
+#[test]
+fn test_parse_split_sheet() {
+    let sheet = CueSheet::parse(SPLIT_CUE).unwrap();
+    assert_eq!(sheet.files.len(), 2);
+    assert_eq!(sheet.files[0].name, "game (Track 1).bin");
+    assert_eq!(sheet.files[1].kind, "BINARY");
+
+    let [data, audio] = &sheet.tracks[..] else {
+        panic!("expected two tracks");
+    };
+    assert_eq!((data.number, data.file, data.sector_size()), (1, 0, 2352));
+    assert!(data.is_data());
+    assert_eq!((audio.number, audio.file), (2, 1));
+    assert!(!audio.is_data());
+    assert_eq!(audio.indexes, vec![(0, 0), (1, 2)]);
+}
+
+#[test]
+fn test_split_and_merged_spans_match() {
+    let data_size = PSX_BIN.len() as u64;
+    let split = CueSheet::parse(SPLIT_CUE)
+        .unwrap()
+        .spans(&[data_size, 16 * 2352])
+        .unwrap();
+    let merged = CueSheet::parse(MERGED_CUE)
+        .unwrap()
+        .spans(&[data_size + 16 * 2352])
+        .unwrap();
+    assert_eq!(split, merged);
+    assert_eq!((split[0].start, split[0].end), (0, data_size));
+    assert_eq!(
+        (split[1].start, split[1].end),
+        (data_size, data_size + 16 * 2352)
+    );
+    assert_eq!((split[0].lba, split[1].lba), (0, 29));
+}
+
+#[test]
+fn test_pregap_and_postgap_shift_disc_addresses() {
+    let sheet = CueSheet::parse(
+        "FILE \"a.bin\" BINARY\n\
+         TRACK 01 MODE1/2048\n\
+         INDEX 01 00:00:00\n\
+         POSTGAP 00:00:03\n\
+         FILE \"b.bin\" BINARY\n\
+         TRACK 02 AUDIO\n\
+         PREGAP 00:02:00\n\
+         INDEX 01 00:00:00\n",
+    )
+    .unwrap();
+    assert_eq!(sheet.tracks[0].sector_size(), 2048);
+    assert_eq!(sheet.tracks[0].postgap, 3);
+    assert_eq!(sheet.tracks[1].pregap, 150);
+
+    // Gaps are not stored, so byte offsets only follow the files
+    let spans = sheet.spans(&[10 * 2048, 4 * 2352]).unwrap();
+    assert_eq!(
+        (spans[1].start, spans[1].end),
+        (10 * 2048, 10 * 2048 + 4 * 2352)
+    );
+    assert_eq!(spans[1].lba, 10 + 3 + 150);
+}
+
+#[test]
+fn test_gap_appended_to_previous_file() {
+    // INDEX 00 of track 2 sits at the end of the first file
+    let sheet = CueSheet::parse(
+        "FILE \"1.bin\" BINARY\n\
+         TRACK 01 MODE2/2352\n\
+         INDEX 01 00:00:00\n\
+         TRACK 02 AUDIO\n\
+         INDEX 00 00:00:08\n\
+         FILE \"2.bin\" BINARY\n\
+         INDEX 01 00:00:00\n",
+    )
+    .unwrap();
+    assert_eq!(sheet.tracks[1].file, 1);
+    let spans = sheet.spans(&[10 * 2352, 4 * 2352]).unwrap();
+    assert_eq!(spans[0].end, 10 * 2352);
+    assert_eq!(spans[1].start, 10 * 2352);
+    assert_eq!(spans[1].lba, 10);
+}
+
+#[test]
+fn test_parse_errors() {
+    for (cue, message) in [
+        ("TRACK 01 MODE1/2048\n", "TRACK before FILE"),
+        (
+            "FILE \"a.bin\" BINARY\nINDEX 01 00:00:00\n",
+            "INDEX before TRACK",
+        ),
+        (
+            "FILE \"a.bin\" BINARY\nTRACK 01 AUDIO\nINDEX 01 00:61:00\n",
+            "bad INDEX time",
+        ),
+        (
+            "FILE \"a.bin\" BINARY\nTRACK 01 AUDIO\nINDEX 00 00:00:00\n",
+            "no INDEX 01",
+        ),
+        (
+            "FILE \"a.bin\" BINARY\nTRACK 02 AUDIO\nINDEX 01 00:00:00\nTRACK 01 AUDIO\n",
+            "must increase",
+        ),
+        ("REM nothing\n", "no tracks"),
+    ] {
+        let err = CueSheet::parse(cue).unwrap_err();
+        assert!(err.contains(message), "{}: {}", message, err);
+    }
+}
+
+#[test]
+fn test_track_beyond_end_of_file() {
+    let sheet = CueSheet::parse(MERGED_CUE).unwrap();
+    let err = sheet.spans(&[10 * 2352]).unwrap_err();
+    assert!(err.contains("beyond the end"), "{}", err);
+}
+
+#[test]
+fn test_gaps_beyond_largest_disc_address() {
+    // Each gap fits in 32 bits on its own, but not added together
+    let sheet = CueSheet::parse(
+        "FILE \"a.bin\" BINARY\n\
+         TRACK 01 AUDIO\n\
+         PREGAP 954437:00:00\n\
+         INDEX 01 00:00:00\n\
+         TRACK 02 AUDIO\n\
+         PREGAP 954437:00:00\n\
+         INDEX 01 00:00:01\n",
+    )
+    .unwrap();
+    let err = sheet.spans(&[2 * 2352]).unwrap_err();
+    assert!(err.contains("largest disc address"), "{}", err);
+}
+
+#[test]
+fn test_with_file_names_keeps_the_rest() {
+    let sheet = CueSheet::parse(SPLIT_CUE).unwrap();
+    let renamed = sheet.with_file_names(&["a.bin".to_string(), "b.bin".to_string()]);
+    assert_eq!(
+        renamed,
+        SPLIT_CUE
+            .replace("game (Track 1).bin", "a.bin")
+            .replace("game (Track 2).bin", "b.bin")
+    );
+    let reparsed = CueSheet::parse(&renamed).unwrap();
+    assert_eq!(reparsed.tracks, sheet.tracks);
+}
+
+#[test]
+fn test_device_reads_across_files() {
+    let parts: Vec<Box<dyn BlockDevice>> = vec![
+        Box::new(b"abc".to_vec()),
+        Box::new(Vec::new()),
+        Box::new(b"defgh".to_vec()),
+    ];
+    let device = CueDevice::new(parts);
+    assert_eq!(device.size(), 8);
+    assert_eq!(device.file_sizes(), vec![3, 0, 5]);
+
+    let mut buf = [0u8; 4];
+    device.read_at(1, &mut buf).unwrap();
+    assert_eq!(&buf, b"bcde");
+    assert!(device.read_at(5, &mut buf).is_err());
+}
+
+#[test]
+fn test_split_and_merged_dumps_read_as_the_same_disc() {
+    let dir = write_dumps("disc");
+    let disc = psx_disc();
+    for cue in ["game.cue", "merged/game.cue"] {
+        let image = DiscImage::open_path(&dir.join(cue)).unwrap();
+        assert_eq!(image.extension, "bin");
+        assert_eq!(read_all(&image).unwrap(), disc, "{}", cue);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_psx_hash_from_cue() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test_files/disc/psx.cue");
+    assert_eq!(compute_psx_hash(&fixture).unwrap(), PSX_HASH);
+
+    let dir = write_dumps("hash");
+    assert_eq!(compute_psx_hash(&dir.join("game.cue")).unwrap(), PSX_HASH);
+    assert_eq!(
+        compute_psx_hash(&dir.join("merged/game.cue")).unwrap(),
+        PSX_HASH
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unsupported_and_missing_files() {
+    let sheet =
+        CueSheet::parse("FILE \"track.wav\" WAVE\nTRACK 01 AUDIO\nINDEX 01 00:00:00\n").unwrap();
+    let err = CueDevice::open(&sheet, Path::new(".")).err().unwrap();
+    assert!(err.contains("WAVE"), "{}", err);
+
+    let sheet = CueSheet::parse("FILE \"missing.bin\" BINARY\nTRACK 01 AUDIO\nINDEX 01 00:00:00\n")
+        .unwrap();
+    assert!(CueDevice::open(&sheet, Path::new(".")).is_err());
+}
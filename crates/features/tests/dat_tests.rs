@@ -0,0 +1,170 @@
+//! DAT loading and matching tests
+
+#![cfg(feature = "dat")]
+
+use stitchr_features::dat::{Dat, DatIndex, clrmamepro, logiqx};
+
+const ROM_USA: &[u8] = b"chrono trigger usa";
+const ROM_JAPAN: &[u8] = b"chrono trigger japan";
+
+fn crc(data: &[u8]) -> String {
+    format!("{:08X}", crc32fast::hash(data))
+}
+
+fn sha1_hex(data: &[u8]) -> String {
+    use stitchr_features::validation::{HashAlgorithm, Validator};
+    // Hex digest text
+    String::from_utf8(Validator::compute(data, HashAlgorithm::Sha1)).unwrap()
+}
+
+fn logiqx_dat() -> String {
+    format!(
+        r#"<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd">
+<datafile>
+	<header>
+		<name>Nintendo - Super Nintendo Entertainment System</name>
+		<description>No-Intro &amp; friends</description>
+	</header>
+	<game name="Chrono Trigger (USA)">
+		<description>Chrono Trigger (USA)</description>
+		<rom name="Chrono Trigger (USA).sfc" size="{}" crc="{}" sha1="{}"/>
+	</game>
+	<game name="Chrono Trigger (Japan) &amp; &#x41;">
+		<rom name="Chrono Trigger (Japan).sfc" size="{}" crc="{}"/>
+	</game>
+</datafile>
+"#,
+        ROM_USA.len(),
+        crc(ROM_USA),
+        sha1_hex(ROM_USA),
+        ROM_JAPAN.len(),
+        crc(ROM_JAPAN),
+    )
+}
+
+fn clrmamepro_dat() -> String {
+    format!(
+        r#"clrmamepro (
+	name "Nintendo - Super Nintendo Entertainment System"
+	version 20240101
+)
+
+game (
+	name "Chrono Trigger (USA)"
+	description "Chrono Trigger (USA)"
+	rom ( name "Chrono Trigger (USA).sfc" size {} crc {} sha1 {} )
+)
+
+game (
+	name "Chrono Trigger (Japan)"
+	rom ( name "Chrono Trigger (Japan).sfc" size {} crc {} )
+)
+"#,
+        ROM_USA.len(),
+        crc(ROM_USA),
+        sha1_hex(ROM_USA),
+        ROM_JAPAN.len(),
+        crc(ROM_JAPAN),
+    )
+}
+
+#[test]
+fn test_parse_logiqx() {
+    let dat = logiqx::parse(&logiqx_dat()).unwrap();
+    assert_eq!(
+        dat.name.as_deref(),
+        Some("Nintendo - Super Nintendo Entertainment System")
+    );
+    assert_eq!(dat.entries.len(), 2);
+
+    let usa = &dat.entries[0];
+    assert_eq!(usa.game, "Chrono Trigger (USA)");
+    assert_eq!(usa.rom, "Chrono Trigger (USA).sfc");
+    assert_eq!(usa.size, Some(ROM_USA.len() as u64));
+    assert_eq!(usa.crc32, Some(crc32fast::hash(ROM_USA)));
+    assert!(usa.sha1.is_some());
+    assert_eq!(dat.entries[1].game, "Chrono Trigger (Japan) & A");
+}
+
+#[test]
+fn test_parse_clrmamepro() {
+    let dat = clrmamepro::parse(&clrmamepro_dat()).unwrap();
+    assert_eq!(
+        dat.name.as_deref(),
+        Some("Nintendo - Super Nintendo Entertainment System")
+    );
+    assert_eq!(dat.entries.len(), 2);
+    assert_eq!(dat.entries[1].game, "Chrono Trigger (Japan)");
+    assert_eq!(dat.entries[1].crc32, Some(crc32fast::hash(ROM_JAPAN)));
+}
+
+#[test]
+fn test_format_autodetect() {
+    assert_eq!(
+        Dat::parse(&logiqx_dat()).unwrap().entries.len(),
+        Dat::parse(&clrmamepro_dat()).unwrap().entries.len()
+    );
+}
+
+#[test]
+fn test_malformed() {
+    assert!(logiqx::parse("<html></html>").is_err());
+    assert!(clrmamepro::parse("game ( name \"x\"").is_err());
+    assert!(clrmamepro::parse("game ( name \"unterminated )").is_err());
+}
+
+#[test]
+fn test_identify() {
+    let mut index = DatIndex::new();
+    index.add(Dat::parse(&clrmamepro_dat()).unwrap());
+
+    assert_eq!(
+        index.identify(ROM_USA).unwrap().game,
+        "Chrono Trigger (USA)"
+    );
+    assert_eq!(
+        index.identify(ROM_JAPAN).unwrap().game,
+        "Chrono Trigger (Japan)"
+    );
+    assert!(index.identify(b"something else").is_none());
+}
+
+#[test]
+fn test_identify_rejects_crc_collision_by_sha1() {
+    let text = format!(
+        "game ( name \"Fake\" rom ( name \"f\" crc {} sha1 {} ) )",
+        crc(ROM_USA),
+        "00".repeat(20)
+    );
+    let mut index = DatIndex::new();
+    index.add(Dat::parse(&text).unwrap());
+    assert!(index.identify(ROM_USA).is_none());
+}
+
+#[test]
+fn test_find_checksum_from_patch_metadata() {
+    let mut index = DatIndex::new();
+    index.add(Dat::parse(&logiqx_dat()).unwrap());
+
+    // BPS/UPS store the source CRC32 little-endian
+    let checksum = crc32fast::hash(ROM_JAPAN).to_le_bytes();
+    let hits = index.find_checksum(&checksum);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].game.starts_with("Chrono Trigger (Japan)"));
+    assert!(index.find_checksum(&[1, 2, 3]).is_empty());
+}
+
+#[test]
+fn test_load_directory() {
+    let dir = std::env::temp_dir().join(format!("stitchr_dat_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.dat"), clrmamepro_dat()).unwrap();
+    std::fs::write(dir.join("b.xml"), logiqx_dat()).unwrap();
+    std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+    let index = DatIndex::load(&dir).unwrap();
+    assert_eq!(index.len(), 4);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
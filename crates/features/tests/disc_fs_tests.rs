@@ -0,0 +1,207 @@
+//! Tests for listing, extracting and replacing files inside disc images
+
+#![cfg(feature = "disc")]
+
+use stitchr_features::disc::ecc::regenerate;
+use stitchr_features::disc::{
+    BlockDevice, DiscImage, Iso9660Reader, IsoFormat, Overlay, copy_to, read_all, replace_file,
+};
+
+const PS2_ISO: &[u8] = include_bytes!("../../../test_files/disc/ps2.iso");
+const PSX_BIN: &[u8] = include_bytes!("../../../test_files/disc/psx.bin");
+const PSX_CHD: &[u8] = include_bytes!("../../../test_files/disc/psx.chd");
+
+const SECTOR: usize = 2048;
+const RAW_SECTOR: usize = 2352;
+
+fn read(image: &[u8], path: &str) -> Result<Vec<u8>, String> {
+    Iso9660Reader::new(&image)?.read_file_path(path)
+}
+
+#[test]
+fn test_list_tree() {
+    let iso = Iso9660Reader::new(&PS2_ISO).unwrap();
+    assert_eq!(iso.format(), IsoFormat::Standard);
+    let entries: Vec<_> = iso
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|entry| (entry.path, entry.is_dir, entry.lba, entry.size))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("DATA".to_string(), true, 21, 2048),
+            ("DATA/README.TXT".to_string(), false, 22, 920),
+            ("SLUS_000.01".to_string(), false, 23, 24000),
+            ("SYSTEM.CNF".to_string(), false, 35, 57),
+        ]
+    );
+}
+
+#[test]
+fn test_raw_and_compressed_list_the_same() {
+    let chd = DiscImage::open(Box::new(PSX_CHD.to_vec())).unwrap();
+    let from_chd = Iso9660Reader::new(&chd).unwrap().list().unwrap();
+    let from_bin = Iso9660Reader::new(&PSX_BIN).unwrap().list().unwrap();
+    assert_eq!(from_chd, from_bin);
+    assert_eq!(from_bin[0].name(), "SLUS_000.02");
+}
+
+#[test]
+fn test_find_ignores_case_version_and_separator() {
+    let iso = Iso9660Reader::new(&PS2_ISO).unwrap();
+    for path in [
+        "DATA/README.TXT",
+        "data/readme.txt",
+        "DATA\\README.TXT;1",
+        "/DATA//README.TXT",
+    ] {
+        assert_eq!(iso.find(path).unwrap().path, "DATA/README.TXT", "{}", path);
+    }
+    assert!(iso.find("DATA").unwrap().is_dir);
+
+    let err = iso.find("DATA/MISSING.TXT").unwrap_err();
+    assert!(err.contains("File not found"), "{}", err);
+    let err = iso.find("SYSTEM.CNF/README.TXT").unwrap_err();
+    assert!(err.contains("Directory not found"), "{}", err);
+    assert!(iso.read(&iso.find("DATA").unwrap()).is_err());
+}
+
+#[test]
+fn test_replace_in_standard_iso() {
+    let mut image = PS2_ISO.to_vec();
+    let replaced = replace_file(&mut image, "data/readme.txt", b"Translated\n").unwrap();
+    assert_eq!(
+        (replaced.path.as_str(), replaced.old_size, replaced.new_size),
+        ("DATA/README.TXT", 920, 11)
+    );
+    assert_eq!(replaced.allocated, SECTOR);
+    assert_eq!(read(&image, "DATA/README.TXT").unwrap(), b"Translated\n");
+    // The slack after the new contents is cleared
+    assert!(image[22 * SECTOR + 11..23 * SECTOR].iter().all(|&b| b == 0));
+    // Nothing else moved
+    assert_eq!(image.len(), PS2_ISO.len());
+    assert_eq!(read(&image, "SLUS_000.01"), read(PS2_ISO, "SLUS_000.01"));
+    assert_eq!(read(&image, "SYSTEM.CNF"), read(PS2_ISO, "SYSTEM.CNF"));
+
+    // Growing up to the allocated sectors
+    let full = vec![0x5Au8; SECTOR];
+    replace_file(&mut image, "DATA/README.TXT", &full).unwrap();
+    assert_eq!(read(&image, "DATA/README.TXT").unwrap(), full);
+}
+
+#[test]
+fn test_replace_too_large() {
+    let mut image = PS2_ISO.to_vec();
+    let err = replace_file(&mut image, "SLUS_000.01", &vec![0u8; 12 * SECTOR + 1]).unwrap_err();
+    assert!(err.contains("24576 bytes are allocated"), "{}", err);
+    assert_eq!(image, PS2_ISO);
+
+    let err = replace_file(&mut image, "DATA", b"").unwrap_err();
+    assert!(err.contains("is a directory"), "{}", err);
+}
+
+#[test]
+fn test_replace_in_raw_image_regenerates_ecc() {
+    let mut image = PSX_BIN.to_vec();
+    let exe = read(PSX_BIN, "SLUS_000.02").unwrap();
+    let mut patched = exe.clone();
+    patched[100..104].copy_from_slice(b"XXXX");
+    patched.truncate(9000);
+
+    let replaced = replace_file(&mut image, "SLUS_000.02", &patched).unwrap();
+    assert_eq!(replaced.sectors, 5 + 1);
+    assert_eq!(read(&image, "SLUS_000.02").unwrap(), patched);
+
+    // Every sector carries valid EDC/ECC
+    for sector in image.chunks(RAW_SECTOR) {
+        let mut regenerated = sector.to_vec();
+        assert!(regenerate(&mut regenerated).is_some());
+        assert_eq!(regenerated, sector);
+    }
+    // Only the directory record's sector, the patched bytes and the cut-off
+    // tail changed; rewritten sectors with the same data are identical
+    let changed: Vec<usize> = (0..image.len() / RAW_SECTOR)
+        .filter(|&i| {
+            let range = i * RAW_SECTOR..(i + 1) * RAW_SECTOR;
+            image[range.clone()] != PSX_BIN[range]
+        })
+        .collect();
+    let root_lba = Iso9660Reader::new(&PSX_BIN).unwrap().root().unwrap().lba as usize;
+    assert_eq!(changed, vec![root_lba, 21, 25]);
+}
+
+#[test]
+fn test_decompressed_image_is_patchable() {
+    let chd = DiscImage::open(Box::new(PSX_CHD.to_vec())).unwrap();
+    let mut disc = read_all(&chd).unwrap();
+    replace_file(&mut disc, "SYSTEM.CNF", b"BOOT = cdrom:\\SLUS_000.02;1\r\n").unwrap();
+    assert_eq!(
+        read(&disc, "SYSTEM.CNF").unwrap(),
+        b"BOOT = cdrom:\\SLUS_000.02;1\r\n"
+    );
+}
+
+#[test]
+fn test_replace_through_overlay_matches_in_memory() {
+    let contents = b"BOOT = cdrom:\\SLUS_000.02;1\r\n";
+    let mut expected = read_all(&DiscImage::open(Box::new(PSX_CHD.to_vec())).unwrap()).unwrap();
+    replace_file(&mut expected, "SYSTEM.CNF", contents).unwrap();
+
+    // Only the rewritten sectors are held; the rest still comes from the CHD
+    let chd = DiscImage::open(Box::new(PSX_CHD.to_vec())).unwrap();
+    let mut overlay = Overlay::new(chd);
+    replace_file(&mut overlay, "SYSTEM.CNF", contents).unwrap();
+    let mut disc = Vec::new();
+    copy_to(&overlay, 0..overlay.size(), &mut disc).unwrap();
+    assert_eq!(disc, expected);
+
+    let mut tail = Vec::new();
+    copy_to(&overlay, 100..2500, &mut tail).unwrap();
+    assert_eq!(tail, expected[100..2500]);
+}
+
+/// The PS2 test ISO with a Joliet descriptor whose root directory is a copy
+/// of the primary one, appended as a last sector
+fn joliet_iso() -> (Vec<u8>, usize) {
+    let mut image = PS2_ISO.to_vec();
+    let joliet_root = image.len() / SECTOR;
+    let root = Iso9660Reader::new(&PS2_ISO).unwrap().root().unwrap().lba as usize;
+    image.extend_from_within(root * SECTOR..(root + 1) * SECTOR);
+
+    // Supplementary descriptor at 17, the terminator moved over the path
+    // table at 18
+    image.copy_within(17 * SECTOR..18 * SECTOR, 18 * SECTOR);
+    image.copy_within(16 * SECTOR..17 * SECTOR, 17 * SECTOR);
+    let svd = 17 * SECTOR;
+    image[svd] = 2;
+    image[svd + 88..svd + 91].copy_from_slice(b"%/E");
+    image[svd + 156 + 2..svd + 156 + 6].copy_from_slice(&(joliet_root as u32).to_le_bytes());
+    (image, joliet_root)
+}
+
+#[test]
+fn test_replace_updates_joliet_record() {
+    let (mut image, joliet_root) = joliet_iso();
+    let iso = Iso9660Reader::new(&image).unwrap();
+    let root = iso.joliet_root().unwrap().unwrap();
+    assert_eq!(root.lba as usize, joliet_root);
+    assert!(
+        Iso9660Reader::new(&PS2_ISO)
+            .unwrap()
+            .joliet_root()
+            .unwrap()
+            .is_none()
+    );
+
+    let replaced = replace_file(&mut image, "SYSTEM.CNF", b"BOOT2 = cdrom0:\\X;1\n").unwrap();
+    // The file's sector and both root directories
+    assert_eq!(replaced.sectors, 3);
+    let iso = Iso9660Reader::new(&image).unwrap();
+    let root = iso.joliet_root().unwrap().unwrap();
+    let joliet = iso.read_dir(&root).unwrap();
+    let record = joliet.iter().find(|entry| entry.lba == 35).unwrap();
+    assert_eq!(record.size, 20);
+    assert_eq!(iso.find("SYSTEM.CNF").unwrap().size, 20);
+}
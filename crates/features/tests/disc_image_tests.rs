@@ -0,0 +1,213 @@
+//! Tests for compressed disc images (CSO, ZSO, CHD)
+
+#![cfg(feature = "retroachievements")]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use stitchr_features::disc::{BlockDevice, DiscImage, ImageFormat, Iso9660Reader, read_all};
+use stitchr_features::retroachievements::{compute_ps2_hash, compute_psx_hash};
+
+const PS2_ISO: &[u8] = include_bytes!("../../../test_files/disc/ps2.iso");
+const PS2_CSO: &[u8] = include_bytes!("../../../test_files/disc/ps2.cso");
+const PS2_ZSO: &[u8] = include_bytes!("../../../test_files/disc/ps2.zso");
+const PS2_CHD: &[u8] = include_bytes!("../../../test_files/disc/ps2.chd");
+const PSX_BIN: &[u8] = include_bytes!("../../../test_files/disc/psx.bin");
+const PSX_CHD: &[u8] = include_bytes!("../../../test_files/disc/psx.chd");
+const PSX_CDFL_CHD: &[u8] = include_bytes!("../../../test_files/disc/psx_cdfl.chd");
+const MODE1_CHD: &[u8] = include_bytes!("../../../test_files/disc/mode1.chd");
+const AUDIO_CHD: &[u8] = include_bytes!("../../../test_files/disc/audio.chd");
+
+const PS2_HASH: &str = "3df79c8a2551801e40ea9f09d562ae1d";
+const PSX_HASH: &str = "0e713b6b1b9548c071b95d8da29bccaf";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test_files/disc")
+        .join(name)
+}
+
+fn open(data: &[u8]) -> DiscImage {
+    DiscImage::open(Box::new(data.to_vec())).unwrap()
+}
+
+#[test]
+fn test_detect_format() {
+    assert_eq!(ImageFormat::detect(PS2_ISO), ImageFormat::Plain);
+    assert_eq!(ImageFormat::detect(PS2_CSO), ImageFormat::Cso);
+    assert_eq!(ImageFormat::detect(PS2_ZSO), ImageFormat::Zso);
+    assert_eq!(ImageFormat::detect(PS2_CHD), ImageFormat::Chd);
+}
+
+#[test]
+fn test_cso_and_zso_decode_to_iso() {
+    for data in [PS2_CSO, PS2_ZSO] {
+        let image = open(data);
+        assert_eq!(image.extension, "iso");
+        assert_eq!(image.size(), PS2_ISO.len() as u64);
+        assert_eq!(read_all(&image).unwrap(), PS2_ISO);
+    }
+}
+
+#[test]
+fn test_cso_v2() {
+    // Deflate blocks, stored where compression does not help
+    let block_size = 2048;
+    let blocks = PS2_ISO.len() / block_size;
+    let mut header = b"CISO".to_vec();
+    header.extend_from_slice(&24u32.to_le_bytes());
+    header.extend_from_slice(&(PS2_ISO.len() as u64).to_le_bytes());
+    header.extend_from_slice(&(block_size as u32).to_le_bytes());
+    header.extend_from_slice(&[2, 0, 0, 0]);
+
+    let mut index = Vec::new();
+    let mut body = Vec::new();
+    let start = header.len() + 4 * (blocks + 1);
+    for block in PS2_ISO.chunks(block_size) {
+        index.push((start + body.len()) as u32);
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(block).unwrap();
+        let compressed = encoder.finish().unwrap();
+        if compressed.len() < block.len() {
+            body.extend_from_slice(&compressed);
+        } else {
+            body.extend_from_slice(block);
+        }
+    }
+    index.push((start + body.len()) as u32);
+
+    let mut cso = header;
+    for entry in index {
+        cso.extend_from_slice(&entry.to_le_bytes());
+    }
+    cso.extend_from_slice(&body);
+    assert_eq!(read_all(&open(&cso)).unwrap(), PS2_ISO);
+}
+
+#[test]
+fn test_dvd_chd_decodes_to_iso() {
+    let image = open(PS2_CHD);
+    assert_eq!(image.format, ImageFormat::Chd);
+    assert_eq!(image.extension, "iso");
+    assert_eq!(read_all(&image).unwrap(), PS2_ISO);
+}
+
+#[test]
+fn test_cd_chd_exposes_tracks_like_extractcd() {
+    let image = open(PSX_CHD);
+    assert_eq!(image.extension, "bin");
+    let data = read_all(&image).unwrap();
+    // Data track, then the audio track with its samples swapped back to
+    // little-endian
+    assert_eq!(&data[..PSX_BIN.len()], PSX_BIN);
+    assert_eq!(data.len(), PSX_BIN.len() + 16 * 2352);
+    assert_eq!(crc32fast::hash(&data[PSX_BIN.len()..]), 0x157984a6);
+}
+
+#[test]
+fn test_cd_chd_flac_audio_hunks() {
+    // The audio track's hunks are `cdfl`, the data track's `cdlz`
+    let data = read_all(&open(PSX_CDFL_CHD)).unwrap();
+    assert_eq!(data, read_all(&open(PSX_CHD)).unwrap());
+    assert_eq!(crc32fast::hash(&data[PSX_BIN.len()..]), 0x157984a6);
+}
+
+#[test]
+fn test_flac_chd_hunks() {
+    // The same audio as a plain image of little-endian `flac` hunks
+    let image = open(AUDIO_CHD);
+    assert_eq!(image.format, ImageFormat::Chd);
+    let data = read_all(&image).unwrap();
+    assert_eq!(data.len(), 16 * 2352);
+    assert_eq!(crc32fast::hash(&data), 0x157984a6);
+}
+
+#[test]
+fn test_cd_chd_regenerates_sync_and_ecc() {
+    let data = read_all(&open(MODE1_CHD)).unwrap();
+    assert_eq!(data.len(), 12 * 2352);
+    assert_eq!(crc32fast::hash(&data), 0x559b0a95);
+}
+
+#[test]
+fn test_unaligned_reads() {
+    let image = open(PSX_CHD);
+    let mut buf = vec![0u8; 5000];
+    image.read_at(2000, &mut buf).unwrap();
+    assert_eq!(buf, &PSX_BIN[2000..7000]);
+
+    let mut past_end = [0u8; 2];
+    assert!(image.read_at(image.size() - 1, &mut past_end).is_err());
+}
+
+#[test]
+fn test_reader_walks_compressed_images() {
+    for data in [PS2_CSO, PS2_ZSO, PS2_CHD] {
+        let image = open(data);
+        let iso = Iso9660Reader::new(&image).unwrap();
+        let readme = iso.read_file_path("DATA/README.TXT").unwrap();
+        assert!(readme.starts_with(b"Test disc for stitchr.\n"));
+    }
+}
+
+#[test]
+fn test_ps2_hash_matches_across_containers() {
+    for name in ["ps2.iso", "ps2.cso", "ps2.zso", "ps2.chd"] {
+        assert_eq!(
+            compute_ps2_hash(&fixture(name)).unwrap(),
+            PS2_HASH,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_psx_hash_from_chd() {
+    assert_eq!(compute_psx_hash(&fixture("psx.bin")).unwrap(), PSX_HASH);
+    assert_eq!(compute_psx_hash(&fixture("psx.chd")).unwrap(), PSX_HASH);
+}
+
+#[test]
+fn test_corrupt_chd_hunk_fails_crc() {
+    // Flip a byte inside the stored (uncompressed) hunk of the CD image
+    let mut chd = PSX_CHD.to_vec();
+    let data = read_all(&open(&chd)).unwrap();
+    let hunk = 2 * 8 * 2352;
+    let position = chd
+        .windows(64)
+        .position(|w| w == &data[hunk + 100..hunk + 164])
+        .unwrap();
+    chd[position] ^= 0xFF;
+    let err = read_all(&open(&chd)).unwrap_err();
+    assert!(err.contains("CRC"), "{}", err);
+}
+
+#[test]
+fn test_unsupported_chd() {
+    let mut chd = PS2_CHD.to_vec();
+    chd[15] = 4;
+    assert!(DiscImage::open(Box::new(chd)).is_err());
+
+    // Depends on a parent image
+    let mut chd = PS2_CHD.to_vec();
+    chd[110] = 1;
+    let err = DiscImage::open(Box::new(chd)).err().unwrap();
+    assert!(err.contains("parent"), "{}", err);
+}
+
+#[test]
+fn test_truncated_cso() {
+    assert!(DiscImage::open(Box::new(PS2_CSO[..40].to_vec())).is_err());
+}
+
+#[test]
+fn test_cso_block_count_overflow() {
+    // A 2^64 - 1 byte image in 1-byte blocks: the index size overflows
+    let mut data = PS2_CSO[..64].to_vec();
+    data[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+    data[16..20].copy_from_slice(&1u32.to_le_bytes());
+    assert!(DiscImage::open(Box::new(data)).is_err());
+}
@@ -0,0 +1,152 @@
+//! Tests for EDC/ECC regeneration of raw CD sectors
+
+#![cfg(feature = "disc")]
+
+use stitchr_features::disc::ecc::{
+    SYNC, SectorFixes, SectorMode, edc, fix_modified_sectors, is_raw_image, regenerate,
+    stale_sectors,
+};
+use stitchr_features::disc::{DiscImage, read_all};
+
+const PSX_BIN: &[u8] = include_bytes!("../../../test_files/disc/psx.bin");
+const PS2_ISO: &[u8] = include_bytes!("../../../test_files/disc/ps2.iso");
+const MODE1_CHD: &[u8] = include_bytes!("../../../test_files/disc/mode1.chd");
+
+const SECTOR: usize = 2352;
+
+fn mode1_image() -> Vec<u8> {
+    read_all(&DiscImage::open(Box::new(MODE1_CHD.to_vec())).unwrap()).unwrap()
+}
+
+fn form2_sector(edc_used: bool) -> Vec<u8> {
+    let mut sector = vec![0u8; SECTOR];
+    sector[..12].copy_from_slice(&SYNC);
+    sector[12..16].copy_from_slice(&[0x00, 0x02, 0x16, 2]);
+    sector[16..24].copy_from_slice(&[0, 0, 0x20, 0, 0, 0, 0x20, 0]);
+    for (i, byte) in sector[24..0x92C].iter_mut().enumerate() {
+        *byte = (i * 7) as u8;
+    }
+    if edc_used {
+        sector[0x92C..].copy_from_slice(&[1, 2, 3, 4]);
+    }
+    sector
+}
+
+#[test]
+fn test_intact_sectors_are_unchanged() {
+    for (image, mode) in [
+        (PSX_BIN.to_vec(), SectorMode::Mode2Form1),
+        (mode1_image(), SectorMode::Mode1),
+    ] {
+        for sector in image.chunks(SECTOR) {
+            let mut regenerated = sector.to_vec();
+            assert_eq!(regenerate(&mut regenerated), Some(mode));
+            assert_eq!(regenerated, sector);
+        }
+    }
+}
+
+#[test]
+fn test_fix_modified_mode2_form1() {
+    let mut patched = PSX_BIN.to_vec();
+    patched[3 * SECTOR + 100] ^= 0xFF;
+    patched[3 * SECTOR + 200] ^= 0xFF;
+    patched[20 * SECTOR + 24] ^= 0x01;
+    assert_eq!(stale_sectors(PSX_BIN, &patched), 2);
+
+    let fixes = fix_modified_sectors(PSX_BIN, &mut patched);
+    assert_eq!(
+        fixes,
+        SectorFixes {
+            modified: 2,
+            mode2_form1: 2,
+            ..Default::default()
+        }
+    );
+    assert_eq!(stale_sectors(PSX_BIN, &patched), 0);
+    // User data around the patched bytes is left as it was
+    assert_eq!(
+        &patched[3 * SECTOR + 24..3 * SECTOR + 100],
+        &PSX_BIN[3 * SECTOR + 24..3 * SECTOR + 100]
+    );
+
+    // Undoing the patch restores the original sectors exactly
+    patched[3 * SECTOR + 100] ^= 0xFF;
+    patched[3 * SECTOR + 200] ^= 0xFF;
+    patched[20 * SECTOR + 24] ^= 0x01;
+    fix_modified_sectors(PSX_BIN, &mut patched);
+    assert_eq!(patched, PSX_BIN);
+}
+
+#[test]
+fn test_fix_modified_mode1() {
+    let original = mode1_image();
+    let mut patched = original.clone();
+    patched[5 * SECTOR + 16..5 * SECTOR + 32].fill(0xAA);
+    let fixes = fix_modified_sectors(&original, &mut patched);
+    assert_eq!(fixes.mode1, 1);
+    assert_eq!(fixes.fixed(), 1);
+    assert_eq!(
+        fixes.to_string(),
+        "fixed 1 of 1 modified data sectors (1 Mode 1)"
+    );
+
+    patched[5 * SECTOR + 16..5 * SECTOR + 32]
+        .copy_from_slice(&original[5 * SECTOR + 16..5 * SECTOR + 32]);
+    fix_modified_sectors(&original, &mut patched);
+    assert_eq!(patched, original);
+}
+
+#[test]
+fn test_form2_gets_edc_only() {
+    let mut sector = form2_sector(true);
+    let before = sector.clone();
+    assert_eq!(regenerate(&mut sector), Some(SectorMode::Mode2Form2));
+    // Subheader and data through the EDC check out; nothing else changed
+    assert_eq!(edc(&sector[0x10..0x930]), 0);
+    assert_eq!(sector[..0x92C], before[..0x92C]);
+
+    // A zero EDC means the disc does not use it
+    let mut unused = form2_sector(false);
+    let before = unused.clone();
+    regenerate(&mut unused);
+    assert_eq!(unused, before);
+}
+
+#[test]
+fn test_form2_counted_separately() {
+    let original = [form2_sector(true), PSX_BIN[..SECTOR].to_vec()].concat();
+    let mut fixed = original.clone();
+    regenerate(&mut fixed[..SECTOR]);
+    let mut patched = fixed.clone();
+    patched[500] ^= 0x55;
+    let fixes = fix_modified_sectors(&fixed, &mut patched);
+    assert_eq!(
+        fixes,
+        SectorFixes {
+            modified: 1,
+            mode2_form2: 1,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_non_data_sectors_are_skipped() {
+    // Audio and appended data without sync are left alone
+    let mut patched = PSX_BIN.to_vec();
+    patched.extend(vec![0x11u8; 2 * SECTOR]);
+    let before = patched.clone();
+    let fixes = fix_modified_sectors(PSX_BIN, &mut patched);
+    assert_eq!(fixes, SectorFixes::default());
+    assert_eq!(patched, before);
+}
+
+#[test]
+fn test_raw_image_detection() {
+    assert!(is_raw_image(PSX_BIN));
+    assert!(is_raw_image(&mode1_image()));
+    assert!(!is_raw_image(PS2_ISO));
+    assert!(!is_raw_image(&vec![0u8; 4 * SECTOR]));
+    assert!(!is_raw_image(&PSX_BIN[..SECTOR * 2 + 1]));
+}
@@ -0,0 +1,338 @@
+//! ROM identification tests
+
+#![cfg(feature = "rom_info")]
+
+use stitchr_features::rom_info::{self, Console, Region};
+
+fn nes_header(flags6: u8, flags7: u8, byte8: u8, byte9: u8, byte12: u8) -> Vec<u8> {
+    let mut rom = vec![0u8; 16 + 0x8000];
+    rom[..4].copy_from_slice(b"NES\x1A");
+    rom[4] = 2;
+    rom[6] = flags6;
+    rom[7] = flags7;
+    rom[8] = byte8;
+    rom[9] = byte9;
+    rom[12] = byte12;
+    rom
+}
+
+/// SNES ROM with an internal header at `offset`
+fn snes_rom(size: usize, offset: usize, map_mode: u8) -> Vec<u8> {
+    let mut rom = vec![0u8; size];
+    let header = &mut rom[offset..offset + 0x20];
+    header[..21].copy_from_slice(b"SUPER TEST           ");
+    header[0x15] = map_mode;
+    header[0x19] = 0x01;
+    header[0x1B] = 0x02;
+    header[0x1C..0x20].copy_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+    rom
+}
+
+#[test]
+fn test_ines() {
+    // Mapper 4, PAL
+    let info = rom_info::identify(&nes_header(0x40, 0x00, 0, 1, 0)).unwrap();
+    assert_eq!(info.console, Console::Nes);
+    assert_eq!(info.mapper.as_deref(), Some("4 (MMC3)"));
+    assert_eq!(info.region, Some(Region::Europe));
+    assert_eq!(info.title, None);
+}
+
+#[test]
+fn test_nes2() {
+    // Mapper 0x10A (high nibble in byte 8), submapper 3, multi-region
+    let info = rom_info::identify(&nes_header(0xA0, 0x08, 0x31, 0, 2)).unwrap();
+    assert_eq!(info.mapper.as_deref(), Some("266.3"));
+    assert_eq!(info.region, Some(Region::World));
+
+    let info = rom_info::identify(&nes_header(0x10, 0x08, 0x00, 0, 0)).unwrap();
+    assert_eq!(info.mapper.as_deref(), Some("1.0 (MMC1)"));
+    assert_eq!(info.region, None);
+}
+
+#[test]
+fn test_snes_lorom_and_hirom() {
+    let info = rom_info::identify(&snes_rom(0x10000, 0x7FC0, 0x20)).unwrap();
+    assert_eq!(info.console, Console::Snes);
+    assert_eq!(info.title.as_deref(), Some("SUPER TEST"));
+    assert_eq!(info.mapper.as_deref(), Some("LoROM"));
+    assert_eq!(info.region, Some(Region::Usa));
+    assert_eq!(info.revision, Some(2));
+
+    let info = rom_info::identify(&snes_rom(0x10000, 0xFFC0, 0x31)).unwrap();
+    assert_eq!(info.mapper.as_deref(), Some("HiROM, FastROM"));
+}
+
+#[test]
+fn test_snes_copier_header_and_game_code() {
+    let mut rom = snes_rom(0x10000, 0xFFC0, 0x21);
+    rom[0xFFC0 + 0x1A] = 0x33;
+    rom[0xFFB2..0xFFB6].copy_from_slice(b"AXYJ");
+    let mut headered = vec![0u8; 512];
+    headered.extend_from_slice(&rom);
+
+    let info = rom_info::identify(&headered).unwrap();
+    assert_eq!(info.game_code.as_deref(), Some("AXYJ"));
+    assert_eq!(info.mapper.as_deref(), Some("HiROM"));
+}
+
+#[test]
+fn test_snes_katakana_title() {
+    let mut rom = snes_rom(0x8000, 0x7FC0, 0x20);
+    rom[0x7FC0..0x7FC3].copy_from_slice(&[0xB1, 0xB2, 0xB3]);
+    let info = rom_info::identify(&rom).unwrap();
+    assert!(info.title.unwrap().starts_with("ｱｲｳ"));
+}
+
+#[test]
+fn test_game_boy() {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x104..0x108].copy_from_slice(&[0xCE, 0xED, 0x66, 0x66]);
+    rom[0x134..0x13D].copy_from_slice(b"TETRIS\0\0\0");
+    rom[0x147] = 0x13;
+    rom[0x14C] = 1;
+
+    let info = rom_info::identify(&rom).unwrap();
+    assert_eq!(info.console, Console::GameBoy);
+    assert_eq!(info.title.as_deref(), Some("TETRIS"));
+    assert_eq!(info.mapper.as_deref(), Some("MBC3+RAM+BATTERY"));
+    assert_eq!(info.region, Some(Region::Japan));
+    assert_eq!(info.revision, Some(1));
+
+    // Color cart with a manufacturer code
+    rom[0x134..0x13F].copy_from_slice(b"POKEMON CRY");
+    rom[0x13F..0x143].copy_from_slice(b"BXTE");
+    rom[0x143] = 0xC0;
+    rom[0x14A] = 1;
+    let info = rom_info::identify(&rom).unwrap();
+    assert_eq!(info.console, Console::GameBoyColor);
+    assert_eq!(info.title.as_deref(), Some("POKEMON CRY"));
+    assert_eq!(info.game_code.as_deref(), Some("BXTE"));
+    assert_eq!(info.region, Some(Region::Other("Overseas")));
+}
+
+#[test]
+fn test_gba() {
+    let mut rom = vec![0u8; 0x200];
+    rom[0x04..0x08].copy_from_slice(&[0x24, 0xFF, 0xAE, 0x51]);
+    rom[0xA0..0xAC].copy_from_slice(b"POKEMON EMER");
+    rom[0xAC..0xB0].copy_from_slice(b"BPEE");
+    rom[0xB2] = 0x96;
+
+    let info = rom_info::identify(&rom).unwrap();
+    assert_eq!(info.console, Console::Gba);
+    assert_eq!(info.title.as_deref(), Some("POKEMON EMER"));
+    assert_eq!(info.game_code.as_deref(), Some("BPEE"));
+    assert_eq!(info.region, Some(Region::Usa));
+}
+
+#[test]
+fn test_nds() {
+    let mut rom = vec![0u8; 0x200];
+    rom[..6].copy_from_slice(b"MARIO ");
+    rom[0x0C..0x10].copy_from_slice(b"AMCP");
+    rom[0x1E] = 1;
+    rom[0x15C..0x15E].copy_from_slice(&0xCF56u16.to_le_bytes());
+
+    let info = rom_info::identify(&rom).unwrap();
+    assert_eq!(info.console, Console::Nds);
+    assert_eq!(info.title.as_deref(), Some("MARIO"));
+    assert_eq!(info.game_code.as_deref(), Some("AMCP"));
+    assert_eq!(info.region, Some(Region::Europe));
+    assert_eq!(info.revision, Some(1));
+}
+
+#[test]
+fn test_n64_any_byte_order() {
+    let mut z64 = vec![0u8; 0x1000];
+    z64[..4].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+    z64[0x20..0x34].copy_from_slice(b"SUPER MARIO 64      ");
+    z64[0x3B..0x3F].copy_from_slice(b"NSMJ");
+    z64[0x3F] = 3;
+    let v64: Vec<u8> = z64.chunks_exact(2).flat_map(|w| [w[1], w[0]]).collect();
+    let n64: Vec<u8> = z64
+        .chunks_exact(4)
+        .flat_map(|w| [w[3], w[2], w[1], w[0]])
+        .collect();
+
+    for rom in [z64, v64, n64] {
+        let info = rom_info::identify(&rom).unwrap();
+        assert_eq!(info.console, Console::N64);
+        assert_eq!(info.title.as_deref(), Some("SUPER MARIO 64"));
+        assert_eq!(info.game_code.as_deref(), Some("NSMJ"));
+        assert_eq!(info.region, Some(Region::Japan));
+        assert_eq!(info.revision, Some(3));
+    }
+}
+
+#[test]
+fn test_megadrive() {
+    let mut rom = vec![0x20u8; 0x400];
+    rom[0x100..0x110].copy_from_slice(b"SEGA GENESIS    ");
+    rom[0x150..0x15B].copy_from_slice(b"SONIC THE H");
+    rom[0x180..0x18E].copy_from_slice(b"GM 00001009-01");
+    rom[0x1F0..0x1F3].copy_from_slice(b"JUE");
+
+    let info = rom_info::identify(&rom).unwrap();
+    assert_eq!(info.console, Console::MegaDrive);
+    assert_eq!(info.title.as_deref(), Some("SONIC THE H"));
+    assert_eq!(info.game_code.as_deref(), Some("GM 00001009"));
+    assert_eq!(info.revision, Some(1));
+    assert_eq!(info.region, Some(Region::World));
+
+    // New-style hex region code: Americas only
+    rom[0x1F0..0x1F3].copy_from_slice(b"4  ");
+    assert_eq!(rom_info::identify(&rom).unwrap().region, Some(Region::Usa));
+    rom[0x1F0..0x1F3].copy_from_slice(b"E  ");
+    assert_eq!(
+        rom_info::identify(&rom).unwrap().region,
+        Some(Region::Europe)
+    );
+}
+
+#[test]
+fn test_sms_and_game_gear() {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x7FF0..0x7FF8].copy_from_slice(b"TMR SEGA");
+    rom[0x7FFC] = 0x01;
+    rom[0x7FFD] = 0x70;
+    rom[0x7FFE] = 0x02;
+    rom[0x7FFF] = 0x4C;
+
+    let info = rom_info::identify(&rom).unwrap();
+    assert_eq!(info.console, Console::MasterSystem);
+    assert_eq!(info.game_code.as_deref(), Some("7001"));
+    assert_eq!(info.revision, Some(2));
+    assert_eq!(info.region, Some(Region::Other("Export")));
+
+    rom[0x7FFF] = 0x7C;
+    let info = rom_info::identify(&rom).unwrap();
+    assert_eq!(info.console, Console::GameGear);
+    assert_eq!(info.region, Some(Region::World));
+}
+
+#[test]
+fn test_pc_engine() {
+    let mut rom = vec![0xFFu8; 0x40000];
+    // Reset vector 0xE010 -> SEI at 0x0010
+    rom[0x1FFE..0x2000].copy_from_slice(&0xE010u16.to_le_bytes());
+    rom[0x10] = 0x78;
+    assert_eq!(rom_info::detect_console(&rom), Some(Console::PcEngine));
+
+    rom[0x10] = 0xEA;
+    assert_eq!(rom_info::detect_console(&rom), None);
+}
+
+/// Minimal 2048-byte-sector ISO9660 image with one root file
+fn iso_image(system_id: &str, volume_id: &str, file: (&str, &[u8])) -> Vec<u8> {
+    let mut image = vec![0u8; 24 * 2048];
+    let pvd = &mut image[16 * 2048..17 * 2048];
+    pvd[0] = 1;
+    pvd[1..6].copy_from_slice(b"CD001");
+    pvd[8..40].fill(b' ');
+    pvd[8..8 + system_id.len()].copy_from_slice(system_id.as_bytes());
+    pvd[40..72].fill(b' ');
+    pvd[40..40 + volume_id.len()].copy_from_slice(volume_id.as_bytes());
+    // Root directory at sector 20, one sector long
+    pvd[156] = 34;
+    pvd[158..162].copy_from_slice(&20u32.to_le_bytes());
+    pvd[166..170].copy_from_slice(&2048u32.to_le_bytes());
+
+    let (name, data) = file;
+    let name = format!("{};1", name);
+    let root = &mut image[20 * 2048..21 * 2048];
+    let len = 33 + name.len() + (name.len() + 1) % 2;
+    root[0] = len as u8;
+    root[2..6].copy_from_slice(&21u32.to_le_bytes());
+    root[10..14].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    root[32] = name.len() as u8;
+    root[33..33 + name.len()].copy_from_slice(name.as_bytes());
+    image[21 * 2048..21 * 2048 + data.len()].copy_from_slice(data);
+    image
+}
+
+/// Convert a 2048-byte-sector image to raw Mode 2 (2352-byte sectors)
+fn to_raw(image: &[u8]) -> Vec<u8> {
+    image
+        .chunks(2048)
+        .flat_map(|sector| {
+            let mut raw = vec![0u8; 2352];
+            raw[24..24 + 2048].copy_from_slice(sector);
+            raw
+        })
+        .collect()
+}
+
+#[test]
+fn test_playstation_discs() {
+    let psx = iso_image(
+        "PLAYSTATION",
+        "FF7",
+        ("SYSTEM.CNF", b"BOOT = cdrom:\\SCUS_941.63;1\r\nTCB = 4\r\n"),
+    );
+    for image in [psx.clone(), to_raw(&psx)] {
+        let info = rom_info::identify(&image).unwrap();
+        assert_eq!(info.console, Console::Psx);
+        assert_eq!(info.title.as_deref(), Some("FF7"));
+        assert_eq!(info.game_code.as_deref(), Some("SCUS-94163"));
+        assert_eq!(info.region, Some(Region::Usa));
+    }
+
+    let ps2 = iso_image(
+        "PLAYSTATION",
+        "SLES_123.45",
+        (
+            "SYSTEM.CNF",
+            b"BOOT2 = cdrom0:\\SLES_123.45;1\nVER = 1.00\n",
+        ),
+    );
+    let info = rom_info::identify(&ps2).unwrap();
+    assert_eq!(info.console, Console::Ps2);
+    assert_eq!(info.game_code.as_deref(), Some("SLES-12345"));
+    assert_eq!(info.region, Some(Region::Europe));
+}
+
+#[test]
+fn test_psp_and_generic_iso() {
+    let psp = iso_image("PSP GAME", "UMD", ("UMD_DATA.BIN", b"ULJS-00001|0001|G"));
+    let info = rom_info::identify(&psp).unwrap();
+    assert_eq!(info.console, Console::Psp);
+    assert_eq!(info.game_code.as_deref(), Some("ULJS-00001"));
+    assert_eq!(info.region, Some(Region::Japan));
+
+    let other = iso_image("LINUX", "DATA DISC", ("README.TXT", b"hi"));
+    let info = rom_info::identify(&other).unwrap();
+    assert_eq!(info.console, Console::Iso9660);
+    assert_eq!(info.title.as_deref(), Some("DATA DISC"));
+}
+
+#[test]
+fn test_saturn_system_area() {
+    let mut image = vec![b' '; 0x8000];
+    image[..16].copy_from_slice(b"SEGA SEGASATURN ");
+    image[0x20..0x29].copy_from_slice(b"MK-81009 ");
+    image[0x2A..0x30].copy_from_slice(b"V1.002");
+    image[0x40] = b'U';
+    image[0x60..0x6B].copy_from_slice(b"NIGHTS INTO");
+
+    let info = rom_info::identify(&image).unwrap();
+    assert_eq!(info.console, Console::Saturn);
+    assert_eq!(info.game_code.as_deref(), Some("MK-81009"));
+    assert_eq!(info.revision, Some(2));
+    assert_eq!(info.region, Some(Region::Usa));
+    assert_eq!(info.title.as_deref(), Some("NIGHTS INTO"));
+}
+
+#[test]
+fn test_unrecognized() {
+    assert_eq!(rom_info::identify(&[0u8; 0x10000]), None);
+    assert_eq!(rom_info::identify(&[]), None);
+}
+
+#[test]
+fn test_console_extensions() {
+    assert_eq!(Console::Nes.extension(), Some("nes"));
+    assert_eq!(Console::Snes.extension(), Some("sfc"));
+    assert_eq!(Console::GameBoyColor.extension(), Some("gbc"));
+    assert_eq!(Console::Saturn.extension(), None);
+}
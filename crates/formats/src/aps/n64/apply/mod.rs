@@ -4,6 +4,7 @@ mod records;
 
 use super::constants::*;
 use super::helpers::{parse_header, validate_source_rom};
+use crate::n64_byte_order::ByteOrder;
 use stitchr_core::{PatchError, Result};
 
 /// Apply APS N64 patch to ROM
@@ -53,6 +54,13 @@ pub fn verify(rom: &[u8], patch: &[u8]) -> Result<()> {
     if let Some(n64_header) = header.n64_header
         && !validate_source_rom(rom, &n64_header)
     {
+        let expected = n64_header.original_byte_order();
+        if let Some(actual) = ByteOrder::detect(rom).filter(|&order| order != expected) {
+            return Err(PatchError::Other(format!(
+                "ROM is {} but the patch was made for {}",
+                actual, expected
+            )));
+        }
         return Err(PatchError::ChecksumMismatch {
             expected: 0,
             actual: 0,
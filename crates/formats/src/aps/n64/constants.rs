@@ -24,6 +24,10 @@ pub const N64_CART_ID_LEN: usize = 3;
 pub const N64_CRC_OFFSET: usize = 0x10;
 pub const N64_CRC_LEN: usize = 8;
 
+/// Original ROM format values in the N64 header
+pub const ORIGINAL_FORMAT_V64: u8 = 0x00;
+pub const ORIGINAL_FORMAT_Z64: u8 = 0x01;
+
 /// N64 header padding length
 pub const N64_PAD_LEN: usize = 5;
 
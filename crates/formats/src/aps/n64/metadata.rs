@@ -1,6 +1,7 @@
 //! APS N64 metadata extraction
 
 use super::helpers::parse_header;
+use crate::n64_byte_order::ByteOrder;
 use stitchr_core::Result;
 
 /// Metadata extracted from APS N64 patch
@@ -10,12 +11,15 @@ pub struct Metadata {
     pub description: String,
     pub cart_id: Option<String>,
     pub crc: Option<String>,
+    pub original_format: Option<ByteOrder>,
 }
 
 /// Extract metadata from APS N64 patch
 pub fn extract_metadata(patch: &[u8]) -> Result<Metadata> {
     let (header, _) = parse_header(patch)?;
 
+    let original_format = header.n64_header.as_ref().map(|h| h.original_byte_order());
+
     let (cart_id, crc) = if let Some(n64_header) = header.n64_header {
         // Convert cart ID to string
         let cart_id_str = String::from_utf8_lossy(&n64_header.cart_id).to_string();
@@ -37,6 +41,7 @@ pub fn extract_metadata(patch: &[u8]) -> Result<Metadata> {
         description: header.description,
         cart_id,
         crc,
+        original_format,
     })
 }
 
@@ -77,5 +82,6 @@ mod tests {
         assert_eq!(metadata.description, "Test Patch");
         assert_eq!(metadata.cart_id, Some("NTE".to_string()));
         assert_eq!(metadata.crc, Some("0102030405060708".to_string()));
+        assert_eq!(metadata.original_format, Some(ByteOrder::BigEndian));
     }
 }
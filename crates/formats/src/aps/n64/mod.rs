@@ -55,6 +55,13 @@ impl PatchFormat for ApsN64Patcher {
             result = result.with_extra("CRC".to_string(), crc);
         }
 
+        if let Some(order) = meta.original_format {
+            result = result.with_extra(
+                "Original Format".to_string(),
+                order.extension().to_uppercase(),
+            );
+        }
+
         Ok(result)
     }
 }
@@ -1,6 +1,7 @@
 //! APS N64 type definitions
 
 use super::constants::*;
+use crate::n64_byte_order::ByteOrder;
 
 /// N64-specific header data
 #[derive(Debug, Clone)]
@@ -20,3 +21,16 @@ pub struct ApsHeader {
     pub n64_header: Option<N64Header>,
     pub output_size: u32,
 }
+
+impl N64Header {
+    /// Byte order of the ROM the patch was made against
+    ///
+    /// The APS spec only defines V64 (0) and Z64 (1); anything else is
+    /// treated as Z64.
+    pub fn original_byte_order(&self) -> ByteOrder {
+        match self.original_format {
+            ORIGINAL_FORMAT_V64 => ByteOrder::ByteSwapped,
+            _ => ByteOrder::BigEndian,
+        }
+    }
+}
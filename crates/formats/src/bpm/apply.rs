@@ -0,0 +1,146 @@
+//! BPM application to directories
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use super::BpmReport;
+use super::entries::{self, Entry, Origin, OriginSide};
+use super::paths;
+use super::validate;
+use crate::bps::BpsPatcher;
+use stitchr_core::{PatchError, PatchFormat, Result};
+
+/// Apply a BPM patch to `source_dir`, writing the result to `target_dir`
+pub fn apply_dir(source_dir: &Path, target_dir: &Path, patch: &[u8]) -> Result<BpmReport> {
+    validate::validate(patch)?;
+    let parsed = entries::parse(patch)?;
+
+    if !source_dir.is_dir() {
+        return Err(PatchError::Other(format!(
+            "Source is not a directory: {}",
+            source_dir.display()
+        )));
+    }
+    if target_dir.exists() && fs::canonicalize(target_dir)? == fs::canonicalize(source_dir)? {
+        return Err(PatchError::Other(
+            "Target directory must differ from source directory".to_string(),
+        ));
+    }
+    fs::create_dir_all(target_dir)?;
+
+    let mut report = BpmReport::default();
+    let mut written = HashSet::new();
+
+    for entry in &parsed.entries {
+        let name = entry.name();
+        let path = paths::resolve(target_dir, name)?;
+
+        if let Entry::CreatePath { .. } = entry {
+            fs::create_dir_all(&path)?;
+            report.directories.push(name.to_string());
+            continue;
+        }
+
+        let data = match entry {
+            Entry::CreateFile { data, crc32, .. } => {
+                check_crc(name, *crc32, data)?;
+                report.created.push(name.to_string());
+                data.to_vec()
+            }
+            Entry::ModifyFile { origin, patch, .. } => {
+                let base = read_origin(source_dir, target_dir, origin)?;
+                let data = apply_bps(&base, patch).map_err(|e| in_file(name, e))?;
+                report.modified.push(name.to_string());
+                data
+            }
+            Entry::MirrorFile { origin, crc32, .. } => {
+                let data = read_origin(source_dir, target_dir, origin)?;
+                check_crc(name, *crc32, &data)?;
+                report.mirrored.push(name.to_string());
+                data
+            }
+            Entry::CreatePath { .. } => unreachable!(),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_file(&path, &data)?;
+        written.insert(name);
+    }
+
+    // Source files the patch does not carry over are dropped from the target
+    report.removed = paths::walk(source_dir)?
+        .files
+        .into_iter()
+        .filter(|name| !written.contains(name.as_str()))
+        .collect();
+
+    Ok(report)
+}
+
+/// Write `data` to a temporary file next to `path`, fsync it and rename it
+/// into place, so a crash never leaves a half-written target file
+fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let written = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&temp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+
+    // Persist the rename; directories cannot be opened for syncing on Windows
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Apply an embedded BPS patch with source and target CRC checks
+pub(super) fn apply_bps(base: &[u8], patch: &[u8]) -> Result<Vec<u8>> {
+    BpsPatcher::verify(base, patch, None)?;
+    let mut data = base.to_vec();
+    BpsPatcher.apply(&mut data, patch)?;
+    BpsPatcher::verify(base, patch, Some(&data))?;
+    Ok(data)
+}
+
+fn read_origin(source_dir: &Path, target_dir: &Path, origin: &Origin) -> Result<Vec<u8>> {
+    let root = match origin.side {
+        OriginSide::Source => source_dir,
+        OriginSide::Target => target_dir,
+    };
+    let path = paths::resolve(root, origin.name)?;
+    fs::read(&path).map_err(|e| {
+        PatchError::Other(format!("Cannot read origin file {}: {}", path.display(), e))
+    })
+}
+
+fn check_crc(name: &str, expected: u32, data: &[u8]) -> Result<()> {
+    let actual = crc32fast::hash(data);
+    if actual != expected {
+        return Err(in_file(
+            name,
+            PatchError::ChecksumMismatch { expected, actual },
+        ));
+    }
+    Ok(())
+}
+
+fn in_file(name: &str, error: PatchError) -> PatchError {
+    PatchError::Other(format!("{}: {}", name, error))
+}
@@ -0,0 +1,31 @@
+//! BPM format constants
+
+/// BPM magic header
+pub const MAGIC: &[u8] = b"BPM1";
+
+/// Magic header size
+pub const MAGIC_SIZE: usize = 4;
+
+/// Footer size (patch CRC32)
+pub const FOOTER_SIZE: usize = 4;
+
+/// Create an (empty) directory
+pub const ACTION_CREATE_PATH: u8 = 0;
+
+/// Create a file from bytes stored in the patch
+pub const ACTION_CREATE_FILE: u8 = 1;
+
+/// Create a file by applying an embedded BPS patch to an origin file
+pub const ACTION_MODIFY_FILE: u8 = 2;
+
+/// Copy an origin file unchanged
+pub const ACTION_MIRROR_FILE: u8 = 3;
+
+/// Origin file is read from the source directory
+pub const ORIGIN_SOURCE: u64 = 0;
+
+/// Origin file is read from the (already written) target directory
+pub const ORIGIN_TARGET: u64 = 1;
+
+/// Maximum size of a single file stored in or produced by a patch (512MB)
+pub const MAX_FILE_SIZE: u64 = 512 * 1024 * 1024;
@@ -0,0 +1,91 @@
+//! BPM patch creation from two directory trees
+//!
+//! Identical files become MirrorFile entries (also across renames, matched
+//! by size and CRC32), changed files become ModifyFile entries with an
+//! embedded BPS patch, and everything else is stored as CreateFile.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::constants::*;
+use super::paths;
+use crate::bps::{BpsPatcher, varint};
+use stitchr_core::Result;
+
+/// Create a BPM patch that turns `source_dir` into `target_dir`
+pub fn create(source_dir: &Path, target_dir: &Path) -> Result<Vec<u8>> {
+    let source = paths::walk(source_dir)?;
+    let target = paths::walk(target_dir)?;
+
+    // Index source files by (size, CRC32) for rename detection
+    let mut by_content: HashMap<(usize, u32), String> = HashMap::new();
+    for name in &source.files {
+        let data = fs::read(paths::resolve(source_dir, name)?)?;
+        by_content
+            .entry((data.len(), crc32fast::hash(&data)))
+            .or_insert_with(|| name.clone());
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    varint::encode(0, &mut out);
+
+    // Only empty directories need an entry; files create their parents
+    for dir in &target.directories {
+        let prefix = format!("{}/", dir);
+        if !target.files.iter().any(|f| f.starts_with(&prefix)) {
+            write_name(&mut out, ACTION_CREATE_PATH, dir);
+        }
+    }
+
+    for name in &target.files {
+        let data = fs::read(paths::resolve(target_dir, name)?)?;
+        let crc32 = crc32fast::hash(&data);
+
+        if let Some(origin) = by_content.get(&(data.len(), crc32)) {
+            let origin_data = fs::read(paths::resolve(source_dir, origin)?)?;
+            if origin_data == data {
+                write_name(&mut out, ACTION_MIRROR_FILE, name);
+                write_origin(&mut out, name, origin);
+                out.extend_from_slice(&crc32.to_le_bytes());
+                continue;
+            }
+        }
+
+        if source.files.binary_search(name).is_ok() {
+            let base = fs::read(paths::resolve(source_dir, name)?)?;
+            let patch = BpsPatcher::create(&base, &data, &[])?;
+            if patch.len() < data.len() {
+                write_name(&mut out, ACTION_MODIFY_FILE, name);
+                write_origin(&mut out, name, name);
+                varint::encode(patch.len() as u64, &mut out);
+                out.extend_from_slice(&patch);
+                continue;
+            }
+        }
+
+        write_name(&mut out, ACTION_CREATE_FILE, name);
+        varint::encode(data.len() as u64, &mut out);
+        out.extend_from_slice(&data);
+        out.extend_from_slice(&crc32.to_le_bytes());
+    }
+
+    let patch_crc = crc32fast::hash(&out);
+    out.extend_from_slice(&patch_crc.to_le_bytes());
+    Ok(out)
+}
+
+fn write_name(out: &mut Vec<u8>, action: u8, name: &str) {
+    varint::encode((((name.len() - 1) as u64) << 2) | action as u64, out);
+    out.extend_from_slice(name.as_bytes());
+}
+
+fn write_origin(out: &mut Vec<u8>, name: &str, origin: &str) {
+    if origin == name {
+        varint::encode(ORIGIN_SOURCE, out);
+    } else {
+        varint::encode(((origin.len() as u64) << 1) | ORIGIN_SOURCE, out);
+        out.extend_from_slice(origin.as_bytes());
+    }
+}
@@ -0,0 +1,199 @@
+//! BPM entry parsing
+//!
+//! After the magic and metadata, a BPM patch is a list of entries up to the
+//! 4-byte footer. Each entry starts with a varint `((name_len - 1) << 2) |
+//! action` followed by the UTF-8 path name, then action-specific data:
+//!
+//! - CreatePath: nothing
+//! - CreateFile: varint size, file data, CRC32 (LE)
+//! - ModifyFile: origin, varint BPS size, embedded BPS patch
+//! - MirrorFile: origin, CRC32 (LE)
+//!
+//! An origin is a varint `(name_len << 1) | side`, where side selects the
+//! source or target directory, followed by the origin name. A zero length
+//! means the origin has the same name as the entry.
+
+use super::constants::*;
+use crate::bps::varint;
+use stitchr_core::{PatchError, Result};
+
+/// Directory an origin file is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OriginSide {
+    Source,
+    Target,
+}
+
+/// File an entry is derived from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin<'a> {
+    pub side: OriginSide,
+    pub name: &'a str,
+}
+
+/// A single BPM entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry<'a> {
+    CreatePath {
+        name: &'a str,
+    },
+    CreateFile {
+        name: &'a str,
+        data: &'a [u8],
+        crc32: u32,
+    },
+    ModifyFile {
+        name: &'a str,
+        origin: Origin<'a>,
+        patch: &'a [u8],
+    },
+    MirrorFile {
+        name: &'a str,
+        origin: Origin<'a>,
+        crc32: u32,
+    },
+}
+
+impl<'a> Entry<'a> {
+    /// Path of the entry relative to the target directory
+    pub fn name(&self) -> &'a str {
+        match self {
+            Entry::CreatePath { name }
+            | Entry::CreateFile { name, .. }
+            | Entry::ModifyFile { name, .. }
+            | Entry::MirrorFile { name, .. } => name,
+        }
+    }
+}
+
+/// Parsed BPM patch (structure only; the patch CRC is checked by `validate`)
+#[derive(Debug)]
+pub struct BpmPatch<'a> {
+    pub metadata: &'a [u8],
+    pub entries: Vec<Entry<'a>>,
+}
+
+/// Parse the header and all entries of a BPM patch
+pub fn parse(patch: &[u8]) -> Result<BpmPatch<'_>> {
+    if patch.len() < MAGIC_SIZE + FOOTER_SIZE {
+        return Err(PatchError::InvalidFormat(format!(
+            "BPM patch too small: {} bytes (minimum {})",
+            patch.len(),
+            MAGIC_SIZE + FOOTER_SIZE
+        )));
+    }
+    if &patch[..MAGIC_SIZE] != MAGIC {
+        return Err(PatchError::InvalidMagic {
+            expected: MAGIC.to_vec(),
+            actual: patch[..MAGIC_SIZE].to_vec(),
+        });
+    }
+
+    let mut reader = Reader {
+        data: &patch[..patch.len() - FOOTER_SIZE],
+        pos: MAGIC_SIZE,
+    };
+
+    let metadata_size = reader.varint("metadata size")?;
+    let metadata = reader.bytes(metadata_size, "metadata")?;
+
+    let mut entries = Vec::new();
+    while reader.pos < reader.data.len() {
+        entries.push(reader.entry()?);
+    }
+
+    Ok(BpmPatch { metadata, entries })
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self, what: &str) -> Result<u64> {
+        let (value, read) = varint::decode(&self.data[self.pos..])
+            .map_err(|_| PatchError::InvalidFormat(format!("Invalid {} varint", what)))?;
+        self.pos += read;
+        Ok(value)
+    }
+
+    fn bytes(&mut self, len: u64, what: &str) -> Result<&'a [u8]> {
+        let remaining = (self.data.len() - self.pos) as u64;
+        if len > remaining {
+            return Err(PatchError::UnexpectedEof(format!(
+                "{} needs {} bytes, {} left",
+                what, len, remaining
+            )));
+        }
+        let start = self.pos;
+        self.pos += len as usize;
+        Ok(&self.data[start..self.pos])
+    }
+
+    fn crc32(&mut self) -> Result<u32> {
+        let bytes = self.bytes(4, "CRC32")?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn name(&mut self, len: u64) -> Result<&'a str> {
+        let bytes = self.bytes(len, "path name")?;
+        std::str::from_utf8(bytes)
+            .map_err(|_| PatchError::InvalidFormat("Path name is not valid UTF-8".to_string()))
+    }
+
+    fn origin(&mut self, name: &'a str) -> Result<Origin<'a>> {
+        let encoded = self.varint("origin")?;
+        let side = if encoded & 1 == ORIGIN_TARGET {
+            OriginSide::Target
+        } else {
+            OriginSide::Source
+        };
+        let len = encoded >> 1;
+        let name = if len == 0 { name } else { self.name(len)? };
+        Ok(Origin { side, name })
+    }
+
+    fn entry(&mut self) -> Result<Entry<'a>> {
+        let encoded = self.varint("entry")?;
+        let action = (encoded & 3) as u8;
+        let name = self.name((encoded >> 2) + 1)?;
+
+        Ok(match action {
+            ACTION_CREATE_PATH => Entry::CreatePath {
+                name: name.strip_suffix('/').unwrap_or(name),
+            },
+            ACTION_CREATE_FILE => {
+                let size = self.varint("file size")?;
+                if size > MAX_FILE_SIZE {
+                    return Err(PatchError::InvalidFormat(format!(
+                        "File size too large: {} (max {})",
+                        size, MAX_FILE_SIZE
+                    )));
+                }
+                let data = self.bytes(size, "file data")?;
+                let crc32 = self.crc32()?;
+                Entry::CreateFile { name, data, crc32 }
+            }
+            ACTION_MODIFY_FILE => {
+                let origin = self.origin(name)?;
+                let size = self.varint("BPS size")?;
+                let patch = self.bytes(size, "embedded BPS patch")?;
+                Entry::ModifyFile {
+                    name,
+                    origin,
+                    patch,
+                }
+            }
+            _ => {
+                let origin = self.origin(name)?;
+                let crc32 = self.crc32()?;
+                Entry::MirrorFile {
+                    name,
+                    origin,
+                    crc32,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,36 @@
+//! BPM metadata extraction
+
+use super::entries::{self, Entry};
+use stitchr_core::{PatchMetadata, PatchType, Result};
+
+/// Extract metadata from BPM patch
+pub fn extract(patch: &[u8]) -> Result<PatchMetadata> {
+    let parsed = entries::parse(patch)?;
+
+    let mut directories = 0;
+    let mut created = 0;
+    let mut modified = 0;
+    let mut mirrored = 0;
+    for entry in &parsed.entries {
+        match entry {
+            Entry::CreatePath { .. } => directories += 1,
+            Entry::CreateFile { .. } => created += 1,
+            Entry::ModifyFile { .. } => modified += 1,
+            Entry::MirrorFile { .. } => mirrored += 1,
+        }
+    }
+
+    let mut metadata = PatchMetadata::new(PatchType::Bpm);
+    if !parsed.metadata.is_empty() {
+        metadata = metadata.with_extra(
+            "metadata".to_string(),
+            String::from_utf8_lossy(parsed.metadata).into_owned(),
+        );
+    }
+
+    Ok(metadata
+        .with_extra("directories".to_string(), directories.to_string())
+        .with_extra("created_files".to_string(), created.to_string())
+        .with_extra("modified_files".to_string(), modified.to_string())
+        .with_extra("mirrored_files".to_string(), mirrored.to_string()))
+}
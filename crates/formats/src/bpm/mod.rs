@@ -0,0 +1,95 @@
+//! BPM (beat multi-file patch) support
+//!
+//! BPM is beat's directory patch format: a list of entries that build a
+//! target directory from a source directory, with changed files carried as
+//! embedded BPS patches and every produced file checked by CRC32.
+
+use std::path::Path;
+
+use stitchr_core::{PatchError, PatchFormat, PatchMetadata, Result};
+
+pub mod apply;
+pub mod constants;
+pub mod entries;
+pub mod metadata;
+pub mod paths;
+pub mod validate;
+
+mod create;
+
+/// Outcome of applying a BPM patch to a directory
+///
+/// All entries are `/`-separated paths relative to the target directory.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BpmReport {
+    /// Directories created explicitly by the patch
+    pub directories: Vec<String>,
+    /// Files stored in the patch
+    pub created: Vec<String>,
+    /// Files produced by an embedded BPS patch
+    pub modified: Vec<String>,
+    /// Files copied unchanged (possibly renamed)
+    pub mirrored: Vec<String>,
+    /// Source files not present in the target
+    pub removed: Vec<String>,
+}
+
+/// BPM format patcher
+pub struct BpmPatcher;
+
+impl BpmPatcher {
+    /// Apply a BPM patch to `source_dir`, writing the result to `target_dir`
+    pub fn apply_dir(source_dir: &Path, target_dir: &Path, patch: &[u8]) -> Result<BpmReport> {
+        apply::apply_dir(source_dir, target_dir, patch)
+    }
+
+    /// Create a BPM patch that turns `source_dir` into `target_dir`
+    pub fn create(source_dir: &Path, target_dir: &Path) -> Result<Vec<u8>> {
+        create::create(source_dir, target_dir)
+    }
+}
+
+impl PatchFormat for BpmPatcher {
+    fn can_handle(data: &[u8]) -> bool {
+        validate::can_handle(data)
+    }
+
+    /// Apply a BPM patch to a single file
+    ///
+    /// Only patches that modify exactly one file in place can be applied to
+    /// a buffer; everything else needs [`BpmPatcher::apply_dir`].
+    fn apply(&self, rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+        validate::validate(patch)?;
+        let parsed = entries::parse(patch)?;
+
+        let files: Vec<_> = parsed
+            .entries
+            .iter()
+            .filter(|e| !matches!(e, entries::Entry::CreatePath { .. }))
+            .collect();
+        match files.as_slice() {
+            [
+                entries::Entry::ModifyFile {
+                    name,
+                    origin,
+                    patch,
+                },
+            ] if origin.side == entries::OriginSide::Source && origin.name == *name => {
+                *rom = apply::apply_bps(rom, patch)?;
+                Ok(())
+            }
+            _ => Err(PatchError::InvalidFormat(format!(
+                "BPM patch covers {} files; apply it to a directory",
+                files.len()
+            ))),
+        }
+    }
+
+    fn metadata(patch: &[u8]) -> Result<PatchMetadata> {
+        metadata::extract(patch)
+    }
+
+    fn validate(patch: &[u8]) -> Result<()> {
+        validate::validate(patch)
+    }
+}
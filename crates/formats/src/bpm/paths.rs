@@ -0,0 +1,64 @@
+//! Path handling for directory patches
+//!
+//! Entry names are `/`-separated paths relative to the source or target
+//! directory. Names that could escape the directory are rejected.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use stitchr_core::{PatchError, Result};
+
+/// Resolve a patch entry name below `root`
+pub fn resolve(root: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.starts_with('/') || name.contains('\\') {
+        return Err(unsafe_name(name));
+    }
+
+    let mut path = root.to_path_buf();
+    for component in name.split('/') {
+        if component.is_empty() || component == "." || component == ".." || component.contains(':')
+        {
+            return Err(unsafe_name(name));
+        }
+        path.push(component);
+    }
+    Ok(path)
+}
+
+fn unsafe_name(name: &str) -> PatchError {
+    PatchError::InvalidFormat(format!("Unsafe path in patch: {:?}", name))
+}
+
+/// Directories and files below a root, as sorted `/`-separated names
+#[derive(Debug, Default)]
+pub struct Tree {
+    pub directories: Vec<String>,
+    pub files: Vec<String>,
+}
+
+/// Recursively list a directory
+pub fn walk(root: &Path) -> Result<Tree> {
+    let mut tree = Tree::default();
+    walk_into(root, "", &mut tree)?;
+    tree.directories.sort();
+    tree.files.sort();
+    Ok(tree)
+}
+
+fn walk_into(dir: &Path, prefix: &str, tree: &mut Tree) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_str().ok_or_else(|| {
+            PatchError::Other(format!("Non UTF-8 file name: {}", entry.path().display()))
+        })?;
+        let name = format!("{}{}", prefix, file_name);
+
+        if entry.file_type()?.is_dir() {
+            walk_into(&entry.path(), &format!("{}/", name), tree)?;
+            tree.directories.push(name);
+        } else {
+            tree.files.push(name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! BPM format validation
+
+use super::constants::{FOOTER_SIZE, MAGIC, MAGIC_SIZE};
+use super::entries::{self, Entry};
+use super::paths;
+use crate::bps::BpsPatcher;
+use std::path::Path;
+use stitchr_core::{PatchError, PatchFormat, Result};
+
+/// Check if data is a BPM patch (magic header check)
+pub fn can_handle(data: &[u8]) -> bool {
+    data.len() >= MAGIC_SIZE && &data[..MAGIC_SIZE] == MAGIC
+}
+
+/// Validate patch CRC32, entry structure, path names and embedded BPS patches
+pub fn validate(patch: &[u8]) -> Result<()> {
+    let parsed = entries::parse(patch)?;
+
+    let data_end = patch.len() - FOOTER_SIZE;
+    let stored = u32::from_le_bytes([
+        patch[data_end],
+        patch[data_end + 1],
+        patch[data_end + 2],
+        patch[data_end + 3],
+    ]);
+    let computed = crc32fast::hash(&patch[..data_end]);
+    if stored != computed {
+        return Err(PatchError::ChecksumMismatch {
+            expected: stored,
+            actual: computed,
+        });
+    }
+
+    for entry in &parsed.entries {
+        paths::resolve(Path::new(""), entry.name())?;
+        match entry {
+            Entry::ModifyFile { origin, patch, .. } => {
+                paths::resolve(Path::new(""), origin.name)?;
+                BpsPatcher::validate(patch)
+                    .map_err(|e| PatchError::Other(format!("{}: {}", entry.name(), e)))?;
+            }
+            Entry::MirrorFile { origin, .. } => {
+                paths::resolve(Path::new(""), origin.name)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
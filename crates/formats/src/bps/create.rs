@@ -0,0 +1,142 @@
+//! BPS patch creation
+//!
+//! Greedy encoder: each target position first tries SourceRead (same offset
+//! in the source), then a hashed index of source blocks for SourceCopy.
+//! Unmatched bytes are collected into TargetRead runs.
+
+use std::collections::HashMap;
+
+use super::constants::*;
+use super::varint;
+use stitchr_core::Result;
+
+/// Size of the source blocks indexed for SourceCopy lookup
+const BLOCK_SIZE: usize = 16;
+
+/// Shortest SourceRead worth ending a TargetRead run for
+const MIN_SOURCE_READ: usize = 4;
+
+/// Shortest SourceCopy worth its offset argument
+const MIN_SOURCE_COPY: usize = 8;
+
+/// Create a BPS patch that turns `source` into `target`
+pub fn create(source: &[u8], target: &[u8], metadata: &[u8]) -> Result<Vec<u8>> {
+    let index = build_index(source);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    varint::encode(source.len() as u64, &mut out);
+    varint::encode(target.len() as u64, &mut out);
+    varint::encode(metadata.len() as u64, &mut out);
+    out.extend_from_slice(metadata);
+
+    let mut source_relative_offset = 0i64;
+    let mut literal_start = 0;
+    let mut pos = 0;
+
+    while pos < target.len() {
+        let read_len = if pos < source.len() {
+            match_length(source, target, pos, pos)
+        } else {
+            0
+        };
+        if read_len >= MIN_SOURCE_READ {
+            emit_target_read(&mut out, &target[literal_start..pos]);
+            emit_command(&mut out, ACTION_SOURCE_READ, read_len);
+            pos += read_len;
+            literal_start = pos;
+            continue;
+        }
+
+        match find_source_copy(source, target, pos, &index) {
+            Some((mut src, mut len)) => {
+                // Pull bytes back out of the pending TargetRead run when the
+                // match also extends backwards
+                let mut start = pos;
+                while start > literal_start && src > 0 && source[src - 1] == target[start - 1] {
+                    start -= 1;
+                    src -= 1;
+                    len += 1;
+                }
+                emit_target_read(&mut out, &target[literal_start..start]);
+                emit_command(&mut out, ACTION_SOURCE_COPY, len);
+                varint::encode(
+                    encode_signed_delta(src as i64 - source_relative_offset),
+                    &mut out,
+                );
+                source_relative_offset = (src + len) as i64;
+                pos = start + len;
+                literal_start = pos;
+            }
+            None => pos += 1,
+        }
+    }
+    emit_target_read(&mut out, &target[literal_start..]);
+
+    out.extend_from_slice(&crc32fast::hash(source).to_le_bytes());
+    out.extend_from_slice(&crc32fast::hash(target).to_le_bytes());
+    let patch_crc = crc32fast::hash(&out);
+    out.extend_from_slice(&patch_crc.to_le_bytes());
+
+    Ok(out)
+}
+
+/// Encode a command byte sequence: ((length - 1) << 2) | action
+fn emit_command(out: &mut Vec<u8>, action: u8, length: usize) {
+    varint::encode((((length - 1) as u64) << 2) | action as u64, out);
+}
+
+/// Emit a TargetRead command for `data` (no-op when empty)
+fn emit_target_read(out: &mut Vec<u8>, data: &[u8]) {
+    if !data.is_empty() {
+        emit_command(out, ACTION_TARGET_READ, data.len());
+        out.extend_from_slice(data);
+    }
+}
+
+/// Encode signed delta (inverse of `helpers::decode_signed_delta`)
+fn encode_signed_delta(delta: i64) -> u64 {
+    (delta.unsigned_abs() << 1) | (delta < 0) as u64
+}
+
+/// Index the first source offset of every aligned block by content hash
+fn build_index(source: &[u8]) -> HashMap<u64, usize> {
+    let mut index = HashMap::new();
+    for start in (0..source.len().saturating_sub(BLOCK_SIZE - 1)).step_by(BLOCK_SIZE) {
+        index
+            .entry(hash_block(&source[start..start + BLOCK_SIZE]))
+            .or_insert(start);
+    }
+    index
+}
+
+/// FNV-1a over one block
+fn hash_block(block: &[u8]) -> u64 {
+    block.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// Look up a SourceCopy candidate for `target[pos..]`
+fn find_source_copy(
+    source: &[u8],
+    target: &[u8],
+    pos: usize,
+    index: &HashMap<u64, usize>,
+) -> Option<(usize, usize)> {
+    if pos + BLOCK_SIZE > target.len() {
+        return None;
+    }
+    let &src = index.get(&hash_block(&target[pos..pos + BLOCK_SIZE]))?;
+    let len = match_length(source, target, src, pos);
+    (len >= MIN_SOURCE_COPY).then_some((src, len))
+}
+
+/// Number of equal bytes at `source[src..]` and `target[pos..]`
+fn match_length(source: &[u8], target: &[u8], src: usize, pos: usize) -> usize {
+    source[src..]
+        .iter()
+        .zip(&target[pos..])
+        .take_while(|(a, b)| a == b)
+        .count()
+}
@@ -1,6 +1,6 @@
 //! BPS metadata extraction
 
-use super::constants::MAGIC_SIZE;
+use super::constants::{FOOTER_SIZE, MAGIC_SIZE};
 use super::varint;
 use stitchr_core::{PatchError, PatchMetadata, PatchType, Result};
 
@@ -46,13 +46,51 @@ pub fn extract_metadata(patch: &[u8]) -> Result<PatchMetadata> {
 
         // Try to parse as UTF-8 string (recommended format is XML UTF-8)
         if let Ok(metadata_str) = String::from_utf8(patch[offset..metadata_end].to_vec()) {
+            let title = manifest_title(&metadata_str);
             metadata = metadata.with_extra("metadata".to_string(), metadata_str);
+            if let Some(title) = title {
+                metadata = metadata.with_extra("title".to_string(), title);
+            }
         }
+        offset = metadata_end;
+    }
+
+    // Footer checksums (source CRC32, target CRC32, patch CRC32)
+    if patch.len() >= offset + FOOTER_SIZE {
+        let footer = &patch[patch.len() - FOOTER_SIZE..];
+        metadata.source_checksum = Some(footer[0..4].to_vec());
+        metadata.target_checksum = Some(footer[4..8].to_vec());
     }
 
     Ok(metadata)
 }
 
+/// Title from a manifest: `<title>` in XML, a `title:` line in BML
+fn manifest_title(manifest: &str) -> Option<String> {
+    let title = match manifest.find("<title") {
+        Some(start) => {
+            let content = &manifest[start..];
+            let open_end = content.find('>')? + 1;
+            let close = content.find("</title>")?;
+            xml_unescape(content.get(open_end..close)?)
+        }
+        None => manifest.lines().find_map(|line| {
+            let value = line.trim_start().strip_prefix("title:")?;
+            Some(value.trim().trim_matches('"').to_string())
+        })?,
+    };
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +125,22 @@ mod tests {
         assert_eq!(meta.extra[0].0, "metadata");
         assert_eq!(meta.extra[0].1, "hello");
     }
+
+    #[test]
+    fn test_manifest_title() {
+        assert_eq!(
+            manifest_title("<information><title>Tom &amp; Jerry</title></information>"),
+            Some("Tom & Jerry".to_string())
+        );
+        assert_eq!(
+            manifest_title("<title lang=\"en\"> Hack </title>"),
+            Some("Hack".to_string())
+        );
+        assert_eq!(
+            manifest_title("game\n  title: \"Super Hack\"\n  author: me"),
+            Some("Super Hack".to_string())
+        );
+        assert_eq!(manifest_title("<title></title>"), None);
+        assert_eq!(manifest_title("hello"), None);
+    }
 }
@@ -1,8 +1,9 @@
 //! BPS (Beat Patching System) format support
 
-use stitchr_core::{PatchFormat, PatchMetadata, Result};
+use stitchr_core::{LintWarning, PatchFormat, PatchMetadata, Result};
 
 mod apply;
+mod create;
 mod helpers;
 mod metadata;
 mod validate;
@@ -28,6 +29,16 @@ pub mod constants {
 /// BPS format patcher
 pub struct BpsPatcher;
 
+impl BpsPatcher {
+    /// Create a BPS patch that turns `source` into `target`
+    ///
+    /// `metadata` is stored verbatim in the header (beat recommends UTF-8
+    /// XML); pass an empty slice for none.
+    pub fn create(source: &[u8], target: &[u8], metadata: &[u8]) -> Result<Vec<u8>> {
+        create::create(source, target, metadata)
+    }
+}
+
 impl PatchFormat for BpsPatcher {
     fn can_handle(data: &[u8]) -> bool {
         validate::can_handle(data)
@@ -45,6 +56,10 @@ impl PatchFormat for BpsPatcher {
         validate::validate(patch)
     }
 
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+        validate::lint(patch)
+    }
+
     fn verify(rom: &[u8], patch: &[u8], target: Option<&[u8]>) -> Result<()> {
         // Verify source ROM checksum
         helpers::validate_source_crc(rom, patch)?;
@@ -3,7 +3,7 @@
 use super::constants::{FOOTER_SIZE, MAGIC, MAGIC_SIZE};
 use super::varint;
 use crc32fast;
-use stitchr_core::{PatchError, Result};
+use stitchr_core::{LintWarning, PatchError, Result, Severity};
 
 /// Check if data is a valid BPS patch (magic header check)
 pub fn can_handle(data: &[u8]) -> bool {
@@ -78,3 +78,35 @@ pub fn validate(patch: &[u8]) -> Result<()> {
 
     Ok(())
 }
+
+/// Report a metadata string that is not XML
+///
+/// beat specifies UTF-8 XML for the metadata block; other tools may show
+/// or reject anything else.
+pub fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+    validate(patch)?;
+
+    let mut offset = MAGIC_SIZE;
+    for _ in 0..2 {
+        offset += varint::decode(&patch[offset..])?.1;
+    }
+    let (metadata_size, bytes_read) = varint::decode(&patch[offset..])?;
+    offset += bytes_read;
+    let metadata = &patch[offset..offset + metadata_size as usize];
+
+    let mut warnings = Vec::new();
+    if !metadata.is_empty() {
+        let is_xml = std::str::from_utf8(metadata).is_ok_and(|text| {
+            let text = text.trim();
+            text.starts_with('<') && text.ends_with('>')
+        });
+        if !is_xml {
+            warnings.push(LintWarning::new(
+                offset,
+                Severity::Warning,
+                format!("{}-byte metadata is not UTF-8 XML", metadata.len()),
+            ));
+        }
+    }
+    Ok(warnings)
+}
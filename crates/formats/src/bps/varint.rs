@@ -68,3 +68,31 @@ pub fn decode(data: &[u8]) -> Result<(u64, usize)> {
         "Incomplete varint at end of data".to_string(),
     ))
 }
+
+/// Encode a variable-length integer and append it to `out`
+///
+/// Inverse of [`decode`]:
+/// ```text
+/// void encode(uint64 data) {
+///   while(true) {
+///     uint8 x = data & 0x7f;
+///     data >>= 7;
+///     if(data == 0) { write(0x80 | x); break; }
+///     write(x);
+///     data--;
+///   }
+/// }
+/// ```
+#[inline]
+pub fn encode(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let x = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(0x80 | x);
+            break;
+        }
+        out.push(x);
+        value -= 1;
+    }
+}
@@ -0,0 +1,288 @@
+//! SNES copier header reconciliation
+//!
+//! Dumps from SMC/SWC copiers carry a 512-byte header in front of the ROM
+//! image. Patches are made for one layout or the other, and applying them to
+//! the wrong one either corrupts every record by 512 bytes (IPS) or fails the
+//! source check (BPS/UPS).
+//!
+//! [`prepare`] works out which layout the patch expects and strips or adds
+//! the header before patching; [`HeaderFix::finish`] then restores the layout
+//! the caller asked for.
+
+use std::fmt;
+
+use stitchr_core::snes::{BLOCK_SIZE, find_internal_header, strip_copier_header};
+use stitchr_core::{PatchFormat, PatchMetadata, PatchType};
+
+pub use stitchr_core::snes::{COPIER_HEADER_SIZE, has_copier_header};
+
+/// Offsets of the internal header checksum complement (LoROM, HiROM, ExHiROM)
+const CHECKSUM_OFFSETS: [usize; 3] = [0x7FDC, 0xFFDC, 0x40FFDC];
+
+/// Copier header layout wanted for the patched ROM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderLayout {
+    /// Same layout as the input ROM
+    #[default]
+    Keep,
+    /// Always write a copier header
+    Headered,
+    /// Never write a copier header
+    Headerless,
+}
+
+/// Change made to the copier header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderChange {
+    /// 512-byte header removed
+    Stripped,
+    /// 512-byte header inserted
+    Added,
+}
+
+impl HeaderChange {
+    fn verb(&self) -> &'static str {
+        match self {
+            Self::Stripped => "Stripped",
+            Self::Added => "Added",
+        }
+    }
+}
+
+/// State carried from [`prepare`] to [`HeaderFix::finish`]
+#[derive(Debug)]
+pub struct HeaderFix {
+    input_headered: bool,
+    is_snes: bool,
+    before: Option<(HeaderChange, &'static str)>,
+    saved_header: Option<Vec<u8>>,
+}
+
+/// What was done to the copier header around patching
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderReport {
+    /// Change made before patching, with the evidence for it
+    pub before: Option<(HeaderChange, &'static str)>,
+    /// Change made after patching to reach the requested layout
+    pub after: Option<HeaderChange>,
+}
+
+impl HeaderReport {
+    /// Whether the ROM was touched at all
+    pub fn is_empty(&self) -> bool {
+        self.before.is_none() && self.after.is_none()
+    }
+}
+
+impl fmt::Display for HeaderReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.before, self.after) {
+            (None, None) => write!(f, "Copier header left unchanged"),
+            (Some((change, reason)), after) => {
+                write!(
+                    f,
+                    "{} {}-byte copier header before patching ({})",
+                    change.verb(),
+                    COPIER_HEADER_SIZE,
+                    reason
+                )?;
+                match after {
+                    Some(HeaderChange::Stripped) => write!(f, "; removed it from the output"),
+                    Some(HeaderChange::Added) => write!(f, "; restored it in the output"),
+                    None => Ok(()),
+                }
+            }
+            (None, Some(change)) => write!(
+                f,
+                "{} {}-byte copier header after patching",
+                change.verb(),
+                COPIER_HEADER_SIZE
+            ),
+        }
+    }
+}
+
+/// Bring `rom` into the copier header layout `patch` expects
+///
+/// Leaves the ROM untouched when the patch gives no clear signal. Errors in
+/// the patch are ignored here; they surface when the patch is applied.
+pub fn prepare(rom: &mut Vec<u8>, patch: &[u8], patch_type: PatchType) -> HeaderFix {
+    let input_headered = has_copier_header(rom.len());
+    let is_snes = find_internal_header(strip_copier_header(rom)).is_some();
+
+    let before = match patch_type {
+        PatchType::Bps | PatchType::Ups => match source_expectation(patch, patch_type) {
+            Some((size, crc)) => by_source(rom, size, crc),
+            None => None,
+        },
+        PatchType::Ips | PatchType::Ips32 if is_snes => {
+            by_ips_offsets(rom.len(), patch, patch_type)
+        }
+        _ => None,
+    };
+
+    let mut saved_header = None;
+    match before {
+        Some((HeaderChange::Stripped, _)) => {
+            saved_header = Some(rom.drain(..COPIER_HEADER_SIZE).collect());
+        }
+        Some((HeaderChange::Added, _)) => {
+            rom.splice(0..0, [0u8; COPIER_HEADER_SIZE]);
+        }
+        None => {}
+    }
+
+    HeaderFix {
+        input_headered,
+        is_snes: is_snes || before.is_some(),
+        before,
+        saved_header,
+    }
+}
+
+impl fmt::Display for HeaderFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let report = HeaderReport {
+            before: self.before,
+            after: None,
+        };
+        report.fmt(f)
+    }
+}
+
+impl HeaderFix {
+    /// Change made before patching, if any
+    pub fn change(&self) -> Option<HeaderChange> {
+        self.before.map(|(change, _)| change)
+    }
+
+    /// Convert the patched ROM to `layout` and report what was done
+    ///
+    /// A header removed in [`prepare`] is put back byte for byte; otherwise
+    /// an added header is blank (all zero).
+    pub fn finish(self, rom: &mut Vec<u8>, layout: HeaderLayout) -> HeaderReport {
+        let patched_headered = match self.change() {
+            Some(HeaderChange::Stripped) => false,
+            Some(HeaderChange::Added) => true,
+            None => self.input_headered,
+        };
+        let want_headered = match layout {
+            HeaderLayout::Keep => self.input_headered,
+            HeaderLayout::Headered => true,
+            HeaderLayout::Headerless => false,
+        };
+
+        let after = if !self.is_snes || patched_headered == want_headered {
+            None
+        } else if want_headered {
+            let header = self
+                .saved_header
+                .unwrap_or_else(|| vec![0u8; COPIER_HEADER_SIZE]);
+            rom.splice(0..0, header);
+            Some(HeaderChange::Added)
+        } else if rom.len() > COPIER_HEADER_SIZE {
+            rom.drain(..COPIER_HEADER_SIZE);
+            Some(HeaderChange::Stripped)
+        } else {
+            None
+        };
+
+        HeaderReport {
+            before: self.before,
+            after,
+        }
+    }
+}
+
+/// Source size and CRC32 recorded in a BPS or UPS patch
+fn source_expectation(patch: &[u8], patch_type: PatchType) -> Option<(usize, u32)> {
+    let metadata: PatchMetadata = match patch_type {
+        #[cfg(feature = "bps")]
+        PatchType::Bps => <crate::bps::BpsPatcher as PatchFormat>::metadata(patch).ok()?,
+        #[cfg(feature = "ups")]
+        PatchType::Ups => <crate::ups::UpsPatcher as PatchFormat>::metadata(patch).ok()?,
+        _ => return None,
+    };
+    let checksum = metadata.source_checksum?;
+    let crc = u32::from_le_bytes(checksum.get(..4)?.try_into().ok()?);
+    Some((metadata.source_size?, crc))
+}
+
+/// Decide by the recorded source size and CRC32
+fn by_source(rom: &[u8], size: usize, crc: u32) -> Option<(HeaderChange, &'static str)> {
+    if rom.len() == size {
+        return None;
+    }
+
+    if has_copier_header(rom.len())
+        && rom.len() - COPIER_HEADER_SIZE == size
+        && crc32fast::hash(&rom[COPIER_HEADER_SIZE..]) == crc
+    {
+        return Some((
+            HeaderChange::Stripped,
+            "patch source size and CRC32 match the ROM without it",
+        ));
+    }
+
+    if has_copier_header(size) && rom.len() + COPIER_HEADER_SIZE == size {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&[0u8; COPIER_HEADER_SIZE]);
+        hasher.update(rom);
+        if hasher.finalize() == crc {
+            return Some((
+                HeaderChange::Added,
+                "patch source size and CRC32 match the ROM with a blank header",
+            ));
+        }
+    }
+
+    None
+}
+
+/// Decide by where IPS records land
+///
+/// Hacks usually rewrite the internal header checksum, so a record at a
+/// checksum offset (or 512 bytes past it) tells the layout apart. Records
+/// that extend the ROM to a block boundary (or 512 bytes past one) count
+/// too. Mixed evidence leaves the ROM alone.
+fn by_ips_offsets(
+    rom_len: usize,
+    patch: &[u8],
+    patch_type: PatchType,
+) -> Option<(HeaderChange, &'static str)> {
+    let ranges: Vec<std::ops::Range<usize>> = match patch_type {
+        #[cfg(feature = "ips")]
+        PatchType::Ips => crate::ips::IpsPatcher::record_ranges(patch).ok()?,
+        #[cfg(feature = "ips")]
+        PatchType::Ips32 => crate::ips::Ips32Patcher::record_ranges(patch).ok()?,
+        _ => return None,
+    };
+
+    let touches = |offset: usize| ranges.iter().any(|r| r.start <= offset && offset < r.end);
+    let mut headerless = CHECKSUM_OFFSETS.iter().filter(|&&o| touches(o)).count();
+    let mut headered = CHECKSUM_OFFSETS
+        .iter()
+        .filter(|&&o| touches(o + COPIER_HEADER_SIZE))
+        .count();
+
+    let end = ranges.iter().map(|r| r.end).max().unwrap_or(0);
+    if end > rom_len {
+        match end % BLOCK_SIZE {
+            0 => headerless += 1,
+            COPIER_HEADER_SIZE => headered += 1,
+            _ => {}
+        }
+    }
+
+    let rom_headered = has_copier_header(rom_len);
+    if rom_headered && headerless > 0 && headered == 0 {
+        Some((
+            HeaderChange::Stripped,
+            "patch records target a headerless ROM",
+        ))
+    } else if !rom_headered && headered > 0 && headerless == 0 {
+        Some((HeaderChange::Added, "patch records target a headered ROM"))
+    } else {
+        None
+    }
+}
@@ -40,9 +40,11 @@ impl EbpMetadata {
 }
 
 /// Extract a string field from JSON manually
+///
+/// Keys match case-insensitively: EBPatcher writes "Title", "Author", ...
 fn extract_string_field(json: &str, field: &str) -> Option<String> {
-    let pattern = format!("\"{}\"", field);
-    let start = json.find(&pattern)?;
+    let pattern = format!("\"{}\"", field.to_ascii_lowercase());
+    let start = json.to_ascii_lowercase().find(&pattern)?;
     let after_key = &json[start + pattern.len()..];
 
     let colon = after_key.find(':')?;
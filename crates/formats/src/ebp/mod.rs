@@ -10,7 +10,7 @@ pub mod validate;
 
 pub mod apply;
 
-use stitchr_core::{PatchError, PatchFormat, PatchMetadata, Result};
+use stitchr_core::{LintWarning, PatchError, PatchFormat, PatchMetadata, Result};
 
 /// EBP patch format handler
 pub struct EbpPatcher;
@@ -24,6 +24,10 @@ impl PatchFormat for EbpPatcher {
         validate::validate(patch)
     }
 
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+        validate::lint(patch)
+    }
+
     fn apply(&self, rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
         Self::validate(patch)?;
         apply::apply(rom, patch)
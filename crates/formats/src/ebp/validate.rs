@@ -1,7 +1,11 @@
 //! EBP validation
 
 use super::constants::*;
-use stitchr_core::Result;
+use stitchr_core::{LintWarning, Result, Severity};
+
+/// JSON fields written by EBP patchers (CoilSnake, EBPatcher), compared
+/// case-insensitively
+const KNOWN_FIELDS: &[&str] = &["patcher", "title", "author", "description", "version"];
 
 /// Validate EBP patch format
 /// EBP is IPS-compatible, so we delegate to IPS validation
@@ -23,3 +27,59 @@ pub fn can_handle(patch: &[u8]) -> bool {
     use super::helpers;
     helpers::find_json_start(patch).is_some()
 }
+
+/// Report IPS record findings and unknown JSON metadata fields
+pub fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+    let (mut warnings, eof_end) = crate::ips::lint_records(patch)?;
+
+    let Some(json_start) = super::helpers::find_json_start(patch).filter(|&s| s >= eof_end) else {
+        warnings.extend(crate::ips::lint_tail(patch, eof_end));
+        return Ok(warnings);
+    };
+    for (position, key) in top_level_keys(&patch[json_start..]) {
+        if !KNOWN_FIELDS.iter().any(|k| k.eq_ignore_ascii_case(&key)) {
+            warnings.push(LintWarning::new(
+                json_start + position,
+                Severity::Info,
+                format!("unknown EBP metadata field \"{}\"", key),
+            ));
+        }
+    }
+    Ok(warnings)
+}
+
+/// Keys of the outermost JSON object, with their byte positions
+fn top_level_keys(json: &[u8]) -> Vec<(usize, String)> {
+    let mut keys = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < json.len() {
+        match json[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    break;
+                }
+            }
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < json.len() && json[i] != b'"' {
+                    i += if json[i] == b'\\' { 2 } else { 1 };
+                }
+                let is_key = json[(i + 1).min(json.len())..]
+                    .iter()
+                    .find(|b| !b.is_ascii_whitespace())
+                    == Some(&b':');
+                if depth == 1 && is_key {
+                    let key = String::from_utf8_lossy(&json[start + 1..i.min(json.len())]);
+                    keys.push((start, key.into_owned()));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    keys
+}
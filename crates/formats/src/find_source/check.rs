@@ -0,0 +1,285 @@
+//! What a patch records about its source, and how to test a candidate
+
+use std::ops::Range;
+
+use stitchr_core::{PatchError, PatchFormat, PatchMetadata, PatchType, Result};
+
+use crate::copier_header::{COPIER_HEADER_SIZE, has_copier_header};
+
+/// Minimum share of records (percent) that must change a candidate for it
+/// to be reported as plausible
+pub const PLAUSIBLE_THRESHOLD: u8 = 50;
+
+/// How sure a match is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// Checksum (or header / block check) recorded in the patch matches
+    Exact,
+    /// Share of records (percent) that land inside the ROM and change it
+    Plausible(u8),
+}
+
+/// Source check recorded in a patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceCheck {
+    /// Source CRC32 (BPS, UPS, PMSR)
+    Crc32 { size: Option<usize>, crc: u32 },
+    /// Source MD5 (RUP)
+    Md5 { size: Option<usize>, md5: [u8; 16] },
+    /// Cart ID and CRC1/CRC2 from the ROM header (APS N64)
+    N64Header {
+        cart_id: [u8; 3],
+        crc: [u8; 8],
+        order: crate::n64_byte_order::ByteOrder,
+    },
+    /// 1024 bytes of the source image (PPF)
+    PpfBlock {
+        size: Option<usize>,
+        offset: usize,
+        data: Vec<u8>,
+    },
+    /// No source information; score how many records change the ROM
+    /// (IPS, PPF without block check)
+    Plausibility {
+        size: Option<usize>,
+        min_size: usize,
+    },
+}
+
+impl SourceCheck {
+    /// Read the source check from a patch
+    pub fn from_patch(patch: &[u8], patch_type: PatchType) -> Result<Self> {
+        match patch_type {
+            #[cfg(feature = "aps")]
+            PatchType::Aps if crate::aps::n64::ApsN64Patcher::can_handle(patch) => {
+                let (header, _) = crate::aps::n64::helpers::parse_header(patch)?;
+                let n64 = header.n64_header.ok_or_else(|| {
+                    PatchError::InvalidFormat("APS N64 patch without ROM header".to_string())
+                })?;
+                Ok(Self::N64Header {
+                    cart_id: n64.cart_id,
+                    crc: n64.crc,
+                    order: n64.original_byte_order(),
+                })
+            }
+            #[cfg(feature = "ppf")]
+            PatchType::Ppf => {
+                let contents = crate::ppf::PpfPatcher::records(patch)?;
+                let size = contents.input_file_size.map(|size| size as usize);
+                Ok(match contents.block_check {
+                    Some(block) => Self::PpfBlock {
+                        size,
+                        offset: block.offset,
+                        data: block.data,
+                    },
+                    None => Self::Plausibility {
+                        size,
+                        min_size: min_size(contents.records.iter().map(|r| r.range())),
+                    },
+                })
+            }
+            #[cfg(feature = "ips")]
+            PatchType::Ips | PatchType::Ips32 => {
+                let ranges = if patch_type == PatchType::Ips {
+                    crate::ips::IpsPatcher::record_ranges(patch)?
+                } else {
+                    crate::ips::Ips32Patcher::record_ranges(patch)?
+                };
+                Ok(Self::Plausibility {
+                    size: None,
+                    min_size: min_size(ranges.into_iter()),
+                })
+            }
+            _ => {
+                let metadata = source_metadata(patch, patch_type)?;
+                let size = metadata.source_size;
+                match metadata.source_checksum.as_deref() {
+                    Some(&[a, b, c, d]) => Ok(Self::Crc32 {
+                        size,
+                        crc: u32::from_le_bytes([a, b, c, d]),
+                    }),
+                    Some(md5) if md5.len() == 16 => Ok(Self::Md5 {
+                        size,
+                        md5: md5.try_into().unwrap(),
+                    }),
+                    _ => Err(PatchError::InvalidFormat(format!(
+                        "{} patches do not record their source ROM",
+                        patch_type.name()
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Whether a file of `len` bytes can be the source
+    ///
+    /// Files 512 bytes over a CRC32 source size are kept when they look
+    /// like copier-headered dumps.
+    pub fn accepts_size(&self, len: usize) -> bool {
+        match self {
+            Self::Crc32 {
+                size: Some(size), ..
+            } => len == *size || (len == size + COPIER_HEADER_SIZE && has_copier_header(len)),
+            Self::Md5 {
+                size: Some(size), ..
+            } => len == *size,
+            Self::N64Header { .. } => len >= N64_HEADER_SIZE,
+            Self::PpfBlock { size, offset, data } => {
+                size.is_none_or(|size| len == size) && len >= offset + data.len()
+            }
+            Self::Plausibility { size, min_size } => {
+                size.is_none_or(|size| len == size) && len >= *min_size
+            }
+            _ => true,
+        }
+    }
+
+    /// Bytes of the file needed by [`SourceCheck::check`] (`None` for all)
+    pub fn prefix_len(&self) -> Option<usize> {
+        match self {
+            Self::N64Header { .. } => Some(N64_HEADER_SIZE),
+            _ => None,
+        }
+    }
+
+    /// Test a candidate ROM
+    ///
+    /// Plausibility scoring applies `patch` to a copy of the ROM; the other
+    /// checks only hash or compare. Returns the confidence and a short
+    /// description of what matched.
+    pub fn check(
+        &self,
+        rom: &[u8],
+        patch: &[u8],
+        patch_type: PatchType,
+    ) -> Option<(Confidence, String)> {
+        match self {
+            Self::Crc32 { size, crc } => {
+                if size.is_none_or(|size| rom.len() == size) && crc32fast::hash(rom) == *crc {
+                    return Some((Confidence::Exact, format!("CRC32 {:08X}", crc)));
+                }
+                let body = rom.get(COPIER_HEADER_SIZE..)?;
+                (has_copier_header(rom.len()) && crc32fast::hash(body) == *crc).then(|| {
+                    (
+                        Confidence::Exact,
+                        format!("CRC32 {:08X} without copier header", crc),
+                    )
+                })
+            }
+            Self::Md5 { md5, .. } => (md5::compute(rom).0 == *md5)
+                .then(|| (Confidence::Exact, format!("MD5 {}", hex(md5)))),
+            Self::N64Header {
+                cart_id,
+                crc,
+                order,
+            } => {
+                use crate::n64_byte_order::{ByteOrder, convert};
+
+                let mut header = rom.get(..N64_HEADER_SIZE)?.to_vec();
+                let input = ByteOrder::detect(&header)?;
+                convert(&mut header, input, *order);
+                let matches = header[N64_CART_ID_OFFSET..N64_CART_ID_OFFSET + cart_id.len()]
+                    == cart_id[..]
+                    && header[N64_CRC_OFFSET..N64_CRC_OFFSET + crc.len()] == crc[..];
+                matches.then(|| {
+                    (
+                        Confidence::Exact,
+                        format!(
+                            "cart ID {}, CRC {}",
+                            String::from_utf8_lossy(cart_id),
+                            hex(crc)
+                        ),
+                    )
+                })
+            }
+            Self::PpfBlock { offset, data, .. } => (rom.get(*offset..offset + data.len())?
+                == &data[..])
+                .then(|| (Confidence::Exact, format!("block check at 0x{:X}", offset))),
+            Self::Plausibility { .. } => {
+                let (changed, total) = score(rom, patch, patch_type)?;
+                let percent = (changed * 100 / total.max(1)) as u8;
+                (total > 0 && percent >= PLAUSIBLE_THRESHOLD).then(|| {
+                    (
+                        Confidence::Plausible(percent),
+                        format!("{}/{} records change the ROM", changed, total),
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Bytes read for an N64 header check
+const N64_HEADER_SIZE: usize = 0x40;
+/// Cart ID offset in an N64 ROM header
+const N64_CART_ID_OFFSET: usize = 0x3C;
+/// CRC1/CRC2 offset in an N64 ROM header
+const N64_CRC_OFFSET: usize = 0x10;
+
+/// Smallest ROM that holds the start of every record
+///
+/// Records may extend a ROM, so only their start offsets must be in range.
+fn min_size(ranges: impl Iterator<Item = Range<usize>>) -> usize {
+    ranges.map(|range| range.start).max().unwrap_or(0)
+}
+
+/// Records that land inside `rom` and change it, out of all records
+fn score(rom: &[u8], patch: &[u8], patch_type: PatchType) -> Option<(usize, usize)> {
+    match patch_type {
+        #[cfg(feature = "ppf")]
+        PatchType::Ppf => {
+            let records = crate::ppf::PpfPatcher::records(patch).ok()?.records;
+            let changed = records
+                .iter()
+                .filter(|r| rom.get(r.range()).is_some_and(|old| old != r.data))
+                .count();
+            Some((changed, records.len()))
+        }
+        #[cfg(feature = "ips")]
+        PatchType::Ips | PatchType::Ips32 => {
+            let ranges = if patch_type == PatchType::Ips {
+                crate::ips::IpsPatcher::record_ranges(patch).ok()?
+            } else {
+                crate::ips::Ips32Patcher::record_ranges(patch).ok()?
+            };
+            let mut patched = rom.to_vec();
+            let applied = if patch_type == PatchType::Ips {
+                crate::ips::IpsPatcher.apply(&mut patched, patch)
+            } else {
+                crate::ips::Ips32Patcher.apply(&mut patched, patch)
+            };
+            applied.ok()?;
+            let changed = ranges
+                .iter()
+                .filter(|range| {
+                    rom.get((*range).clone())
+                        .is_some_and(|old| Some(old) != patched.get((*range).clone()))
+                })
+                .count();
+            Some((changed, ranges.len()))
+        }
+        _ => None,
+    }
+}
+
+/// Metadata of formats that record a source checksum
+fn source_metadata(patch: &[u8], patch_type: PatchType) -> Result<PatchMetadata> {
+    match patch_type {
+        #[cfg(feature = "bps")]
+        PatchType::Bps => <crate::bps::BpsPatcher as PatchFormat>::metadata(patch),
+        #[cfg(feature = "ups")]
+        PatchType::Ups => <crate::ups::UpsPatcher as PatchFormat>::metadata(patch),
+        #[cfg(feature = "rup")]
+        PatchType::Rup => <crate::rup::RupPatcher as PatchFormat>::metadata(patch),
+        #[cfg(feature = "pmsr")]
+        PatchType::Pmsr => <crate::pmsr::PmsrPatcher as PatchFormat>::metadata(patch),
+        _ => Err(PatchError::InvalidFormat(format!(
+            "{} patches do not record their source ROM",
+            patch_type.name()
+        ))),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
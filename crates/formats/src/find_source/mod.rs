@@ -0,0 +1,131 @@
+//! Source ROM lookup across a ROM library
+//!
+//! BPS, UPS, PMSR and RUP patches record the size and checksum of the ROM
+//! they were made against, APS N64 patches its cart ID and CRC, and PPF
+//! patches a 1024-byte block of the source image. [`find_source`] walks a
+//! directory tree, prunes files by size, then checks the remaining
+//! candidates in parallel.
+//!
+//! IPS patches and PPF patches without a block check record nothing about
+//! their source; candidates are scored by how many records land inside the
+//! ROM and actually change it, and reported as [`Confidence::Plausible`].
+
+mod check;
+
+pub use check::{Confidence, PLAUSIBLE_THRESHOLD, SourceCheck};
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use stitchr_core::{PatchType, Result};
+
+/// A candidate source ROM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMatch {
+    pub path: PathBuf,
+    pub confidence: Confidence,
+    /// What matched, e.g. "CRC32 1234ABCD"
+    pub detail: String,
+}
+
+/// Find the ROMs under `root` that `patch` can be applied to
+///
+/// Symlinked directories are not followed and unreadable files are
+/// skipped. Exact matches come first, then plausible ones by descending
+/// score; ties are ordered by path.
+pub fn find_source(patch: &[u8], patch_type: PatchType, root: &Path) -> Result<Vec<SourceMatch>> {
+    let check = SourceCheck::from_patch(patch, patch_type)?;
+
+    let mut candidates = Vec::new();
+    collect_candidates(root, &check, &mut candidates)?;
+    candidates.sort();
+
+    let mut matches = scan(&candidates, &check, patch, patch_type);
+    matches.sort_by(|a, b| {
+        rank(a.confidence)
+            .cmp(&rank(b.confidence))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(matches)
+}
+
+/// Sort key: exact first, then higher scores
+fn rank(confidence: Confidence) -> u8 {
+    match confidence {
+        Confidence::Exact => 0,
+        Confidence::Plausible(percent) => 101 - percent.min(100),
+    }
+}
+
+/// Files under `dir` whose size `check` accepts
+fn collect_candidates(dir: &Path, check: &SourceCheck, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let Ok(entry) = entry else { continue };
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+
+        if file_type.is_dir() {
+            // Unreadable subdirectories are skipped, not fatal
+            let _ = collect_candidates(&path, check, out);
+        } else if let Ok(metadata) = fs::metadata(&path)
+            && metadata.is_file()
+            && check.accepts_size(metadata.len() as usize)
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Check candidates on all available cores
+fn scan(
+    candidates: &[PathBuf],
+    check: &SourceCheck,
+    patch: &[u8],
+    patch_type: PatchType,
+) -> Vec<SourceMatch> {
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(candidates.len().max(1));
+    let next = AtomicUsize::new(0);
+    let matches = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while let Some(path) = candidates.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let Ok(rom) = read_candidate(path, check.prefix_len()) else {
+                        continue;
+                    };
+                    if let Some((confidence, detail)) = check.check(&rom, patch, patch_type) {
+                        matches.lock().unwrap().push(SourceMatch {
+                            path: path.clone(),
+                            confidence,
+                            detail,
+                        });
+                    }
+                }
+            });
+        }
+    });
+
+    matches.into_inner().unwrap()
+}
+
+/// Read a whole file, or only its first `prefix` bytes
+fn read_candidate(path: &Path, prefix: Option<usize>) -> std::io::Result<Vec<u8>> {
+    match prefix {
+        Some(len) => {
+            let mut data = Vec::with_capacity(len);
+            File::open(path)?.take(len as u64).read_to_end(&mut data)?;
+            Ok(data)
+        }
+        None => fs::read(path),
+    }
+}
@@ -0,0 +1,50 @@
+//! GDIFF patch application
+
+use super::commands::{Command, CommandReader};
+use super::constants::MAX_TARGET_SIZE;
+use stitchr_core::{PatchError, Result};
+
+/// Apply a GDIFF patch to a ROM
+///
+/// The output is built sequentially from DATA and COPY commands; COPY reads
+/// from the original ROM.
+pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+    let mut reader = CommandReader::new(patch)?;
+    let mut target = Vec::new();
+
+    while let Some(command) = reader.next_command()? {
+        match command {
+            Command::Data(data) => {
+                check_growth(target.len(), data.len() as u64)?;
+                target.extend_from_slice(data);
+            }
+            Command::Copy { position, length } => {
+                check_growth(target.len(), length)?;
+                let start = position as usize;
+                let end = position
+                    .checked_add(length)
+                    .filter(|&end| end <= rom.len() as u64)
+                    .ok_or(PatchError::OutOfBounds {
+                        offset: start,
+                        rom_size: rom.len(),
+                    })? as usize;
+                target.extend_from_slice(&rom[start..end]);
+            }
+        }
+    }
+
+    *rom = target;
+    Ok(())
+}
+
+/// Reject output that would grow past `MAX_TARGET_SIZE`
+fn check_growth(current: usize, length: u64) -> Result<()> {
+    let size = (current as u64).saturating_add(length);
+    if size > MAX_TARGET_SIZE {
+        return Err(PatchError::InvalidFormat(format!(
+            "Target size too large: {} (max {})",
+            size, MAX_TARGET_SIZE
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! GDIFF command stream decoding
+
+use super::constants::*;
+use stitchr_core::{PatchError, Result};
+
+/// A decoded GDIFF command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command<'a> {
+    /// Append literal bytes to the output
+    Data(&'a [u8]),
+    /// Append `length` bytes of the source starting at `position`
+    Copy { position: u64, length: u64 },
+}
+
+/// Sequential reader over the commands of a GDIFF patch
+pub struct CommandReader<'a> {
+    patch: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+impl<'a> CommandReader<'a> {
+    /// Create a reader after checking the magic and version
+    pub fn new(patch: &'a [u8]) -> Result<Self> {
+        if patch.len() < MAGIC_SIZE || &patch[..MAGIC_SIZE] != MAGIC {
+            return Err(PatchError::InvalidMagic {
+                expected: MAGIC.to_vec(),
+                actual: patch.get(..MAGIC_SIZE).unwrap_or(patch).to_vec(),
+            });
+        }
+        match patch.get(MAGIC_SIZE) {
+            Some(&VERSION) => {}
+            Some(&version) => {
+                return Err(PatchError::UnsupportedVersion(format!(
+                    "GDIFF version {}",
+                    version
+                )));
+            }
+            None => {
+                return Err(PatchError::UnexpectedEof(
+                    "Missing GDIFF version".to_string(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            patch,
+            offset: HEADER_SIZE,
+            done: false,
+        })
+    }
+
+    /// Position of the next unread byte
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    /// Read the next command, or `None` once the EOF opcode was consumed
+    pub fn next_command(&mut self) -> Result<Option<Command<'a>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let opcode = self.read_uint(1)? as u8;
+        let command = match opcode {
+            OP_EOF => {
+                self.done = true;
+                return Ok(None);
+            }
+            1..=OP_DATA_MAX_INLINE => self.read_data(opcode as u64)?,
+            OP_DATA_USHORT => {
+                let length = self.read_uint(2)?;
+                self.read_data(length)?
+            }
+            OP_DATA_INT => {
+                let length = self.read_uint(4)?;
+                self.read_data(length)?
+            }
+            _ => {
+                let (position_width, length_width) = copy_widths(opcode);
+                Command::Copy {
+                    position: self.read_uint(position_width)?,
+                    length: self.read_uint(length_width)?,
+                }
+            }
+        };
+
+        Ok(Some(command))
+    }
+
+    /// Read a big-endian unsigned integer of `width` bytes
+    fn read_uint(&mut self, width: usize) -> Result<u64> {
+        let bytes = self.take(width)?;
+        Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    fn read_data(&mut self, length: u64) -> Result<Command<'a>> {
+        let length = usize::try_from(length).map_err(|_| PatchError::CorruptedData)?;
+        Ok(Command::Data(self.take(length)?))
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(length)
+            .filter(|&end| end <= self.patch.len())
+            .ok_or_else(|| PatchError::UnexpectedEof("Truncated GDIFF command".to_string()))?;
+        let bytes = &self.patch[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+}
+
+/// Byte widths of (position, length) for a COPY opcode (249-255)
+fn copy_widths(opcode: u8) -> (usize, usize) {
+    match opcode {
+        OP_COPY_USHORT_UBYTE => (2, 1),
+        OP_COPY_USHORT_USHORT => (2, 2),
+        OP_COPY_USHORT_INT => (2, 4),
+        OP_COPY_INT_UBYTE => (4, 1),
+        OP_COPY_INT_USHORT => (4, 2),
+        OP_COPY_INT_INT => (4, 4),
+        _ => (8, 4),
+    }
+}
@@ -0,0 +1,37 @@
+//! GDIFF format constants
+
+/// Magic bytes 0xD1FFD1FF
+pub const MAGIC: &[u8] = &[0xD1, 0xFF, 0xD1, 0xFF];
+pub const MAGIC_SIZE: usize = 4;
+
+/// Only defined format version
+pub const VERSION: u8 = 4;
+
+/// Header size: magic (4) + version (1)
+pub const HEADER_SIZE: usize = 5;
+
+/// End of patch
+pub const OP_EOF: u8 = 0;
+/// Largest DATA opcode whose value is the data length itself (1-246)
+pub const OP_DATA_MAX_INLINE: u8 = 246;
+/// DATA with a ushort length
+pub const OP_DATA_USHORT: u8 = 247;
+/// DATA with an int length
+pub const OP_DATA_INT: u8 = 248;
+/// COPY with ushort position, ubyte length
+pub const OP_COPY_USHORT_UBYTE: u8 = 249;
+/// COPY with ushort position, ushort length
+pub const OP_COPY_USHORT_USHORT: u8 = 250;
+/// COPY with ushort position, int length
+pub const OP_COPY_USHORT_INT: u8 = 251;
+/// COPY with int position, ubyte length
+pub const OP_COPY_INT_UBYTE: u8 = 252;
+/// COPY with int position, ushort length
+pub const OP_COPY_INT_USHORT: u8 = 253;
+/// COPY with int position, int length
+pub const OP_COPY_INT_INT: u8 = 254;
+/// COPY with long position, int length
+pub const OP_COPY_LONG_INT: u8 = 255;
+
+/// Maximum allowed target size (OOM protection)
+pub const MAX_TARGET_SIZE: u64 = 512 * 1024 * 1024;
@@ -0,0 +1,167 @@
+//! GDIFF patch creation
+//!
+//! Greedy encoder: every target position first tries the same offset in the
+//! source (the common case for ROM hacks), then a hashed index of source
+//! blocks to find moved data. Matches are emitted as COPY, everything else
+//! as DATA.
+
+use std::collections::HashMap;
+
+use super::constants::*;
+use stitchr_core::Result;
+
+/// Size of the source blocks indexed for match lookup
+const BLOCK_SIZE: usize = 16;
+
+/// Shortest match worth a COPY command (its opcode plus arguments)
+const MIN_MATCH: usize = 8;
+
+/// Longest length a single DATA or COPY int argument can carry
+const MAX_INT_LENGTH: usize = i32::MAX as usize;
+
+/// Create a GDIFF patch that turns `source` into `target`
+pub fn create(source: &[u8], target: &[u8]) -> Result<Vec<u8>> {
+    let index = build_index(source);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+
+    let mut literal_start = 0;
+    let mut pos = 0;
+    while pos < target.len() {
+        match find_match(source, target, pos, &index) {
+            Some((mut src, mut len)) => {
+                // Pull bytes back out of the pending literal run when the
+                // match also extends backwards
+                let mut start = pos;
+                while start > literal_start && src > 0 && source[src - 1] == target[start - 1] {
+                    start -= 1;
+                    src -= 1;
+                    len += 1;
+                }
+                emit_data(&mut out, &target[literal_start..start]);
+                emit_copy(&mut out, src as u64, len);
+                pos = start + len;
+                literal_start = pos;
+            }
+            None => pos += 1,
+        }
+    }
+    emit_data(&mut out, &target[literal_start..]);
+    out.push(OP_EOF);
+
+    Ok(out)
+}
+
+/// Index the first source offset of every aligned block by content hash
+fn build_index(source: &[u8]) -> HashMap<u64, usize> {
+    let mut index = HashMap::new();
+    for start in (0..source.len().saturating_sub(BLOCK_SIZE - 1)).step_by(BLOCK_SIZE) {
+        index
+            .entry(hash_block(&source[start..start + BLOCK_SIZE]))
+            .or_insert(start);
+    }
+    index
+}
+
+/// FNV-1a over one block
+fn hash_block(block: &[u8]) -> u64 {
+    block.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// Find the longest usable match for `target[pos..]` as (source offset, length)
+fn find_match(
+    source: &[u8],
+    target: &[u8],
+    pos: usize,
+    index: &HashMap<u64, usize>,
+) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    let mut consider = |src: usize| {
+        let len = match_length(source, target, src, pos);
+        if len >= MIN_MATCH && best.is_none_or(|(_, best_len)| len > best_len) {
+            best = Some((src, len));
+        }
+    };
+
+    if pos < source.len() {
+        consider(pos);
+    }
+    if pos + BLOCK_SIZE <= target.len()
+        && let Some(&src) = index.get(&hash_block(&target[pos..pos + BLOCK_SIZE]))
+        && src != pos
+    {
+        consider(src);
+    }
+
+    best
+}
+
+/// Number of equal bytes at `source[src..]` and `target[pos..]`
+fn match_length(source: &[u8], target: &[u8], src: usize, pos: usize) -> usize {
+    source[src..]
+        .iter()
+        .zip(&target[pos..])
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// Emit DATA commands for `data`, choosing the smallest length encoding
+fn emit_data(out: &mut Vec<u8>, data: &[u8]) {
+    for chunk in data.chunks(MAX_INT_LENGTH) {
+        let len = chunk.len();
+        if len <= OP_DATA_MAX_INLINE as usize {
+            out.push(len as u8);
+        } else if len <= u16::MAX as usize {
+            out.push(OP_DATA_USHORT);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            out.push(OP_DATA_INT);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        out.extend_from_slice(chunk);
+    }
+}
+
+/// Emit COPY commands, choosing the smallest position/length encoding
+fn emit_copy(out: &mut Vec<u8>, mut position: u64, mut length: usize) {
+    while length > 0 {
+        let len = length.min(MAX_INT_LENGTH);
+
+        let position_width = if position <= u16::MAX as u64 {
+            2
+        } else if position <= i32::MAX as u64 {
+            4
+        } else {
+            8
+        };
+        let length_width = if len <= u8::MAX as usize {
+            1
+        } else if len <= u16::MAX as usize {
+            2
+        } else {
+            4
+        };
+
+        let (opcode, length_width) = match (position_width, length_width) {
+            (2, 1) => (OP_COPY_USHORT_UBYTE, 1),
+            (2, 2) => (OP_COPY_USHORT_USHORT, 2),
+            (2, _) => (OP_COPY_USHORT_INT, 4),
+            (4, 1) => (OP_COPY_INT_UBYTE, 1),
+            (4, 2) => (OP_COPY_INT_USHORT, 2),
+            (4, _) => (OP_COPY_INT_INT, 4),
+            // The long-position form only comes with an int length
+            _ => (OP_COPY_LONG_INT, 4),
+        };
+
+        out.push(opcode);
+        out.extend_from_slice(&position.to_be_bytes()[8 - position_width..]);
+        out.extend_from_slice(&(len as u64).to_be_bytes()[8 - length_width..]);
+
+        position += len as u64;
+        length -= len;
+    }
+}
@@ -0,0 +1,41 @@
+//! GDIFF metadata extraction
+
+use super::commands::{Command, CommandReader};
+use stitchr_core::{PatchMetadata, PatchType, Result};
+
+/// Extract metadata from a GDIFF patch
+///
+/// GDIFF has no header fields besides the version, so the output size is
+/// the sum of all command lengths. The furthest COPY end is reported as the
+/// minimum source size.
+pub fn extract(patch: &[u8]) -> Result<PatchMetadata> {
+    let mut reader = CommandReader::new(patch)?;
+
+    let mut target_size = 0u64;
+    let mut min_source_size = 0u64;
+    let mut data_commands = 0usize;
+    let mut copy_commands = 0usize;
+
+    while let Some(command) = reader.next_command()? {
+        match command {
+            Command::Data(data) => {
+                target_size = target_size.saturating_add(data.len() as u64);
+                data_commands += 1;
+            }
+            Command::Copy { position, length } => {
+                target_size = target_size.saturating_add(length);
+                min_source_size = min_source_size.max(position.saturating_add(length));
+                copy_commands += 1;
+            }
+        }
+    }
+
+    let mut metadata = PatchMetadata::new(PatchType::Gdiff);
+    metadata.target_size = Some(target_size as usize);
+
+    Ok(metadata
+        .with_extra("version".to_string(), "4".to_string())
+        .with_extra("data_commands".to_string(), data_commands.to_string())
+        .with_extra("copy_commands".to_string(), copy_commands.to_string())
+        .with_extra("min_source_size".to_string(), min_source_size.to_string()))
+}
@@ -0,0 +1,43 @@
+//! GDIFF (W3C Generic Diff Format) support
+//!
+//! GDIFF is a generic binary diff (W3C NOTE-gdiff-19970901): a magic and
+//! version header followed by DATA and COPY commands that build the output
+//! sequentially.
+
+use stitchr_core::{PatchFormat, PatchMetadata, Result};
+
+pub mod apply;
+pub mod commands;
+pub mod constants;
+pub mod metadata;
+pub mod validate;
+
+mod create;
+
+/// GDIFF format patcher
+pub struct GdiffPatcher;
+
+impl GdiffPatcher {
+    /// Create a GDIFF patch that turns `source` into `target`
+    pub fn create(source: &[u8], target: &[u8]) -> Result<Vec<u8>> {
+        create::create(source, target)
+    }
+}
+
+impl PatchFormat for GdiffPatcher {
+    fn can_handle(data: &[u8]) -> bool {
+        validate::can_handle(data)
+    }
+
+    fn apply(&self, rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+        apply::apply(rom, patch)
+    }
+
+    fn metadata(patch: &[u8]) -> Result<PatchMetadata> {
+        metadata::extract(patch)
+    }
+
+    fn validate(patch: &[u8]) -> Result<()> {
+        validate::validate(patch)
+    }
+}
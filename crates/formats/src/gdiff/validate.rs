@@ -0,0 +1,17 @@
+//! GDIFF validation
+
+use super::commands::CommandReader;
+use super::constants::{HEADER_SIZE, MAGIC, MAGIC_SIZE, VERSION};
+use stitchr_core::Result;
+
+/// Check for GDIFF magic and version 4
+pub fn can_handle(data: &[u8]) -> bool {
+    data.len() >= HEADER_SIZE && &data[..MAGIC_SIZE] == MAGIC && data[MAGIC_SIZE] == VERSION
+}
+
+/// Validate that every command is complete and the stream ends with EOF
+pub fn validate(patch: &[u8]) -> Result<()> {
+    let mut reader = CommandReader::new(patch)?;
+    while reader.next_command()?.is_some() {}
+    Ok(())
+}
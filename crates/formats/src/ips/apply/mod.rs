@@ -2,23 +2,23 @@
 
 mod records;
 
-use crate::ips::constants::{EOF_MARKER, HEADER};
-use crate::ips::io::read_u24_be;
+use crate::ips::variant::Variant;
 use stitchr_core::{PatchError, Result};
 
-/// Apply IPS patch to ROM data
-pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
-    validate_header(patch)?;
+/// Apply IPS (or IPS32) patch to ROM data
+pub fn apply(rom: &mut Vec<u8>, patch: &[u8], variant: Variant) -> Result<()> {
+    validate_header(patch, variant)?;
 
-    let mut offset = HEADER.len();
+    let mut offset = variant.header.len();
     let patch_len = patch.len();
+    let width = variant.offset_width;
 
-    while offset + 3 <= patch_len {
-        let record_offset = read_u24_be(&patch[offset..offset + 3]);
-        offset += 3;
+    while offset + width <= patch_len {
+        let record_offset = variant.read_offset(&patch[offset..offset + width]);
+        offset += width;
 
-        if record_offset == EOF_MARKER {
-            handle_eof(rom, patch, offset)?;
+        if record_offset == variant.eof_marker {
+            handle_eof(rom, patch, offset, variant);
             return Ok(());
         }
 
@@ -29,26 +29,26 @@ pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
 }
 
 /// Validate IPS header
-fn validate_header(patch: &[u8]) -> Result<()> {
-    if patch.len() < HEADER.len() || &patch[0..HEADER.len()] != HEADER {
+fn validate_header(patch: &[u8], variant: Variant) -> Result<()> {
+    if !variant.matches(patch) {
         return Err(PatchError::InvalidMagic {
-            expected: HEADER.to_vec(),
-            actual: patch.get(0..HEADER.len()).unwrap_or(&[]).to_vec(),
+            expected: variant.header.to_vec(),
+            actual: patch.get(0..variant.header.len()).unwrap_or(&[]).to_vec(),
         });
     }
     Ok(())
 }
 
 /// Handle EOF marker and optional truncation
-fn handle_eof(rom: &mut Vec<u8>, patch: &[u8], offset: usize) -> Result<()> {
-    if offset + 3 <= patch.len() {
-        let truncate_size = read_u24_be(&patch[offset..offset + 3]);
+fn handle_eof(rom: &mut Vec<u8>, patch: &[u8], offset: usize, variant: Variant) {
+    let width = variant.offset_width;
+    if offset + width <= patch.len() {
+        let truncate_size = variant.read_offset(&patch[offset..offset + width]);
         rom.truncate(truncate_size as usize);
     }
-    Ok(())
 }
 
 /// Check if data is a valid IPS patch (magic header check)
-pub fn can_handle(data: &[u8]) -> bool {
-    data.len() >= HEADER.len() && &data[0..HEADER.len()] == HEADER
+pub fn can_handle(data: &[u8], variant: Variant) -> bool {
+    variant.matches(data)
 }
@@ -1,5 +1,6 @@
 //! IPS record handlers (RLE and normal)
 
+use crate::ips::constants::MAX_TARGET_SIZE;
 use crate::ips::io::read_u16_be;
 use stitchr_core::{PatchError, Result};
 
@@ -56,7 +57,7 @@ fn apply_rle_record(
     let value = patch[offset + 2];
 
     // Ensure ROM is large enough for this write
-    ensure_rom_size(rom, record_offset + rle_size);
+    ensure_rom_size(rom, record_offset + rle_size)?;
 
     // Fill the range with the repeated value
     rom[record_offset..record_offset + rle_size].fill(value);
@@ -77,16 +78,26 @@ fn apply_normal_record(
         return Err(PatchError::CorruptedData);
     }
 
-    ensure_rom_size(rom, record_offset + size);
+    ensure_rom_size(rom, record_offset + size)?;
     rom[record_offset..record_offset + size].copy_from_slice(&patch[offset..offset + size]);
 
     Ok(offset + size)
 }
 
 /// Ensure ROM is large enough for the operation
+///
+/// IPS32 offsets can address 4 GiB, so growth is capped at
+/// `MAX_TARGET_SIZE` to avoid OOM on hostile patches.
 #[inline]
-fn ensure_rom_size(rom: &mut Vec<u8>, required_size: usize) {
+fn ensure_rom_size(rom: &mut Vec<u8>, required_size: usize) -> Result<()> {
     if rom.len() < required_size {
+        if required_size > MAX_TARGET_SIZE {
+            return Err(PatchError::InvalidFormat(format!(
+                "Target size too large: {} (max {})",
+                required_size, MAX_TARGET_SIZE
+            )));
+        }
         rom.resize(required_size, 0);
     }
+    Ok(())
 }
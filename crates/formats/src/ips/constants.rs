@@ -2,5 +2,10 @@
 
 pub(super) const HEADER: &[u8] = b"PATCH";
 pub(super) const EOF_MARKER: u32 = 0x454F46;
+pub(super) const IPS32_HEADER: &[u8] = b"IPS32";
+pub(super) const IPS32_EOF_MARKER: u32 = 0x45454F46;
 pub const MAX_ROM_SIZE: usize = 16 * 1024 * 1024;
 pub const MAX_RECORD_SIZE: usize = 65535;
+
+/// Upper bound for the patched ROM (OOM protection for 32-bit offsets)
+pub(super) const MAX_TARGET_SIZE: usize = 512 * 1024 * 1024;
@@ -0,0 +1,167 @@
+//! IPS patch creation
+//!
+//! Diffs a source and target ROM and emits the records needed to turn one
+//! into the other. Runs of identical bytes are stored as RLE records and
+//! short unchanged gaps are folded into the surrounding record when that is
+//! cheaper than starting a new one.
+
+use stitchr_core::{PatchError, Result};
+
+use super::constants::MAX_RECORD_SIZE;
+use super::io::write_uint_be;
+use super::variant::Variant;
+
+/// Create an IPS (or IPS32) patch that turns `source` into `target`
+pub fn create(source: &[u8], target: &[u8], variant: Variant) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    out.extend_from_slice(variant.header);
+
+    for (start, end) in changed_ranges(source, target, variant) {
+        emit_range(&mut out, target, start, end, variant)?;
+    }
+
+    write_uint_be(&mut out, variant.eof_marker, variant.offset_width);
+
+    if target.len() < source.len() {
+        let size = u32::try_from(target.len())
+            .ok()
+            .filter(|&size| u64::from(size) <= variant.max_offset())
+            .ok_or_else(|| too_large(target.len(), variant))?;
+        write_uint_be(&mut out, size, variant.offset_width);
+    }
+
+    Ok(out)
+}
+
+/// Collect `[start, end)` ranges of target bytes that must be written
+///
+/// Bytes past the end of the source always count as changed so the patch
+/// grows the ROM to the right size.
+fn changed_ranges(source: &[u8], target: &[u8], variant: Variant) -> Vec<(usize, usize)> {
+    let differs = |i: usize| i >= source.len() || source[i] != target[i];
+    // Cost of opening a new record; gaps shorter than this are cheaper to copy
+    let record_overhead = variant.offset_width + 2;
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < target.len() {
+        if !differs(i) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < target.len() && differs(i) {
+            i += 1;
+        }
+
+        match ranges.last_mut() {
+            Some(last) if start - last.1 <= record_overhead => last.1 = i,
+            _ => ranges.push((start, i)),
+        }
+    }
+
+    ranges
+}
+
+/// Emit records covering `target[start..end]`
+pub(super) fn emit_range(
+    out: &mut Vec<u8>,
+    target: &[u8],
+    start: usize,
+    end: usize,
+    variant: Variant,
+) -> Result<()> {
+    // An RLE record costs a full record header plus 3 bytes and usually forces
+    // a new literal record after it, so only split out runs that beat that.
+    let rle_threshold = 2 * (variant.offset_width + 2) + 3;
+
+    let mut literal_start = start;
+    let mut i = start;
+    while i < end {
+        let run = run_length(target, i, end);
+        if run >= rle_threshold {
+            emit_literal(out, target, literal_start, i, variant)?;
+            emit_rle(out, target, i, run, variant)?;
+            i += run;
+            literal_start = i;
+        } else {
+            i += run;
+        }
+    }
+    emit_literal(out, target, literal_start, end, variant)
+}
+
+/// Length of the run of identical bytes starting at `pos` (bounded by `end`)
+fn run_length(data: &[u8], pos: usize, end: usize) -> usize {
+    let value = data[pos];
+    data[pos..end].iter().take_while(|&&b| b == value).count()
+}
+
+/// Emit literal records for `target[start..end]`, splitting at the size limit
+fn emit_literal(
+    out: &mut Vec<u8>,
+    target: &[u8],
+    mut start: usize,
+    end: usize,
+    variant: Variant,
+) -> Result<()> {
+    while start < end {
+        // A record starting at the EOF marker offset would end the patch early,
+        // so start one byte earlier and rewrite that byte as well.
+        if start as u64 == u64::from(variant.eof_marker) {
+            start -= 1;
+        }
+        let len = (end - start).min(MAX_RECORD_SIZE);
+        write_record_offset(out, start, variant)?;
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+        out.extend_from_slice(&target[start..start + len]);
+        start += len;
+    }
+    Ok(())
+}
+
+/// Emit RLE records for a run of `len` copies of `target[start]`
+fn emit_rle(
+    out: &mut Vec<u8>,
+    target: &[u8],
+    mut start: usize,
+    len: usize,
+    variant: Variant,
+) -> Result<()> {
+    let value = target[start];
+    let end = start + len;
+    while start < end {
+        // Same EOF marker collision as above, but the preceding byte may
+        // differ from the run value, so write this byte as a literal.
+        if start as u64 == u64::from(variant.eof_marker) {
+            emit_literal(out, target, start, start + 1, variant)?;
+            start += 1;
+            continue;
+        }
+        let count = (end - start).min(MAX_RECORD_SIZE);
+        write_record_offset(out, start, variant)?;
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&(count as u16).to_be_bytes());
+        out.push(value);
+        start += count;
+    }
+    Ok(())
+}
+
+/// Write a record offset, rejecting offsets the variant cannot address
+fn write_record_offset(out: &mut Vec<u8>, offset: usize, variant: Variant) -> Result<()> {
+    let value = u32::try_from(offset)
+        .ok()
+        .filter(|&value| u64::from(value) <= variant.max_offset())
+        .ok_or_else(|| too_large(offset, variant))?;
+    write_uint_be(out, value, variant.offset_width);
+    Ok(())
+}
+
+fn too_large(offset: usize, variant: Variant) -> PatchError {
+    PatchError::OutOfBounds {
+        offset,
+        rom_size: variant.max_offset() as usize,
+    }
+}
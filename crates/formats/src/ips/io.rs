@@ -2,6 +2,12 @@
 //!
 //! Provides big-endian integer reading/writing utilities.
 
+/// Read 32-bit big-endian unsigned integer
+#[inline]
+pub(super) fn read_u32_be(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
 /// Read 24-bit big-endian unsigned integer
 #[inline]
 pub(super) fn read_u24_be(bytes: &[u8]) -> u32 {
@@ -13,3 +19,9 @@ pub(super) fn read_u24_be(bytes: &[u8]) -> u32 {
 pub(super) fn read_u16_be(bytes: &[u8]) -> u16 {
     ((bytes[0] as u16) << 8) | (bytes[1] as u16)
 }
+
+/// Write the low `width` bytes of `value` in big-endian order
+#[inline]
+pub(super) fn write_uint_be(out: &mut Vec<u8>, value: u32, width: usize) {
+    out.extend_from_slice(&value.to_be_bytes()[4 - width..]);
+}
@@ -1,19 +1,21 @@
 //! IPS metadata extraction
 
-use stitchr_core::{PatchError, PatchMetadata, PatchType, Result};
+use std::ops::Range;
 
-use super::constants::{EOF_MARKER, HEADER};
-use super::io::{read_u16_be, read_u24_be};
+use stitchr_core::{PatchError, PatchMetadata, Result};
 
-/// Extract metadata from IPS patch
+use super::io::read_u16_be;
+use super::variant::Variant;
+
+/// Extract metadata from IPS (or IPS32) patch
 ///
 /// Note: IPS format doesn't store checksums, so we estimate sizes
 /// by analyzing patch records.
-pub fn extract(patch: &[u8]) -> Result<PatchMetadata> {
-    validate_patch(patch)?;
+pub fn extract(patch: &[u8], variant: Variant) -> Result<PatchMetadata> {
+    validate_patch(patch, variant)?;
 
-    let mut metadata = PatchMetadata::new(PatchType::Ips);
-    let max_offset = calculate_max_offset(patch, &mut metadata)?;
+    let mut metadata = PatchMetadata::new(variant.patch_type);
+    let max_offset = calculate_max_offset(patch, variant, &mut metadata)?;
 
     if metadata.target_size.is_none() {
         metadata.target_size = Some(max_offset);
@@ -23,29 +25,37 @@ pub fn extract(patch: &[u8]) -> Result<PatchMetadata> {
 }
 
 /// Validate patch format
-fn validate_patch(patch: &[u8]) -> Result<()> {
-    if patch.len() < HEADER.len() || &patch[0..HEADER.len()] != HEADER {
-        return Err(PatchError::InvalidFormat("Not an IPS patch".to_string()));
+fn validate_patch(patch: &[u8], variant: Variant) -> Result<()> {
+    if !variant.matches(patch) {
+        return Err(PatchError::InvalidFormat(format!(
+            "Not an {} patch",
+            variant.name
+        )));
     }
     Ok(())
 }
 
 /// Calculate maximum offset from patch records
-fn calculate_max_offset(patch: &[u8], metadata: &mut PatchMetadata) -> Result<usize> {
+fn calculate_max_offset(
+    patch: &[u8],
+    variant: Variant,
+    metadata: &mut PatchMetadata,
+) -> Result<usize> {
     let mut max_offset = 0usize;
-    let mut offset = HEADER.len();
+    let mut offset = variant.header.len();
+    let width = variant.offset_width;
 
-    while offset + 3 <= patch.len() {
-        let record_offset = read_u24_be(&patch[offset..offset + 3]);
+    while offset + width <= patch.len() {
+        let record_offset = variant.read_offset(&patch[offset..offset + width]);
 
-        if record_offset == EOF_MARKER {
-            if let Some(truncate_size) = try_read_truncate_size(patch, offset + 3) {
+        if record_offset == variant.eof_marker {
+            if let Some(truncate_size) = try_read_truncate_size(patch, offset + width, variant) {
                 metadata.target_size = Some(truncate_size);
             }
             break;
         }
 
-        offset += 3;
+        offset += width;
         if offset + 2 > patch.len() {
             break;
         }
@@ -58,10 +68,38 @@ fn calculate_max_offset(patch: &[u8], metadata: &mut PatchMetadata) -> Result<us
     Ok(max_offset)
 }
 
+/// List the ROM byte range written by each record, in patch order
+pub fn record_ranges(patch: &[u8], variant: Variant) -> Result<Vec<Range<usize>>> {
+    validate_patch(patch, variant)?;
+
+    let mut ranges = Vec::new();
+    let mut offset = variant.header.len();
+    let width = variant.offset_width;
+
+    while offset + width <= patch.len() {
+        let record_offset = variant.read_offset(&patch[offset..offset + width]);
+        if record_offset == variant.eof_marker {
+            return Ok(ranges);
+        }
+
+        offset += width;
+        if offset + 2 > patch.len() {
+            break;
+        }
+
+        let (new_offset, record_end) = process_record(patch, offset, record_offset as usize)?;
+        ranges.push(record_offset as usize..record_end);
+        offset = new_offset;
+    }
+
+    Err(PatchError::InvalidFormat("Missing EOF marker".to_string()))
+}
+
 /// Try to read truncation size from EOF marker
-fn try_read_truncate_size(patch: &[u8], offset: usize) -> Option<usize> {
-    if offset + 3 <= patch.len() {
-        Some(read_u24_be(&patch[offset..offset + 3]) as usize)
+fn try_read_truncate_size(patch: &[u8], offset: usize, variant: Variant) -> Option<usize> {
+    let width = variant.offset_width;
+    if offset + width <= patch.len() {
+        Some(variant.read_offset(&patch[offset..offset + width]) as usize)
     } else {
         None
     }
@@ -1,32 +1,128 @@
 //! IPS (International Patching System) format support
+//!
+//! Also handles IPS32, the 32-bit offset extension (`IPS32` header, `EEOF`
+//! terminator) used for ROMs larger than 16 MiB.
 
-use stitchr_core::{PatchFormat, PatchMetadata, Result};
+use std::ops::Range;
+
+use stitchr_core::{LintWarning, PatchFormat, PatchMetadata, Result};
 
 mod apply;
 mod constants;
+mod create;
 mod io;
 mod metadata;
+mod optimize;
 mod validate;
+mod variant;
 
 pub use constants::{MAX_RECORD_SIZE, MAX_ROM_SIZE};
 
+use variant::{IPS, IPS32};
+
 /// IPS format patcher
 pub struct IpsPatcher;
 
+impl IpsPatcher {
+    /// Create an IPS patch that turns `source` into `target`
+    ///
+    /// # Errors
+    /// Returns `OutOfBounds` if the target needs offsets past 16 MiB (use
+    /// [`Ips32Patcher::create`] instead)
+    pub fn create(source: &[u8], target: &[u8]) -> Result<Vec<u8>> {
+        create::create(source, target, IPS)
+    }
+
+    /// ROM byte range written by each record, in patch order
+    pub fn record_ranges(patch: &[u8]) -> Result<Vec<Range<usize>>> {
+        metadata::record_ranges(patch, IPS)
+    }
+
+    /// Re-encode `patch` into its smallest equivalent form
+    ///
+    /// Merges adjacent and overlapping records and uses RLE for runs. With
+    /// `source`, writes that leave bytes unchanged are dropped as well, and
+    /// the result is only equivalent when applied to that ROM.
+    pub fn optimize(patch: &[u8], source: Option<&[u8]>) -> Result<Vec<u8>> {
+        optimize::optimize(patch, source, IPS)
+    }
+}
+
 impl PatchFormat for IpsPatcher {
     fn can_handle(data: &[u8]) -> bool {
-        apply::can_handle(data)
+        apply::can_handle(data, IPS)
+    }
+
+    fn apply(&self, rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+        apply::apply(rom, patch, IPS)
+    }
+
+    fn metadata(patch: &[u8]) -> Result<PatchMetadata> {
+        metadata::extract(patch, IPS)
+    }
+
+    fn validate(patch: &[u8]) -> Result<()> {
+        validate::validate(patch, IPS)
+    }
+
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+        validate::lint(patch, IPS)
+    }
+}
+
+/// Lint findings for the IPS records of a patch and the offset just past
+/// its EOF marker (for IPS-based formats such as EBP)
+pub(crate) fn lint_records(patch: &[u8]) -> Result<(Vec<LintWarning>, usize)> {
+    validate::lint_records(patch, IPS)
+}
+
+/// Lint finding for data after the EOF marker of an IPS-based patch
+pub(crate) fn lint_tail(patch: &[u8], eof_end: usize) -> Option<LintWarning> {
+    validate::lint_tail(patch, eof_end, IPS)
+}
+
+/// IPS32 format patcher
+pub struct Ips32Patcher;
+
+impl Ips32Patcher {
+    /// Create an IPS32 patch that turns `source` into `target`
+    pub fn create(source: &[u8], target: &[u8]) -> Result<Vec<u8>> {
+        create::create(source, target, IPS32)
+    }
+
+    /// ROM byte range written by each record, in patch order
+    pub fn record_ranges(patch: &[u8]) -> Result<Vec<Range<usize>>> {
+        metadata::record_ranges(patch, IPS32)
+    }
+
+    /// Re-encode `patch` into its smallest equivalent form
+    ///
+    /// Merges adjacent and overlapping records and uses RLE for runs. With
+    /// `source`, writes that leave bytes unchanged are dropped as well, and
+    /// the result is only equivalent when applied to that ROM.
+    pub fn optimize(patch: &[u8], source: Option<&[u8]>) -> Result<Vec<u8>> {
+        optimize::optimize(patch, source, IPS32)
+    }
+}
+
+impl PatchFormat for Ips32Patcher {
+    fn can_handle(data: &[u8]) -> bool {
+        apply::can_handle(data, IPS32)
     }
 
     fn apply(&self, rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
-        apply::apply(rom, patch)
+        apply::apply(rom, patch, IPS32)
     }
 
     fn metadata(patch: &[u8]) -> Result<PatchMetadata> {
-        metadata::extract(patch)
+        metadata::extract(patch, IPS32)
     }
 
     fn validate(patch: &[u8]) -> Result<()> {
-        validate::validate(patch)
+        validate::validate(patch, IPS32)
+    }
+
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+        validate::lint(patch, IPS32)
     }
 }
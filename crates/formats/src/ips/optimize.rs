@@ -0,0 +1,118 @@
+//! IPS patch optimization
+//!
+//! Decodes the bytes a patch effectively writes (later records win) and
+//! re-emits them with the same record layout as [`super::create`]: adjacent
+//! and overlapping records merge, and long runs become RLE records. With the
+//! source ROM, writes that leave a byte unchanged are dropped and short
+//! unchanged gaps are bridged, which is a fresh diff of source and output.
+
+use stitchr_core::{PatchError, Result};
+
+use super::constants::MAX_TARGET_SIZE;
+use super::create::{create, emit_range};
+use super::io::{read_u16_be, write_uint_be};
+use super::validate::validate;
+use super::variant::Variant;
+
+/// Bytes written by a patch, indexed by ROM offset
+struct Writes {
+    data: Vec<u8>,
+    written: Vec<bool>,
+    truncate: Option<u32>,
+}
+
+impl Writes {
+    fn write(&mut self, offset: usize, bytes: impl ExactSizeIterator<Item = u8>) -> Result<()> {
+        let end = offset + bytes.len();
+        if end > MAX_TARGET_SIZE {
+            return Err(PatchError::InvalidFormat(format!(
+                "Target size too large: {} (max {})",
+                end, MAX_TARGET_SIZE
+            )));
+        }
+        if end > self.data.len() {
+            self.data.resize(end, 0);
+            self.written.resize(end, false);
+        }
+        for (i, byte) in bytes.enumerate() {
+            self.data[offset + i] = byte;
+            self.written[offset + i] = true;
+        }
+        Ok(())
+    }
+
+    /// `[start, end)` ranges of written bytes
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for (i, &written) in self.written.iter().enumerate() {
+            if !written {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(last) if last.1 == i => last.1 = i + 1,
+                _ => ranges.push((i, i + 1)),
+            }
+        }
+        ranges
+    }
+}
+
+/// Re-encode an IPS (or IPS32) patch into its minimal equivalent form
+///
+/// Without `source`, only the patch's own records are rearranged, so the
+/// result works on any ROM the original works on. With `source`, the result
+/// is only equivalent for that ROM.
+pub fn optimize(patch: &[u8], source: Option<&[u8]>, variant: Variant) -> Result<Vec<u8>> {
+    if let Some(source) = source {
+        let mut target = source.to_vec();
+        super::apply::apply(&mut target, patch, variant)?;
+        return create(source, &target, variant);
+    }
+
+    let writes = decode(patch, variant)?;
+    let mut out = variant.header.to_vec();
+    for (start, end) in writes.ranges() {
+        emit_range(&mut out, &writes.data, start, end, variant)?;
+    }
+    write_uint_be(&mut out, variant.eof_marker, variant.offset_width);
+    if let Some(size) = writes.truncate {
+        write_uint_be(&mut out, size, variant.offset_width);
+    }
+    Ok(out)
+}
+
+/// Collect every byte the patch writes, and its truncation size
+fn decode(patch: &[u8], variant: Variant) -> Result<Writes> {
+    validate(patch, variant)?;
+
+    let width = variant.offset_width;
+    let mut writes = Writes {
+        data: Vec::new(),
+        written: Vec::new(),
+        truncate: None,
+    };
+    let mut pos = variant.header.len();
+    loop {
+        // validate() checked the record structure and the EOF marker
+        let offset = variant.read_offset(&patch[pos..pos + width]);
+        pos += width;
+        if offset == variant.eof_marker {
+            if pos + width <= patch.len() {
+                writes.truncate = Some(variant.read_offset(&patch[pos..pos + width]));
+            }
+            return Ok(writes);
+        }
+
+        let size = read_u16_be(&patch[pos..pos + 2]) as usize;
+        pos += 2;
+        if size == 0 {
+            let count = read_u16_be(&patch[pos..pos + 2]) as usize;
+            let value = patch[pos + 2];
+            writes.write(offset as usize, std::iter::repeat_n(value, count))?;
+            pos += 3;
+        } else {
+            writes.write(offset as usize, patch[pos..pos + size].iter().copied())?;
+            pos += size;
+        }
+    }
+}
@@ -1,40 +1,44 @@
 //! IPS patch validation
 
-use stitchr_core::{PatchError, Result};
+use stitchr_core::{LintWarning, PatchError, Result, Severity};
 
-use super::constants::{EOF_MARKER, HEADER};
-use super::io::{read_u16_be, read_u24_be};
+use super::io::read_u16_be;
+use super::variant::Variant;
 
-/// Validate IPS patch structure
+/// Validate IPS (or IPS32) patch structure
 ///
 /// Checks for:
 /// - Valid magic header
 /// - Proper record structure
 /// - Presence of EOF marker
-pub fn validate(patch: &[u8]) -> Result<()> {
-    validate_header(patch)?;
-    validate_records(patch)?;
+pub fn validate(patch: &[u8], variant: Variant) -> Result<()> {
+    validate_header(patch, variant)?;
+    validate_records(patch, variant)?;
     Ok(())
 }
 
 /// Validate header magic bytes
-fn validate_header(patch: &[u8]) -> Result<()> {
-    if patch.len() < HEADER.len() || &patch[0..HEADER.len()] != HEADER {
-        return Err(PatchError::InvalidFormat("Not an IPS patch".to_string()));
+fn validate_header(patch: &[u8], variant: Variant) -> Result<()> {
+    if !variant.matches(patch) {
+        return Err(PatchError::InvalidFormat(format!(
+            "Not an {} patch",
+            variant.name
+        )));
     }
     Ok(())
 }
 
 /// Validate all records in patch
-fn validate_records(patch: &[u8]) -> Result<()> {
-    let mut offset = HEADER.len();
+fn validate_records(patch: &[u8], variant: Variant) -> Result<()> {
+    let mut offset = variant.header.len();
     let patch_len = patch.len();
+    let width = variant.offset_width;
 
-    while offset + 3 <= patch_len {
-        let record_offset = read_u24_be(&patch[offset..offset + 3]);
-        offset += 3;
+    while offset + width <= patch_len {
+        let record_offset = variant.read_offset(&patch[offset..offset + width]);
+        offset += width;
 
-        if record_offset == EOF_MARKER {
+        if record_offset == variant.eof_marker {
             return Ok(()); // Valid EOF found
         }
 
@@ -67,3 +71,123 @@ fn validate_record(patch: &[u8], mut offset: usize, patch_len: usize) -> Result<
         Ok(offset + size as usize)
     }
 }
+
+/// Report suspicious records and data after the EOF marker
+pub fn lint(patch: &[u8], variant: Variant) -> Result<Vec<LintWarning>> {
+    let (mut warnings, eof_end) = lint_records(patch, variant)?;
+    warnings.extend(lint_tail(patch, eof_end, variant));
+    Ok(warnings)
+}
+
+/// Report zero-length, overlapping and out-of-order records
+///
+/// Returns the findings and the patch offset just past the EOF marker.
+pub fn lint_records(patch: &[u8], variant: Variant) -> Result<(Vec<LintWarning>, usize)> {
+    validate(patch, variant)?;
+
+    let mut warnings = Vec::new();
+    let width = variant.offset_width;
+    let mut offset = variant.header.len();
+    let mut previous: Option<(usize, usize)> = None;
+
+    loop {
+        let record_start = offset;
+        let rom_offset = variant.read_offset(&patch[offset..offset + width]) as usize;
+        offset += width;
+        if rom_offset == variant.eof_marker as usize {
+            return Ok((warnings, offset));
+        }
+
+        let size = read_u16_be(&patch[offset..offset + 2]) as usize;
+        let (len, next) = if size == 0 {
+            (
+                read_u16_be(&patch[offset + 2..offset + 4]) as usize,
+                offset + 5,
+            )
+        } else {
+            (size, offset + 2 + size)
+        };
+        offset = next;
+
+        if len == 0 {
+            warnings.push(LintWarning::new(
+                record_start,
+                Severity::Warning,
+                format!("zero-length RLE record at ROM offset 0x{:X}", rom_offset),
+            ));
+            continue;
+        }
+
+        let end = rom_offset + len;
+        if let Some((prev_start, prev_end)) = previous {
+            if rom_offset < prev_end && prev_start < end {
+                warnings.push(LintWarning::new(
+                    record_start,
+                    Severity::Warning,
+                    format!(
+                        "record 0x{:X}-0x{:X} overlaps the previous record 0x{:X}-0x{:X}",
+                        rom_offset, end, prev_start, prev_end
+                    ),
+                ));
+            } else if rom_offset < prev_start {
+                warnings.push(LintWarning::new(
+                    record_start,
+                    Severity::Info,
+                    format!(
+                        "record at ROM offset 0x{:X} comes after the record at 0x{:X}",
+                        rom_offset, prev_start
+                    ),
+                ));
+            }
+        }
+        previous = Some((rom_offset, end));
+    }
+}
+
+/// Report data after the EOF marker (other than a truncation size)
+///
+/// A record meant for the ROM offset that spells the EOF marker ends the
+/// patch early; when the remaining bytes parse as records up to a second
+/// EOF marker, that is what happened.
+pub fn lint_tail(patch: &[u8], eof_end: usize, variant: Variant) -> Option<LintWarning> {
+    let eof_start = eof_end - variant.offset_width;
+    let trailing = patch.len() - eof_end;
+    if trailing == 0 || trailing == variant.offset_width {
+        return None;
+    }
+
+    if parses_as_records(patch, eof_end, variant) {
+        return Some(LintWarning::new(
+            eof_start,
+            Severity::Error,
+            format!(
+                "record at ROM offset 0x{:X} is read as the EOF marker; the {} bytes after it \
+                 are ignored",
+                variant.eof_marker, trailing
+            ),
+        ));
+    }
+    Some(LintWarning::new(
+        eof_end,
+        Severity::Warning,
+        format!("{} bytes of trailing data after EOF", trailing),
+    ))
+}
+
+/// Whether `patch[offset..]` is a record body followed by records and EOF
+fn parses_as_records(patch: &[u8], offset: usize, variant: Variant) -> bool {
+    let width = variant.offset_width;
+    let Ok(mut offset) = validate_record(patch, offset, patch.len()) else {
+        return false;
+    };
+    while offset + width <= patch.len() {
+        if variant.read_offset(&patch[offset..offset + width]) == variant.eof_marker {
+            return true;
+        }
+        match validate_record(patch, offset + width, patch.len()) {
+            Ok(next) => offset = next,
+            Err(_) => return false,
+        }
+    }
+    false
+}
@@ -0,0 +1,64 @@
+//! IPS layout variants
+//!
+//! Classic IPS and IPS32 share the same record structure and only differ
+//! in header magic, offset width and EOF marker.
+
+use stitchr_core::PatchType;
+
+use super::constants::{EOF_MARKER, HEADER, IPS32_EOF_MARKER, IPS32_HEADER};
+use super::io::{read_u24_be, read_u32_be};
+
+/// Describes the on-disk layout of an IPS flavour
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Variant {
+    /// Short name used in error messages
+    pub name: &'static str,
+    /// Magic header at the start of the patch
+    pub header: &'static [u8],
+    /// Offset value that terminates the record list
+    pub eof_marker: u32,
+    /// Width of record offsets (and truncation size) in bytes
+    pub offset_width: usize,
+    /// Patch type reported in metadata
+    pub patch_type: PatchType,
+}
+
+/// Classic IPS: `PATCH` header, 24-bit offsets, `EOF` terminator
+pub(super) const IPS: Variant = Variant {
+    name: "IPS",
+    header: HEADER,
+    eof_marker: EOF_MARKER,
+    offset_width: 3,
+    patch_type: PatchType::Ips,
+};
+
+/// IPS32: `IPS32` header, 32-bit offsets, `EEOF` terminator
+pub(super) const IPS32: Variant = Variant {
+    name: "IPS32",
+    header: IPS32_HEADER,
+    eof_marker: IPS32_EOF_MARKER,
+    offset_width: 4,
+    patch_type: PatchType::Ips32,
+};
+
+impl Variant {
+    /// Check the magic header
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.header.len() && &data[0..self.header.len()] == self.header
+    }
+
+    /// Read a record offset (or truncation size) at the start of `bytes`
+    #[inline]
+    pub fn read_offset(&self, bytes: &[u8]) -> u32 {
+        if self.offset_width == 4 {
+            read_u32_be(bytes)
+        } else {
+            read_u24_be(bytes)
+        }
+    }
+
+    /// Largest offset a record can start at
+    pub fn max_offset(&self) -> u64 {
+        (1u64 << (self.offset_width * 8)) - 1
+    }
+}
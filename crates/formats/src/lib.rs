@@ -1,23 +1,37 @@
 //! Patch format implementations
 //!
 //! This crate provides implementations for various ROM patch formats:
-//! - IPS (International Patching System)
+//! - IPS (International Patching System) and IPS32
 //! - BPS (Beat Patching System)
+//! - BPM (beat multi-file patches)
 //! - UPS (Universal Patching System)
 //! - APS (Nintendo 64 APS Format)
 //! - EBP (Extended Binary Patch)
 //! - RUP (Rupture Patches)
 //! - PPF (PlayStation Patch Format)
 //! - xdelta (Generic binary diff)
+//! - BDF (bsdiff)
+//! - PMSR (Paper Mario Star Rod)
+//! - GDIFF (W3C Generic Diff Format)
 
 use stitchr_core::{PatchFormat, PatchType};
 
+pub mod copier_header;
+pub mod find_source;
+pub mod n64_byte_order;
+pub mod optimize;
+pub mod patch_state;
+pub mod record_summary;
+
 #[cfg(feature = "ips")]
 pub mod ips;
 
 #[cfg(feature = "bps")]
 pub mod bps;
 
+#[cfg(feature = "bpm")]
+pub mod bpm;
+
 #[cfg(feature = "ups")]
 pub mod ups;
 
@@ -39,6 +53,12 @@ pub mod xdelta;
 #[cfg(feature = "bdf")]
 pub mod bdf;
 
+#[cfg(feature = "pmsr")]
+pub mod pmsr;
+
+#[cfg(feature = "gdiff")]
+pub mod gdiff;
+
 /// Auto-detect patch format from file data
 pub fn detect_format(data: &[u8]) -> Option<PatchType> {
     // EBP must be checked before IPS (both use PATCH magic)
@@ -52,11 +72,21 @@ pub fn detect_format(data: &[u8]) -> Option<PatchType> {
         return Some(PatchType::Ips);
     }
 
+    #[cfg(feature = "ips")]
+    if ips::Ips32Patcher::can_handle(data) {
+        return Some(PatchType::Ips32);
+    }
+
     #[cfg(feature = "bps")]
     if bps::BpsPatcher::can_handle(data) {
         return Some(PatchType::Bps);
     }
 
+    #[cfg(feature = "bpm")]
+    if bpm::BpmPatcher::can_handle(data) {
+        return Some(PatchType::Bpm);
+    }
+
     #[cfg(feature = "ups")]
     if ups::UpsPatcher::can_handle(data) {
         return Some(PatchType::Ups);
@@ -87,5 +117,15 @@ pub fn detect_format(data: &[u8]) -> Option<PatchType> {
         return Some(PatchType::Bdf);
     }
 
+    #[cfg(feature = "pmsr")]
+    if pmsr::PmsrPatcher::can_handle(data) {
+        return Some(PatchType::Pmsr);
+    }
+
+    #[cfg(feature = "gdiff")]
+    if gdiff::GdiffPatcher::can_handle(data) {
+        return Some(PatchType::Gdiff);
+    }
+
     None
 }
@@ -0,0 +1,262 @@
+//! N64 ROM byte-order normalization
+//!
+//! N64 dumps come in three layouts, told apart by the first four bytes:
+//! - Big-endian (.z64): `80 37 12 40`, the native cartridge order
+//! - Byte-swapped (.v64): `37 80 40 12`, every 16-bit word swapped
+//! - Little-endian (.n64): `40 12 37 80`, every 32-bit word reversed
+//!
+//! Patches are almost always made against .z64. [`prepare`] converts the
+//! ROM to the order the patch expects before patching, and
+//! [`ByteOrderFix::finish`] converts it back or keeps it, as requested.
+
+use std::fmt;
+
+use stitchr_core::{PatchFormat, PatchType};
+
+/// N64 ROM byte order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Big-endian (.z64)
+    BigEndian,
+    /// 16-bit words swapped (.v64)
+    ByteSwapped,
+    /// 32-bit words reversed (.n64)
+    LittleEndian,
+}
+
+impl ByteOrder {
+    /// Detect the byte order from the ROM's first four bytes
+    pub fn detect(rom: &[u8]) -> Option<Self> {
+        match rom.get(..4)? {
+            [0x80, 0x37, 0x12, 0x40] => Some(Self::BigEndian),
+            [0x37, 0x80, 0x40, 0x12] => Some(Self::ByteSwapped),
+            [0x40, 0x12, 0x37, 0x80] => Some(Self::LittleEndian),
+            _ => None,
+        }
+    }
+
+    /// Customary file extension for this order
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::BigEndian => "z64",
+            Self::ByteSwapped => "v64",
+            Self::LittleEndian => "n64",
+        }
+    }
+
+    /// Human-readable name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BigEndian => "big-endian",
+            Self::ByteSwapped => "byte-swapped",
+            Self::LittleEndian => "little-endian",
+        }
+    }
+}
+
+impl fmt::Display for ByteOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (.{})", self.name(), self.extension())
+    }
+}
+
+/// Convert `rom` in place from one byte order to another
+///
+/// Both swaps are their own inverse, so converting to big-endian and back
+/// uses the same operation. A trailing partial word is left untouched.
+pub fn convert(rom: &mut [u8], from: ByteOrder, to: ByteOrder) {
+    if from != to {
+        to_big_endian(rom, from);
+        to_big_endian(rom, to);
+    }
+}
+
+/// Swap `rom` between `order` and big-endian
+fn to_big_endian(rom: &mut [u8], order: ByteOrder) {
+    match order {
+        ByteOrder::BigEndian => {}
+        ByteOrder::ByteSwapped => rom.chunks_exact_mut(2).for_each(|w| w.swap(0, 1)),
+        ByteOrder::LittleEndian => rom.chunks_exact_mut(4).for_each(|w| w.reverse()),
+    }
+}
+
+/// Byte order wanted for the patched ROM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputOrder {
+    /// Same order as the input ROM
+    #[default]
+    Keep,
+    /// Always big-endian (.z64)
+    BigEndian,
+}
+
+/// State carried from [`prepare`] to [`ByteOrderFix::finish`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteOrderFix {
+    /// Order of the input ROM (`None` if not an N64 ROM)
+    pub input: Option<ByteOrder>,
+    /// Order the patch was applied in
+    pub patched: Option<ByteOrder>,
+}
+
+/// What was done to the byte order around patching
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteOrderReport {
+    /// Conversion before patching (from, to)
+    pub before: Option<(ByteOrder, ByteOrder)>,
+    /// Conversion after patching (from, to)
+    pub after: Option<(ByteOrder, ByteOrder)>,
+    /// Order of the input ROM (`None` if not an N64 ROM)
+    pub input: Option<ByteOrder>,
+    /// Order of the written ROM
+    pub output: Option<ByteOrder>,
+}
+
+impl ByteOrderReport {
+    /// Whether the ROM was converted at all
+    pub fn is_empty(&self) -> bool {
+        self.before.is_none() && self.after.is_none()
+    }
+
+    /// Whether the output order differs from the input order
+    pub fn order_changed(&self) -> bool {
+        self.input != self.output
+    }
+}
+
+impl fmt::Display for ByteOrderReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.before, self.after) {
+            (Some((from, to)), Some((_, back))) if back == from => write!(
+                f,
+                "Converted N64 ROM from {} to {} for patching; converted back afterwards",
+                from, to
+            ),
+            (Some((from, to)), Some((_, out))) => write!(
+                f,
+                "Converted N64 ROM from {} to {} for patching; wrote output as {}",
+                from, to, out
+            ),
+            (Some((from, to)), None) => write!(
+                f,
+                "Converted N64 ROM from {} to {} for patching; output kept as {}",
+                from, to, to
+            ),
+            (None, Some((from, to))) => {
+                write!(f, "Converted patched N64 ROM from {} to {}", from, to)
+            }
+            (None, None) => write!(f, "N64 byte order left unchanged"),
+        }
+    }
+}
+
+impl fmt::Display for ByteOrderFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        ByteOrderReport {
+            before: self.conversion(),
+            after: None,
+            input: self.input,
+            output: self.patched,
+        }
+        .fmt(f)
+    }
+}
+
+/// Convert an N64 `rom` to the byte order `patch` expects
+///
+/// APS N64 patches record the order they were made against in their header;
+/// BPS and UPS patches whose source CRC32 already matches the ROM as loaded
+/// leave it alone. Everything else is applied to big-endian. ROMs without a
+/// recognizable N64 header are never touched.
+pub fn prepare(rom: &mut [u8], patch: &[u8], patch_type: PatchType) -> ByteOrderFix {
+    let Some(input) = ByteOrder::detect(rom) else {
+        return ByteOrderFix {
+            input: None,
+            patched: None,
+        };
+    };
+
+    let patched = expected_order(patch, patch_type)
+        .or_else(|| {
+            source_crc(patch, patch_type)
+                .filter(|&crc| crc == crc32fast::hash(rom))
+                .map(|_| input)
+        })
+        .unwrap_or(ByteOrder::BigEndian);
+    convert(rom, input, patched);
+
+    ByteOrderFix {
+        input: Some(input),
+        patched: Some(patched),
+    }
+}
+
+impl ByteOrderFix {
+    /// Conversion done before patching (from, to), if any
+    pub fn conversion(&self) -> Option<(ByteOrder, ByteOrder)> {
+        match (self.input, self.patched) {
+            (Some(input), Some(patched)) if input != patched => Some((input, patched)),
+            _ => None,
+        }
+    }
+
+    /// Convert the patched ROM to the requested order and report
+    pub fn finish(self, rom: &mut [u8], output: OutputOrder) -> ByteOrderReport {
+        let (Some(input), Some(patched)) = (self.input, self.patched) else {
+            return ByteOrderReport {
+                before: None,
+                after: None,
+                input: None,
+                output: None,
+            };
+        };
+
+        let wanted = match output {
+            OutputOrder::Keep => input,
+            OutputOrder::BigEndian => ByteOrder::BigEndian,
+        };
+        let after = (patched != wanted).then(|| {
+            convert(rom, patched, wanted);
+            (patched, wanted)
+        });
+
+        ByteOrderReport {
+            before: self.conversion(),
+            after,
+            input: Some(input),
+            output: Some(wanted),
+        }
+    }
+}
+
+/// Byte order named in an APS N64 header
+fn expected_order(patch: &[u8], patch_type: PatchType) -> Option<ByteOrder> {
+    match patch_type {
+        #[cfg(feature = "aps")]
+        PatchType::Aps if crate::aps::n64::ApsN64Patcher::can_handle(patch) => {
+            let (header, _) = crate::aps::n64::helpers::parse_header(patch).ok()?;
+            header.n64_header.map(|h| h.original_byte_order())
+        }
+        _ => None,
+    }
+}
+
+/// Source CRC32 recorded in a BPS or UPS patch
+fn source_crc(patch: &[u8], patch_type: PatchType) -> Option<u32> {
+    let checksum: Vec<u8> = match patch_type {
+        #[cfg(feature = "bps")]
+        PatchType::Bps => {
+            <crate::bps::BpsPatcher as PatchFormat>::metadata(patch)
+                .ok()?
+                .source_checksum?
+        }
+        #[cfg(feature = "ups")]
+        PatchType::Ups => {
+            <crate::ups::UpsPatcher as PatchFormat>::metadata(patch)
+                .ok()?
+                .source_checksum?
+        }
+        _ => return None,
+    };
+    Some(u32::from_le_bytes(checksum.get(..4)?.try_into().ok()?))
+}
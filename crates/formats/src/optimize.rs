@@ -0,0 +1,148 @@
+//! Patch optimization
+//!
+//! Re-encodes a patch into the smallest equivalent patch of the same format
+//! (IPS, IPS32, PPF) and checks that both produce the same output before
+//! handing the result back. Equivalence is checked on the source ROM when
+//! one is given, otherwise on a synthetic image covering every record.
+
+use stitchr_core::{PatchError, PatchFormat, PatchType, Result};
+
+/// An optimized patch and the size of the patch it replaces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Optimized {
+    pub patch: Vec<u8>,
+    pub original_size: usize,
+}
+
+impl Optimized {
+    /// Size of the optimized patch
+    pub fn optimized_size(&self) -> usize {
+        self.patch.len()
+    }
+
+    /// Bytes saved compared to the original patch
+    pub fn saved(&self) -> usize {
+        self.original_size.saturating_sub(self.patch.len())
+    }
+}
+
+/// Whether patches of this type can be optimized
+pub fn can_optimize(patch_type: PatchType) -> bool {
+    match patch_type {
+        #[cfg(feature = "ips")]
+        PatchType::Ips | PatchType::Ips32 => true,
+        #[cfg(feature = "ppf")]
+        PatchType::Ppf => true,
+        _ => false,
+    }
+}
+
+/// Re-encode `patch` into its smallest equivalent form
+///
+/// With `source`, writes that leave the source unchanged are dropped and the
+/// result is only equivalent for that ROM. `drop_undo` strips PPF3 undo
+/// data. If re-encoding does not make the patch smaller (and no undo data is
+/// dropped), the original patch is returned.
+///
+/// # Errors
+/// Fails for unsupported formats, invalid patches, and if the optimized
+/// patch does not reproduce the original output.
+pub fn optimize(
+    patch: &[u8],
+    patch_type: PatchType,
+    source: Option<&[u8]>,
+    drop_undo: bool,
+) -> Result<Optimized> {
+    let optimized: Vec<u8> = match patch_type {
+        #[cfg(feature = "ips")]
+        PatchType::Ips => crate::ips::IpsPatcher::optimize(patch, source)?,
+        #[cfg(feature = "ips")]
+        PatchType::Ips32 => crate::ips::Ips32Patcher::optimize(patch, source)?,
+        #[cfg(feature = "ppf")]
+        PatchType::Ppf => crate::ppf::PpfPatcher::optimize(patch, source, drop_undo)?,
+        _ => {
+            return Err(PatchError::Other(format!(
+                "{} patches cannot be optimized",
+                patch_type.name()
+            )));
+        }
+    };
+
+    let patch_out = if optimized.len() < patch.len() || (drop_undo && optimized != patch) {
+        check_equivalent(patch, &optimized, patch_type, source)?;
+        optimized
+    } else {
+        patch.to_vec()
+    };
+    Ok(Optimized {
+        patch: patch_out,
+        original_size: patch.len(),
+    })
+}
+
+/// Apply both patches to the same ROM and compare the results
+fn check_equivalent(
+    original: &[u8],
+    optimized: &[u8],
+    patch_type: PatchType,
+    source: Option<&[u8]>,
+) -> Result<()> {
+    let rom = match source {
+        Some(source) => source.to_vec(),
+        None => test_image(original, patch_type)?,
+    };
+
+    let mut expected = rom.clone();
+    apply(&mut expected, original, patch_type)?;
+    let mut actual = rom;
+    apply(&mut actual, optimized, patch_type)?;
+
+    if actual != expected {
+        return Err(PatchError::Other(
+            "Optimized patch does not reproduce the original output".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Image covering every record, filled with a non-constant pattern so a
+/// missing write shows up
+fn test_image(patch: &[u8], patch_type: PatchType) -> Result<Vec<u8>> {
+    let len = match patch_type {
+        #[cfg(feature = "ips")]
+        PatchType::Ips => crate::ips::IpsPatcher::record_ranges(patch)?
+            .into_iter()
+            .map(|r| r.end)
+            .max(),
+        #[cfg(feature = "ips")]
+        PatchType::Ips32 => crate::ips::Ips32Patcher::record_ranges(patch)?
+            .into_iter()
+            .map(|r| r.end)
+            .max(),
+        #[cfg(feature = "ppf")]
+        PatchType::Ppf => crate::ppf::PpfPatcher::records(patch)?
+            .records
+            .iter()
+            .map(|r| r.range().end)
+            .max(),
+        _ => None,
+    };
+    Ok((0..len.unwrap_or(0))
+        .map(|i| (i as u32).wrapping_mul(0x9E37_79B1).to_be_bytes()[0])
+        .collect())
+}
+
+fn apply(rom: &mut Vec<u8>, patch: &[u8], patch_type: PatchType) -> Result<()> {
+    match patch_type {
+        #[cfg(feature = "ips")]
+        PatchType::Ips => crate::ips::IpsPatcher.apply(rom, patch),
+        #[cfg(feature = "ips")]
+        PatchType::Ips32 => crate::ips::Ips32Patcher.apply(rom, patch),
+        #[cfg(feature = "ppf")]
+        PatchType::Ppf => crate::ppf::PpfPatcher.apply(rom, patch),
+        _ => Err(PatchError::Other(format!(
+            "{} patches cannot be optimized",
+            patch_type.name()
+        ))),
+    }
+}
@@ -0,0 +1,148 @@
+//! Already-applied patch detection
+//!
+//! Running a patch against a ROM that already has it applied either fails
+//! the source check (BPS), silently reverts it (UPS and RUP are symmetric)
+//! or rewrites the same bytes (IPS, PPF). [`detect`] tells these cases apart
+//! before anything is written:
+//! - BPS/UPS: the ROM matches the target size and CRC32, not the source
+//! - RUP: the ROM matches the target MD5, not the source
+//! - IPS/IPS32/PPF: every record's bytes are already in the ROM
+//!
+//! UPS and RUP patches can also be undone with [`revert`].
+
+use std::fmt;
+
+use stitchr_core::{PatchError, PatchFormat, PatchMetadata, PatchType, Result};
+
+/// Whether a ROM already has a patch applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchState {
+    /// Nothing suggests the patch was applied
+    NotApplied,
+    /// The ROM already is the patch output, with the evidence for it
+    Applied(&'static str),
+}
+
+impl PatchState {
+    /// Whether the patch is already applied
+    pub fn is_applied(&self) -> bool {
+        matches!(self, Self::Applied(_))
+    }
+}
+
+impl fmt::Display for PatchState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotApplied => write!(f, "Patch not applied yet"),
+            Self::Applied(evidence) => write!(f, "Patch already applied ({})", evidence),
+        }
+    }
+}
+
+/// Check whether `rom` already has `patch` applied
+pub fn detect(rom: &[u8], patch: &[u8], patch_type: PatchType) -> PatchState {
+    let evidence = match patch_type {
+        PatchType::Bps | PatchType::Ups | PatchType::Rup => by_checksums(rom, patch, patch_type),
+        #[cfg(feature = "ips")]
+        PatchType::Ips | PatchType::Ips32 => by_ips_records(rom, patch, patch_type),
+        #[cfg(feature = "ppf")]
+        PatchType::Ppf => by_ppf_records(rom, patch),
+        _ => None,
+    };
+    evidence.map_or(PatchState::NotApplied, PatchState::Applied)
+}
+
+/// Whether patches of this type can be undone with [`revert`]
+pub fn can_revert(patch_type: PatchType) -> bool {
+    match patch_type {
+        #[cfg(feature = "ups")]
+        PatchType::Ups => true,
+        #[cfg(feature = "rup")]
+        PatchType::Rup => true,
+        _ => false,
+    }
+}
+
+/// Undo `patch` on a ROM that already has it applied (UPS and RUP)
+pub fn revert(rom: &mut Vec<u8>, patch: &[u8], patch_type: PatchType) -> Result<()> {
+    match patch_type {
+        #[cfg(feature = "ups")]
+        PatchType::Ups => crate::ups::UpsPatcher::revert(rom, patch),
+        #[cfg(feature = "rup")]
+        PatchType::Rup => crate::rup::RupPatcher::revert(rom, patch),
+        _ => Err(PatchError::Other(format!(
+            "{} patches cannot be reverted",
+            patch_type.name()
+        ))),
+    }
+}
+
+/// Target checksum (and size) matches, source checksum does not
+fn by_checksums(rom: &[u8], patch: &[u8], patch_type: PatchType) -> Option<&'static str> {
+    let metadata = metadata(patch, patch_type)?;
+    let target = metadata.target_checksum.as_deref()?;
+    if metadata.target_size.is_some_and(|size| size != rom.len()) {
+        return None;
+    }
+
+    let (digest, evidence): (Vec<u8>, _) = match target.len() {
+        4 => (
+            crc32fast::hash(rom).to_le_bytes().to_vec(),
+            "target CRC32 matches the ROM",
+        ),
+        16 => (md5::compute(rom).0.to_vec(), "target MD5 matches the ROM"),
+        _ => return None,
+    };
+    // A patch that leaves the ROM unchanged has source == target
+    let is_source = metadata.source_checksum.as_deref() == Some(&digest[..])
+        && metadata.source_size.is_none_or(|size| size == rom.len());
+    (digest == target && !is_source).then_some(evidence)
+}
+
+fn metadata(patch: &[u8], patch_type: PatchType) -> Option<PatchMetadata> {
+    match patch_type {
+        #[cfg(feature = "bps")]
+        PatchType::Bps => <crate::bps::BpsPatcher as PatchFormat>::metadata(patch).ok(),
+        #[cfg(feature = "ups")]
+        PatchType::Ups => <crate::ups::UpsPatcher as PatchFormat>::metadata(patch).ok(),
+        #[cfg(feature = "rup")]
+        PatchType::Rup => <crate::rup::RupPatcher as PatchFormat>::metadata(patch).ok(),
+        _ => None,
+    }
+}
+
+/// Applying the patch to a copy changes nothing
+#[cfg(feature = "ips")]
+fn by_ips_records(rom: &[u8], patch: &[u8], patch_type: PatchType) -> Option<&'static str> {
+    use crate::ips::{Ips32Patcher, IpsPatcher};
+
+    let ips = patch_type == PatchType::Ips;
+    let ranges = if ips {
+        IpsPatcher::record_ranges(patch)
+    } else {
+        Ips32Patcher::record_ranges(patch)
+    };
+    // Patches without records would trivially "match"
+    if ranges.ok()?.is_empty() {
+        return None;
+    }
+
+    let mut patched = rom.to_vec();
+    if ips {
+        IpsPatcher.apply(&mut patched, patch).ok()?;
+    } else {
+        Ips32Patcher.apply(&mut patched, patch).ok()?;
+    }
+    (patched == rom).then_some("every record's bytes are already in the ROM")
+}
+
+/// Every record lies inside the ROM and its data is already there
+#[cfg(feature = "ppf")]
+fn by_ppf_records(rom: &[u8], patch: &[u8]) -> Option<&'static str> {
+    let records = crate::ppf::PpfPatcher::records(patch).ok()?.records;
+    let applied = !records.is_empty()
+        && records
+            .iter()
+            .all(|record| rom.get(record.range()) == Some(&record.data[..]));
+    applied.then_some("every record's bytes are already in the ROM")
+}
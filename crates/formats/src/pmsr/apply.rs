@@ -0,0 +1,33 @@
+//! PMSR patch application
+
+use super::constants::MAX_TARGET_SIZE;
+use super::helpers::{parse_records, target_size};
+use super::validate::verify_source;
+use stitchr_core::{PatchError, Result};
+
+/// Apply a PMSR patch to a ROM
+///
+/// The ROM must be a clean Paper Mario (USA) v1.0 ROM; it grows past its 40
+/// MiB if a record extends beyond the end.
+pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+    let records = parse_records(patch)?;
+    verify_source(rom)?;
+
+    let size = target_size(rom.len(), &records);
+    if size > MAX_TARGET_SIZE {
+        return Err(PatchError::InvalidFormat(format!(
+            "Target size too large: {} (max {})",
+            size, MAX_TARGET_SIZE
+        )));
+    }
+
+    rom.try_reserve_exact(size - rom.len())
+        .map_err(|_| PatchError::Other("Failed to allocate memory for target ROM".to_string()))?;
+    rom.resize(size, 0);
+
+    for record in &records {
+        rom[record.offset..record.offset + record.data.len()].copy_from_slice(record.data);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+//! PMSR (Paper Mario Star Rod) format constants
+
+/// Magic bytes "PMSR"
+pub const MAGIC: &[u8] = b"PMSR";
+pub const MAGIC_SIZE: usize = 4;
+
+/// Header size: magic (4) + record count (4)
+pub const HEADER_SIZE: usize = 8;
+
+/// Record header size: offset (4) + length (4)
+pub const RECORD_HEADER_SIZE: usize = 8;
+
+/// CRC32 of a clean Paper Mario (USA) v1.0 ROM in big-endian (.z64) order
+pub const PAPER_MARIO_USA10_CRC32: u32 = 0xA7F5_CD7E;
+
+/// Size of the ROM Star Rod mods are built against (40 MiB)
+pub const PAPER_MARIO_USA10_FILE_SIZE: usize = 40 * 1024 * 1024;
+
+/// Maximum allowed target size (OOM protection)
+pub const MAX_TARGET_SIZE: usize = 512 * 1024 * 1024;
@@ -0,0 +1,64 @@
+//! PMSR record parsing
+
+use super::constants::*;
+use stitchr_core::{PatchError, Result};
+
+/// A single PMSR record: raw bytes written at a fixed ROM offset
+#[derive(Debug, Clone, Copy)]
+pub struct PmsrRecord<'a> {
+    pub offset: usize,
+    pub data: &'a [u8],
+}
+
+/// Read a big-endian u32 at `offset`
+fn read_u32_be(patch: &[u8], offset: usize) -> Result<u32> {
+    patch
+        .get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| PatchError::UnexpectedEof("Truncated PMSR patch".to_string()))
+}
+
+/// Parse all records of a PMSR patch
+pub fn parse_records(patch: &[u8]) -> Result<Vec<PmsrRecord<'_>>> {
+    if patch.len() < HEADER_SIZE || &patch[..MAGIC_SIZE] != MAGIC {
+        return Err(PatchError::InvalidFormat("Not a PMSR patch".to_string()));
+    }
+
+    let count = read_u32_be(patch, MAGIC_SIZE)? as usize;
+
+    // Every record needs at least its header, so reject impossible counts
+    // before allocating
+    if count > (patch.len() - HEADER_SIZE) / RECORD_HEADER_SIZE {
+        return Err(PatchError::CorruptedData);
+    }
+
+    let mut records = Vec::with_capacity(count);
+    let mut offset = HEADER_SIZE;
+
+    for _ in 0..count {
+        let rom_offset = read_u32_be(patch, offset)? as usize;
+        let length = read_u32_be(patch, offset + 4)? as usize;
+        offset += RECORD_HEADER_SIZE;
+
+        let end = offset
+            .checked_add(length)
+            .filter(|&end| end <= patch.len())
+            .ok_or(PatchError::CorruptedData)?;
+
+        records.push(PmsrRecord {
+            offset: rom_offset,
+            data: &patch[offset..end],
+        });
+        offset = end;
+    }
+
+    Ok(records)
+}
+
+/// Size of the patched ROM: at least 40 MiB, grown to fit every record
+pub fn target_size(rom_len: usize, records: &[PmsrRecord]) -> usize {
+    records
+        .iter()
+        .map(|r| r.offset.saturating_add(r.data.len()))
+        .fold(rom_len.max(PAPER_MARIO_USA10_FILE_SIZE), usize::max)
+}
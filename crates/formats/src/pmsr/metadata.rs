@@ -0,0 +1,21 @@
+//! PMSR metadata extraction
+
+use super::constants::*;
+use super::helpers::{parse_records, target_size};
+use stitchr_core::{PatchMetadata, PatchType, Result};
+
+/// Extract metadata from a PMSR patch
+pub fn extract(patch: &[u8]) -> Result<PatchMetadata> {
+    let records = parse_records(patch)?;
+    let bytes_written: usize = records.iter().map(|r| r.data.len()).sum();
+
+    let mut metadata = PatchMetadata::new(PatchType::Pmsr);
+    metadata.source_size = Some(PAPER_MARIO_USA10_FILE_SIZE);
+    metadata.target_size = Some(target_size(PAPER_MARIO_USA10_FILE_SIZE, &records));
+    metadata.source_checksum = Some(PAPER_MARIO_USA10_CRC32.to_le_bytes().to_vec());
+
+    Ok(metadata
+        .with_extra("game".to_string(), "Paper Mario (USA)".to_string())
+        .with_extra("record_count".to_string(), records.len().to_string())
+        .with_extra("bytes_written".to_string(), bytes_written.to_string()))
+}
@@ -0,0 +1,42 @@
+//! PMSR (Paper Mario Star Rod) format support
+//!
+//! Star Rod mods ship as a list of raw records (offset, length, data) for a
+//! clean Paper Mario (USA) v1.0 ROM, expanded to 40 MiB.
+
+use stitchr_core::{PatchFormat, PatchMetadata, Result};
+
+pub mod apply;
+pub mod constants;
+pub mod helpers;
+pub mod metadata;
+pub mod validate;
+
+/// PMSR format patcher
+pub struct PmsrPatcher;
+
+impl PatchFormat for PmsrPatcher {
+    fn can_handle(data: &[u8]) -> bool {
+        validate::can_handle(data)
+    }
+
+    fn apply(&self, rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+        apply::apply(rom, patch)
+    }
+
+    fn metadata(patch: &[u8]) -> Result<PatchMetadata> {
+        metadata::extract(patch)
+    }
+
+    fn validate(patch: &[u8]) -> Result<()> {
+        validate::validate(patch)
+    }
+
+    fn verify(rom: &[u8], patch: &[u8], target: Option<&[u8]>) -> Result<()> {
+        validate::validate(patch)?;
+        // PMSR carries no target checksum; only the source can be checked
+        if target.is_none() {
+            validate::verify_source(rom)?;
+        }
+        Ok(())
+    }
+}
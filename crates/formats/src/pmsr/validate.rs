@@ -0,0 +1,36 @@
+//! PMSR validation and source ROM verification
+
+use super::constants::*;
+use super::helpers::parse_records;
+use stitchr_core::{PatchError, Result};
+
+/// Check if data is a PMSR patch (magic header check)
+pub fn can_handle(data: &[u8]) -> bool {
+    data.len() >= MAGIC_SIZE && &data[..MAGIC_SIZE] == MAGIC
+}
+
+/// Validate PMSR patch structure
+pub fn validate(patch: &[u8]) -> Result<()> {
+    parse_records(patch)?;
+    Ok(())
+}
+
+/// Verify that the source is a clean Paper Mario (USA) v1.0 ROM
+pub fn verify_source(rom: &[u8]) -> Result<()> {
+    if rom.len() != PAPER_MARIO_USA10_FILE_SIZE {
+        return Err(PatchError::SizeMismatch {
+            expected: PAPER_MARIO_USA10_FILE_SIZE,
+            actual: rom.len(),
+        });
+    }
+
+    let actual = crc32fast::hash(rom);
+    if actual != PAPER_MARIO_USA10_CRC32 {
+        return Err(PatchError::ChecksumMismatch {
+            expected: PAPER_MARIO_USA10_CRC32,
+            actual,
+        });
+    }
+
+    Ok(())
+}
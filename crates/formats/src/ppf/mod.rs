@@ -7,13 +7,31 @@ pub mod apply;
 pub mod constants;
 pub mod helpers;
 pub mod metadata;
+pub mod optimize;
+pub mod records;
 pub mod validate;
 
-use stitchr_core::{PatchFormat, PatchMetadata, Result};
+use stitchr_core::{LintWarning, PatchFormat, PatchMetadata, Result};
 
 /// PPF format patcher
 pub struct PpfPatcher;
 
+impl PpfPatcher {
+    /// Block check data and records, in patch order
+    pub fn records(patch: &[u8]) -> Result<records::PpfContents> {
+        records::parse(patch)
+    }
+
+    /// Re-encode `patch` into its smallest equivalent form
+    ///
+    /// Merges adjacent and overlapping records and, with `drop_undo`, strips
+    /// PPF3 undo data. With `source`, writes that leave bytes unchanged are
+    /// dropped as well, and the result is only equivalent for that image.
+    pub fn optimize(patch: &[u8], source: Option<&[u8]>, drop_undo: bool) -> Result<Vec<u8>> {
+        optimize::optimize_patch(patch, source, drop_undo)
+    }
+}
+
 impl PatchFormat for PpfPatcher {
     fn can_handle(data: &[u8]) -> bool {
         validate::can_handle(data)
@@ -30,4 +48,8 @@ impl PatchFormat for PpfPatcher {
     fn validate(patch: &[u8]) -> Result<()> {
         validate::validate_patch(patch)
     }
+
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+        validate::lint_patch(patch)
+    }
 }
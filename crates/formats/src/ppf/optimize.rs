@@ -0,0 +1,105 @@
+//! PPF (PlayStation Patch Format) optimization.
+//!
+//! Decodes the bytes a patch effectively writes (later records win, undo
+//! data comes from the first record touching a byte) and re-emits them as
+//! few records as possible. The header, block check and FILE_ID.DIZ
+//! trailer are kept as they are.
+
+use crate::ppf::records::{BLOCK_CHECK_SIZE, parse};
+use std::collections::BTreeMap;
+use stitchr_core::Result;
+
+/// Largest amount of data one record can carry (u8 length)
+const MAX_RECORD_DATA: usize = 255;
+/// PPF3 header byte that flags undo data
+const UNDO_FLAG_OFFSET: usize = 58;
+
+/// Re-encodes a PPF patch into its minimal equivalent form.
+///
+/// # Arguments
+///
+/// * `patch` - The patch data.
+/// * `source` - The unpatched image; writes that leave its bytes unchanged
+///   are dropped and short unchanged gaps are bridged. The result is then
+///   only equivalent for this image.
+/// * `drop_undo` - Strip the undo data of a PPF3 patch.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>>` - The optimized patch.
+pub fn optimize_patch(patch: &[u8], source: Option<&[u8]>, drop_undo: bool) -> Result<Vec<u8>> {
+    let contents = parse(patch)?;
+    let offset_width = if contents.version == 3 { 8 } else { 4 };
+    let header_len = if contents.version == 1 { 56 } else { 60 };
+    let block_check_len = if contents.block_check.is_some() {
+        BLOCK_CHECK_SIZE
+    } else {
+        0
+    };
+    let records_start = header_len + block_check_len;
+    let undo = contents.records.iter().any(|r| r.undo.is_some());
+    let records_end = contents.records.last().map_or(records_start, |r| {
+        r.position + offset_width + 1 + r.data.len() * if undo { 2 } else { 1 }
+    });
+
+    // Image offset -> (data, undo)
+    let mut writes: BTreeMap<u64, (u8, Option<u8>)> = BTreeMap::new();
+    for record in &contents.records {
+        for (i, &byte) in record.data.iter().enumerate() {
+            let undo_byte = record.undo.as_ref().map(|u| u[i]);
+            writes
+                .entry(record.offset + i as u64)
+                .and_modify(|w| w.0 = byte)
+                .or_insert((byte, undo_byte));
+        }
+    }
+
+    let source_byte = |offset: u64| source.and_then(|s| s.get(offset as usize).copied());
+    if source.is_some() {
+        writes.retain(|&offset, w| source_byte(offset) != Some(w.0));
+    }
+
+    let keep_undo = undo && !drop_undo;
+    let mut out = patch[..records_start].to_vec();
+    if contents.version == 3 && !keep_undo {
+        out[UNDO_FLAG_OFFSET] = 0;
+    }
+
+    let record_overhead = offset_width + 1;
+    let gap_cost = if keep_undo { 2 } else { 1 };
+    let mut run: Vec<(u64, (u8, Option<u8>))> = Vec::new();
+    for (&offset, &write) in &writes {
+        if let Some(&(last, _)) = run.last() {
+            let gap = offset - last - 1;
+            let bridge = gap > 0
+                && gap as usize * gap_cost <= record_overhead
+                && (last + 1..offset).all(|o| source_byte(o).is_some());
+            if bridge {
+                for o in last + 1..offset {
+                    let byte = source_byte(o).unwrap_or_default();
+                    run.push((o, (byte, Some(byte))));
+                }
+            } else if gap > 0 {
+                emit_run(&mut out, &run, offset_width, keep_undo);
+                run.clear();
+            }
+        }
+        run.push((offset, write));
+    }
+    emit_run(&mut out, &run, offset_width, keep_undo);
+
+    out.extend_from_slice(&patch[records_end..]);
+    Ok(out)
+}
+
+/// Emits records for a run of consecutive image bytes.
+fn emit_run(out: &mut Vec<u8>, run: &[(u64, (u8, Option<u8>))], offset_width: usize, undo: bool) {
+    for chunk in run.chunks(MAX_RECORD_DATA) {
+        out.extend_from_slice(&chunk[0].0.to_le_bytes()[..offset_width]);
+        out.push(chunk.len() as u8);
+        out.extend(chunk.iter().map(|(_, (data, _))| *data));
+        if undo {
+            out.extend(chunk.iter().map(|(_, (_, u))| u.unwrap_or_default()));
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! PPF record listing
+//!
+//! Parses the whole patch into its block check data and records without
+//! applying it, for callers that need to inspect what a patch writes.
+
+use crate::ppf::helpers::parse_header;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Read};
+use std::ops::Range;
+use stitchr_core::{PatchError, Result};
+
+/// Size of the block check data
+pub const BLOCK_CHECK_SIZE: usize = 1024;
+/// Image offset of the block check data for BIN images (image type 0)
+pub const BLOCK_CHECK_OFFSET_BIN: usize = 0x9320;
+/// Image offset of the block check data for GI images (image type 1)
+pub const BLOCK_CHECK_OFFSET_GI: usize = 0x80A0;
+
+/// One PPF record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PpfRecord {
+    /// Offset of the record in the patch
+    pub position: usize,
+    /// Image offset the data is written to
+    pub offset: u64,
+    pub data: Vec<u8>,
+    /// Original bytes, when the patch carries undo data
+    pub undo: Option<Vec<u8>>,
+}
+
+impl PpfRecord {
+    /// Image byte range written by this record
+    pub fn range(&self) -> Range<usize> {
+        self.offset as usize..self.offset as usize + self.data.len()
+    }
+}
+
+/// 1024 bytes of the source image the patch was made against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockCheck {
+    /// Offset of the block in the source image
+    pub offset: usize,
+    pub data: Vec<u8>,
+}
+
+/// A parsed PPF patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PpfContents {
+    pub version: u8,
+    /// Source image size (PPF2 only)
+    pub input_file_size: Option<u32>,
+    pub block_check: Option<BlockCheck>,
+    pub records: Vec<PpfRecord>,
+}
+
+/// Parse a PPF patch into its block check and records
+pub fn parse(patch: &[u8]) -> Result<PpfContents> {
+    let mut cursor = Cursor::new(patch);
+    let header = parse_header(&mut cursor)?;
+
+    let block_check = if header.block_check {
+        let mut data = vec![0u8; BLOCK_CHECK_SIZE];
+        cursor
+            .read_exact(&mut data)
+            .map_err(|_| PatchError::UnexpectedEof("block check".to_string()))?;
+        let offset = if header.version == 3 && header.image_type == 1 {
+            BLOCK_CHECK_OFFSET_GI
+        } else {
+            BLOCK_CHECK_OFFSET_BIN
+        };
+        Some(BlockCheck { offset, data })
+    } else {
+        None
+    };
+
+    let mut records = Vec::new();
+    while (cursor.position() as usize) < patch.len() {
+        let position = cursor.position() as usize;
+        if patch[position..].starts_with(b"@BEG") {
+            break;
+        }
+
+        let offset = if header.version == 3 {
+            cursor.read_u64::<LittleEndian>()
+        } else {
+            cursor.read_u32::<LittleEndian>().map(u64::from)
+        }
+        .map_err(|_| PatchError::CorruptedData)?;
+        let len = cursor.read_u8().map_err(|_| PatchError::CorruptedData)? as usize;
+
+        let mut data = vec![0u8; len];
+        cursor
+            .read_exact(&mut data)
+            .map_err(|_| PatchError::CorruptedData)?;
+        let undo = if header.undo_data {
+            let mut undo = vec![0u8; len];
+            cursor
+                .read_exact(&mut undo)
+                .map_err(|_| PatchError::CorruptedData)?;
+            Some(undo)
+        } else {
+            None
+        };
+
+        if offset.checked_add(len as u64).is_none() {
+            return Err(PatchError::Other("Offset + data_len overflow".to_string()));
+        }
+        records.push(PpfRecord {
+            position,
+            offset,
+            data,
+            undo,
+        });
+    }
+
+    Ok(PpfContents {
+        version: header.version,
+        input_file_size: (header.version == 2).then_some(header.input_file_size),
+        block_check,
+        records,
+    })
+}
@@ -4,7 +4,7 @@
 
 use crate::ppf::{constants::*, helpers::parse_header};
 use std::io::{Cursor, Read, Seek, SeekFrom};
-use stitchr_core::{PatchError, Result};
+use stitchr_core::{LintWarning, PatchError, Result, Severity};
 
 /// Checks if the provided data can be handled as a PPF patch.
 ///
@@ -104,3 +104,41 @@ pub fn validate_patch(patch: &[u8]) -> Result<()> {
 
     Ok(())
 }
+
+/// Reports records that write past the image size.
+///
+/// Only PPF2 records the size of the image it was made for.
+///
+/// # Arguments
+///
+/// * `patch` - The patch data.
+///
+/// # Returns
+///
+/// * `Result<Vec<LintWarning>>` - The findings, or an error if the patch is
+///   invalid.
+pub fn lint_patch(patch: &[u8]) -> Result<Vec<LintWarning>> {
+    validate_patch(patch)?;
+    let contents = super::records::parse(patch)?;
+
+    let Some(size) = contents.input_file_size else {
+        return Ok(Vec::new());
+    };
+    Ok(contents
+        .records
+        .iter()
+        .filter(|record| record.range().end > size as usize)
+        .map(|record| {
+            LintWarning::new(
+                record.position,
+                Severity::Warning,
+                format!(
+                    "record 0x{:X}-0x{:X} is past the image size 0x{:X}",
+                    record.offset,
+                    record.range().end,
+                    size
+                ),
+            )
+        })
+        .collect())
+}
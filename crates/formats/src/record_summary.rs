@@ -0,0 +1,74 @@
+//! Record-level patch summaries
+//!
+//! Formats made of "write these bytes at this offset" records (IPS, IPS32,
+//! EBP, UPS, PPF, PMSR) can say up front how much of the ROM they touch.
+//! Delta formats (BPS, xdelta, ...) rebuild the whole target from copy and
+//! insert instructions instead; they, APS and RUP are not summarized.
+
+use std::ops::Range;
+
+use stitchr_core::{PatchType, Result};
+
+/// What the records of a patch write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordSummary {
+    /// Number of records
+    pub records: usize,
+    /// Distinct ROM bytes written (overlapping records count once)
+    pub bytes_changed: usize,
+    /// Highest ROM offset written, if any record writes data
+    pub highest_offset: Option<usize>,
+}
+
+impl RecordSummary {
+    /// Summarize records given as the ROM ranges they write
+    pub fn from_ranges(ranges: &[Range<usize>]) -> Self {
+        let mut sorted: Vec<&Range<usize>> = ranges.iter().filter(|r| !r.is_empty()).collect();
+        sorted.sort_by_key(|r| r.start);
+
+        let mut bytes_changed = 0;
+        let mut covered_to = 0;
+        for range in &sorted {
+            let start = range.start.max(covered_to);
+            if range.end > start {
+                bytes_changed += range.end - start;
+                covered_to = range.end;
+            }
+        }
+
+        Self {
+            records: ranges.len(),
+            bytes_changed,
+            highest_offset: sorted.iter().map(|r| r.end - 1).max(),
+        }
+    }
+}
+
+/// Summarize the records of `patch`, or `None` for formats that are not
+/// summarized
+pub fn summarize(patch: &[u8], patch_type: PatchType) -> Result<Option<RecordSummary>> {
+    let ranges: Vec<Range<usize>> = match patch_type {
+        #[cfg(feature = "ips")]
+        PatchType::Ips => crate::ips::IpsPatcher::record_ranges(patch)?,
+        #[cfg(feature = "ips")]
+        PatchType::Ips32 => crate::ips::Ips32Patcher::record_ranges(patch)?,
+        // EBP records are plain IPS records
+        #[cfg(feature = "ebp")]
+        PatchType::Ebp => crate::ips::IpsPatcher::record_ranges(patch)?,
+        #[cfg(feature = "ups")]
+        PatchType::Ups => crate::ups::UpsPatcher::record_ranges(patch)?,
+        #[cfg(feature = "ppf")]
+        PatchType::Ppf => crate::ppf::PpfPatcher::records(patch)?
+            .records
+            .iter()
+            .map(|r| r.range())
+            .collect(),
+        #[cfg(feature = "pmsr")]
+        PatchType::Pmsr => crate::pmsr::helpers::parse_records(patch)?
+            .iter()
+            .map(|r| r.offset..r.offset + r.data.len())
+            .collect(),
+        _ => return Ok(None),
+    };
+    Ok(Some(RecordSummary::from_ranges(&ranges)))
+}
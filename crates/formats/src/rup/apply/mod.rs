@@ -11,11 +11,27 @@ use records::apply_xor_records;
 use stitchr_core::{PatchError, Result};
 
 /// Apply RUP patch to ROM
+///
+/// A ROM matching a file's target MD5 is restored to the source instead.
 pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+    run(rom, patch, false)
+}
+
+/// Restore the source of a ROM that matches a file's target MD5
+pub fn revert(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+    run(rom, patch, true)
+}
+
+fn run(rom: &mut Vec<u8>, patch: &[u8], revert_only: bool) -> Result<()> {
     parse_header(patch)?;
 
     let rom_md5 = compute_md5(rom);
     let (file_meta, undo) = find_matching_file(patch, &rom_md5)?;
+    if revert_only && !undo {
+        return Err(PatchError::Other(
+            "ROM matches the patch source, not its target".to_string(),
+        ));
+    }
 
     let target_size = if undo {
         file_meta.source_size
@@ -82,10 +82,17 @@ pub fn extract(patch: &[u8]) -> Result<PatchMetadata> {
 
             let (source_size, consumed) = decode_vlv(&patch[offset..])?;
             offset += consumed;
-            let (target_size, _consumed) = decode_vlv(&patch[offset..])?;
+            let (target_size, consumed) = decode_vlv(&patch[offset..])?;
+            offset += consumed;
 
             meta.source_size = Some(source_size as usize);
             meta.target_size = Some(target_size as usize);
+
+            // Source and target MD5 follow the sizes
+            if let Some(md5s) = patch.get(offset..offset + 32) {
+                meta.source_checksum = Some(md5s[..16].to_vec());
+                meta.target_checksum = Some(md5s[16..].to_vec());
+            }
             break;
         } else if command == COMMAND_END {
             return Err(PatchError::InvalidFormat("No files in patch".to_string()));
@@ -12,6 +12,16 @@ use stitchr_core::{PatchFormat, PatchMetadata, Result};
 
 pub struct RupPatcher;
 
+impl RupPatcher {
+    /// Undo the patch on a ROM that matches a file's target MD5
+    ///
+    /// `apply` already does this on its own; `revert` refuses ROMs that
+    /// match the source, so it never patches forward by accident.
+    pub fn revert(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+        apply::revert(rom, patch)
+    }
+}
+
 impl PatchFormat for RupPatcher {
     fn can_handle(data: &[u8]) -> bool {
         validate::can_handle(data)
@@ -3,17 +3,37 @@
 use super::constants::*;
 use super::helpers::*;
 use super::varint;
+use std::ops::Range;
 use stitchr_core::{PatchError, Result};
 
 /// Apply a UPS patch to a ROM
 pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+    let (_input_size, output_size, offset) = read_header(patch)?;
+    resize(rom, output_size)?;
+    xor_records(rom, patch, offset)
+}
+
+/// Turn a patched ROM back into the source
+///
+/// XOR is its own inverse, so the same records undo the patch; the ROM is
+/// worked on at the larger of the two sizes and cut to the source size.
+pub fn revert(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+    let (input_size, output_size, offset) = read_header(patch)?;
+    resize(rom, input_size.max(output_size))?;
+    xor_records(rom, patch, offset)?;
+    rom.truncate(input_size as usize);
+    Ok(())
+}
+
+/// Check magic and footer, return (input_size, output_size, data_offset)
+fn read_header(patch: &[u8]) -> Result<(u64, u64, usize)> {
     // Validate magic
     if patch.len() < MAGIC_SIZE || &patch[..MAGIC_SIZE] != MAGIC {
         return Err(PatchError::InvalidFormat("Invalid UPS magic".to_string()));
     }
 
     // Parse header
-    let (_input_size, output_size, mut offset) = parse_header(patch)?;
+    let header = parse_header(patch)?;
 
     // Validate patch has footer
     if patch.len() < FOOTER_SIZE {
@@ -22,21 +42,58 @@ pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
         ));
     }
 
-    // Limit output size to prevent ASAN crashes
+    Ok(header)
+}
+
+/// Resize the ROM to `size`, capped to prevent ASAN crashes
+fn resize(rom: &mut Vec<u8>, size: u64) -> Result<()> {
     const MAX_TARGET_SIZE: u64 = 512 * 1024 * 1024;
-    if output_size > MAX_TARGET_SIZE {
+    if size > MAX_TARGET_SIZE {
         return Err(PatchError::InvalidFormat(format!(
             "Target size too large: {} (max {})",
-            output_size, MAX_TARGET_SIZE
+            size, MAX_TARGET_SIZE
         )));
     }
 
     // Safe resize
-    rom.try_reserve(output_size as usize)
+    rom.try_reserve(size as usize)
         .map_err(|_| PatchError::Other("Failed to allocate memory for target ROM".to_string()))?;
-    rom.resize(output_size as usize, 0);
+    rom.resize(size as usize, 0);
+    Ok(())
+}
+
+/// XOR the patch records starting at `offset` into the ROM
+fn xor_records(rom: &mut [u8], patch: &[u8], offset: usize) -> Result<()> {
+    for_each_record(patch, offset, |rom_pos, data| {
+        let target = rom
+            .get_mut(rom_pos..rom_pos + data.len())
+            .ok_or_else(|| PatchError::InvalidFormat("XOR record exceeds ROM size".to_string()))?;
+        // Apply XOR: output = input XOR patch_data
+        for (byte, xor) in target.iter_mut().zip(data) {
+            *byte ^= xor;
+        }
+        Ok(())
+    })
+}
 
-    // Process XOR records
+/// ROM byte range changed by each XOR record, in patch order
+pub fn record_ranges(patch: &[u8]) -> Result<Vec<Range<usize>>> {
+    let (_input_size, _output_size, offset) = read_header(patch)?;
+    let mut ranges = Vec::new();
+    for_each_record(patch, offset, |rom_pos, data| {
+        ranges.push(rom_pos..rom_pos + data.len());
+        Ok(())
+    })?;
+    Ok(ranges)
+}
+
+/// Walk the XOR records starting at `offset`, passing each record's ROM
+/// position and XOR data to `f`
+fn for_each_record(
+    patch: &[u8],
+    mut offset: usize,
+    mut f: impl FnMut(usize, &[u8]) -> Result<()>,
+) -> Result<()> {
     let mut rom_pos: usize = 0;
 
     while offset < patch.len() - FOOTER_SIZE {
@@ -48,28 +105,17 @@ pub fn apply(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
             .checked_add(relative_offset as usize)
             .ok_or(PatchError::Other("ROM position overflow".to_string()))?;
 
-        // Read XOR data until 0x00 terminator
-        while offset < patch.len() && patch[offset] != 0x00 {
-            if rom_pos >= rom.len() {
-                return Err(PatchError::InvalidFormat(
-                    "XOR record exceeds ROM size".to_string(),
-                ));
-            }
-
-            // Apply XOR: output = input XOR patch_data
-            rom[rom_pos] ^= patch[offset];
-            rom_pos += 1;
-            offset += 1;
-        }
+        // XOR data runs until the 0x00 terminator
+        let len = patch[offset..]
+            .iter()
+            .position(|&b| b == 0x00)
+            .ok_or_else(|| {
+                PatchError::InvalidFormat("Missing XOR record terminator".to_string())
+            })?;
+        f(rom_pos, &patch[offset..offset + len])?;
 
-        // Skip terminator
-        if offset >= patch.len() {
-            return Err(PatchError::InvalidFormat(
-                "Missing XOR record terminator".to_string(),
-            ));
-        }
-        offset += 1; // Skip 0x00
-        rom_pos += 1; // Skip 1 byte in ROM
+        offset += len + 1; // Skip 0x00
+        rom_pos += len + 1; // Skip 1 byte in ROM
     }
 
     Ok(())
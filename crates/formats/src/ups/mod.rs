@@ -8,10 +8,29 @@ mod validate;
 
 pub mod varint;
 
-use stitchr_core::{PatchFormat, PatchMetadata, Result};
+use std::ops::Range;
+
+use stitchr_core::{LintWarning, PatchFormat, PatchMetadata, Result};
 
 pub struct UpsPatcher;
 
+impl UpsPatcher {
+    /// Undo the patch on a ROM that matches its target
+    ///
+    /// UPS records are XOR deltas, so the same patch works in both
+    /// directions. The result must match the source CRC32; on error the ROM
+    /// contents are unspecified.
+    pub fn revert(rom: &mut Vec<u8>, patch: &[u8]) -> Result<()> {
+        apply::revert(rom, patch)?;
+        validate::verify(rom, patch, None)
+    }
+
+    /// ROM byte range changed by each XOR record, in patch order
+    pub fn record_ranges(patch: &[u8]) -> Result<Vec<Range<usize>>> {
+        apply::record_ranges(patch)
+    }
+}
+
 impl PatchFormat for UpsPatcher {
     fn can_handle(data: &[u8]) -> bool {
         validate::can_handle(data)
@@ -29,6 +48,10 @@ impl PatchFormat for UpsPatcher {
         validate::validate(patch)
     }
 
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+        validate::lint(patch)
+    }
+
     fn verify(rom: &[u8], patch: &[u8], target: Option<&[u8]>) -> Result<()> {
         validate::verify(rom, patch, target)
     }
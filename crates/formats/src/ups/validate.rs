@@ -2,7 +2,7 @@
 
 use super::constants::{FOOTER_SIZE, MAGIC, MAGIC_SIZE};
 use super::helpers;
-use stitchr_core::{PatchError, Result};
+use stitchr_core::{LintWarning, PatchError, Result, Severity};
 
 /// Check if data is a valid UPS patch (magic header check)
 pub fn can_handle(data: &[u8]) -> bool {
@@ -27,6 +27,21 @@ pub fn validate(patch: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Report a patch CRC32 that does not match the patch contents
+pub fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+    match validate(patch) {
+        Err(PatchError::ChecksumMismatch { expected, actual }) => Ok(vec![LintWarning::new(
+            patch.len() - 4,
+            Severity::Error,
+            format!(
+                "patch CRC32 is {:08X}, footer says {:08X}",
+                actual, expected
+            ),
+        )]),
+        result => result.map(|_| Vec::new()),
+    }
+}
+
 /// Verify ROM checksums (input or output)
 pub fn verify(rom: &[u8], patch: &[u8], target: Option<&[u8]>) -> Result<()> {
     if let Some(target_rom) = target {
@@ -77,17 +77,42 @@ impl WindowHeader {
     }
 }
 
-pub fn calculate_target_size(patch: &[u8], offset: u64) -> Result<u64> {
+impl WindowHeader {
+    /// Combined length of the add/run data, instructions and addresses
+    /// sections that follow the header
+    pub fn sections_length(&self) -> Result<u64> {
+        self.add_run_data_length
+            .checked_add(self.instructions_length)
+            .and_then(|len| len.checked_add(self.addresses_length))
+            .ok_or_else(|| PatchError::InvalidFormat("Window sections too large".to_string()))
+    }
+}
+
+/// Decode every window header from `offset` on, skipping the window data
+///
+/// `visit` gets the offset each window starts at and its header.
+pub fn walk_windows(
+    patch: &[u8],
+    offset: u64,
+    mut visit: impl FnMut(u64, &WindowHeader) -> Result<()>,
+) -> Result<()> {
     let mut parser = VcdiffParser::new_at_offset(patch, offset);
-    let mut target_size = 0;
     while !parser.is_eof() {
+        let start = parser.position();
         let win_header = WindowHeader::decode(&mut parser)?;
-        target_size += win_header.target_window_length;
-        parser.skip(
-            win_header.add_run_data_length
-                + win_header.instructions_length
-                + win_header.addresses_length,
-        )?;
+        visit(start, &win_header)?;
+        parser.skip(win_header.sections_length()?)?;
     }
+    Ok(())
+}
+
+pub fn calculate_target_size(patch: &[u8], offset: u64) -> Result<u64> {
+    let mut target_size: u64 = 0;
+    walk_windows(patch, offset, |_, win_header| {
+        target_size = target_size
+            .checked_add(win_header.target_window_length)
+            .ok_or_else(|| PatchError::InvalidFormat("Target size too large".to_string()))?;
+        Ok(())
+    })?;
     Ok(target_size)
 }
@@ -12,7 +12,7 @@ pub mod metadata;
 pub mod parser;
 pub mod validate;
 
-use stitchr_core::{PatchError, PatchFormat, PatchMetadata, Result};
+use stitchr_core::{LintWarning, PatchError, PatchFormat, PatchMetadata, Result};
 
 /// xdelta format patcher
 pub struct XdeltaPatcher;
@@ -39,4 +39,8 @@ impl PatchFormat for XdeltaPatcher {
     fn validate(patch: &[u8]) -> Result<()> {
         validate::validate(patch)
     }
+
+    fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+        validate::lint(patch)
+    }
 }
@@ -1,7 +1,9 @@
 //! xdelta validation
 
-use super::constants::VCDIFF_HEADER;
-use stitchr_core::{PatchError, Result};
+use super::constants::{VCD_ADLER32, VCD_APPHEADER, VCD_CODETABLE, VCD_DECOMPRESS, VCDIFF_HEADER};
+use super::headers::walk_windows;
+use super::parser::VcdiffParser;
+use stitchr_core::{LintWarning, PatchError, Result, Severity};
 
 /// Check if the patch data has xdelta magic
 pub fn can_handle(data: &[u8]) -> bool {
@@ -15,3 +17,40 @@ pub fn validate(patch: &[u8]) -> Result<()> {
     }
     Ok(())
 }
+
+/// Report windows without an Adler32 checksum
+///
+/// Without it a wrong source file goes unnoticed until the output is used.
+pub fn lint(patch: &[u8]) -> Result<Vec<LintWarning>> {
+    validate(patch)?;
+
+    let mut parser = VcdiffParser::new(patch);
+    parser.seek(4)?;
+    let header_indicator = parser.read_u8()?;
+    if (header_indicator & VCD_DECOMPRESS) != 0 {
+        parser.read_u8()?;
+    }
+    if (header_indicator & VCD_CODETABLE) != 0 {
+        let len = parser.read_7bit_encoded_int()?;
+        parser.skip(len)?;
+    }
+    if (header_indicator & VCD_APPHEADER) != 0 {
+        let len = parser.read_7bit_encoded_int()?;
+        parser.skip(len)?;
+    }
+
+    let mut warnings = Vec::new();
+    let mut index = 0;
+    walk_windows(patch, parser.position(), |window_start, window| {
+        if (window.indicator & VCD_ADLER32) == 0 {
+            warnings.push(LintWarning::new(
+                window_start as usize,
+                Severity::Warning,
+                format!("window {} has no Adler32 checksum", index),
+            ));
+        }
+        index += 1;
+        Ok(())
+    })?;
+    Ok(warnings)
+}
@@ -0,0 +1,144 @@
+//! BPM directory application tests
+
+use super::{finish, patterned, populate, scratch};
+use std::fs;
+use stitchr_core::PatchFormat;
+use stitchr_formats::bpm::BpmPatcher;
+use stitchr_formats::bps::BpsPatcher;
+
+/// BPM1, no metadata
+const HEADER: &[u8] = b"BPM1\x80";
+
+fn name_entry(action: u8, name: &str) -> Vec<u8> {
+    // Names in these tests are short enough for a one-byte varint
+    vec![0x80 | (((name.len() as u8) - 1) << 2) | action]
+}
+
+fn create_file(name: &str, data: &[u8], crc: u32) -> Vec<u8> {
+    let mut entry = name_entry(1, name);
+    entry.extend_from_slice(name.as_bytes());
+    entry.push(0x80 | data.len() as u8);
+    entry.extend_from_slice(data);
+    entry.extend_from_slice(&crc.to_le_bytes());
+    entry
+}
+
+#[test]
+fn test_apply_create_file() {
+    let root = scratch("apply-create");
+    let source = root.join("src");
+    fs::create_dir_all(&source).unwrap();
+
+    let mut patch = HEADER.to_vec();
+    patch.extend(create_file("hello.txt", b"hi", crc32fast::hash(b"hi")));
+    let patch = finish(patch);
+
+    let report = BpmPatcher::apply_dir(&source, &root.join("out"), &patch).unwrap();
+    assert_eq!(report.created, ["hello.txt"]);
+    assert_eq!(fs::read(root.join("out/hello.txt")).unwrap(), b"hi");
+    // The temporary file was renamed into place
+    assert_eq!(fs::read_dir(root.join("out")).unwrap().count(), 1);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_apply_create_file_crc_mismatch() {
+    let root = scratch("apply-create-crc");
+    let source = root.join("src");
+    fs::create_dir_all(&source).unwrap();
+
+    let mut patch = HEADER.to_vec();
+    patch.extend(create_file("hello.txt", b"hi", 0xDEADBEEF));
+    let patch = finish(patch);
+
+    let err = BpmPatcher::apply_dir(&source, &root.join("out"), &patch).unwrap_err();
+    assert!(err.to_string().contains("hello.txt"));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_apply_modify_checks_source_crc() {
+    let root = scratch("apply-modify-crc");
+    let (a, b) = (root.join("a"), root.join("b"));
+    let rom = patterned(4096, 1);
+    let mut hacked = rom.clone();
+    hacked[100] ^= 1;
+    populate(&a, &[("rom.bin", &rom)]);
+    populate(&b, &[("rom.bin", &hacked)]);
+    let patch = BpmPatcher::create(&a, &b).unwrap();
+
+    // Same file name, wrong contents
+    let wrong = root.join("wrong");
+    populate(&wrong, &[("rom.bin", &patterned(4096, 2))]);
+    let err = BpmPatcher::apply_dir(&wrong, &root.join("out"), &patch).unwrap_err();
+    assert!(err.to_string().contains("rom.bin"));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_apply_rejects_path_traversal() {
+    let root = scratch("apply-traversal");
+    let source = root.join("src");
+    fs::create_dir_all(&source).unwrap();
+
+    for name in ["../evil", "/etc/evil", "a/../../evil", "a\\evil"] {
+        let mut patch = HEADER.to_vec();
+        patch.extend(create_file(name, b"x", crc32fast::hash(b"x")));
+        let patch = finish(patch);
+        assert!(BpmPatcher::apply_dir(&source, &root.join("out"), &patch).is_err());
+    }
+    assert!(!root.join("evil").exists());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_apply_rejects_same_directory() {
+    let root = scratch("apply-same");
+    let patch = finish(HEADER.to_vec());
+    assert!(BpmPatcher::apply_dir(&root, &root, &patch).is_err());
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_apply_buffer_rejects_multi_file() {
+    let mut patch = HEADER.to_vec();
+    patch.extend(create_file("a", b"x", crc32fast::hash(b"x")));
+    patch.extend(create_file("b", b"y", crc32fast::hash(b"y")));
+    let patch = finish(patch);
+
+    let mut rom = vec![0u8; 16];
+    assert!(BpmPatcher.apply(&mut rom, &patch).is_err());
+}
+
+#[test]
+fn test_apply_modify_from_target_origin() {
+    let root = scratch("apply-target-origin");
+    let source = root.join("src");
+    fs::create_dir_all(&source).unwrap();
+
+    let base = patterned(64, 0);
+    let mut derived = base.clone();
+    derived[0] = 0xFF;
+    let bps = BpsPatcher::create(&base, &derived, &[]).unwrap();
+
+    let mut patch = HEADER.to_vec();
+    patch.extend(create_file("a", &base, crc32fast::hash(&base)));
+    // ModifyFile "b" from target "a"
+    patch.extend(name_entry(2, "b"));
+    patch.extend_from_slice(b"b");
+    patch.push(0x80 | (1 << 1) | 1);
+    patch.extend_from_slice(b"a");
+    patch.push(0x80 | bps.len() as u8);
+    patch.extend_from_slice(&bps);
+    let patch = finish(patch);
+
+    let report = BpmPatcher::apply_dir(&source, &root.join("out"), &patch).unwrap();
+    assert_eq!(report.modified, ["b"]);
+    assert_eq!(fs::read(root.join("out/b")).unwrap(), derived);
+
+    fs::remove_dir_all(root).unwrap();
+}
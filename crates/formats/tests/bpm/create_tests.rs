@@ -0,0 +1,77 @@
+//! BPM creation round-trip tests
+
+use super::{patterned, populate, scratch};
+use std::fs;
+use stitchr_core::PatchFormat;
+use stitchr_formats::bpm::BpmPatcher;
+
+#[test]
+fn test_create_roundtrip() {
+    let root = scratch("create-roundtrip");
+    let (source, target, output) = (root.join("a"), root.join("b"), root.join("out"));
+
+    let rom = patterned(50_000, 0);
+    let mut hacked = rom.clone();
+    hacked[1000..1010].fill(0xEA);
+    let readme = b"original readme".to_vec();
+    let music = patterned(3000, 9);
+
+    populate(
+        &source,
+        &[
+            ("game.sfc", &rom),
+            ("docs/readme.txt", &readme),
+            ("music.bin", &music),
+            ("obsolete.dat", b"gone"),
+        ],
+    );
+    populate(
+        &target,
+        &[
+            ("game.sfc", &hacked),
+            ("docs/readme.txt", &readme),
+            ("audio/music.bin", &music),
+            ("new/extra.txt", b"brand new"),
+        ],
+    );
+    fs::create_dir_all(target.join("saves")).unwrap();
+
+    let patch = BpmPatcher::create(&source, &target).unwrap();
+    BpmPatcher::validate(&patch).unwrap();
+    assert!(patch.len() < 1000);
+
+    let report = BpmPatcher::apply_dir(&source, &output, &patch).unwrap();
+    assert_eq!(report.modified, ["game.sfc"]);
+    assert_eq!(report.mirrored, ["audio/music.bin", "docs/readme.txt"]);
+    assert_eq!(report.created, ["new/extra.txt"]);
+    assert_eq!(report.directories, ["saves"]);
+    assert_eq!(report.removed, ["music.bin", "obsolete.dat"]);
+
+    assert_eq!(fs::read(output.join("game.sfc")).unwrap(), hacked);
+    assert_eq!(fs::read(output.join("audio/music.bin")).unwrap(), music);
+    assert_eq!(
+        fs::read(output.join("new/extra.txt")).unwrap(),
+        b"brand new"
+    );
+    assert!(output.join("saves").is_dir());
+    assert!(!output.join("obsolete.dat").exists());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_create_single_file_applies_to_buffer() {
+    let root = scratch("create-single");
+    let rom = patterned(20_000, 3);
+    let mut hacked = rom.clone();
+    hacked[42] = 0;
+    populate(&root.join("a"), &[("rom.bin", &rom)]);
+    populate(&root.join("b"), &[("rom.bin", &hacked)]);
+
+    let patch = BpmPatcher::create(&root.join("a"), &root.join("b")).unwrap();
+    let mut buffer = rom.clone();
+    BpmPatcher.apply(&mut buffer, &patch).unwrap();
+    assert_eq!(buffer, hacked);
+
+    fs::remove_dir_all(root).unwrap();
+}
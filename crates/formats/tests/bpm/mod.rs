@@ -0,0 +1,36 @@
+//! BPM format tests
+
+mod apply_tests;
+mod create_tests;
+mod validate_tests;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Fresh scratch directory under the system temp dir
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stitchr-bpm-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write `files` (name, contents) below `root`
+pub fn populate(root: &Path, files: &[(&str, &[u8])]) {
+    for (name, data) in files {
+        let path = root.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+    }
+}
+
+pub fn patterned(size: usize, seed: usize) -> Vec<u8> {
+    (0..size).map(|i| ((i * 7 + seed) % 251) as u8).collect()
+}
+
+/// Append the patch CRC32 footer
+pub fn finish(mut patch: Vec<u8>) -> Vec<u8> {
+    let crc = crc32fast::hash(&patch);
+    patch.extend_from_slice(&crc.to_le_bytes());
+    patch
+}
@@ -0,0 +1,55 @@
+//! BPM validation and metadata tests
+
+use super::finish;
+use stitchr_core::PatchFormat;
+use stitchr_formats::bpm::BpmPatcher;
+
+#[test]
+fn test_can_handle() {
+    assert!(BpmPatcher::can_handle(b"BPM1\x80\0\0\0\0"));
+    assert!(!BpmPatcher::can_handle(b"BPS1"));
+    assert!(!BpmPatcher::can_handle(b"BP"));
+}
+
+#[test]
+fn test_validate_empty_patch() {
+    let patch = finish(b"BPM1\x80".to_vec());
+    BpmPatcher::validate(&patch).unwrap();
+}
+
+#[test]
+fn test_validate_patch_crc_mismatch() {
+    let mut patch = finish(b"BPM1\x80".to_vec());
+    patch[5] ^= 0xFF;
+    assert!(BpmPatcher::validate(&patch).is_err());
+}
+
+#[test]
+fn test_validate_truncated_entry() {
+    // CreateFile "a" claiming 16 bytes of data
+    let patch = finish(b"BPM1\x80\x81a\x90abc".to_vec());
+    assert!(BpmPatcher::validate(&patch).is_err());
+}
+
+#[test]
+fn test_metadata_counts() {
+    let mut patch = b"BPM1\x85notes".to_vec();
+    // CreatePath "d"
+    patch.extend_from_slice(b"\x80d");
+    // CreateFile "f" (empty)
+    patch.extend_from_slice(b"\x81f\x80");
+    patch.extend_from_slice(&crc32fast::hash(b"").to_le_bytes());
+    let patch = finish(patch);
+
+    let meta = BpmPatcher::metadata(&patch).unwrap();
+    let get = |k: &str| {
+        meta.extra
+            .iter()
+            .find(|(key, _)| key == k)
+            .map(|(_, v)| v.as_str())
+    };
+    assert_eq!(get("metadata"), Some("notes"));
+    assert_eq!(get("directories"), Some("1"));
+    assert_eq!(get("created_files"), Some("1"));
+    assert_eq!(get("modified_files"), Some("0"));
+}
@@ -0,0 +1,4 @@
+//! BPM integration tests
+
+#![cfg(feature = "bpm")]
+mod bpm;
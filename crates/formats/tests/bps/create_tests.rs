@@ -0,0 +1,88 @@
+//! BPS encoder tests
+
+use stitchr_core::PatchFormat;
+use stitchr_formats::bps::BpsPatcher;
+
+fn patterned(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+fn roundtrip(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let patch = BpsPatcher::create(source, target, &[]).unwrap();
+    BpsPatcher::validate(&patch).unwrap();
+    BpsPatcher::verify(source, &patch, Some(target)).unwrap();
+    let mut rom = source.to_vec();
+    BpsPatcher.apply(&mut rom, &patch).unwrap();
+    assert_eq!(rom, target);
+    patch
+}
+
+#[test]
+fn test_create_identical() {
+    let rom = patterned(100_000);
+    let patch = roundtrip(&rom, &rom);
+    assert!(patch.len() < 32);
+}
+
+#[test]
+fn test_create_in_place_edits() {
+    let source = patterned(70_000);
+    let mut target = source.clone();
+    target[10] ^= 0xFF;
+    target[40_000..40_010].fill(0);
+    target[69_999] = 1;
+
+    let patch = roundtrip(&source, &target);
+    assert!(patch.len() < 64);
+}
+
+#[test]
+fn test_create_moved_block() {
+    let source = patterned(4096);
+    let mut target = source[2048..].to_vec();
+    target.extend_from_slice(&source[..2048]);
+
+    let patch = roundtrip(&source, &target);
+    assert!(patch.len() < 64);
+}
+
+#[test]
+fn test_create_grow_and_shrink() {
+    let source = patterned(1000);
+    let mut grown = source.clone();
+    grown.extend_from_slice(b"appended data that is not in the source");
+    roundtrip(&source, &grown);
+    roundtrip(&source, &source[..500]);
+}
+
+#[test]
+fn test_create_empty() {
+    roundtrip(&[], &[]);
+    roundtrip(&[], b"new");
+    roundtrip(b"old", &[]);
+}
+
+#[test]
+fn test_create_stores_metadata() {
+    let source = patterned(256);
+    let patch = BpsPatcher::create(&source, &source, b"<patch/>").unwrap();
+    let metadata = BpsPatcher::metadata(&patch).unwrap();
+    assert!(metadata.extra.iter().any(|(_, v)| v.contains("<patch/>")));
+}
+
+#[test]
+fn test_metadata_reports_footer_checksums() {
+    let source = patterned(512);
+    let target = patterned(600);
+    let patch = BpsPatcher::create(&source, &target, &[]).unwrap();
+    let metadata = BpsPatcher::metadata(&patch).unwrap();
+
+    assert_eq!(
+        metadata.source_checksum,
+        Some(crc32fast::hash(&source).to_le_bytes().to_vec())
+    );
+    assert_eq!(
+        metadata.target_checksum,
+        Some(crc32fast::hash(&target).to_le_bytes().to_vec())
+    );
+}
@@ -1,5 +1,6 @@
 mod apply;
 mod checksum_validation;
+mod create_tests;
 mod metadata_tests;
 mod validate_tests;
 mod varint_tests;
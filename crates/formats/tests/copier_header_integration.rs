@@ -0,0 +1,235 @@
+//! SNES copier header reconciliation tests
+
+#![cfg(all(feature = "ips", feature = "bps", feature = "ups"))]
+
+use stitchr_core::{PatchFormat, PatchType};
+use stitchr_formats::bps::BpsPatcher;
+use stitchr_formats::copier_header::{self, COPIER_HEADER_SIZE, HeaderChange, HeaderLayout};
+use stitchr_formats::ips::IpsPatcher;
+use stitchr_formats::ups::UpsPatcher;
+
+/// 64 KiB LoROM image with an internal header and a consistent
+/// checksum/complement pair
+fn snes_rom() -> Vec<u8> {
+    let mut rom: Vec<u8> = (0..0x10000).map(|i| (i * 13 % 251) as u8).collect();
+    rom[0x7FC0..0x7FD5].copy_from_slice(b"TEST GAME            ");
+    rom[0x7FD5] = 0x20;
+    rom[0x7FDC..0x7FE0].copy_from_slice(&[0x34, 0x12, 0xCB, 0xED]);
+    rom
+}
+
+fn headered(rom: &[u8]) -> Vec<u8> {
+    let mut out: Vec<u8> = (0..COPIER_HEADER_SIZE).map(|i| i as u8).collect();
+    out.extend_from_slice(rom);
+    out
+}
+
+/// Hack that rewrites some code and the internal checksum
+fn hacked(rom: &[u8]) -> Vec<u8> {
+    let mut out = rom.to_vec();
+    out[0x1000..0x1010].fill(0xEA);
+    out[0x7FDC..0x7FE0].copy_from_slice(&[0x00, 0x00, 0xFF, 0xFF]);
+    out
+}
+
+fn apply(rom: &mut Vec<u8>, patch: &[u8], patch_type: PatchType) {
+    match patch_type {
+        PatchType::Ips => IpsPatcher.apply(rom, patch).unwrap(),
+        PatchType::Bps => BpsPatcher.apply(rom, patch).unwrap(),
+        PatchType::Ups => UpsPatcher.apply(rom, patch).unwrap(),
+        _ => unreachable!(),
+    }
+}
+
+/// UPS patch for two same-sized images, one XOR hunk per changed run
+fn ups_patch(source: &[u8], target: &[u8]) -> Vec<u8> {
+    fn vlv(patch: &mut Vec<u8>, mut value: usize) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                patch.push(byte | 0x80);
+                return;
+            }
+            patch.push(byte);
+            value -= 1;
+        }
+    }
+
+    let mut patch = b"UPS1".to_vec();
+    vlv(&mut patch, source.len());
+    vlv(&mut patch, target.len());
+    let (mut pos, mut i) = (0, 0);
+    while i < source.len() {
+        if source[i] == target[i] {
+            i += 1;
+            continue;
+        }
+        vlv(&mut patch, i - pos);
+        while i < source.len() && source[i] != target[i] {
+            patch.push(source[i] ^ target[i]);
+            i += 1;
+        }
+        patch.push(0x00);
+        pos = i + 1;
+        i += 1;
+    }
+
+    patch.extend_from_slice(&crc32fast::hash(source).to_le_bytes());
+    patch.extend_from_slice(&crc32fast::hash(target).to_le_bytes());
+    let crc = crc32fast::hash(&patch);
+    patch.extend_from_slice(&crc.to_le_bytes());
+    patch
+}
+
+#[test]
+fn test_has_copier_header() {
+    assert!(copier_header::has_copier_header(512 + 8192));
+    assert!(!copier_header::has_copier_header(8192));
+    assert!(!copier_header::has_copier_header(512));
+}
+
+#[test]
+fn test_bps_headerless_patch_on_headered_rom() {
+    let clean = snes_rom();
+    let patch = BpsPatcher::create(&clean, &hacked(&clean), &[]).unwrap();
+
+    let input = headered(&clean);
+    let mut rom = input.clone();
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Bps);
+    assert_eq!(fix.change(), Some(HeaderChange::Stripped));
+    assert_eq!(rom, clean);
+
+    apply(&mut rom, &patch, PatchType::Bps);
+    let report = fix.finish(&mut rom, HeaderLayout::Keep);
+    assert_eq!(report.after, Some(HeaderChange::Added));
+    // Original header bytes come back
+    assert_eq!(rom, headered(&hacked(&clean)));
+    assert!(
+        report
+            .to_string()
+            .contains("Stripped 512-byte copier header")
+    );
+}
+
+#[test]
+fn test_bps_headered_patch_on_headerless_rom() {
+    let clean = snes_rom();
+    let mut blank_headered = vec![0u8; COPIER_HEADER_SIZE];
+    blank_headered.extend_from_slice(&clean);
+    let mut target = blank_headered.clone();
+    target[COPIER_HEADER_SIZE + 0x20] = 0x99;
+    let patch = BpsPatcher::create(&blank_headered, &target, &[]).unwrap();
+
+    let mut rom = clean.clone();
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Bps);
+    assert_eq!(fix.change(), Some(HeaderChange::Added));
+
+    apply(&mut rom, &patch, PatchType::Bps);
+    let report = fix.finish(&mut rom, HeaderLayout::Keep);
+    assert_eq!(report.after, Some(HeaderChange::Stripped));
+    assert_eq!(rom, &target[COPIER_HEADER_SIZE..]);
+}
+
+#[test]
+fn test_ups_headerless_patch_on_headered_rom() {
+    let clean = snes_rom();
+    let patch = ups_patch(&clean, &hacked(&clean));
+
+    let mut rom = headered(&clean);
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Ups);
+    assert_eq!(fix.change(), Some(HeaderChange::Stripped));
+    assert_eq!(rom, clean);
+
+    apply(&mut rom, &patch, PatchType::Ups);
+    let report = fix.finish(&mut rom, HeaderLayout::Keep);
+    assert_eq!(report.after, Some(HeaderChange::Added));
+    assert_eq!(rom, headered(&hacked(&clean)));
+}
+
+#[test]
+fn test_ups_headered_patch_on_headerless_rom() {
+    let clean = snes_rom();
+    let mut blank_headered = vec![0u8; COPIER_HEADER_SIZE];
+    blank_headered.extend_from_slice(&clean);
+    let mut target = blank_headered.clone();
+    target[COPIER_HEADER_SIZE..].copy_from_slice(&hacked(&clean));
+    let patch = ups_patch(&blank_headered, &target);
+
+    let mut rom = clean.clone();
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Ups);
+    assert_eq!(fix.change(), Some(HeaderChange::Added));
+
+    apply(&mut rom, &patch, PatchType::Ups);
+    fix.finish(&mut rom, HeaderLayout::Headerless);
+    assert_eq!(rom, hacked(&clean));
+}
+
+#[test]
+fn test_ips_headerless_patch_on_headered_rom() {
+    let clean = snes_rom();
+    let patch = IpsPatcher::create(&clean, &hacked(&clean)).unwrap();
+
+    let mut rom = headered(&clean);
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Ips);
+    assert_eq!(fix.change(), Some(HeaderChange::Stripped));
+
+    apply(&mut rom, &patch, PatchType::Ips);
+    fix.finish(&mut rom, HeaderLayout::Keep);
+    assert_eq!(rom, headered(&hacked(&clean)));
+}
+
+#[test]
+fn test_ips_headered_patch_on_headerless_rom() {
+    let clean = snes_rom();
+    let patch = IpsPatcher::create(&headered(&clean), &headered(&hacked(&clean))).unwrap();
+
+    let mut rom = clean.clone();
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Ips);
+    assert_eq!(fix.change(), Some(HeaderChange::Added));
+
+    apply(&mut rom, &patch, PatchType::Ips);
+    fix.finish(&mut rom, HeaderLayout::Headerless);
+    assert_eq!(rom, hacked(&clean));
+}
+
+#[test]
+fn test_matching_layout_untouched() {
+    let clean = snes_rom();
+    let patch = IpsPatcher::create(&clean, &hacked(&clean)).unwrap();
+
+    let mut rom = clean.clone();
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Ips);
+    assert_eq!(fix.change(), None);
+
+    apply(&mut rom, &patch, PatchType::Ips);
+    let report = fix.finish(&mut rom, HeaderLayout::Keep);
+    assert!(report.is_empty());
+    assert_eq!(rom, hacked(&clean));
+}
+
+#[test]
+fn test_requested_layout_adds_blank_header() {
+    let clean = snes_rom();
+    let patch = IpsPatcher::create(&clean, &hacked(&clean)).unwrap();
+
+    let mut rom = clean.clone();
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Ips);
+    apply(&mut rom, &patch, PatchType::Ips);
+    let report = fix.finish(&mut rom, HeaderLayout::Headered);
+
+    assert_eq!(report.after, Some(HeaderChange::Added));
+    assert_eq!(rom.len(), clean.len() + COPIER_HEADER_SIZE);
+    assert!(rom[..COPIER_HEADER_SIZE].iter().all(|&b| b == 0));
+}
+
+#[test]
+fn test_non_snes_rom_untouched() {
+    // Headered size but no internal header
+    let mut rom = vec![0u8; 0x10000 + COPIER_HEADER_SIZE];
+    let patch = IpsPatcher::create(&rom[COPIER_HEADER_SIZE..], &[1u8; 0x10000]).unwrap();
+    let fix = copier_header::prepare(&mut rom, &patch, PatchType::Ips);
+    assert_eq!(fix.change(), None);
+    let report = fix.finish(&mut rom, HeaderLayout::Headerless);
+    assert!(report.is_empty());
+}
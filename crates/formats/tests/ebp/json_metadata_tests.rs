@@ -19,6 +19,17 @@ fn test_json_author() {
     assert_eq!(author.unwrap().1, "Me");
 }
 
+#[test]
+fn test_json_capitalized_keys() {
+    // EBPatcher writes capitalized keys
+    let patch = b"PATCHEOF{\"patcher\":\"EBPatcher\",\"Author\":\"Me\",\"Title\":\"T\"}";
+    let metadata = EbpPatcher::metadata(patch).unwrap();
+    let author = metadata.extra.iter().find(|(k, _)| k == "author");
+    assert_eq!(author.unwrap().1, "Me");
+    let title = metadata.extra.iter().find(|(k, _)| k == "title");
+    assert_eq!(title.unwrap().1, "T");
+}
+
 #[test]
 fn test_json_full() {
     let patch =
@@ -0,0 +1,172 @@
+//! Source ROM lookup tests
+
+#![cfg(all(feature = "ips", feature = "bps", feature = "ppf", feature = "aps"))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use stitchr_core::PatchType;
+use stitchr_formats::bps::BpsPatcher;
+use stitchr_formats::copier_header::COPIER_HEADER_SIZE;
+use stitchr_formats::find_source::{Confidence, SourceCheck, find_source};
+use stitchr_formats::ips::IpsPatcher;
+
+/// Fresh scratch directory under the system temp dir
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "stitchr-find-source-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(root: &Path, name: &str, data: &[u8]) -> PathBuf {
+    let path = root.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, data).unwrap();
+    path
+}
+
+fn rom(seed: u8, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i as u32 * 31 + seed as u32) as u8)
+        .collect()
+}
+
+fn hacked(rom: &[u8]) -> Vec<u8> {
+    let mut out = rom.to_vec();
+    out[0x100..0x140].fill(0xEA);
+    out
+}
+
+#[test]
+fn test_bps_exact_match_in_subdirectory() {
+    let dir = scratch("bps");
+    let source = rom(1, 0x4000);
+    let patch = BpsPatcher::create(&source, &hacked(&source), &[]).unwrap();
+
+    let wanted = write(&dir, "snes/game.sfc", &source);
+    write(&dir, "snes/other.sfc", &rom(2, 0x4000));
+    write(&dir, "small.sfc", &source[..0x2000]);
+
+    let matches = find_source(&patch, PatchType::Bps, &dir).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, wanted);
+    assert_eq!(matches[0].confidence, Confidence::Exact);
+    assert!(matches[0].detail.starts_with("CRC32"));
+}
+
+#[test]
+fn test_bps_match_with_copier_header() {
+    let dir = scratch("headered");
+    let source = rom(3, 0x4000);
+    let patch = BpsPatcher::create(&source, &hacked(&source), &[]).unwrap();
+
+    let mut headered = vec![0u8; COPIER_HEADER_SIZE];
+    headered.extend_from_slice(&source);
+    let wanted = write(&dir, "game.smc", &headered);
+
+    let matches = find_source(&patch, PatchType::Bps, &dir).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, wanted);
+    assert!(matches[0].detail.contains("without copier header"));
+}
+
+#[test]
+fn test_size_pruning() {
+    let source = rom(4, 0x4000);
+    let patch = BpsPatcher::create(&source, &hacked(&source), &[]).unwrap();
+    let check = SourceCheck::from_patch(&patch, PatchType::Bps).unwrap();
+
+    assert!(check.accepts_size(0x4000));
+    assert!(check.accepts_size(0x4000 + COPIER_HEADER_SIZE));
+    assert!(!check.accepts_size(0x4001));
+}
+
+#[test]
+fn test_ips_plausibility_ranks_candidates() {
+    let dir = scratch("ips");
+    let source = rom(5, 0x4000);
+    let patch = IpsPatcher::create(&source, &hacked(&source)).unwrap();
+
+    let wanted = write(&dir, "a.sfc", &source);
+    // Already patched: no record changes anything
+    write(&dir, "b.sfc", &hacked(&source));
+    // Too small to hold the records
+    write(&dir, "c.sfc", &source[..0x80]);
+
+    let matches = find_source(&patch, PatchType::Ips, &dir).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, wanted);
+    assert_eq!(matches[0].confidence, Confidence::Plausible(100));
+}
+
+#[test]
+fn test_ppf_block_check() {
+    let dir = scratch("ppf");
+    let source = rom(6, 0xA000);
+    let mut patch = Vec::new();
+    patch.extend_from_slice(b"PPF30");
+    patch.push(0x02);
+    patch.extend_from_slice(&[0u8; 50]);
+    patch.extend_from_slice(&[0x00, 0x01, 0x00, 0x00]); // BIN, block check
+    patch.extend_from_slice(&source[0x9320..0x9320 + 1024]);
+    patch.extend_from_slice(&0x10u64.to_le_bytes());
+    patch.push(1);
+    patch.push(0xFF);
+
+    let wanted = write(&dir, "disc.bin", &source);
+    write(&dir, "other.bin", &rom(7, 0xA000));
+
+    let matches = find_source(&patch, PatchType::Ppf, &dir).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, wanted);
+    assert_eq!(matches[0].detail, "block check at 0x9320");
+}
+
+#[test]
+fn test_aps_n64_header_match_any_byte_order() {
+    let dir = scratch("aps");
+    let mut z64 = rom(8, 0x1000);
+    z64[..4].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+    z64[0x10..0x18].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    z64[0x3C..0x3F].copy_from_slice(b"NTE");
+
+    let mut patch = Vec::new();
+    patch.extend_from_slice(b"APS10");
+    patch.push(0x01); // N64 header
+    patch.push(0x00); // encoding
+    patch.extend_from_slice(&[b' '; 50]);
+    patch.push(0x01); // z64
+    patch.extend_from_slice(b"NTE");
+    patch.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    patch.extend_from_slice(&[0u8; 5]);
+    patch.extend_from_slice(&0x1000u32.to_le_bytes());
+    patch.extend_from_slice(&0x20u32.to_le_bytes());
+    patch.push(1);
+    patch.push(0xAA);
+
+    // Byte-swapped dump of the same ROM
+    let v64: Vec<u8> = z64.chunks(2).flat_map(|w| [w[1], w[0]]).collect();
+    let wanted = write(&dir, "game.v64", &v64);
+    let mut other = z64.clone();
+    other[0x3C..0x3F].copy_from_slice(b"NXX");
+    write(&dir, "other.z64", &other);
+
+    let matches = find_source(&patch, PatchType::Aps, &dir).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, wanted);
+    assert!(matches[0].detail.starts_with("cart ID NTE"));
+}
+
+#[test]
+fn test_no_source_information() {
+    let dir = scratch("none");
+    let patch = b"PATCHEOF";
+    // IPS always works via plausibility; formats without any source info fail
+    assert!(find_source(patch, PatchType::Ips, &dir).unwrap().is_empty());
+    assert!(SourceCheck::from_patch(b"BDF", PatchType::Bdf).is_err());
+}
@@ -0,0 +1,65 @@
+//! GDIFF application tests
+
+use super::build_patch;
+use stitchr_core::PatchFormat;
+use stitchr_formats::gdiff::GdiffPatcher;
+
+fn apply(rom: &[u8], patch: &[u8]) -> stitchr_core::Result<Vec<u8>> {
+    let mut rom = rom.to_vec();
+    GdiffPatcher.apply(&mut rom, patch)?;
+    Ok(rom)
+}
+
+#[test]
+fn test_apply_inline_data() {
+    let patch = build_patch(&[3, b'a', b'b', b'c']);
+    assert_eq!(apply(&[], &patch).unwrap(), b"abc");
+}
+
+#[test]
+fn test_apply_data_ushort_and_int() {
+    let mut commands = vec![247, 0x01, 0x00];
+    commands.extend_from_slice(&[0x11; 256]);
+    commands.extend_from_slice(&[248, 0x00, 0x00, 0x00, 0x02, 0x22, 0x33]);
+    let patch = build_patch(&commands);
+
+    let out = apply(&[], &patch).unwrap();
+    assert_eq!(out.len(), 258);
+    assert_eq!(&out[254..], &[0x11, 0x11, 0x22, 0x33]);
+}
+
+#[test]
+fn test_apply_every_copy_width() {
+    let rom: Vec<u8> = (0..=255u8).collect();
+    let commands = [
+        249, 0x00, 0x01, 0x02, // ushort pos 1, ubyte len 2
+        250, 0x00, 0x03, 0x00, 0x01, // ushort pos 3, ushort len 1
+        251, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, // ushort pos 4, int len 1
+        252, 0x00, 0x00, 0x00, 0x05, 0x01, // int pos 5, ubyte len 1
+        253, 0x00, 0x00, 0x00, 0x06, 0x00, 0x01, // int pos 6, ushort len 1
+        254, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x01, // int pos 7, int len 1
+        255, 0, 0, 0, 0, 0, 0, 0, 0x08, 0x00, 0x00, 0x00, 0x01, // long pos 8, int len 1
+    ];
+    let patch = build_patch(&commands);
+
+    assert_eq!(apply(&rom, &patch).unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_apply_copy_out_of_bounds() {
+    let patch = build_patch(&[249, 0x00, 0x08, 0x04]);
+    assert!(apply(&[0u8; 10], &patch).is_err());
+}
+
+#[test]
+fn test_apply_missing_eof() {
+    let mut patch = build_patch(&[1, 0xAA]);
+    patch.pop();
+    assert!(apply(&[], &patch).is_err());
+}
+
+#[test]
+fn test_apply_rejects_huge_target() {
+    let patch = build_patch(&[254, 0, 0, 0, 0, 0x7F, 0xFF, 0xFF, 0xFF]);
+    assert!(apply(&[0u8; 16], &patch).is_err());
+}
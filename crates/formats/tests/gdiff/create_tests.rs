@@ -0,0 +1,57 @@
+//! GDIFF encoder tests
+
+use stitchr_core::PatchFormat;
+use stitchr_formats::gdiff::GdiffPatcher;
+
+fn patterned(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+fn roundtrip(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let patch = GdiffPatcher::create(source, target).unwrap();
+    GdiffPatcher::validate(&patch).unwrap();
+    let mut rom = source.to_vec();
+    GdiffPatcher.apply(&mut rom, &patch).unwrap();
+    assert_eq!(rom, target);
+    patch
+}
+
+#[test]
+fn test_create_identical() {
+    let rom = patterned(100_000);
+    let patch = roundtrip(&rom, &rom);
+    assert!(patch.len() < 16);
+}
+
+#[test]
+fn test_create_in_place_edits() {
+    let source = patterned(70_000);
+    let mut target = source.clone();
+    target[10] ^= 0xFF;
+    target[40_000..40_010].fill(0);
+    target[69_999] = 1;
+
+    let patch = roundtrip(&source, &target);
+    assert!(patch.len() < 64);
+}
+
+#[test]
+fn test_create_moved_block() {
+    let source = patterned(4096);
+    let mut target = vec![0xEE; 37];
+    target.extend_from_slice(&source[1000..3000]);
+
+    let patch = roundtrip(&source, &target);
+    assert!(patch.len() < 64);
+}
+
+#[test]
+fn test_create_grow_shrink_and_empty() {
+    let source = patterned(512);
+    let mut grown = source.clone();
+    grown.extend(patterned(300).iter().map(|b| b ^ 0x5A));
+    roundtrip(&source, &grown);
+    roundtrip(&source, &source[..100]);
+    roundtrip(&source, &[]);
+    roundtrip(&[], &source);
+}
@@ -0,0 +1,24 @@
+//! GDIFF metadata tests
+
+use super::build_patch;
+use stitchr_core::{PatchFormat, PatchType};
+use stitchr_formats::gdiff::GdiffPatcher;
+
+#[test]
+fn test_metadata_infers_target_size() {
+    let patch = build_patch(&[2, 0xAA, 0xBB, 250, 0x00, 0x10, 0x01, 0x00]);
+    let metadata = GdiffPatcher::metadata(&patch).unwrap();
+
+    assert_eq!(metadata.patch_type, PatchType::Gdiff);
+    assert_eq!(metadata.target_size, Some(2 + 256));
+    assert!(
+        metadata
+            .extra
+            .contains(&("min_source_size".to_string(), "272".to_string()))
+    );
+}
+
+#[test]
+fn test_metadata_invalid() {
+    assert!(GdiffPatcher::metadata(b"PATCH").is_err());
+}
@@ -0,0 +1,17 @@
+//! GDIFF format tests
+
+mod apply_tests;
+mod create_tests;
+mod metadata_tests;
+mod validate_tests;
+
+/// GDIFF header: magic + version 4
+pub const HEADER: &[u8] = &[0xD1, 0xFF, 0xD1, 0xFF, 0x04];
+
+/// Build a patch from raw command bytes and append EOF
+pub fn build_patch(commands: &[u8]) -> Vec<u8> {
+    let mut patch = HEADER.to_vec();
+    patch.extend_from_slice(commands);
+    patch.push(0x00);
+    patch
+}
@@ -0,0 +1,33 @@
+//! GDIFF validation tests
+
+use super::{HEADER, build_patch};
+use stitchr_core::{PatchError, PatchFormat, PatchType};
+use stitchr_formats::detect_format;
+use stitchr_formats::gdiff::GdiffPatcher;
+
+#[test]
+fn test_can_handle() {
+    assert!(GdiffPatcher::can_handle(HEADER));
+    assert!(!GdiffPatcher::can_handle(&[0xD1, 0xFF, 0xD1, 0xFF, 0x03]));
+    assert!(!GdiffPatcher::can_handle(&[0xD1, 0xFF, 0xD1]));
+}
+
+#[test]
+fn test_detect_format() {
+    assert_eq!(detect_format(&build_patch(&[])), Some(PatchType::Gdiff));
+}
+
+#[test]
+fn test_validate() {
+    assert!(GdiffPatcher::validate(&build_patch(&[2, 0, 0, 249, 0, 0, 1])).is_ok());
+    // Truncated DATA payload
+    assert!(GdiffPatcher::validate(&build_patch(&[5, 0])).is_err());
+    // Truncated COPY arguments
+    assert!(GdiffPatcher::validate(&[0xD1, 0xFF, 0xD1, 0xFF, 0x04, 254, 0, 0]).is_err());
+}
+
+#[test]
+fn test_validate_wrong_version() {
+    let result = GdiffPatcher::validate(&[0xD1, 0xFF, 0xD1, 0xFF, 0x05, 0x00]);
+    assert!(matches!(result, Err(PatchError::UnsupportedVersion(_))));
+}
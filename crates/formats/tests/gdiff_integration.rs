@@ -0,0 +1,4 @@
+//! GDIFF integration tests
+
+#![cfg(feature = "gdiff")]
+mod gdiff;
@@ -0,0 +1,83 @@
+//! Tests for IPS/IPS32 patch creation
+
+use stitchr_core::PatchFormat;
+use stitchr_formats::ips::{Ips32Patcher, IpsPatcher};
+
+fn generate_patterned_rom(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i % 251) as u8).collect()
+}
+
+fn roundtrip(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let patch = IpsPatcher::create(source, target).unwrap();
+    IpsPatcher::validate(&patch).unwrap();
+    let mut rom = source.to_vec();
+    IpsPatcher.apply(&mut rom, &patch).unwrap();
+    assert_eq!(rom, target);
+    patch
+}
+
+#[test]
+fn test_create_identical_is_empty() {
+    let rom = generate_patterned_rom(256);
+    let patch = roundtrip(&rom, &rom);
+    assert_eq!(patch, b"PATCHEOF");
+}
+
+#[test]
+fn test_create_scattered_changes() {
+    let source = generate_patterned_rom(4096);
+    let mut target = source.clone();
+    target[10] = 0xFF;
+    target[12] = 0xFE;
+    target[3000] = 0x00;
+
+    let patch = roundtrip(&source, &target);
+    // Nearby changes share a record: 2 records + header + footer
+    assert_eq!(patch.len(), 5 + (3 + 2 + 3) + (3 + 2 + 1) + 3);
+}
+
+#[test]
+fn test_create_uses_rle_for_runs() {
+    let source = generate_patterned_rom(1024);
+    let mut target = source.clone();
+    target[100..600].fill(0xEE);
+
+    let patch = roundtrip(&source, &target);
+    assert!(patch.len() < 32);
+}
+
+#[test]
+fn test_create_grow_and_shrink() {
+    let source = generate_patterned_rom(64);
+    let mut grown = source.clone();
+    grown.extend_from_slice(&[0u8; 40]);
+    roundtrip(&source, &grown);
+
+    let shrunk = source[..20].to_vec();
+    roundtrip(&source, &shrunk);
+}
+
+#[test]
+fn test_create_avoids_eof_offset() {
+    let size = 0x454F46 + 64;
+    let source = vec![0u8; size];
+    let mut target = source.clone();
+    target[0x454F46] = 0x11;
+    target[0x454F46 + 1..0x454F46 + 40].fill(0x22);
+
+    roundtrip(&source, &target);
+}
+
+#[test]
+fn test_create_ips_rejects_large_offsets() {
+    let source = vec![0u8; 0x0100_0010];
+    let mut target = source.clone();
+    target[0x0100_0004] = 1;
+
+    assert!(IpsPatcher::create(&source, &target).is_err());
+
+    let patch = Ips32Patcher::create(&source, &target).unwrap();
+    let mut rom = source.clone();
+    Ips32Patcher.apply(&mut rom, &patch).unwrap();
+    assert_eq!(rom, target);
+}
@@ -0,0 +1,105 @@
+//! Tests for IPS32 (32-bit offset) patches
+
+use stitchr_core::{PatchFormat, PatchType};
+use stitchr_formats::detect_format;
+use stitchr_formats::ips::{Ips32Patcher, IpsPatcher};
+
+/// Build an IPS32 patch with a single normal record
+fn single_record_patch(offset: u32, data: &[u8]) -> Vec<u8> {
+    let mut patch = Vec::new();
+    patch.extend_from_slice(b"IPS32");
+    patch.extend_from_slice(&offset.to_be_bytes());
+    patch.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    patch.extend_from_slice(data);
+    patch.extend_from_slice(b"EEOF");
+    patch
+}
+
+#[test]
+fn test_can_handle() {
+    assert!(Ips32Patcher::can_handle(b"IPS32EEOF"));
+    assert!(!Ips32Patcher::can_handle(b"PATCHEOF"));
+    assert!(!IpsPatcher::can_handle(b"IPS32EEOF"));
+}
+
+#[test]
+fn test_detect_format() {
+    assert_eq!(detect_format(b"IPS32EEOF"), Some(PatchType::Ips32));
+    assert_eq!(detect_format(b"PATCHEOF"), Some(PatchType::Ips));
+}
+
+#[test]
+fn test_apply_record_past_16mib() {
+    let offset = 0x0100_0010u32;
+    let mut rom = vec![0u8; 0x0100_0020];
+    let patch = single_record_patch(offset, &[0xAA, 0xBB]);
+
+    Ips32Patcher.apply(&mut rom, &patch).unwrap();
+
+    assert_eq!(rom[offset as usize], 0xAA);
+    assert_eq!(rom[offset as usize + 1], 0xBB);
+}
+
+#[test]
+fn test_apply_rle_and_truncate() {
+    let mut rom = vec![0u8; 32];
+    let mut patch = Vec::new();
+    patch.extend_from_slice(b"IPS32");
+    patch.extend_from_slice(&4u32.to_be_bytes());
+    patch.extend_from_slice(&[0x00, 0x00]); // RLE marker
+    patch.extend_from_slice(&[0x00, 0x04]); // Count 4
+    patch.push(0x7F);
+    patch.extend_from_slice(b"EEOF");
+    patch.extend_from_slice(&16u32.to_be_bytes()); // Truncate to 16 bytes
+
+    Ips32Patcher.apply(&mut rom, &patch).unwrap();
+
+    assert_eq!(rom.len(), 16);
+    assert_eq!(&rom[4..8], &[0x7F; 4]);
+}
+
+#[test]
+fn test_offset_matching_classic_eof_is_a_record() {
+    // 0x00454F46 terminates classic IPS but is an ordinary offset in IPS32
+    let offset = 0x0045_4F46u32;
+    let mut rom = vec![0u8; offset as usize + 1];
+    let patch = single_record_patch(offset, &[0x01]);
+
+    Ips32Patcher.apply(&mut rom, &patch).unwrap();
+
+    assert_eq!(rom[offset as usize], 0x01);
+}
+
+#[test]
+fn test_apply_missing_eeof() {
+    let mut rom = vec![0u8; 16];
+    let mut patch = single_record_patch(0, &[0x01]);
+    patch.truncate(patch.len() - 4);
+
+    assert!(Ips32Patcher.apply(&mut rom, &patch).is_err());
+}
+
+#[test]
+fn test_apply_rejects_huge_target() {
+    let mut rom = vec![0u8; 16];
+    let patch = single_record_patch(0xF000_0000, &[0x01]);
+
+    assert!(Ips32Patcher.apply(&mut rom, &patch).is_err());
+}
+
+#[test]
+fn test_validate() {
+    assert!(Ips32Patcher::validate(b"IPS32EEOF").is_ok());
+    assert!(Ips32Patcher::validate(&single_record_patch(0x0200_0000, &[1, 2, 3])).is_ok());
+    assert!(Ips32Patcher::validate(b"PATCHEOF").is_err());
+    assert!(Ips32Patcher::validate(b"IPS32\x00\x00\x00\x00\x00\x03EEOF").is_err());
+}
+
+#[test]
+fn test_metadata() {
+    let patch = single_record_patch(0x0100_0000, &[1, 2, 3, 4]);
+    let metadata = Ips32Patcher::metadata(&patch).unwrap();
+
+    assert_eq!(metadata.patch_type, PatchType::Ips32);
+    assert_eq!(metadata.target_size, Some(0x0100_0004));
+}
@@ -2,5 +2,7 @@
 
 mod apply;
 mod checksum_validation;
+mod create_tests;
+mod ips32_tests;
 mod metadata_tests;
 mod validate_tests;
@@ -0,0 +1,200 @@
+//! Patch lint tests
+
+#![cfg(all(
+    feature = "ips",
+    feature = "bps",
+    feature = "ups",
+    feature = "ppf",
+    feature = "ebp",
+    feature = "xdelta"
+))]
+
+use stitchr_core::{LintWarning, PatchFormat, Severity};
+use stitchr_formats::bps::BpsPatcher;
+use stitchr_formats::ebp::EbpPatcher;
+use stitchr_formats::ips::{Ips32Patcher, IpsPatcher};
+use stitchr_formats::ppf::PpfPatcher;
+use stitchr_formats::ups::UpsPatcher;
+use stitchr_formats::xdelta::XdeltaPatcher;
+
+/// IPS patch from (offset, data) records; empty data makes a zero-length
+/// RLE record
+fn ips(records: &[(u32, &[u8])], tail: &[u8]) -> Vec<u8> {
+    let mut patch = b"PATCH".to_vec();
+    for (offset, data) in records {
+        patch.extend_from_slice(&offset.to_be_bytes()[1..]);
+        if data.is_empty() {
+            patch.extend_from_slice(&[0, 0, 0, 0, 0xFF]);
+        } else {
+            patch.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            patch.extend_from_slice(data);
+        }
+    }
+    patch.extend_from_slice(b"EOF");
+    patch.extend_from_slice(tail);
+    patch
+}
+
+fn severities(warnings: &[LintWarning]) -> Vec<Severity> {
+    warnings.iter().map(|w| w.severity).collect()
+}
+
+#[test]
+fn test_ips_clean_patch() {
+    let patch = ips(&[(0x10, b"AB"), (0x20, b"CD")], &[]);
+    assert!(IpsPatcher::lint(&patch).unwrap().is_empty());
+
+    // A truncation size after EOF is not trailing data
+    let patch = ips(&[(0x10, b"AB")], &[0x00, 0x40, 0x00]);
+    assert!(IpsPatcher::lint(&patch).unwrap().is_empty());
+}
+
+#[test]
+fn test_ips_overlapping_and_out_of_order_records() {
+    let patch = ips(&[(0x20, b"ABCD"), (0x22, b"EF"), (0x10, b"GH")], &[]);
+    let warnings = IpsPatcher::lint(&patch).unwrap();
+
+    assert_eq!(severities(&warnings), [Severity::Warning, Severity::Info]);
+    assert_eq!(warnings[0].offset, 5 + 3 + 2 + 4);
+    assert!(warnings[0].message.contains("overlaps"));
+}
+
+#[test]
+fn test_ips_zero_length_record() {
+    let patch = ips(&[(0x10, b"")], &[]);
+    let warnings = IpsPatcher::lint(&patch).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].offset, 5);
+    assert!(warnings[0].message.contains("zero-length"));
+}
+
+#[test]
+fn test_ips_record_at_eof_offset() {
+    // A record for 0x454F46 spells "EOF" and ends the patch early
+    let mut patch = b"PATCH".to_vec();
+    patch.extend_from_slice(b"EOF");
+    patch.extend_from_slice(&[0x00, 0x02, 0xAA, 0xBB]);
+    patch.extend_from_slice(&[0x00, 0x00, 0x10, 0x00, 0x01, 0xCC]);
+    patch.extend_from_slice(b"EOF");
+
+    let warnings = IpsPatcher::lint(&patch).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Error);
+    assert_eq!(warnings[0].offset, 5);
+    assert!(warnings[0].message.contains("0x454F46"));
+}
+
+#[test]
+fn test_ips_trailing_garbage() {
+    let patch = ips(&[(0x10, b"AB")], b"garbage");
+    let warnings = IpsPatcher::lint(&patch).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert_eq!(warnings[0].offset, patch.len() - 7);
+}
+
+#[test]
+fn test_ips32_lint() {
+    let patch = Ips32Patcher::create(&[0u8; 64], &[1u8; 64]).unwrap();
+    assert!(Ips32Patcher::lint(&patch).unwrap().is_empty());
+}
+
+#[test]
+fn test_invalid_patch_is_an_error() {
+    assert!(IpsPatcher::lint(b"PATCH\x00\x00").is_err());
+}
+
+#[test]
+fn test_bps_metadata_not_xml() {
+    let source = vec![0u8; 64];
+    let target = vec![1u8; 64];
+
+    let patch = BpsPatcher::create(&source, &target, b"<?xml version=\"1.0\"?><patch/>").unwrap();
+    assert!(BpsPatcher::lint(&patch).unwrap().is_empty());
+
+    let patch = BpsPatcher::create(&source, &target, b"made by me").unwrap();
+    let warnings = BpsPatcher::lint(&patch).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(&patch[warnings[0].offset..warnings[0].offset + 4], b"made");
+}
+
+#[test]
+fn test_ups_patch_crc_mismatch() {
+    let mut patch = b"UPS1".to_vec();
+    patch.extend_from_slice(&[0x81, 0x81, 0x80, 0x01, 0x00]);
+    patch.extend_from_slice(&crc32fast::hash(&[0]).to_le_bytes());
+    patch.extend_from_slice(&crc32fast::hash(&[1]).to_le_bytes());
+    let crc = crc32fast::hash(&patch);
+    patch.extend_from_slice(&crc.to_le_bytes());
+    assert!(UpsPatcher::lint(&patch).unwrap().is_empty());
+
+    let last = patch.len() - 1;
+    patch[last] ^= 0xFF;
+    let warnings = UpsPatcher::lint(&patch).unwrap();
+    assert_eq!(severities(&warnings), [Severity::Error]);
+    assert_eq!(warnings[0].offset, patch.len() - 4);
+}
+
+#[test]
+fn test_ppf2_record_past_image_size() {
+    let mut patch = b"PPF20".to_vec();
+    patch.push(0x01);
+    patch.extend_from_slice(&[0u8; 50]);
+    patch.extend_from_slice(&0x100u32.to_le_bytes());
+    patch.extend_from_slice(&[0u8; 1024]);
+    let second = patch.len() + 6;
+    patch.extend_from_slice(&0x10u32.to_le_bytes());
+    patch.extend_from_slice(&[1, 0xAA]);
+    patch.extend_from_slice(&0xFFu32.to_le_bytes());
+    patch.extend_from_slice(&[2, 0xBB, 0xCC]);
+
+    let warnings = PpfPatcher::lint(&patch).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].offset, second);
+}
+
+#[test]
+fn test_ebp_unknown_fields() {
+    let mut patch = ips(&[(0x10, b"AB")], &[]);
+    let json_start = patch.len();
+    patch.extend_from_slice(br#"{"Title": "Hack", "patcher": "EBPatcher", "nested": {"x": 1}}"#);
+
+    let warnings = EbpPatcher::lint(&patch).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].offset, json_start + 42);
+    assert!(warnings[0].message.contains("\"nested\""));
+}
+
+#[test]
+fn test_xdelta_window_without_adler32() {
+    let mut patch = vec![0xD6, 0xC3, 0xC4, 0x00, 0x00];
+    // Window 0: no source, one ADD of 'A', no checksum
+    patch.extend_from_slice(&[0x00, 0x07, 0x01, 0x00, 0x01, 0x01, 0x00, b'A', 0x02]);
+    let second = patch.len();
+    // Window 1: same, with an Adler32
+    patch.extend_from_slice(&[0x04, 0x0B, 0x01, 0x00, 0x01, 0x01, 0x00]);
+    patch.extend_from_slice(&0x0042_0042u32.to_be_bytes());
+    patch.extend_from_slice(&[b'A', 0x02]);
+    patch.extend_from_slice(&[0x00, 0x07, 0x01, 0x00, 0x01, 0x01, 0x00, b'A', 0x02]);
+
+    let warnings = XdeltaPatcher::lint(&patch).unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].offset, 5);
+    assert!(warnings[1].offset > second);
+    assert_eq!(warnings[1].message, "window 2 has no Adler32 checksum");
+}
+
+#[test]
+fn test_xdelta_window_sections_overflow() {
+    let mut patch = vec![0xD6, 0xC3, 0xC4, 0x00, 0x00];
+    patch.extend_from_slice(&[0x00, 0x07, 0x01, 0x00]);
+    // Add/run data and instruction lengths of 2^63 each
+    let huge = [0x81, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+    patch.extend_from_slice(&huge);
+    patch.extend_from_slice(&huge);
+    patch.push(0x00);
+
+    assert!(XdeltaPatcher::lint(&patch).is_err());
+}
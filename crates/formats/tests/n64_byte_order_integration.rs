@@ -0,0 +1,188 @@
+//! N64 byte-order normalization tests
+
+#![cfg(all(feature = "ips", feature = "bps", feature = "aps"))]
+
+use stitchr_core::{PatchFormat, PatchType};
+use stitchr_formats::aps::n64::ApsN64Patcher;
+use stitchr_formats::bps::BpsPatcher;
+use stitchr_formats::ips::IpsPatcher;
+use stitchr_formats::n64_byte_order::{self, ByteOrder, OutputOrder};
+
+/// 4 KiB big-endian ROM with the usual magic, cart ID and CRC fields
+fn z64_rom() -> Vec<u8> {
+    let mut rom: Vec<u8> = (0..4096).map(|i| (i * 7 % 251) as u8).collect();
+    rom[..4].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+    rom[0x10..0x18].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    rom[0x3C..0x3F].copy_from_slice(b"NSM");
+    rom
+}
+
+fn reorder(rom: &[u8], order: ByteOrder) -> Vec<u8> {
+    let mut out = rom.to_vec();
+    n64_byte_order::convert(&mut out, ByteOrder::BigEndian, order);
+    out
+}
+
+fn hacked(rom: &[u8]) -> Vec<u8> {
+    let mut out = rom.to_vec();
+    out[0x200..0x205].copy_from_slice(b"HELLO");
+    out
+}
+
+/// APS N64 patch writing "HELLO" at 0x200 (in the given original format)
+fn aps_patch(original_format: u8, cart_rom: &[u8]) -> Vec<u8> {
+    let mut patch = b"APS10".to_vec();
+    patch.push(0x01);
+    patch.push(0x00);
+    patch.extend_from_slice(&[0u8; 50]);
+    patch.push(original_format);
+    patch.extend_from_slice(&cart_rom[0x3C..0x3F]);
+    patch.extend_from_slice(&cart_rom[0x10..0x18]);
+    patch.extend_from_slice(&[0u8; 5]);
+    patch.extend_from_slice(&(cart_rom.len() as u32).to_le_bytes());
+    patch.extend_from_slice(&0x200u32.to_le_bytes());
+    patch.push(5);
+    patch.extend_from_slice(b"HELLO");
+    patch
+}
+
+#[test]
+fn test_detect() {
+    let rom = z64_rom();
+    assert_eq!(ByteOrder::detect(&rom), Some(ByteOrder::BigEndian));
+    assert_eq!(
+        ByteOrder::detect(&reorder(&rom, ByteOrder::ByteSwapped)),
+        Some(ByteOrder::ByteSwapped)
+    );
+    assert_eq!(
+        ByteOrder::detect(&reorder(&rom, ByteOrder::LittleEndian)),
+        Some(ByteOrder::LittleEndian)
+    );
+    assert_eq!(ByteOrder::detect(b"NES\x1a"), None);
+}
+
+#[test]
+fn test_convert_roundtrip() {
+    let rom = z64_rom();
+    for order in [ByteOrder::ByteSwapped, ByteOrder::LittleEndian] {
+        let mut converted = reorder(&rom, order);
+        assert_ne!(converted, rom);
+        n64_byte_order::convert(&mut converted, order, ByteOrder::BigEndian);
+        assert_eq!(converted, rom);
+    }
+    // v64 <-> n64 directly
+    let mut rom_v64 = reorder(&rom, ByteOrder::ByteSwapped);
+    n64_byte_order::convert(
+        &mut rom_v64,
+        ByteOrder::ByteSwapped,
+        ByteOrder::LittleEndian,
+    );
+    assert_eq!(rom_v64, reorder(&rom, ByteOrder::LittleEndian));
+}
+
+#[test]
+fn test_ips_on_v64_keep_order() {
+    let clean = z64_rom();
+    let patch = IpsPatcher::create(&clean, &hacked(&clean)).unwrap();
+
+    let mut rom = reorder(&clean, ByteOrder::ByteSwapped);
+    let fix = n64_byte_order::prepare(&mut rom, &patch, PatchType::Ips);
+    assert_eq!(
+        fix.conversion(),
+        Some((ByteOrder::ByteSwapped, ByteOrder::BigEndian))
+    );
+    IpsPatcher.apply(&mut rom, &patch).unwrap();
+
+    let report = fix.finish(&mut rom, OutputOrder::Keep);
+    assert!(!report.order_changed());
+    assert_eq!(rom, reorder(&hacked(&clean), ByteOrder::ByteSwapped));
+    assert!(report.to_string().contains("converted back"));
+}
+
+#[test]
+fn test_ips_on_n64_write_z64() {
+    let clean = z64_rom();
+    let patch = IpsPatcher::create(&clean, &hacked(&clean)).unwrap();
+
+    let mut rom = reorder(&clean, ByteOrder::LittleEndian);
+    let fix = n64_byte_order::prepare(&mut rom, &patch, PatchType::Ips);
+    IpsPatcher.apply(&mut rom, &patch).unwrap();
+
+    let report = fix.finish(&mut rom, OutputOrder::BigEndian);
+    assert!(report.order_changed());
+    assert_eq!(report.output, Some(ByteOrder::BigEndian));
+    assert_eq!(rom, hacked(&clean));
+}
+
+#[test]
+fn test_z64_input_untouched() {
+    let clean = z64_rom();
+    let patch = IpsPatcher::create(&clean, &hacked(&clean)).unwrap();
+
+    let mut rom = clean.clone();
+    let fix = n64_byte_order::prepare(&mut rom, &patch, PatchType::Ips);
+    assert_eq!(fix.conversion(), None);
+    assert!(fix.finish(&mut rom, OutputOrder::Keep).is_empty());
+    assert_eq!(rom, clean);
+}
+
+#[test]
+fn test_bps_made_for_v64_not_converted() {
+    let clean = reorder(&z64_rom(), ByteOrder::ByteSwapped);
+    let patch = BpsPatcher::create(&clean, &hacked(&clean), &[]).unwrap();
+
+    let mut rom = clean.clone();
+    let fix = n64_byte_order::prepare(&mut rom, &patch, PatchType::Bps);
+    assert_eq!(fix.conversion(), None);
+    assert_eq!(rom, clean);
+}
+
+#[test]
+fn test_aps_original_format_v64() {
+    let clean = z64_rom();
+    let v64 = reorder(&clean, ByteOrder::ByteSwapped);
+    let patch = aps_patch(0x00, &v64);
+
+    // A z64 dump is converted to the v64 layout the patch was made against
+    let mut rom = clean.clone();
+    let fix = n64_byte_order::prepare(&mut rom, &patch, PatchType::Aps);
+    assert_eq!(
+        fix.conversion(),
+        Some((ByteOrder::BigEndian, ByteOrder::ByteSwapped))
+    );
+    ApsN64Patcher::verify(&rom, &patch, None).unwrap();
+    ApsN64Patcher.apply(&mut rom, &patch).unwrap();
+    fix.finish(&mut rom, OutputOrder::Keep);
+
+    let mut expected = v64.clone();
+    expected[0x200..0x205].copy_from_slice(b"HELLO");
+    n64_byte_order::convert(&mut expected, ByteOrder::ByteSwapped, ByteOrder::BigEndian);
+    assert_eq!(rom, expected);
+}
+
+#[test]
+fn test_aps_metadata_and_verify_report_order() {
+    let clean = z64_rom();
+    let patch = aps_patch(0x01, &clean);
+
+    let meta = ApsN64Patcher::metadata(&patch).unwrap();
+    assert!(
+        meta.extra
+            .iter()
+            .any(|(k, v)| k == "Original Format" && v == "Z64")
+    );
+
+    let err =
+        ApsN64Patcher::verify(&reorder(&clean, ByteOrder::ByteSwapped), &patch, None).unwrap_err();
+    assert!(err.to_string().contains("byte-swapped"));
+}
+
+#[test]
+fn test_non_n64_rom_untouched() {
+    let mut rom = vec![0x11u8; 64];
+    let patch = IpsPatcher::create(&rom, &[0x22u8; 64]).unwrap();
+    let fix = n64_byte_order::prepare(&mut rom, &patch, PatchType::Ips);
+    assert_eq!(fix.input, None);
+    assert!(fix.finish(&mut rom, OutputOrder::BigEndian).is_empty());
+    assert_eq!(rom, vec![0x11u8; 64]);
+}
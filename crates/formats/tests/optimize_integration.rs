@@ -0,0 +1,160 @@
+//! Patch optimization tests
+
+#![cfg(all(feature = "ips", feature = "ppf", feature = "bps"))]
+
+use stitchr_core::{PatchFormat, PatchType};
+use stitchr_formats::bps::BpsPatcher;
+use stitchr_formats::ips::IpsPatcher;
+use stitchr_formats::optimize::{can_optimize, optimize};
+use stitchr_formats::ppf::PpfPatcher;
+
+fn rom() -> Vec<u8> {
+    (0..0x400).map(|i| (i * 13 + 5) as u8).collect()
+}
+
+/// IPS patch with one literal record per (offset, byte)
+fn ips_per_byte(writes: &[(u32, u8)], truncate: Option<u32>) -> Vec<u8> {
+    let mut patch = b"PATCH".to_vec();
+    for &(offset, byte) in writes {
+        patch.extend_from_slice(&offset.to_be_bytes()[1..]);
+        patch.extend_from_slice(&[0x00, 0x01, byte]);
+    }
+    patch.extend_from_slice(b"EOF");
+    if let Some(size) = truncate {
+        patch.extend_from_slice(&size.to_be_bytes()[1..]);
+    }
+    patch
+}
+
+fn apply_ips(rom: &[u8], patch: &[u8]) -> Vec<u8> {
+    let mut out = rom.to_vec();
+    IpsPatcher.apply(&mut out, patch).unwrap();
+    out
+}
+
+#[test]
+fn test_ips_merges_records_and_uses_rle() {
+    let writes: Vec<(u32, u8)> = (0x100..0x140)
+        .map(|i| (i, 0xEA))
+        .chain((0x200..0x210).map(|i| (i, i as u8)))
+        .collect();
+    let patch = ips_per_byte(&writes, None);
+
+    let optimized = optimize(&patch, PatchType::Ips, None, false).unwrap();
+    assert!(optimized.optimized_size() < patch.len() / 4);
+    assert_eq!(optimized.original_size, patch.len());
+    assert_eq!(
+        IpsPatcher::record_ranges(&optimized.patch).unwrap().len(),
+        2
+    );
+    assert_eq!(
+        apply_ips(&rom(), &optimized.patch),
+        apply_ips(&rom(), &patch)
+    );
+}
+
+#[test]
+fn test_ips_later_records_win() {
+    let patch = ips_per_byte(&[(0x10, 1), (0x11, 2), (0x10, 3), (0x12, 4)], Some(0x300));
+    let optimized = IpsPatcher::optimize(&patch, None).unwrap();
+
+    let out = apply_ips(&rom(), &optimized);
+    assert_eq!(&out[0x10..0x13], &[3, 2, 4]);
+    assert_eq!(out.len(), 0x300);
+    assert_eq!(out, apply_ips(&rom(), &patch));
+}
+
+#[test]
+fn test_ips_drops_unchanged_writes_with_source() {
+    let source = rom();
+    let writes: Vec<(u32, u8)> = (0x80..0x180).map(|i| (i, source[i as usize])).collect();
+    let mut patch = ips_per_byte(&writes, None);
+    patch.truncate(patch.len() - 3);
+    patch.extend_from_slice(&[0x00, 0x00, 0x20, 0x00, 0x01, 0xFF]);
+    patch.extend_from_slice(b"EOF");
+
+    let optimized = optimize(&patch, PatchType::Ips, Some(&source), false).unwrap();
+    let ranges = IpsPatcher::record_ranges(&optimized.patch).unwrap();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0], 0x20..0x21);
+    assert_eq!(
+        apply_ips(&source, &optimized.patch),
+        apply_ips(&source, &patch)
+    );
+}
+
+#[test]
+fn test_minimal_patch_is_returned_unchanged() {
+    let mut target = rom();
+    target[0x40..0x48].copy_from_slice(b"STITCHR!");
+    let patch = IpsPatcher::create(&rom(), &target).unwrap();
+
+    let optimized = optimize(&patch, PatchType::Ips, None, false).unwrap();
+    assert_eq!(optimized.patch, patch);
+    assert_eq!(optimized.saved(), 0);
+}
+
+/// PPF3 patch with undo data, one record per (offset, byte)
+fn ppf3_per_byte(source: &[u8], writes: &[(u64, u8)]) -> Vec<u8> {
+    let mut patch = b"PPF30".to_vec();
+    patch.push(0x02);
+    patch.extend_from_slice(&[b' '; 50]);
+    patch.extend_from_slice(&[0x00, 0x00, 0x01, 0x00]);
+    for &(offset, byte) in writes {
+        patch.extend_from_slice(&offset.to_le_bytes());
+        patch.extend_from_slice(&[1, byte, source[offset as usize]]);
+    }
+    patch.extend_from_slice(b"@BEG\r\nhello\r\n@END_FILE_ID.DIZ");
+    patch.extend_from_slice(&0x0Cu32.to_le_bytes());
+    patch
+}
+
+fn apply_ppf(rom: &[u8], patch: &[u8]) -> Vec<u8> {
+    let mut out = rom.to_vec();
+    PpfPatcher.apply(&mut out, patch).unwrap();
+    out
+}
+
+#[test]
+fn test_ppf3_merges_records_and_keeps_undo() {
+    let source = rom();
+    let writes: Vec<(u64, u8)> = (0x100..0x180).map(|i| (i, 0xEA)).collect();
+    let patch = ppf3_per_byte(&source, &writes);
+
+    let optimized = optimize(&patch, PatchType::Ppf, None, false).unwrap();
+    assert!(optimized.optimized_size() < patch.len() / 4);
+    assert!(optimized.patch.ends_with(&patch[patch.len() - 33..]));
+
+    let contents = PpfPatcher::records(&optimized.patch).unwrap();
+    assert_eq!(contents.records.len(), 1);
+    assert_eq!(
+        contents.records[0].undo.as_deref(),
+        Some(&source[0x100..0x180])
+    );
+    assert_eq!(
+        apply_ppf(&source, &optimized.patch),
+        apply_ppf(&source, &patch)
+    );
+}
+
+#[test]
+fn test_ppf3_drop_undo() {
+    let source = rom();
+    let writes: Vec<(u64, u8)> = (0..300).map(|i| (0x10 + i, i as u8)).collect();
+    let patch = ppf3_per_byte(&source, &writes);
+
+    let optimized = PpfPatcher::optimize(&patch, None, true).unwrap();
+    assert_eq!(optimized[58], 0);
+    let contents = PpfPatcher::records(&optimized).unwrap();
+    // Records carry at most 255 bytes
+    assert_eq!(contents.records.len(), 2);
+    assert!(contents.records.iter().all(|r| r.undo.is_none()));
+    assert_eq!(apply_ppf(&source, &optimized), apply_ppf(&source, &patch));
+}
+
+#[test]
+fn test_unsupported_format() {
+    assert!(!can_optimize(PatchType::Bps));
+    let patch = BpsPatcher::create(&rom(), &rom(), &[]).unwrap();
+    assert!(optimize(&patch, PatchType::Bps, None, false).is_err());
+}
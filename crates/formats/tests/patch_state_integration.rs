@@ -0,0 +1,179 @@
+//! Already-applied patch detection and revert tests
+
+#![cfg(all(
+    feature = "ips",
+    feature = "bps",
+    feature = "ups",
+    feature = "rup",
+    feature = "ppf"
+))]
+
+use stitchr_core::{PatchFormat, PatchType};
+use stitchr_formats::bps::BpsPatcher;
+use stitchr_formats::ips::IpsPatcher;
+use stitchr_formats::patch_state::{self, PatchState};
+use stitchr_formats::rup::RupPatcher;
+use stitchr_formats::ups::UpsPatcher;
+
+fn source() -> Vec<u8> {
+    (0..64).map(|i| (i * 7 + 3) as u8).collect()
+}
+
+/// Grown by 16 bytes with a few changed runs
+fn target() -> Vec<u8> {
+    let mut out = source();
+    out[4..8].fill(0xEA);
+    out[20] ^= 0xFF;
+    out.extend((0..16).map(|i| 0x80 + i as u8));
+    out
+}
+
+/// UPS patch with one record per run of changed bytes (sizes below 128)
+fn ups_patch(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut patch = b"UPS1".to_vec();
+    patch.push(source.len() as u8 | 0x80);
+    patch.push(target.len() as u8 | 0x80);
+
+    let byte = |data: &[u8], i: usize| data.get(i).copied().unwrap_or(0);
+    let len = source.len().max(target.len());
+    let (mut pos, mut i) = (0, 0);
+    while i < len {
+        if byte(source, i) == byte(target, i) {
+            i += 1;
+            continue;
+        }
+        patch.push((i - pos) as u8 | 0x80);
+        while i < len && byte(source, i) != byte(target, i) {
+            patch.push(byte(source, i) ^ byte(target, i));
+            i += 1;
+        }
+        patch.push(0x00);
+        pos = i + 1;
+        i += 1;
+    }
+
+    patch.extend_from_slice(&crc32fast::hash(source).to_le_bytes());
+    patch.extend_from_slice(&crc32fast::hash(target).to_le_bytes());
+    let crc = crc32fast::hash(&patch);
+    patch.extend_from_slice(&crc.to_le_bytes());
+    patch
+}
+
+/// NINJA2 patch for a single file with one XOR record per changed byte
+/// (target no smaller than source)
+fn rup_patch(source: &[u8], target: &[u8]) -> Vec<u8> {
+    fn vlv(patch: &mut Vec<u8>, value: usize) {
+        patch.push(4);
+        patch.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    let mut patch = vec![0u8; 0x800];
+    patch[..6].copy_from_slice(b"NINJA2");
+    patch.push(0x01);
+    vlv(&mut patch, 8);
+    patch.extend_from_slice(b"game.sfc");
+    patch.push(3);
+    vlv(&mut patch, source.len());
+    vlv(&mut patch, target.len());
+    patch.extend_from_slice(&md5::compute(source).0);
+    patch.extend_from_slice(&md5::compute(target).0);
+    if target.len() != source.len() {
+        patch.push(b'A');
+        vlv(&mut patch, target.len() - source.len());
+        patch.extend(target[source.len()..].iter().map(|b| b ^ 0xFF));
+    }
+    for (i, (a, b)) in source.iter().zip(target).enumerate() {
+        if a != b {
+            patch.push(0x02);
+            vlv(&mut patch, i);
+            vlv(&mut patch, 1);
+            patch.push(a ^ b);
+        }
+    }
+    patch.push(0x00);
+    patch
+}
+
+#[test]
+fn test_bps_applied_by_target_crc() {
+    let patch = BpsPatcher::create(&source(), &target(), &[]).unwrap();
+
+    assert_eq!(
+        patch_state::detect(&source(), &patch, PatchType::Bps),
+        PatchState::NotApplied
+    );
+    let state = patch_state::detect(&target(), &patch, PatchType::Bps);
+    assert_eq!(state, PatchState::Applied("target CRC32 matches the ROM"));
+    assert!(!patch_state::can_revert(PatchType::Bps));
+    assert!(patch_state::revert(&mut target(), &patch, PatchType::Bps).is_err());
+}
+
+#[test]
+fn test_ups_applied_and_revert() {
+    let patch = ups_patch(&source(), &target());
+    let mut rom = source();
+    UpsPatcher.apply(&mut rom, &patch).unwrap();
+    assert_eq!(rom, target());
+
+    assert!(patch_state::detect(&rom, &patch, PatchType::Ups).is_applied());
+    assert!(patch_state::can_revert(PatchType::Ups));
+    patch_state::revert(&mut rom, &patch, PatchType::Ups).unwrap();
+    assert_eq!(rom, source());
+    assert!(!patch_state::detect(&rom, &patch, PatchType::Ups).is_applied());
+}
+
+#[test]
+fn test_ups_revert_rejects_unpatched_rom() {
+    let patch = ups_patch(&source(), &target());
+    let mut rom = source();
+    assert!(UpsPatcher::revert(&mut rom, &patch).is_err());
+}
+
+#[test]
+fn test_rup_applied_and_revert() {
+    let patch = rup_patch(&source(), &target());
+    let mut rom = target();
+
+    let state = patch_state::detect(&rom, &patch, PatchType::Rup);
+    assert_eq!(state, PatchState::Applied("target MD5 matches the ROM"));
+    patch_state::revert(&mut rom, &patch, PatchType::Rup).unwrap();
+    assert_eq!(rom, source());
+
+    // Revert never patches forward
+    assert!(RupPatcher::revert(&mut rom, &patch).is_err());
+    assert_eq!(rom, source());
+}
+
+#[test]
+fn test_ips_applied_when_records_match() {
+    let patch = IpsPatcher::create(&source(), &target()).unwrap();
+
+    assert!(!patch_state::detect(&source(), &patch, PatchType::Ips).is_applied());
+    let state = patch_state::detect(&target(), &patch, PatchType::Ips);
+    assert_eq!(
+        state.to_string(),
+        "Patch already applied (every record's bytes are already in the ROM)"
+    );
+}
+
+#[test]
+fn test_ppf_applied_when_records_match() {
+    let mut patch = b"PPF30".to_vec();
+    patch.push(0x02);
+    patch.extend_from_slice(&[0u8; 50]);
+    patch.extend_from_slice(&[0, 0, 0, 0]);
+    patch.extend_from_slice(&4u64.to_le_bytes());
+    patch.push(4);
+    patch.extend_from_slice(&[0xEA; 4]);
+
+    assert!(!patch_state::detect(&source(), &patch, PatchType::Ppf).is_applied());
+    assert!(patch_state::detect(&target(), &patch, PatchType::Ppf).is_applied());
+}
+
+#[test]
+fn test_empty_ips_is_never_applied() {
+    assert_eq!(
+        patch_state::detect(&source(), b"PATCHEOF", PatchType::Ips),
+        PatchState::NotApplied
+    );
+}
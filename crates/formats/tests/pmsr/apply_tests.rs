@@ -0,0 +1,78 @@
+//! PMSR application tests
+
+use super::{build_patch, clean_rom};
+use stitchr_core::{PatchError, PatchFormat};
+use stitchr_formats::pmsr::PmsrPatcher;
+use stitchr_formats::pmsr::constants::PAPER_MARIO_USA10_FILE_SIZE;
+
+#[test]
+fn test_apply_to_clean_rom() {
+    let mut rom = clean_rom();
+    let patch = build_patch(&[(0x10, &[0xAA, 0xBB])]);
+
+    PmsrPatcher.apply(&mut rom, &patch).unwrap();
+
+    assert_eq!(rom.len(), PAPER_MARIO_USA10_FILE_SIZE);
+    assert_eq!(&rom[0x0E..0x13], &[0x00, 0x00, 0xAA, 0xBB, 0x00]);
+}
+
+#[test]
+fn test_apply_rejects_modified_rom() {
+    let mut rom = clean_rom();
+    rom[0x1000] ^= 0xFF;
+    let before = rom.clone();
+    let patch = build_patch(&[(0x10, &[0xAA, 0xBB])]);
+
+    let result = PmsrPatcher.apply(&mut rom, &patch);
+    assert!(matches!(result, Err(PatchError::ChecksumMismatch { .. })));
+    assert_eq!(rom, before);
+}
+
+#[test]
+fn test_apply_rejects_unexpanded_rom() {
+    let mut rom = vec![0x11; 1024];
+    let patch = build_patch(&[(0x10, &[0xAA, 0xBB])]);
+
+    let result = PmsrPatcher.apply(&mut rom, &patch);
+    assert!(matches!(result, Err(PatchError::SizeMismatch { .. })));
+    assert_eq!(rom.len(), 1024);
+}
+
+#[test]
+fn test_apply_record_past_40mib_grows_rom() {
+    let mut rom = clean_rom();
+    let offset = PAPER_MARIO_USA10_FILE_SIZE as u32 + 8;
+    let patch = build_patch(&[(offset, &[1, 2, 3, 4])]);
+
+    PmsrPatcher.apply(&mut rom, &patch).unwrap();
+
+    assert_eq!(rom.len(), PAPER_MARIO_USA10_FILE_SIZE + 12);
+    assert_eq!(&rom[offset as usize..], &[1, 2, 3, 4]);
+}
+
+#[test]
+fn test_apply_multiple_records_in_order() {
+    let mut rom = clean_rom();
+    let patch = build_patch(&[(0, &[1, 1, 1, 1]), (2, &[2, 2])]);
+
+    PmsrPatcher.apply(&mut rom, &patch).unwrap();
+
+    assert_eq!(&rom[0..4], &[1, 1, 2, 2]);
+}
+
+#[test]
+fn test_apply_rejects_truncated_record() {
+    let mut rom = vec![0u8; 64];
+    let mut patch = build_patch(&[(0, &[1, 2, 3, 4])]);
+    patch.truncate(patch.len() - 2);
+
+    assert!(PmsrPatcher.apply(&mut rom, &patch).is_err());
+}
+
+#[test]
+fn test_apply_rejects_huge_target() {
+    let mut rom = clean_rom();
+    let patch = build_patch(&[(0xF000_0000, &[1])]);
+
+    assert!(PmsrPatcher.apply(&mut rom, &patch).is_err());
+}
@@ -0,0 +1,34 @@
+//! PMSR metadata tests
+
+use super::build_patch;
+use stitchr_core::{PatchFormat, PatchType};
+use stitchr_formats::pmsr::PmsrPatcher;
+
+#[test]
+fn test_metadata() {
+    let patch = build_patch(&[(0, &[1, 2, 3]), (0x100, &[4])]);
+    let metadata = PmsrPatcher::metadata(&patch).unwrap();
+
+    assert_eq!(metadata.patch_type, PatchType::Pmsr);
+    assert_eq!(metadata.source_size, Some(40 * 1024 * 1024));
+    assert_eq!(metadata.target_size, Some(40 * 1024 * 1024));
+    assert_eq!(
+        metadata.source_checksum,
+        Some(0xA7F5_CD7Eu32.to_le_bytes().to_vec())
+    );
+    assert!(
+        metadata
+            .extra
+            .contains(&("record_count".to_string(), "2".to_string()))
+    );
+    assert!(
+        metadata
+            .extra
+            .contains(&("bytes_written".to_string(), "4".to_string()))
+    );
+}
+
+#[test]
+fn test_metadata_invalid() {
+    assert!(PmsrPatcher::metadata(b"NOTPMSR").is_err());
+}
@@ -0,0 +1,63 @@
+//! PMSR format tests
+
+mod apply_tests;
+mod metadata_tests;
+mod validate_tests;
+
+/// Build a PMSR patch from (offset, data) records
+pub fn build_patch(records: &[(u32, &[u8])]) -> Vec<u8> {
+    let mut patch = Vec::new();
+    patch.extend_from_slice(b"PMSR");
+    patch.extend_from_slice(&(records.len() as u32).to_be_bytes());
+    for (offset, data) in records {
+        patch.extend_from_slice(&offset.to_be_bytes());
+        patch.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        patch.extend_from_slice(data);
+    }
+    patch
+}
+
+/// A 40 MiB ROM with the CRC32 of a clean Paper Mario (USA) v1.0 ROM
+///
+/// Zeros, except for the last four bytes, which are solved for: CRC32 is
+/// affine in them, so each bit's effect on the checksum forms a linear system.
+pub fn clean_rom() -> Vec<u8> {
+    use stitchr_formats::pmsr::constants::{PAPER_MARIO_USA10_CRC32, PAPER_MARIO_USA10_FILE_SIZE};
+
+    let mut rom = vec![0u8; PAPER_MARIO_USA10_FILE_SIZE];
+    let tail_at = rom.len() - 4;
+    let mut prefix = crc32fast::Hasher::new();
+    prefix.update(&rom[..tail_at]);
+    let crc_with_tail = |tail: u32| {
+        let mut hasher = prefix.clone();
+        hasher.update(&tail.to_le_bytes());
+        hasher.finalize()
+    };
+
+    // Gauss-Jordan elimination over GF(2): (effect on the CRC, tail bits)
+    let base = crc_with_tail(0);
+    let mut rows: Vec<(u32, u32)> = (0..32)
+        .map(|i| (crc_with_tail(1 << i) ^ base, 1 << i))
+        .collect();
+    for bit in 0..32 {
+        let pivot = (bit..32)
+            .find(|&r| rows[r].0 & (1 << bit) != 0)
+            .expect("CRC32 is invertible in its last four bytes");
+        rows.swap(bit, pivot);
+        let (effect, tail) = rows[bit];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != bit && row.0 & (1 << bit) != 0 {
+                row.0 ^= effect;
+                row.1 ^= tail;
+            }
+        }
+    }
+    let wanted = base ^ PAPER_MARIO_USA10_CRC32;
+    let tail = (0..32)
+        .filter(|bit| wanted & (1 << bit) != 0)
+        .fold(0, |tail, bit| tail ^ rows[bit].1);
+
+    rom[tail_at..].copy_from_slice(&tail.to_le_bytes());
+    assert_eq!(crc32fast::hash(&rom), PAPER_MARIO_USA10_CRC32);
+    rom
+}
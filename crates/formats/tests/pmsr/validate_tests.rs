@@ -0,0 +1,57 @@
+//! PMSR validation and verification tests
+
+use super::build_patch;
+use stitchr_core::{PatchError, PatchFormat, PatchType};
+use stitchr_formats::detect_format;
+use stitchr_formats::pmsr::PmsrPatcher;
+
+#[test]
+fn test_can_handle() {
+    assert!(PmsrPatcher::can_handle(b"PMSR\x00\x00\x00\x00"));
+    assert!(!PmsrPatcher::can_handle(b"PATCH"));
+    assert!(!PmsrPatcher::can_handle(b"PMS"));
+}
+
+#[test]
+fn test_detect_format() {
+    let patch = build_patch(&[(0, &[1])]);
+    assert_eq!(detect_format(&patch), Some(PatchType::Pmsr));
+}
+
+#[test]
+fn test_validate() {
+    assert!(PmsrPatcher::validate(&build_patch(&[])).is_ok());
+    assert!(PmsrPatcher::validate(&build_patch(&[(4, &[1, 2]), (8, &[3])])).is_ok());
+    assert!(PmsrPatcher::validate(b"PMSR").is_err());
+    assert!(PmsrPatcher::validate(b"NOPE\x00\x00\x00\x00").is_err());
+}
+
+#[test]
+fn test_validate_rejects_impossible_record_count() {
+    let patch = b"PMSR\xFF\xFF\xFF\xFF\x00\x00\x00\x00\x00\x00\x00\x00";
+    assert!(PmsrPatcher::validate(patch).is_err());
+}
+
+#[test]
+fn test_verify_rejects_wrong_size() {
+    let patch = build_patch(&[]);
+    let rom = vec![0u8; 1024];
+
+    let result = PmsrPatcher::verify(&rom, &patch, None);
+    assert!(matches!(result, Err(PatchError::SizeMismatch { .. })));
+}
+
+#[test]
+fn test_verify_rejects_wrong_crc() {
+    let patch = build_patch(&[]);
+    let rom = vec![0u8; 40 * 1024 * 1024];
+
+    let result = PmsrPatcher::verify(&rom, &patch, None);
+    assert!(matches!(
+        result,
+        Err(PatchError::ChecksumMismatch {
+            expected: 0xA7F5_CD7E,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,4 @@
+//! PMSR integration tests
+
+#![cfg(feature = "pmsr")]
+mod pmsr;
@@ -0,0 +1,85 @@
+//! Record summary tests
+
+#![cfg(all(
+    feature = "ips",
+    feature = "ups",
+    feature = "ppf",
+    feature = "bps",
+    feature = "ebp"
+))]
+
+use stitchr_core::PatchType;
+use stitchr_formats::bps::BpsPatcher;
+use stitchr_formats::ips::IpsPatcher;
+use stitchr_formats::record_summary::{RecordSummary, summarize};
+use stitchr_formats::ups::UpsPatcher;
+
+fn source() -> Vec<u8> {
+    (0..0x200).map(|i| (i * 7 + 1) as u8).collect()
+}
+
+#[test]
+fn test_overlapping_ranges_count_once() {
+    let summary = RecordSummary::from_ranges(&[0x10..0x20, 0x18..0x28, 0x40..0x40, 0x100..0x101]);
+    assert_eq!(summary.records, 4);
+    assert_eq!(summary.bytes_changed, 0x18 + 1);
+    assert_eq!(summary.highest_offset, Some(0x100));
+
+    let empty = RecordSummary::from_ranges(&[]);
+    assert_eq!(empty.bytes_changed, 0);
+    assert_eq!(empty.highest_offset, None);
+}
+
+#[test]
+fn test_ips_summary() {
+    let mut target = source();
+    target[0x20..0x30].fill(0xEA);
+    target[0x1F0] ^= 0xFF;
+    let patch = IpsPatcher::create(&source(), &target).unwrap();
+
+    let summary = summarize(&patch, PatchType::Ips).unwrap().unwrap();
+    assert_eq!(summary.records, 2);
+    assert_eq!(summary.bytes_changed, 0x11);
+    assert_eq!(summary.highest_offset, Some(0x1F0));
+
+    // EBP records are read as IPS records
+    let mut ebp = patch.clone();
+    ebp.extend_from_slice(br#"{"title":"x"}"#);
+    assert_eq!(summarize(&ebp, PatchType::Ebp).unwrap(), Some(summary));
+}
+
+#[test]
+fn test_ups_record_ranges() {
+    // Two XOR records: 3 bytes at 0x04, 1 byte at 0x10
+    let mut patch = b"UPS1".to_vec();
+    patch.extend_from_slice(&[0xA0, 0xA0]);
+    patch.extend_from_slice(&[0x84, 0x01, 0x02, 0x03, 0x00]);
+    patch.extend_from_slice(&[0x88, 0xFF, 0x00]);
+    patch.extend_from_slice(&[0u8; 12]);
+
+    let ranges = UpsPatcher::record_ranges(&patch).unwrap();
+    assert_eq!(ranges, vec![0x04..0x07, 0x10..0x11]);
+    let summary = summarize(&patch, PatchType::Ups).unwrap().unwrap();
+    assert_eq!(summary.bytes_changed, 4);
+    assert_eq!(summary.highest_offset, Some(0x10));
+}
+
+#[test]
+fn test_ppf_summary() {
+    let mut patch = b"PPF30".to_vec();
+    patch.push(0x02);
+    patch.extend_from_slice(&[0u8; 50]);
+    patch.extend_from_slice(&[0, 0, 0, 0]);
+    patch.extend_from_slice(&0x800u64.to_le_bytes());
+    patch.extend_from_slice(&[2, 0xAA, 0xBB]);
+
+    let summary = summarize(&patch, PatchType::Ppf).unwrap().unwrap();
+    assert_eq!(summary.records, 1);
+    assert_eq!(summary.highest_offset, Some(0x801));
+}
+
+#[test]
+fn test_delta_formats_not_summarized() {
+    let patch = BpsPatcher::create(&source(), &source(), &[]).unwrap();
+    assert_eq!(summarize(&patch, PatchType::Bps).unwrap(), None);
+}
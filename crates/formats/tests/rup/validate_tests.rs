@@ -11,6 +11,8 @@ fn test_can_handle() {
     assert!(RupPatcher::can_handle(&valid));
     assert!(!RupPatcher::can_handle(b"PATCH"));
     assert!(!RupPatcher::can_handle(b"NINJA"));
+    // Legacy NINJA 1.x patches are not supported
+    assert!(!RupPatcher::can_handle(b"NINJA1"));
     assert!(!RupPatcher::can_handle(b""));
 }
 
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_features::archive::sevenz;
+
+fuzz_target!(|data: &[u8]| {
+    // Covers header parsing and folder decoding in sevenz-rust
+    if let Ok(entries) = sevenz::list(data) {
+        for entry in entries {
+            let _ = sevenz::extract(data, &entry.name);
+        }
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_core::PatchFormat;
+use stitchr_formats::bpm::BpmPatcher;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = BpmPatcher::validate(data);
+    let _ = BpmPatcher::metadata(data);
+
+    // Buffer application only accepts single-file patches; must never panic
+    let mut rom = vec![0u8; 256];
+    let _ = BpmPatcher.apply(&mut rom, data);
+});
@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_features::disc::{BlockDevice, ChdDevice};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(chd) = ChdDevice::open(Box::new(data.to_vec())) else {
+        return;
+    };
+    let _ = chd.first_track_sector_size();
+
+    // Hunks are decoded on demand; read the first, middle and last one
+    let size = chd.size();
+    let mut buf = vec![0u8; 4096];
+    for offset in [0, size / 2, size.saturating_sub(buf.len() as u64)] {
+        let len = (size - offset.min(size)).min(buf.len() as u64) as usize;
+        let _ = chd.read_at(offset, &mut buf[..len]);
+    }
+});
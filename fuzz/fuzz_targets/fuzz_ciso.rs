@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_features::disc::{BlockDevice, CisoDevice};
+
+fuzz_target!(|data: &[u8]| {
+    // CSO (deflate) and ZSO (LZ4) share the block index
+    let Ok(ciso) = CisoDevice::open(Box::new(data.to_vec())) else {
+        return;
+    };
+
+    let size = ciso.size();
+    let mut buf = vec![0u8; 4096];
+    for offset in [0, size / 2, size.saturating_sub(buf.len() as u64)] {
+        let len = (size - offset.min(size)).min(buf.len() as u64) as usize;
+        let _ = ciso.read_at(offset, &mut buf[..len]);
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_core::PatchFormat;
+use stitchr_formats::gdiff::GdiffPatcher;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = GdiffPatcher::validate(data);
+    let _ = GdiffPatcher::metadata(data);
+
+    // COPY positions/lengths are attacker-controlled; apply() must bounds-check
+    let mut rom = vec![0u8; 256];
+    let _ = GdiffPatcher.apply(&mut rom, data);
+
+    // Encoder round-trip: diffing the fuzz input against a fixed source must
+    // reproduce it exactly
+    let source = vec![0u8; 256];
+    if let Ok(patch) = GdiffPatcher::create(&source, data) {
+        let mut rom = source.clone();
+        GdiffPatcher.apply(&mut rom, &patch).expect("encoder produced invalid patch");
+        assert_eq!(rom, data);
+    }
+});
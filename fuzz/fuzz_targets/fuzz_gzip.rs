@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_features::archive::gzip;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = gzip::entry(data);
+    let _ = gzip::decompress(data);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_core::PatchFormat;
+use stitchr_formats::ips::Ips32Patcher;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Ips32Patcher::validate(data);
+    let _ = Ips32Patcher::metadata(data);
+
+    // 32-bit offsets can point far past the ROM; apply() must bound growth
+    let mut rom = vec![0u8; 256];
+    let _ = Ips32Patcher.apply(&mut rom, data);
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_core::PatchFormat;
+use stitchr_formats::pmsr::PmsrPatcher;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = PmsrPatcher::validate(data);
+    let _ = PmsrPatcher::metadata(data);
+
+    let mut rom = vec![0u8; 256];
+    let _ = PmsrPatcher.apply(&mut rom, data);
+});
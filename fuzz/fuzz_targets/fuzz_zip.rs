@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use stitchr_features::archive::zip;
+
+fuzz_target!(|data: &[u8]| {
+    // Extraction re-reads the central directory, so listing must agree
+    if let Ok(entries) = zip::list(data) {
+        for entry in entries {
+            let _ = zip::extract(data, &entry.name);
+        }
+    }
+});